@@ -1,7 +1,17 @@
-use clap::{Parser, Subcommand};
-use duckpond::{Lakehouse, StorageBackend, StorageConfig};
-use sqlx::any::{install_default_drivers, AnyPoolOptions};
-use std::time::Duration;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use duckpond::database::Database;
+use duckpond::migration;
+use duckpond::models::{ColumnInfo, RefKind};
+use duckpond::parquet::reader::read_parquet_schema;
+use duckpond::{
+    BadRecordPolicy, DuckPond, DuckPondConfig, ExportFormat, ExportOptions, ImportOptions,
+    Lakehouse, StorageBackend, StorageConfig, DUCKLAKE_SPEC_VERSION,
+};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 #[derive(Parser)]
 #[command(name = "duckpond")]
@@ -23,10 +33,52 @@ struct Cli {
     #[arg(long, env = "DUCKLAKE_STORAGE_PATH")]
     storage_path: Option<String>,
 
+    #[command(flatten)]
+    checkout: Checkout,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+/// The branch and point in history commands work on
+#[derive(Args)]
+struct Checkout {
+    /// Read and write this branch instead of main
+    #[arg(long, global = true, env = "DUCKLAKE_BRANCH")]
+    branch: Option<String>,
+
+    /// Read the catalog as of this snapshot
+    #[arg(long, global = true, conflicts_with = "at_time")]
+    at_snapshot: Option<i64>,
+
+    /// Read the catalog as of this time, e.g. "2026-09-30T23:59:59Z"
+    #[arg(long, global = true, value_parser = parse_timestamp)]
+    at_time: Option<DateTime<Utc>>,
+}
+
+impl Checkout {
+    /// Open the lakehouse on the chosen branch and snapshot
+    async fn open_lakehouse(
+        &self,
+        database_url: String,
+        storage_config: StorageConfig,
+    ) -> duckpond::Result<Lakehouse> {
+        let mut lakehouse = Lakehouse::new(database_url, storage_config).await?;
+        if let Some(branch) = &self.branch {
+            lakehouse = lakehouse.on_branch(branch).await?;
+        }
+        let snapshot_id = match (self.at_snapshot, self.at_time) {
+            (Some(snapshot_id), _) => Some(snapshot_id),
+            (None, Some(timestamp)) => Some(lakehouse.core().snapshot_at_time(timestamp).await?),
+            (None, None) => None,
+        };
+        match snapshot_id {
+            Some(snapshot_id) => lakehouse.as_of(snapshot_id).await,
+            None => Ok(lakehouse),
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Database migration commands
@@ -34,7 +86,7 @@ enum Commands {
     Migrate(MigrateCommands),
     /// Initialize a new DuckPond catalog
     Init {
-        /// Force initialization even if catalog exists
+        /// Reinitialize an existing catalog, keeping its snapshots and data
         #[arg(long)]
         force: bool,
     },
@@ -49,9 +101,12 @@ enum Commands {
         schema: String,
         /// Table name
         table: String,
-        /// Column definitions as JSON
-        #[arg(long)]
-        columns: String,
+        /// Column definitions as JSON: `[{"name", "type", "nullable", "children"}]`
+        #[arg(long, required_unless_present = "from_parquet")]
+        columns: Option<String>,
+        /// Take the columns from the schema of a local Parquet file
+        #[arg(long, conflicts_with = "columns")]
+        from_parquet: Option<PathBuf>,
     },
     /// List schemas
     ListSchemas,
@@ -67,6 +122,161 @@ enum Commands {
         /// Table name
         table: String,
     },
+    /// Manage SQL views
+    #[command(subcommand)]
+    View(ViewCommands),
+    /// Show or set the sort order used when writing a table
+    SortOrder {
+        /// Schema name
+        schema: String,
+        /// Table name
+        table: String,
+        /// Sort order, e.g. "event_time, user_id DESC" or "ZORDER(lat, lon)"
+        order: Option<String>,
+        /// Remove the table's sort order
+        #[arg(long, conflicts_with = "order")]
+        clear: bool,
+    },
+    /// Show or set the largest write, in rows, kept in the catalog instead of a Parquet file
+    DataInlining {
+        /// New row limit; 0 disables inlining
+        #[arg(long)]
+        row_limit: Option<usize>,
+    },
+    /// Move a table's inlined rows into Parquet files
+    FlushInlined {
+        /// Schema name
+        schema: String,
+        /// Table name
+        table: String,
+    },
+    /// Register Parquet files already in storage with a table, without copying them
+    AddFiles {
+        /// Schema name
+        schema: String,
+        /// Table name
+        table: String,
+        /// File paths, relative to the storage path unless absolute
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+    /// Import a CSV or newline-delimited JSON file, creating the table if needed
+    Import {
+        /// Schema name
+        schema: String,
+        /// Table name
+        table: String,
+        /// File to import
+        file: PathBuf,
+        /// File format; by default taken from the file extension
+        #[arg(long, value_enum)]
+        format: Option<ImportFormat>,
+        /// The CSV file has no header line
+        #[arg(long)]
+        no_header: bool,
+        /// CSV field delimiter, a single character or "tab"
+        #[arg(long, value_parser = parse_delimiter)]
+        delimiter: Option<u8>,
+        /// CSV value to read as NULL (empty values by default)
+        #[arg(long)]
+        null_string: Option<String>,
+        /// Format of date values, e.g. "%d/%m/%Y"
+        #[arg(long)]
+        date_format: Option<String>,
+        /// Format of timestamp values, e.g. "%Y-%m-%d %H:%M:%S"
+        #[arg(long)]
+        timestamp_format: Option<String>,
+        /// Skip records that do not fit the table instead of failing
+        #[arg(long)]
+        skip_bad_records: bool,
+        /// Records written to each Parquet file
+        #[arg(long, default_value = "1000000")]
+        records_per_file: usize,
+    },
+    /// Export a table to a Parquet, CSV, newline-delimited JSON or Arrow IPC file
+    Export {
+        /// Schema name
+        schema: String,
+        /// Table name
+        table: String,
+        /// File to write
+        #[arg(long)]
+        out: PathBuf,
+        /// File format; by default taken from the file extension
+        #[arg(long, value_enum)]
+        format: Option<ExportFormatArg>,
+        /// Only export rows matching a filter, e.g. "status = 'active' AND amount > 100"
+        #[arg(long = "where")]
+        filter: Option<String>,
+    },
+    /// Serve the lakehouse to other tools until interrupted
+    Serve {
+        /// Serve Arrow Flight SQL on this address, e.g. 127.0.0.1:50051
+        #[arg(long)]
+        flight: Option<SocketAddr>,
+        /// Serve the HTTP/JSON API on this address, e.g. 127.0.0.1:8080
+        #[arg(long)]
+        http: Option<SocketAddr>,
+    },
+    /// Expire old snapshots and schedule unreachable files for deletion
+    ExpireSnapshots {
+        /// Expire snapshots older than this age, e.g. "7d", "12h" or "30m"
+        #[arg(long, default_value = "7d", value_parser = parse_duration)]
+        older_than: Duration,
+        /// Always keep this many of the newest snapshots
+        #[arg(long, default_value = "1")]
+        retain_last: usize,
+    },
+    /// Delete files scheduled for deletion from storage
+    Vacuum {
+        /// Only delete files scheduled at least this long ago
+        #[arg(long, default_value = "1h", value_parser = parse_duration)]
+        grace_period: Duration,
+    },
+    /// List (or delete) Parquet files in storage that the catalog does not reference
+    Orphans {
+        /// Ignore files modified more recently than this, e.g. "1d"
+        #[arg(long, default_value = "1d", value_parser = parse_duration)]
+        older_than: Duration,
+        /// Delete the orphaned files instead of only listing them
+        #[arg(long)]
+        delete: bool,
+    },
+    /// Show the snapshot history and the changes each snapshot made
+    History {
+        /// Only show this many of the newest snapshots
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Show the files added and removed per table between two snapshots
+    Diff {
+        /// Snapshot to compare from
+        from: i64,
+        /// Snapshot to compare to
+        to: i64,
+    },
+    /// Restore a table, or the whole catalog, to a prior snapshot
+    ///
+    /// Commits a new snapshot; history and data files are kept.
+    Rollback {
+        /// Snapshot to restore
+        snapshot: i64,
+        /// Schema of the table to restore
+        #[arg(long, requires = "table")]
+        schema: Option<String>,
+        /// Table to restore instead of the whole catalog
+        #[arg(long, requires = "schema")]
+        table: Option<String>,
+    },
+    /// Manage key/value tags on schemas, tables, views and columns
+    #[command(subcommand)]
+    ObjectTag(ObjectTagCommands),
+    /// Manage named snapshot tags
+    #[command(subcommand)]
+    Tag(TagCommands),
+    /// Manage branches with their own snapshot lineage
+    #[command(subcommand)]
+    Branch(BranchCommands),
     /// Query data from a table
     Query {
         /// Schema name
@@ -90,28 +300,121 @@ enum MigrateCommands {
     /// Run pending migrations
     Run,
     /// Revert the last migration
-    Revert,
-    /// Show migration status
+    Revert {
+        /// Revert every migration newer than this version instead
+        #[arg(long)]
+        target: Option<i64>,
+    },
+    /// Show applied and pending migrations
     Info,
-    /// Add a new migration
+    /// Migrate and upgrade the catalog to the latest DuckLake spec version
+    Upgrade,
+    /// Add an empty migration, with its revert script, for every backend
+    ///
+    /// Migrations are built into DuckPond, so rebuild it to apply the new one.
     Add {
-        /// Migration name
-        #[arg(long)]
+        /// Migration name, such as `add_owner_column`
         name: String,
+        /// Directory holding the `sqlite`, `postgres` and `mysql` migrations
+        #[arg(long, default_value = "crates/duckpond-core/migrations")]
+        dir: PathBuf,
     },
 }
 
 #[derive(Subcommand)]
-enum TimeTravelCommands {
-    ListSchemas,
-    ListTables {
-        #[arg(long)]
-        schema_id: i64,
+enum ViewCommands {
+    /// Create a view from a query
+    Create {
+        /// Schema name
+        schema: String,
+        /// View name
+        name: String,
+        /// Query defining the view
+        sql: String,
+        /// SQL dialect the query is written in
+        #[arg(long, default_value = "duckdb")]
+        dialect: String,
+        /// Names for the view's columns (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        column_aliases: Vec<String>,
     },
-    ShowTable {
+    /// Drop a view
+    Drop {
+        /// Schema name
+        schema: String,
+        /// View name
+        name: String,
+    },
+    /// List views in a schema
+    List {
+        /// Schema name
+        schema: String,
+    },
+    /// Show a view's definition
+    Show {
+        /// Schema name
+        schema: String,
+        /// View name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ObjectTagCommands {
+    /// Set a tag, e.g. `set analytics.users.email pii true`
+    Set {
+        /// Schema, schema.table (or view) or schema.table.column
+        target: String,
+        key: String,
+        value: String,
+    },
+    /// Remove a tag
+    Unset {
+        /// Schema, schema.table (or view) or schema.table.column
+        target: String,
+        key: String,
+    },
+    /// List the tags of an object; a table's listing includes its columns
+    List {
+        /// Schema, schema.table (or view) or schema.table.column
+        target: String,
+    },
+}
+
+/// What an object tag command applies to
+enum TagTarget {
+    Object(i64),
+    Column { table_id: i64, column_id: i64 },
+}
+
+#[derive(Subcommand)]
+enum TagCommands {
+    /// Tag a snapshot
+    Create {
+        name: String,
+        /// Snapshot to tag, the current one by default
         #[arg(long)]
-        table_id: i64,
+        snapshot: Option<i64>,
     },
+    /// Remove a tag
+    Drop { name: String },
+    /// List tags
+    List,
+}
+
+#[derive(Subcommand)]
+enum BranchCommands {
+    /// Create a branch from the current snapshot of main
+    ///
+    /// Main keeps committing meanwhile; once it has, the branch can no longer
+    /// be merged and has to be dropped.
+    Create { name: String },
+    /// Fast-forward main to the head of a branch
+    Merge { name: String },
+    /// Remove a branch, discarding its unmerged snapshots
+    Drop { name: String },
+    /// List branches
+    List,
 }
 
 #[tokio::main]
@@ -138,47 +441,106 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         region: None,
     };
 
+    let checkout = cli.checkout;
     match cli.command {
+        Commands::Migrate(MigrateCommands::Add { name, dir }) => {
+            for path in migration::add_migration(&dir, &name)? {
+                println!("Created {}", path.display());
+            }
+            Ok(())
+        }
         Commands::Migrate(migrate_cmd) => {
+            let config = DuckPondConfig::new(cli.database_url, cli.data_path);
+            let database = Database::new(&config).await?;
+            let dialect = database.backend().dialect();
+
             match migrate_cmd {
                 MigrateCommands::Run => {
                     println!("Running database migrations...");
-
-                    // Install drivers for AnyPool to work
-                    install_default_drivers();
-
-                    let pool = AnyPoolOptions::new()
-                        .max_connections(10)
-                        .acquire_timeout(Duration::from_secs(30))
-                        .connect(&cli.database_url)
-                        .await?;
-
-                    sqlx::migrate!("./migrations").run(&pool).await?;
+                    dialect.migrator().run(database.pool()).await?;
                     println!("Migrations completed successfully!");
                     Ok(())
                 }
-                MigrateCommands::Revert => {
-                    println!("Migration revert not implemented yet");
+                MigrateCommands::Revert { target } => {
+                    let reverted =
+                        migration::revert_migrations(database.pool(), dialect, target).await?;
+                    if reverted.is_empty() {
+                        println!("No migrations to revert");
+                    }
+                    for version in reverted {
+                        println!("Reverted migration {}", version);
+                    }
                     Ok(())
                 }
                 MigrateCommands::Info => {
-                    println!("Migration info not implemented yet");
+                    let migrations = migration::migration_info(database.pool(), dialect).await?;
+                    println!(
+                        "{:<16} {:<18} {:<10} {:<16} DESCRIPTION",
+                        "VERSION", "STATUS", "REVERSIBLE", "CHECKSUM"
+                    );
+                    for m in migrations {
+                        println!(
+                            "{:<16} {:<18} {:<10} {:<16} {}",
+                            m.version,
+                            m.status.to_string(),
+                            if m.reversible { "yes" } else { "no" },
+                            &m.checksum[..16],
+                            m.description
+                        );
+                    }
+
+                    let duckpond =
+                        DuckPond::new(database.pool().clone(), database.backend().clone());
+                    if let Ok(Some(version)) = duckpond.spec_version().await {
+                        println!("\nDuckLake spec version: {}", version);
+                    }
                     Ok(())
                 }
-                MigrateCommands::Add { name } => {
-                    println!("Migration add not implemented yet: {}", name);
+                MigrateCommands::Upgrade => {
+                    dialect.migrator().run(database.pool()).await?;
+
+                    let duckpond =
+                        DuckPond::new(database.pool().clone(), database.backend().clone());
+                    let steps = duckpond.upgrade_spec().await?;
+                    if steps.is_empty() {
+                        println!(
+                            "Catalog is already at DuckLake spec {}",
+                            DUCKLAKE_SPEC_VERSION
+                        );
+                    }
+                    for (from, to) in steps {
+                        println!("Upgraded catalog from DuckLake spec {} to {}", from, to);
+                    }
                     Ok(())
                 }
+                // Needs no catalog, so handled before connecting
+                MigrateCommands::Add { .. } => unreachable!(),
             }
         }
         Commands::Init { force } => {
             println!("Initializing DuckPond catalog...");
-            // TODO: Implement initialization
-            println!("Force: {}", force);
+
+            let config = DuckPondConfig::new(cli.database_url, storage_config.path.clone());
+            let database = Database::new(&config).await?;
+            database
+                .backend()
+                .dialect()
+                .migrator()
+                .run(database.pool())
+                .await?;
+
+            let duckpond = DuckPond::new(database.pool().clone(), database.backend().clone());
+            duckpond.initialize(&storage_config.path, force).await?;
+            println!(
+                "Catalog initialized (DuckLake spec {}, data path '{}')",
+                DUCKLAKE_SPEC_VERSION, storage_config.path
+            );
             Ok(())
         }
         Commands::CreateSchema { name } => {
-            let lakehouse = Lakehouse::new(cli.database_url, storage_config).await?;
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
 
             lakehouse.core().create_schema(&name).await?;
             println!("Schema '{}' created successfully", name);
@@ -188,29 +550,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             schema,
             table,
             columns,
+            from_parquet,
         } => {
-            let lakehouse = Lakehouse::new(cli.database_url, storage_config).await?;
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
 
-            // Parse column definitions from JSON
-            let column_defs: Vec<serde_json::Value> = serde_json::from_str(&columns)?;
-            let mut duckpond_columns = Vec::new();
-
-            for col in column_defs.iter() {
-                if let Some(name) = col.get("name").and_then(|v| v.as_str()) {
-                    let data_type = col.get("type").and_then(|v| v.as_str()).unwrap_or("string");
-                    let nullable = col
-                        .get("nullable")
-                        .and_then(|v| v.as_bool())
-                        .unwrap_or(true);
-
-                    duckpond_columns.push(duckpond::ColumnDefinition {
-                        column_id: None,
-                        name: name.to_string(),
-                        data_type: data_type.to_string(),
-                        nullable,
-                    });
+            // Parse column definitions from JSON, or infer them from Parquet
+            let duckpond_columns = match (columns, from_parquet) {
+                (_, Some(path)) => {
+                    let arrow_schema = read_parquet_schema(std::fs::read(&path)?)?;
+                    duckpond::schema::column_definitions(&arrow_schema)?
                 }
-            }
+                (Some(columns), None) => {
+                    let column_defs: Vec<serde_json::Value> = serde_json::from_str(&columns)?;
+                    parse_column_definitions(&column_defs)?
+                }
+                (None, None) => unreachable!("clap requires --columns or --from-parquet"),
+            };
 
             // First get the schema to find its ID
             let schemas = lakehouse.core().list_schemas().await?;
@@ -227,7 +584,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Ok(())
         }
         Commands::ListSchemas => {
-            let lakehouse = Lakehouse::new(cli.database_url, storage_config).await?;
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
             let schemas = lakehouse.core().list_schemas().await?;
 
             println!("Schemas:");
@@ -237,7 +596,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Ok(())
         }
         Commands::ListTables { schema } => {
-            let lakehouse = Lakehouse::new(cli.database_url, storage_config).await?;
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
 
             // First get the schema to find its ID
             let schemas = lakehouse.core().list_schemas().await?;
@@ -255,7 +616,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Ok(())
         }
         Commands::ShowTable { schema, table } => {
-            let lakehouse = Lakehouse::new(cli.database_url, storage_config).await?;
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
 
             // First get the schema to find its ID
             let schemas = lakehouse.core().list_schemas().await?;
@@ -277,16 +640,558 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .await?;
 
             println!("Table structure for '{}.{}':", schema, table);
-            for column in structure {
-                let nullable = if column.nulls_allowed {
-                    "NULL"
+            print_columns(&structure, 1);
+            Ok(())
+        }
+        Commands::SortOrder {
+            schema,
+            table,
+            order,
+            clear,
+        } => {
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
+
+            if clear {
+                lakehouse.set_sort_order(&schema, &table, None).await?;
+                println!("Sort order cleared for '{}.{}'", schema, table);
+            } else if let Some(order) = order {
+                let sort_order: duckpond::SortOrder = order.parse()?;
+                lakehouse
+                    .set_sort_order(&schema, &table, Some(sort_order.clone()))
+                    .await?;
+                println!(
+                    "Sort order for '{}.{}' set to {}",
+                    schema, table, sort_order
+                );
+            } else {
+                match lakehouse.sort_order(&schema, &table).await? {
+                    Some(sort_order) => {
+                        println!("Sort order for '{}.{}': {}", schema, table, sort_order)
+                    }
+                    None => println!("Table '{}.{}' has no sort order", schema, table),
+                }
+            }
+            Ok(())
+        }
+        Commands::DataInlining { row_limit } => {
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
+
+            match row_limit {
+                Some(row_limit) => {
+                    lakehouse.set_data_inlining_row_limit(row_limit).await?;
+                    println!("Data inlining row limit set to {}", row_limit);
+                }
+                None => println!(
+                    "Data inlining row limit: {}",
+                    lakehouse.data_inlining_row_limit().await?
+                ),
+            }
+            Ok(())
+        }
+        Commands::FlushInlined { schema, table } => {
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
+
+            let rows = lakehouse.flush_inlined_data(&schema, &table).await?;
+            println!(
+                "Flushed {} inlined rows of '{}.{}' to Parquet",
+                rows, schema, table
+            );
+            Ok(())
+        }
+        Commands::AddFiles {
+            schema,
+            table,
+            paths,
+        } => {
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
+
+            let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+            let data_file_ids = lakehouse.add_files(&schema, &table, &paths).await?;
+            println!(
+                "Added {} files to '{}.{}'",
+                data_file_ids.len(),
+                schema,
+                table
+            );
+            Ok(())
+        }
+        Commands::Import {
+            schema,
+            table,
+            file,
+            format,
+            no_header,
+            delimiter,
+            null_string,
+            date_format,
+            timestamp_format,
+            skip_bad_records,
+            records_per_file,
+        } => {
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
+
+            let format = match format {
+                Some(format) => format,
+                None => ImportFormat::from_path(&file).ok_or_else(|| {
+                    format!(
+                        "cannot tell the format of '{}', pass --format",
+                        file.display()
+                    )
+                })?,
+            };
+            let tsv = file.extension().is_some_and(|e| e == "tsv");
+            let options = ImportOptions {
+                has_header: !no_header,
+                delimiter: delimiter.unwrap_or(if tsv { b'\t' } else { b',' }),
+                null_string,
+                date_format,
+                timestamp_format,
+                bad_records: if skip_bad_records {
+                    BadRecordPolicy::Skip
                 } else {
-                    "NOT NULL"
-                };
+                    BadRecordPolicy::Fail
+                },
+                records_per_file,
+                ..Default::default()
+            };
+
+            let reader = std::fs::File::open(&file)?;
+            let summary = match format {
+                ImportFormat::Csv => {
+                    lakehouse
+                        .import_csv(&schema, &table, reader, &options)
+                        .await?
+                }
+                ImportFormat::Json => {
+                    lakehouse
+                        .import_json(&schema, &table, reader, &options)
+                        .await?
+                }
+            };
+            println!(
+                "Imported {} records into '{}.{}' ({} files)",
+                summary.records_imported,
+                schema,
+                table,
+                summary.data_file_ids.len()
+            );
+            if summary.records_skipped > 0 {
+                println!("Skipped {} bad records", summary.records_skipped);
+            }
+            Ok(())
+        }
+        Commands::Export {
+            schema,
+            table,
+            out,
+            format,
+            filter,
+        } => {
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
+
+            let format = match format {
+                Some(format) => format.into(),
+                None => ExportFormat::from_path(&out).ok_or_else(|| {
+                    format!(
+                        "cannot tell the format of '{}', pass --format",
+                        out.display()
+                    )
+                })?,
+            };
+            let options = ExportOptions {
+                format,
+                filter: filter.map(|filter| filter.parse()).transpose()?,
+            };
+
+            let writer = std::io::BufWriter::new(std::fs::File::create(&out)?);
+            let rows = lakehouse.export(&schema, &table, writer, &options).await?;
+            println!(
+                "Exported {} rows from '{}.{}' to {}",
+                rows,
+                schema,
+                table,
+                out.display()
+            );
+            Ok(())
+        }
+        Commands::Serve { flight, http } => {
+            if flight.is_none() && http.is_none() {
+                return Err("pass --flight and/or --http <address> to choose what to serve".into());
+            }
+            let lakehouse = Arc::new(
+                checkout
+                    .open_lakehouse(cli.database_url, storage_config)
+                    .await?,
+            );
+
+            let flight = async {
+                match flight {
+                    Some(addr) => duckpond_flight::serve(lakehouse.clone(), addr)
+                        .await
+                        .map_err(Box::<dyn std::error::Error>::from),
+                    None => Ok(()),
+                }
+            };
+            let http = async {
+                match http {
+                    Some(addr) => duckpond_http::serve(lakehouse.clone(), addr)
+                        .await
+                        .map_err(Box::<dyn std::error::Error>::from),
+                    None => Ok(()),
+                }
+            };
+            tokio::try_join!(flight, http)?;
+            Ok(())
+        }
+        Commands::ExpireSnapshots {
+            older_than,
+            retain_last,
+        } => {
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
+
+            let expiration = lakehouse
+                .core()
+                .expire_snapshots(Utc::now() - older_than, retain_last)
+                .await?;
+            println!(
+                "Expired {} snapshot(s), scheduled {} file(s) for deletion",
+                expiration.expired_snapshots.len(),
+                expiration.scheduled_files.len()
+            );
+            for path in expiration.scheduled_files {
+                println!("  {}", path);
+            }
+            Ok(())
+        }
+        Commands::Vacuum { grace_period } => {
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
+
+            let deleted = lakehouse.vacuum(grace_period).await?;
+            println!("Deleted {} file(s)", deleted.len());
+            for path in deleted {
+                println!("  {}", path);
+            }
+            Ok(())
+        }
+        Commands::Orphans { older_than, delete } => {
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
+
+            if delete {
+                let deleted = lakehouse.remove_orphans(older_than).await?;
+                println!("Deleted {} orphaned file(s)", deleted.len());
+                for path in deleted {
+                    println!("  {}", path);
+                }
+            } else {
+                let orphans = lakehouse.find_orphans(older_than).await?;
+                println!("Found {} orphaned file(s)", orphans.len());
+                for file in orphans {
+                    println!("  {} ({} bytes)", file.path, file.size);
+                }
+            }
+            Ok(())
+        }
+        Commands::History { limit } => {
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
+            let snapshots = lakehouse.core().list_snapshots().await?;
+            let skip = limit.map_or(0, |limit| snapshots.len().saturating_sub(limit));
+
+            for snapshot in snapshots.into_iter().skip(skip).rev() {
                 println!(
-                    "  {} {} {}",
-                    column.column_name, column.column_type, nullable
+                    "Snapshot {} ({}, schema version {})",
+                    snapshot.snapshot_id,
+                    snapshot.snapshot_time.to_rfc3339(),
+                    snapshot.schema_version
                 );
+                for change in snapshot.changes {
+                    println!("  {}", change);
+                }
+            }
+            Ok(())
+        }
+        Commands::Diff { from, to } => {
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
+            let core = lakehouse.core();
+            let diffs = core.diff(from, to).await?;
+
+            // Name tables as of the later snapshot, falling back to their ID
+            let mut names = HashMap::new();
+            let time_travel = core.at_snapshot(to);
+            for schema in time_travel.list_schemas().await? {
+                for table in time_travel.list_tables(schema.schema_id).await? {
+                    names.insert(
+                        table.table_id,
+                        format!("{}.{}", schema.schema_name, table.table_name),
+                    );
+                }
+            }
+
+            if diffs.is_empty() {
+                println!("No file changes between snapshots {} and {}", from, to);
+            }
+            for diff in diffs {
+                match names.get(&diff.table_id) {
+                    Some(name) => println!("Table {}:", name),
+                    None => println!("Table {}:", diff.table_id),
+                }
+                for (sign, files) in [("+", &diff.added_files), ("-", &diff.removed_files)] {
+                    for file in files {
+                        println!(
+                            "  {} {:?} {} ({} rows, {} bytes, snapshot {})",
+                            sign,
+                            file.kind,
+                            file.path,
+                            file.record_count,
+                            file.file_size_bytes,
+                            file.snapshot_id
+                        );
+                    }
+                }
+            }
+            Ok(())
+        }
+        Commands::Rollback {
+            snapshot,
+            schema,
+            table,
+        } => {
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
+            let core = lakehouse.core();
+
+            let restored = match (schema, table) {
+                (Some(schema), Some(table)) => {
+                    let schema_info = core
+                        .list_schemas()
+                        .await?
+                        .into_iter()
+                        .find(|s| s.schema_name == schema)
+                        .ok_or_else(|| format!("Schema '{}' not found", schema))?;
+                    let table_info = core
+                        .list_tables(schema_info.schema_id)
+                        .await?
+                        .into_iter()
+                        .find(|t| t.table_name == table)
+                        .ok_or_else(|| {
+                            format!("Table '{}' not found in schema '{}'", table, schema)
+                        })?;
+                    let restored = core.restore_table(table_info.table_id, snapshot).await?;
+                    println!(
+                        "Restored table '{}.{}' to snapshot {}",
+                        schema, table, snapshot
+                    );
+                    restored
+                }
+                _ => {
+                    let restored = core.restore(snapshot).await?;
+                    println!("Restored catalog to snapshot {}", snapshot);
+                    restored
+                }
+            };
+            println!("Current snapshot: {}", restored);
+            Ok(())
+        }
+        Commands::View(view_cmd) => {
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
+            let core = lakehouse.core();
+
+            let schema = match &view_cmd {
+                ViewCommands::Create { schema, .. }
+                | ViewCommands::Drop { schema, .. }
+                | ViewCommands::List { schema }
+                | ViewCommands::Show { schema, .. } => schema.clone(),
+            };
+            let schema_info = core
+                .list_schemas()
+                .await?
+                .into_iter()
+                .find(|s| s.schema_name == schema)
+                .ok_or_else(|| format!("Schema '{}' not found", schema))?;
+
+            match view_cmd {
+                ViewCommands::Create {
+                    name,
+                    sql,
+                    dialect,
+                    column_aliases,
+                    ..
+                } => {
+                    core.create_view(schema_info.schema_id, &name, &dialect, &sql, column_aliases)
+                        .await?;
+                    println!("View '{}.{}' created successfully", schema, name);
+                }
+                ViewCommands::Drop { name, .. } => {
+                    core.drop_view(schema_info.schema_id, &name).await?;
+                    println!("View '{}.{}' dropped", schema, name);
+                }
+                ViewCommands::List { .. } => {
+                    println!("Views in schema '{}':", schema);
+                    for view in core.list_views(schema_info.schema_id).await? {
+                        println!("  - {}", view.view_name);
+                    }
+                }
+                ViewCommands::Show { name, .. } => {
+                    let view = core.get_view(schema_info.schema_id, &name).await?;
+                    println!("View '{}.{}' ({}):", schema, view.view_name, view.dialect);
+                    if !view.column_aliases.is_empty() {
+                        println!("  Columns: {}", view.column_aliases.join(", "));
+                    }
+                    println!("  {}", view.sql);
+                }
+            }
+            Ok(())
+        }
+        Commands::ObjectTag(tag_cmd) => {
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
+            let core = lakehouse.core();
+
+            match tag_cmd {
+                ObjectTagCommands::Set { target, key, value } => {
+                    match resolve_tag_target(core, &target).await? {
+                        TagTarget::Object(object_id) => {
+                            core.set_tag(object_id, &key, &value).await?
+                        }
+                        TagTarget::Column {
+                            table_id,
+                            column_id,
+                        } => {
+                            core.set_column_tag(table_id, column_id, &key, &value)
+                                .await?
+                        }
+                    }
+                    println!("Tagged '{}' with {}={}", target, key, value);
+                }
+                ObjectTagCommands::Unset { target, key } => {
+                    match resolve_tag_target(core, &target).await? {
+                        TagTarget::Object(object_id) => core.unset_tag(object_id, &key).await?,
+                        TagTarget::Column {
+                            table_id,
+                            column_id,
+                        } => core.unset_column_tag(table_id, column_id, &key).await?,
+                    }
+                    println!("Removed tag '{}' from '{}'", key, target);
+                }
+                ObjectTagCommands::List { target } => {
+                    match resolve_tag_target(core, &target).await? {
+                        TagTarget::Object(object_id) => {
+                            for tag in core.list_tags(object_id).await? {
+                                println!("{}={}", tag.key, tag.value);
+                            }
+                            let columns = core.table_structure(object_id).await?;
+                            for tag in core.list_column_tags(object_id).await? {
+                                let column = columns
+                                    .iter()
+                                    .find(|c| c.column_id == tag.column_id)
+                                    .map_or(tag.column_id.to_string(), |c| c.column_name.clone());
+                                println!("{}: {}={}", column, tag.key, tag.value);
+                            }
+                        }
+                        TagTarget::Column {
+                            table_id,
+                            column_id,
+                        } => {
+                            for tag in core.list_column_tags(table_id).await? {
+                                if tag.column_id == column_id {
+                                    println!("{}={}", tag.key, tag.value);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+        Commands::Tag(tag_cmd) => {
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
+            let core = lakehouse.core();
+
+            match tag_cmd {
+                TagCommands::Create { name, snapshot } => {
+                    let tag = core.create_tag(&name, snapshot).await?;
+                    println!("Tagged snapshot {} as '{}'", tag.snapshot_id, tag.name);
+                }
+                TagCommands::Drop { name } => {
+                    core.drop_tag(&name).await?;
+                    println!("Tag '{}' dropped", name);
+                }
+                TagCommands::List => {
+                    for tag in core.list_refs().await? {
+                        if tag.kind == RefKind::Tag {
+                            println!("{} -> snapshot {}", tag.name, tag.snapshot_id);
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+        Commands::Branch(branch_cmd) => {
+            let lakehouse = Lakehouse::new(cli.database_url, storage_config).await?;
+            let core = lakehouse.core();
+
+            match branch_cmd {
+                BranchCommands::Create { name } => {
+                    let created = core.create_branch(&name).await?;
+                    println!(
+                        "Branch '{}' created at snapshot {}",
+                        created.name, created.snapshot_id
+                    );
+                }
+                BranchCommands::Merge { name } => {
+                    let snapshot_id = core.merge_branch(&name).await?;
+                    println!(
+                        "Merged branch '{}', main is at snapshot {}",
+                        name, snapshot_id
+                    );
+                }
+                BranchCommands::Drop { name } => {
+                    let discarded = core.drop_branch(&name).await?;
+                    println!(
+                        "Branch '{}' dropped, discarded {} snapshot(s)",
+                        name,
+                        discarded.len()
+                    );
+                }
+                BranchCommands::List => {
+                    for branch in core.list_refs().await? {
+                        if branch.kind == RefKind::Branch {
+                            println!(
+                                "{} -> snapshot {} ({} unmerged)",
+                                branch.name,
+                                branch.snapshot_id,
+                                core.branch_snapshots(&branch.name).await?.len()
+                            );
+                        }
+                    }
+                }
             }
             Ok(())
         }
@@ -297,7 +1202,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             columns,
             format,
         } => {
-            let lakehouse = Lakehouse::new(cli.database_url, storage_config).await?;
+            let lakehouse = checkout
+                .open_lakehouse(cli.database_url, storage_config)
+                .await?;
 
             // First get the schema to find its ID
             let schemas = lakehouse.core().list_schemas().await?;
@@ -326,3 +1233,184 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 }
+
+/// Parse `{"name", "type", "nullable", "children"}` column definitions
+fn parse_column_definitions(
+    columns: &[serde_json::Value],
+) -> Result<Vec<duckpond::ColumnDefinition>, String> {
+    columns
+        .iter()
+        .map(|col| {
+            let name = col
+                .get("name")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| format!("Column definition without a name: {}", col))?;
+            let data_type = col
+                .get("type")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| format!("Column '{}' has no type", name))?;
+            let nullable = col
+                .get("nullable")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true);
+            let children = match col.get("children").and_then(|v| v.as_array()) {
+                Some(children) => parse_column_definitions(children)?,
+                None => Vec::new(),
+            };
+
+            Ok(duckpond::ColumnDefinition {
+                column_id: None,
+                name: name.to_string(),
+                data_type: data_type.to_string(),
+                nullable,
+                children,
+            })
+        })
+        .collect()
+}
+
+/// Print columns with their child columns indented below them
+fn print_columns(columns: &[ColumnInfo], depth: usize) {
+    for column in columns {
+        let nullable = if column.nulls_allowed {
+            "NULL"
+        } else {
+            "NOT NULL"
+        };
+        println!(
+            "{}{} {} {}",
+            "  ".repeat(depth),
+            column.column_name,
+            column.column_type,
+            nullable
+        );
+        print_columns(&column.children, depth + 1);
+    }
+}
+
+/// Resolve `schema`, `schema.table`, `schema.view` or `schema.table.column`
+async fn resolve_tag_target(core: &DuckPond, target: &str) -> Result<TagTarget, String> {
+    let parts: Vec<&str> = target.split('.').collect();
+    let schema = core
+        .list_schemas()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|s| s.schema_name == parts[0])
+        .ok_or_else(|| format!("Schema '{}' not found", parts[0]))?;
+    let Some(name) = parts.get(1) else {
+        return Ok(TagTarget::Object(schema.schema_id));
+    };
+
+    let tables = core
+        .list_tables(schema.schema_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let table = tables.iter().find(|t| t.table_name == *name);
+    match (table, parts.get(2), parts.len()) {
+        (Some(table), None, _) => Ok(TagTarget::Object(table.table_id)),
+        (Some(table), Some(column_name), 3) => {
+            let column = core
+                .table_structure(table.table_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .find(|c| c.column_name == *column_name)
+                .ok_or_else(|| format!("Column '{}' not found in '{}'", column_name, name))?;
+            Ok(TagTarget::Column {
+                table_id: table.table_id,
+                column_id: column.column_id,
+            })
+        }
+        (None, None, _) => core
+            .get_view(schema.schema_id, name)
+            .await
+            .map(|view| TagTarget::Object(view.view_id))
+            .map_err(|_| format!("Table or view '{}' not found", target)),
+        _ => Err(format!("Cannot resolve '{}'", target)),
+    }
+}
+
+/// Parse an RFC 3339 timestamp, or a date and time in UTC
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").map(|t| t.and_utc()))
+        .map_err(|_| format!("invalid timestamp '{}'", value))
+}
+
+/// Parse a duration such as "30s", "15m", "12h", "7d" or "2w"
+/// Formats of `import`
+#[derive(Clone, Copy, ValueEnum)]
+enum ImportFormat {
+    Csv,
+    /// Newline-delimited JSON
+    Json,
+}
+
+impl ImportFormat {
+    fn from_path(path: &std::path::Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "csv" | "tsv" => Some(Self::Csv),
+            "json" | "jsonl" | "ndjson" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Formats of `export`
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormatArg {
+    Parquet,
+    Csv,
+    /// Newline-delimited JSON
+    Jsonl,
+    /// Arrow IPC file
+    Arrow,
+}
+
+impl From<ExportFormatArg> for ExportFormat {
+    fn from(format: ExportFormatArg) -> Self {
+        match format {
+            ExportFormatArg::Parquet => Self::Parquet,
+            ExportFormatArg::Csv => Self::Csv,
+            ExportFormatArg::Jsonl => Self::Json,
+            ExportFormatArg::Arrow => Self::Arrow,
+        }
+    }
+}
+
+/// Parse a CSV delimiter: a single ASCII character, or "tab"
+fn parse_delimiter(value: &str) -> Result<u8, String> {
+    match value {
+        "tab" | "\\t" => Ok(b'\t'),
+        _ if value.len() == 1 && value.is_ascii() => Ok(value.as_bytes()[0]),
+        _ => Err(format!(
+            "invalid delimiter '{}' (expected a single character or \"tab\")",
+            value
+        )),
+    }
+}
+
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| format!("invalid duration '{}'", value))?;
+
+    match unit {
+        "s" => Ok(Duration::seconds(amount)),
+        "m" => Ok(Duration::minutes(amount)),
+        "h" => Ok(Duration::hours(amount)),
+        "d" => Ok(Duration::days(amount)),
+        "w" => Ok(Duration::weeks(amount)),
+        _ => Err(format!(
+            "invalid duration unit in '{}' (expected s, m, h, d or w)",
+            value
+        )),
+    }
+}
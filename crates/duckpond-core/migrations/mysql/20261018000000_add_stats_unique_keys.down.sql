@@ -0,0 +1,2 @@
+DROP INDEX idx_ducklake_table_column_stats_unique ON ducklake_table_column_stats;
+DROP INDEX idx_ducklake_table_stats_unique ON ducklake_table_stats;
@@ -0,0 +1,25 @@
+-- DuckLake 0.2: schema paths relative to the data path, table paths relative
+-- to their schema's, and name mappings for data files whose columns do not
+-- carry DuckLake field IDs
+
+ALTER TABLE ducklake_schema ADD COLUMN path VARCHAR(1024);
+ALTER TABLE ducklake_schema ADD COLUMN path_is_relative BOOLEAN;
+
+ALTER TABLE ducklake_table ADD COLUMN path VARCHAR(1024);
+ALTER TABLE ducklake_table ADD COLUMN path_is_relative BOOLEAN;
+
+ALTER TABLE ducklake_data_file ADD COLUMN mapping_id BIGINT;
+
+CREATE TABLE ducklake_column_mapping (
+    mapping_id BIGINT PRIMARY KEY,
+    table_id BIGINT NOT NULL,
+    type VARCHAR(50) NOT NULL
+);
+
+CREATE TABLE ducklake_name_mapping (
+    mapping_id BIGINT NOT NULL,
+    column_id BIGINT NOT NULL,
+    source_name VARCHAR(255) NOT NULL,
+    target_field_id BIGINT NOT NULL,
+    parent_column BIGINT
+);
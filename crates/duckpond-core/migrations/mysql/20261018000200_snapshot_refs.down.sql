@@ -0,0 +1,2 @@
+DROP TABLE duckpond_branch_snapshot;
+DROP TABLE duckpond_snapshot_ref;
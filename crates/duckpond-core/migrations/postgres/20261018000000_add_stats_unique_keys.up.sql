@@ -0,0 +1,6 @@
+-- Unique keys for the statistics tables so they can be maintained with upserts
+
+CREATE UNIQUE INDEX idx_ducklake_table_stats_unique
+    ON ducklake_table_stats(table_id);
+CREATE UNIQUE INDEX idx_ducklake_table_column_stats_unique
+    ON ducklake_table_column_stats(table_id, column_id);
@@ -0,0 +1,38 @@
+-- Catalogs upgraded to 0.2 go back to the previous spec version, with
+-- file paths relative to the data path again
+UPDATE ducklake_data_file
+SET path = COALESCE((
+    SELECT s.path || t.path
+    FROM ducklake_table AS t
+    JOIN ducklake_schema AS s ON s.schema_id = t.schema_id
+    WHERE t.table_id = ducklake_data_file.table_id
+    LIMIT 1
+), '') || path
+WHERE
+    path_is_relative AND
+    EXISTS (SELECT 1 FROM ducklake_metadata WHERE key = 'version' AND value = '0.2');
+
+UPDATE ducklake_delete_file
+SET path = COALESCE((
+    SELECT s.path || t.path
+    FROM ducklake_table AS t
+    JOIN ducklake_schema AS s ON s.schema_id = t.schema_id
+    WHERE t.table_id = ducklake_delete_file.table_id
+    LIMIT 1
+), '') || path
+WHERE
+    path_is_relative AND
+    EXISTS (SELECT 1 FROM ducklake_metadata WHERE key = 'version' AND value = '0.2');
+
+UPDATE ducklake_metadata SET value = '0.1' WHERE key = 'version' AND value = '0.2';
+
+DROP TABLE ducklake_name_mapping;
+DROP TABLE ducklake_column_mapping;
+
+ALTER TABLE ducklake_data_file DROP COLUMN mapping_id;
+
+ALTER TABLE ducklake_table DROP COLUMN path_is_relative;
+ALTER TABLE ducklake_table DROP COLUMN path;
+
+ALTER TABLE ducklake_schema DROP COLUMN path_is_relative;
+ALTER TABLE ducklake_schema DROP COLUMN path;
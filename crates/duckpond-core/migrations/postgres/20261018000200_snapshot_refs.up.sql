@@ -0,0 +1,17 @@
+-- Named snapshot references (not part of the DuckLake specification): tags
+-- pin a snapshot, branches track a lineage of snapshots from base_snapshot
+-- (exclusive) to snapshot_id
+
+CREATE TABLE duckpond_snapshot_ref (
+    name VARCHAR(255) PRIMARY KEY,
+    ref_type VARCHAR(16) NOT NULL,
+    snapshot_id BIGINT NOT NULL,
+    base_snapshot BIGINT
+);
+
+-- Snapshots committed to a branch, which main does not see until the branch
+-- is merged; main's own snapshots keep interleaving with them meanwhile
+CREATE TABLE duckpond_branch_snapshot (
+    snapshot_id BIGINT PRIMARY KEY,
+    branch_name VARCHAR(255) NOT NULL
+);
@@ -0,0 +1,15 @@
+-- Unique keys for the statistics tables so they can be maintained with upserts
+
+-- Earlier versions appended a new column statistics row on every write;
+-- keep only the most recent row per column before adding the constraint
+DELETE FROM ducklake_table_column_stats
+WHERE rowid NOT IN (
+    SELECT MAX(rowid)
+    FROM ducklake_table_column_stats
+    GROUP BY table_id, column_id
+);
+
+CREATE UNIQUE INDEX idx_ducklake_table_stats_unique
+    ON ducklake_table_stats(table_id);
+CREATE UNIQUE INDEX idx_ducklake_table_column_stats_unique
+    ON ducklake_table_column_stats(table_id, column_id);
@@ -0,0 +1,8 @@
+SELECT
+    snapshot_id,
+    CAST(snapshot_time AS CHAR) AS snapshot_time,
+    schema_version,
+    next_catalog_id,
+    next_file_id
+FROM ducklake_snapshot
+ORDER BY snapshot_id;
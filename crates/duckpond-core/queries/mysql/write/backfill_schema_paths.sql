@@ -0,0 +1,3 @@
+UPDATE ducklake_schema
+SET path = CONCAT(schema_name, '/'), path_is_relative = $1
+WHERE path IS NULL;
@@ -0,0 +1,3 @@
+UPDATE ducklake_table
+SET path = CONCAT(table_name, '/'), path_is_relative = $1
+WHERE path IS NULL;
@@ -0,0 +1,24 @@
+INSERT INTO ducklake_table_column_stats (
+    table_id,
+    column_id,
+    contains_null,
+    contains_nan,
+    min_value,
+    max_value
+)
+VALUES ($1, $2, ($3 > 0), ($4 > 0), $5, $6)
+ON DUPLICATE KEY UPDATE
+    contains_null = contains_null OR VALUES(contains_null),
+    contains_nan = COALESCE(contains_nan, false) OR COALESCE(VALUES(contains_nan), false),
+    min_value = CASE
+        WHEN VALUES(min_value) IS NULL THEN min_value
+        WHEN min_value IS NULL THEN VALUES(min_value)
+        WHEN VALUES(min_value) < min_value THEN VALUES(min_value)
+        ELSE min_value
+    END,
+    max_value = CASE
+        WHEN VALUES(max_value) IS NULL THEN max_value
+        WHEN max_value IS NULL THEN VALUES(max_value)
+        WHEN VALUES(max_value) > max_value THEN VALUES(max_value)
+        ELSE max_value
+    END;
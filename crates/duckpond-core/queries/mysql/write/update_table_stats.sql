@@ -0,0 +1,11 @@
+INSERT INTO ducklake_table_stats (
+    table_id,
+    record_count,
+    next_row_id,
+    file_size_bytes
+)
+VALUES ($1, $2, $2, $3)
+ON DUPLICATE KEY UPDATE
+    record_count = record_count + VALUES(record_count),
+    next_row_id = next_row_id + VALUES(next_row_id),
+    file_size_bytes = file_size_bytes + VALUES(file_size_bytes);
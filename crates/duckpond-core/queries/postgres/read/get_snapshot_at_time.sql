@@ -0,0 +1,6 @@
+SELECT snapshot_id
+FROM ducklake_snapshot
+WHERE snapshot_time <= CAST($1 AS TIMESTAMPTZ) AND snapshot_id <= $2 AND
+    snapshot_id NOT IN ({hidden})
+ORDER BY snapshot_time DESC, snapshot_id DESC
+LIMIT 1;
@@ -0,0 +1,8 @@
+INSERT INTO ducklake_snapshot (
+    snapshot_id,
+    snapshot_time,
+    schema_version,
+    next_catalog_id,
+    next_file_id
+)
+VALUES ($1, CAST($2 AS TIMESTAMPTZ), $3, $4, $5);
@@ -0,0 +1,6 @@
+SELECT COUNT(*) AS changed
+FROM {table}
+WHERE
+    begin_snapshot NOT IN ({hidden}) AND
+    ((begin_snapshot > $1 AND (end_snapshot IS NULL OR end_snapshot IN ({hidden}))) OR
+     (begin_snapshot <= $1 AND end_snapshot > $1 AND end_snapshot NOT IN ({hidden})));
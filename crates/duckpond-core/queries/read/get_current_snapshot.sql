@@ -1,3 +1,9 @@
 SELECT snapshot_id FROM ducklake_snapshot
-WHERE snapshot_id =
-    (SELECT max(snapshot_id) FROM ducklake_snapshot);
\ No newline at end of file
+WHERE snapshot_id = (
+    SELECT max(s.snapshot_id)
+    FROM ducklake_snapshot AS s
+    WHERE NOT EXISTS (
+        SELECT 1 FROM duckpond_branch_snapshot AS b
+        WHERE b.snapshot_id = s.snapshot_id
+    )
+);
@@ -0,0 +1,3 @@
+SELECT value
+FROM ducklake_metadata
+WHERE "key" = $1;
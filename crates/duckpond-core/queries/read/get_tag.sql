@@ -0,0 +1,9 @@
+SELECT value
+FROM ducklake_tag
+WHERE
+    object_id = $1 AND
+    "key" = $2 AND
+    $3 >= begin_snapshot AND
+    begin_snapshot NOT IN ({hidden}) AND
+    ($3 < end_snapshot OR end_snapshot IS NULL OR
+        end_snapshot IN ({hidden}));
@@ -0,0 +1,4 @@
+SELECT snapshot_id
+FROM duckpond_branch_snapshot
+WHERE branch_name = $1
+ORDER BY snapshot_id;
@@ -0,0 +1,9 @@
+SELECT column_id, "key", value
+FROM ducklake_column_tag
+WHERE
+    table_id = $1 AND
+    $2 >= begin_snapshot AND
+    begin_snapshot NOT IN ({hidden}) AND
+    ($2 < end_snapshot OR end_snapshot IS NULL OR
+        end_snapshot IN ({hidden}))
+ORDER BY column_id, "key";
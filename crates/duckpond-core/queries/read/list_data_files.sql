@@ -1,16 +1,23 @@
-SELECT data.data_file_id, data.path AS data_file_path, CAST(data.path_is_relative AS INTEGER) as path_is_relative, 
-       data.record_count, data.file_size_bytes, del.path AS delete_file_path
+SELECT data.data_file_id, data.path AS data_file_path,
+       CASE WHEN data.path_is_relative THEN 1 ELSE 0 END AS path_is_relative,
+       data.record_count, data.file_size_bytes, data.row_id_start, data.begin_snapshot,
+       del.path AS delete_file_path,
+       CASE WHEN del.path_is_relative THEN 1 ELSE 0 END AS delete_file_path_is_relative
 FROM ducklake_data_file AS data
 LEFT JOIN (
     SELECT *
     FROM ducklake_delete_file
     WHERE
         $2 >= begin_snapshot AND
-        ($2 < end_snapshot OR end_snapshot IS NULL)
+        begin_snapshot NOT IN ({hidden}) AND
+        ($2 < end_snapshot OR end_snapshot IS NULL OR
+            end_snapshot IN ({hidden}))
     ) AS del
 USING (data_file_id)
 WHERE
     data.table_id = $1 AND
     $2 >= data.begin_snapshot AND
-    ($2 < data.end_snapshot OR data.end_snapshot IS NULL)
-ORDER BY data.file_order; 
\ No newline at end of file
+    data.begin_snapshot NOT IN ({hidden}) AND
+    ($2 < data.end_snapshot OR data.end_snapshot IS NULL OR
+        data.end_snapshot IN ({hidden}))
+ORDER BY data.file_order, data.data_file_id; 
\ No newline at end of file
@@ -0,0 +1,9 @@
+SELECT data_file_id
+FROM ducklake_data_file
+WHERE
+    begin_snapshot <= $1 AND
+    begin_snapshot NOT IN ({hidden}) AND
+    end_snapshot > $1 AND
+    end_snapshot NOT IN ({hidden}) AND
+    ($2 IS NULL OR table_id = $2)
+ORDER BY data_file_id;
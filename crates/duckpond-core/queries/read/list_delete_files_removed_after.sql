@@ -0,0 +1,9 @@
+SELECT delete_file_id, data_file_id
+FROM ducklake_delete_file
+WHERE
+    begin_snapshot <= $1 AND
+    begin_snapshot NOT IN ({hidden}) AND
+    end_snapshot > $1 AND
+    end_snapshot NOT IN ({hidden}) AND
+    ($2 IS NULL OR table_id = $2)
+ORDER BY delete_file_id;
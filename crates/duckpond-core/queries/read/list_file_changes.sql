@@ -0,0 +1,66 @@
+SELECT
+    'added' AS change,
+    'data' AS kind,
+    table_id,
+    data_file_id AS file_id,
+    path,
+    CASE WHEN path_is_relative THEN 1 ELSE 0 END AS path_is_relative,
+    record_count,
+    file_size_bytes,
+    begin_snapshot AS snapshot_id
+FROM ducklake_data_file
+WHERE
+    begin_snapshot > $1 AND begin_snapshot <= $2 AND
+    begin_snapshot NOT IN ({hidden}) AND
+    (end_snapshot IS NULL OR end_snapshot > $2 OR end_snapshot IN ({hidden}))
+UNION ALL
+SELECT
+    'removed' AS change,
+    'data' AS kind,
+    table_id,
+    data_file_id AS file_id,
+    path,
+    CASE WHEN path_is_relative THEN 1 ELSE 0 END AS path_is_relative,
+    record_count,
+    file_size_bytes,
+    end_snapshot AS snapshot_id
+FROM ducklake_data_file
+WHERE
+    begin_snapshot <= $1 AND
+    begin_snapshot NOT IN ({hidden}) AND
+    end_snapshot > $1 AND end_snapshot <= $2 AND
+    end_snapshot NOT IN ({hidden})
+UNION ALL
+SELECT
+    'added' AS change,
+    'delete' AS kind,
+    table_id,
+    delete_file_id AS file_id,
+    path,
+    CASE WHEN path_is_relative THEN 1 ELSE 0 END AS path_is_relative,
+    delete_count AS record_count,
+    file_size_bytes,
+    begin_snapshot AS snapshot_id
+FROM ducklake_delete_file
+WHERE
+    begin_snapshot > $1 AND begin_snapshot <= $2 AND
+    begin_snapshot NOT IN ({hidden}) AND
+    (end_snapshot IS NULL OR end_snapshot > $2 OR end_snapshot IN ({hidden}))
+UNION ALL
+SELECT
+    'removed' AS change,
+    'delete' AS kind,
+    table_id,
+    delete_file_id AS file_id,
+    path,
+    CASE WHEN path_is_relative THEN 1 ELSE 0 END AS path_is_relative,
+    delete_count AS record_count,
+    file_size_bytes,
+    end_snapshot AS snapshot_id
+FROM ducklake_delete_file
+WHERE
+    begin_snapshot <= $1 AND
+    begin_snapshot NOT IN ({hidden}) AND
+    end_snapshot > $1 AND end_snapshot <= $2 AND
+    end_snapshot NOT IN ({hidden})
+ORDER BY table_id, snapshot_id, file_id;
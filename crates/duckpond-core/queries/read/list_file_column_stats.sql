@@ -0,0 +1,11 @@
+SELECT data_file_id,
+       column_id,
+       value_count,
+       null_count,
+       CASE WHEN contains_nan IS NULL THEN NULL WHEN contains_nan THEN 1 ELSE 0 END
+           AS contains_nan,
+       min_value,
+       max_value
+FROM ducklake_file_column_statistics
+WHERE table_id = $1
+ORDER BY data_file_id, column_id;
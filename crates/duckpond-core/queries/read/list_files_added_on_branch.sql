@@ -0,0 +1,19 @@
+SELECT
+    data_file_id AS file_id,
+    table_id,
+    path,
+    CASE WHEN path_is_relative THEN 1 ELSE 0 END AS path_is_relative
+FROM ducklake_data_file
+WHERE begin_snapshot IN (
+    SELECT snapshot_id FROM duckpond_branch_snapshot WHERE branch_name = $1
+)
+UNION ALL
+SELECT
+    delete_file_id AS file_id,
+    table_id,
+    path,
+    CASE WHEN path_is_relative THEN 1 ELSE 0 END AS path_is_relative
+FROM ducklake_delete_file
+WHERE begin_snapshot IN (
+    SELECT snapshot_id FROM duckpond_branch_snapshot WHERE branch_name = $1
+);
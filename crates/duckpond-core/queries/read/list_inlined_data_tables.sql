@@ -0,0 +1,6 @@
+SELECT table_id, table_name, schema_snapshot
+FROM ducklake_inlined_data_tables
+WHERE
+    ($1 IS NULL OR table_id = $1) AND
+    schema_snapshot NOT IN ({hidden})
+ORDER BY table_id, schema_snapshot;
@@ -0,0 +1,8 @@
+SELECT row_id, begin_snapshot{columns}
+FROM {table}
+WHERE
+    $1 >= begin_snapshot AND
+    begin_snapshot NOT IN ({hidden}) AND
+    ($1 < end_snapshot OR end_snapshot IS NULL OR
+        end_snapshot IN ({hidden}))
+ORDER BY row_id;
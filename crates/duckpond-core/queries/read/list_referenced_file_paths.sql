@@ -0,0 +1,17 @@
+SELECT
+    table_id,
+    path,
+    CASE WHEN path_is_relative THEN 1 ELSE 0 END AS path_is_relative
+FROM ducklake_data_file
+UNION
+SELECT
+    table_id,
+    path,
+    CASE WHEN path_is_relative THEN 1 ELSE 0 END AS path_is_relative
+FROM ducklake_delete_file
+UNION
+SELECT
+    NULL AS table_id,
+    path,
+    CASE WHEN path_is_relative THEN 1 ELSE 0 END AS path_is_relative
+FROM ducklake_files_scheduled_for_deletion;
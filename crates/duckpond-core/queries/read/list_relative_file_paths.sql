@@ -0,0 +1,7 @@
+SELECT 'data' AS kind, data_file_id AS file_id, table_id, path
+FROM ducklake_data_file
+WHERE path_is_relative
+UNION ALL
+SELECT 'delete' AS kind, delete_file_id AS file_id, table_id, path
+FROM ducklake_delete_file
+WHERE path_is_relative;
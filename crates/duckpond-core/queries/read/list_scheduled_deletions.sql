@@ -0,0 +1,7 @@
+SELECT
+    data_file_id,
+    path,
+    CASE WHEN path_is_relative THEN 1 ELSE 0 END AS path_is_relative,
+    CAST(schedule_start AS VARCHAR(64)) AS schedule_start
+FROM ducklake_files_scheduled_for_deletion
+ORDER BY schedule_start, data_file_id;
@@ -2,4 +2,6 @@ SELECT schema_id, schema_name
 FROM ducklake_schema
 WHERE
     $1 >= begin_snapshot AND
-    ($1 < end_snapshot OR end_snapshot IS NULL); 
\ No newline at end of file
+    begin_snapshot NOT IN ({hidden}) AND
+    ($1 < end_snapshot OR end_snapshot IS NULL OR
+        end_snapshot IN ({hidden})); 
\ No newline at end of file
@@ -0,0 +1,3 @@
+SELECT snapshot_id, changes_made
+FROM ducklake_snapshot_changes
+ORDER BY snapshot_id;
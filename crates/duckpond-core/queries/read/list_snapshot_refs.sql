@@ -0,0 +1,3 @@
+SELECT name, ref_type, snapshot_id, base_snapshot
+FROM duckpond_snapshot_ref
+ORDER BY name;
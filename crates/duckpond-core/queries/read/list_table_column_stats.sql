@@ -0,0 +1,9 @@
+SELECT column_id,
+       CASE WHEN contains_null THEN 1 ELSE 0 END AS contains_null,
+       CASE WHEN contains_nan IS NULL THEN NULL WHEN contains_nan THEN 1 ELSE 0 END
+           AS contains_nan,
+       min_value,
+       max_value
+FROM ducklake_table_column_stats
+WHERE table_id = $1
+ORDER BY column_id;
@@ -0,0 +1,12 @@
+SELECT data_file_id, path,
+       CASE WHEN path_is_relative THEN 1 ELSE 0 END AS path_is_relative,
+       record_count, row_id_start, begin_snapshot,
+       CASE WHEN end_snapshot IN ({hidden}) THEN NULL ELSE end_snapshot END
+           AS end_snapshot
+FROM ducklake_data_file
+WHERE
+    table_id = $1 AND
+    begin_snapshot <= $3 AND
+    begin_snapshot NOT IN ({hidden}) AND
+    (end_snapshot IS NULL OR end_snapshot > $2 OR end_snapshot IN ({hidden}))
+ORDER BY begin_snapshot, data_file_id;
@@ -0,0 +1,12 @@
+SELECT delete_file_id, data_file_id, path,
+       CASE WHEN path_is_relative THEN 1 ELSE 0 END AS path_is_relative,
+       begin_snapshot,
+       CASE WHEN end_snapshot IN ({hidden}) THEN NULL ELSE end_snapshot END
+           AS end_snapshot
+FROM ducklake_delete_file
+WHERE
+    table_id = $1 AND
+    begin_snapshot <= $3 AND
+    begin_snapshot NOT IN ({hidden}) AND
+    (end_snapshot IS NULL OR end_snapshot > $2 OR end_snapshot IN ({hidden}))
+ORDER BY begin_snapshot, delete_file_id;
@@ -0,0 +1,8 @@
+SELECT DISTINCT
+    t.table_id,
+    s.path AS schema_path,
+    CASE WHEN s.path_is_relative THEN 1 ELSE 0 END AS schema_path_is_relative,
+    t.path AS table_path,
+    CASE WHEN t.path_is_relative THEN 1 ELSE 0 END AS table_path_is_relative
+FROM ducklake_table AS t
+JOIN ducklake_schema AS s ON s.schema_id = t.schema_id;
@@ -3,4 +3,6 @@ FROM ducklake_table
 WHERE
     schema_id = $1 AND
     $2 >= begin_snapshot AND
-    ($2 < end_snapshot OR end_snapshot IS NULL); 
\ No newline at end of file
+    begin_snapshot NOT IN ({hidden}) AND
+    ($2 < end_snapshot OR end_snapshot IS NULL OR
+        end_snapshot IN ({hidden})); 
\ No newline at end of file
@@ -0,0 +1,7 @@
+SELECT DISTINCT table_id
+FROM {table}
+WHERE
+    begin_snapshot NOT IN ({hidden}) AND
+    ((begin_snapshot > $1 AND (end_snapshot IS NULL OR end_snapshot IN ({hidden}))) OR
+     (begin_snapshot <= $1 AND end_snapshot > $1 AND end_snapshot NOT IN ({hidden}))) AND
+    ($2 IS NULL OR table_id = $2);
@@ -0,0 +1,77 @@
+SELECT
+    data_file_id AS file_id,
+    table_id,
+    path,
+    CASE WHEN path_is_relative THEN 1 ELSE 0 END AS path_is_relative
+FROM ducklake_data_file
+WHERE
+    end_snapshot IS NOT NULL AND
+    NOT EXISTS (
+        SELECT 1 FROM ducklake_snapshot
+        WHERE
+            ducklake_snapshot.snapshot_id >= ducklake_data_file.begin_snapshot AND
+            ducklake_snapshot.snapshot_id < ducklake_data_file.end_snapshot
+    ) AND
+    NOT EXISTS (
+        SELECT 1 FROM ducklake_data_file AS live
+        WHERE
+            live.path = ducklake_data_file.path AND (
+                live.end_snapshot IS NULL OR EXISTS (
+                    SELECT 1 FROM ducklake_snapshot
+                    WHERE
+                        ducklake_snapshot.snapshot_id >= live.begin_snapshot AND
+                        ducklake_snapshot.snapshot_id < live.end_snapshot
+                )
+            )
+    ) AND
+    NOT EXISTS (
+        SELECT 1 FROM ducklake_delete_file AS live
+        WHERE
+            live.path = ducklake_data_file.path AND (
+                live.end_snapshot IS NULL OR EXISTS (
+                    SELECT 1 FROM ducklake_snapshot
+                    WHERE
+                        ducklake_snapshot.snapshot_id >= live.begin_snapshot AND
+                        ducklake_snapshot.snapshot_id < live.end_snapshot
+                )
+            )
+    )
+UNION ALL
+SELECT
+    delete_file_id AS file_id,
+    table_id,
+    path,
+    CASE WHEN path_is_relative THEN 1 ELSE 0 END AS path_is_relative
+FROM ducklake_delete_file
+WHERE
+    end_snapshot IS NOT NULL AND
+    NOT EXISTS (
+        SELECT 1 FROM ducklake_snapshot
+        WHERE
+            ducklake_snapshot.snapshot_id >= ducklake_delete_file.begin_snapshot AND
+            ducklake_snapshot.snapshot_id < ducklake_delete_file.end_snapshot
+    ) AND
+    NOT EXISTS (
+        SELECT 1 FROM ducklake_data_file AS live
+        WHERE
+            live.path = ducklake_delete_file.path AND (
+                live.end_snapshot IS NULL OR EXISTS (
+                    SELECT 1 FROM ducklake_snapshot
+                    WHERE
+                        ducklake_snapshot.snapshot_id >= live.begin_snapshot AND
+                        ducklake_snapshot.snapshot_id < live.end_snapshot
+                )
+            )
+    ) AND
+    NOT EXISTS (
+        SELECT 1 FROM ducklake_delete_file AS live
+        WHERE
+            live.path = ducklake_delete_file.path AND (
+                live.end_snapshot IS NULL OR EXISTS (
+                    SELECT 1 FROM ducklake_snapshot
+                    WHERE
+                        ducklake_snapshot.snapshot_id >= live.begin_snapshot AND
+                        ducklake_snapshot.snapshot_id < live.end_snapshot
+                )
+            )
+    );
@@ -0,0 +1,16 @@
+SELECT
+    view_id,
+    view_uuid,
+    schema_id,
+    view_name,
+    dialect,
+    "sql",
+    column_aliases
+FROM ducklake_view
+WHERE
+    schema_id = $1 AND
+    $2 >= begin_snapshot AND
+    begin_snapshot NOT IN ({hidden}) AND
+    ($2 < end_snapshot OR end_snapshot IS NULL OR
+        end_snapshot IN ({hidden}))
+ORDER BY view_name;
@@ -1,8 +1,11 @@
-SELECT column_id, column_name, column_type, CAST(nulls_allowed AS INTEGER) as nulls_allowed
+SELECT column_id, column_name, column_type,
+       CASE WHEN nulls_allowed THEN 1 ELSE 0 END AS nulls_allowed,
+       parent_column
 FROM ducklake_column
 WHERE
     table_id = $1 AND
-    parent_column IS NULL AND
     $2 >= begin_snapshot AND
-    ($2 < end_snapshot OR end_snapshot IS NULL)
-ORDER BY column_order; 
\ No newline at end of file
+    begin_snapshot NOT IN ({hidden}) AND
+    ($2 < end_snapshot OR end_snapshot IS NULL OR
+        end_snapshot IN ({hidden}))
+ORDER BY column_order;
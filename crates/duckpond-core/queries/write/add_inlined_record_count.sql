@@ -0,0 +1,5 @@
+UPDATE ducklake_table_stats
+SET record_count = record_count + (
+    SELECT COUNT(*) FROM {table} WHERE end_snapshot IS NULL
+)
+WHERE table_id = $1;
@@ -0,0 +1,12 @@
+INSERT INTO ducklake_table_stats (
+    table_id,
+    record_count,
+    next_row_id,
+    file_size_bytes
+)
+VALUES ($1, $2, $2, $3)
+ON CONFLICT (table_id) DO UPDATE SET
+    record_count = ducklake_table_stats.record_count + excluded.record_count,
+    next_row_id = ducklake_table_stats.next_row_id + excluded.next_row_id,
+    file_size_bytes = ducklake_table_stats.file_size_bytes + excluded.file_size_bytes
+RETURNING next_row_id - $2 AS row_id_start;
@@ -0,0 +1,37 @@
+INSERT INTO ducklake_data_file (
+    data_file_id,
+    table_id,
+    begin_snapshot,
+    end_snapshot,
+    file_order,
+    path,
+    path_is_relative,
+    file_format,
+    record_count,
+    file_size_bytes,
+    footer_size,
+    row_id_start,
+    partition_id,
+    encryption_key,
+    partial_file_info,
+    mapping_id
+)
+SELECT
+    $1,
+    table_id,
+    $2,
+    NULL,
+    file_order,
+    path,
+    path_is_relative,
+    file_format,
+    record_count,
+    file_size_bytes,
+    footer_size,
+    row_id_start,
+    partition_id,
+    encryption_key,
+    partial_file_info,
+    mapping_id
+FROM ducklake_data_file
+WHERE data_file_id = $3;
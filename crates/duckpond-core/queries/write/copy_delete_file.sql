@@ -0,0 +1,29 @@
+INSERT INTO ducklake_delete_file (
+    delete_file_id,
+    table_id,
+    begin_snapshot,
+    end_snapshot,
+    data_file_id,
+    path,
+    path_is_relative,
+    format,
+    delete_count,
+    file_size_bytes,
+    footer_size,
+    encryption_key
+)
+SELECT
+    $1,
+    table_id,
+    $2,
+    NULL,
+    $3,
+    path,
+    path_is_relative,
+    format,
+    delete_count,
+    file_size_bytes,
+    footer_size,
+    encryption_key
+FROM ducklake_delete_file
+WHERE delete_file_id = $4;
@@ -0,0 +1,23 @@
+INSERT INTO ducklake_file_column_statistics (
+    data_file_id,
+    table_id,
+    column_id,
+    column_size_bytes,
+    value_count,
+    null_count,
+    min_value,
+    max_value,
+    contains_nan
+)
+SELECT
+    $1,
+    table_id,
+    column_id,
+    column_size_bytes,
+    value_count,
+    null_count,
+    min_value,
+    max_value,
+    contains_nan
+FROM ducklake_file_column_statistics
+WHERE data_file_id = $2;
@@ -0,0 +1,9 @@
+INSERT INTO ducklake_file_partition_value (
+    data_file_id,
+    table_id,
+    partition_key_index,
+    partition_value
+)
+SELECT $1, table_id, partition_key_index, partition_value
+FROM ducklake_file_partition_value
+WHERE data_file_id = $2;
@@ -6,6 +6,7 @@ INSERT INTO ducklake_column (
     column_order,
     column_name,
     column_type,
-    nulls_allowed
+    nulls_allowed,
+    parent_column
 )
-VALUES ($1, $2, NULL, $3, $4, $5, $6, $7); 
\ No newline at end of file
+VALUES ($1, $2, NULL, $3, $4, $5, $6, $7, $8);
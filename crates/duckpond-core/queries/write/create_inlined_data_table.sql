@@ -0,0 +1,5 @@
+CREATE TABLE {table} (
+    row_id BIGINT NOT NULL,
+    begin_snapshot BIGINT NOT NULL,
+    end_snapshot BIGINT{columns}
+);
@@ -3,6 +3,8 @@ INSERT INTO ducklake_schema (
     schema_uuid,
     begin_snapshot,
     end_snapshot,
-    schema_name
+    schema_name,
+    path,
+    path_is_relative
 )
-VALUES ($1, $2, $3, NULL, $4); 
\ No newline at end of file
+VALUES ($1, $2, $3, NULL, $4, $5, $6);
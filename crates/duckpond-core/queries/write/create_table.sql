@@ -4,6 +4,8 @@ INSERT INTO ducklake_table (
     begin_snapshot,
     end_snapshot,
     schema_id,
-    table_name
+    table_name,
+    path,
+    path_is_relative
 )
-VALUES ($1, $2, $3, NULL, $4, $5); 
\ No newline at end of file
+VALUES ($1, $2, $3, NULL, $4, $5, $6, $7);
@@ -0,0 +1,12 @@
+INSERT INTO ducklake_view (
+    view_id,
+    view_uuid,
+    begin_snapshot,
+    end_snapshot,
+    schema_id,
+    view_name,
+    dialect,
+    "sql",
+    column_aliases
+)
+VALUES ($1, $2, $3, NULL, $4, $5, $6, $7, $8);
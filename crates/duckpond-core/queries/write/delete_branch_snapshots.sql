@@ -0,0 +1,2 @@
+DELETE FROM duckpond_branch_snapshot
+WHERE branch_name = $1;
@@ -0,0 +1,4 @@
+DELETE FROM ducklake_inlined_data_tables
+WHERE schema_snapshot IN (
+    SELECT snapshot_id FROM duckpond_branch_snapshot WHERE branch_name = $1
+);
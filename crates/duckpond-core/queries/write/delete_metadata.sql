@@ -0,0 +1,2 @@
+DELETE FROM ducklake_metadata
+WHERE "key" = $1;
@@ -0,0 +1,2 @@
+DELETE FROM {table}
+WHERE data_file_id NOT IN (SELECT data_file_id FROM ducklake_data_file);
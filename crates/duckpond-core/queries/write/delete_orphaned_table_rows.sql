@@ -0,0 +1,2 @@
+DELETE FROM {table}
+WHERE table_id NOT IN (SELECT table_id FROM ducklake_table);
@@ -0,0 +1,4 @@
+DELETE FROM {table}
+WHERE begin_snapshot IN (
+    SELECT snapshot_id FROM duckpond_branch_snapshot WHERE branch_name = $1
+);
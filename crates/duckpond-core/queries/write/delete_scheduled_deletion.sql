@@ -0,0 +1,2 @@
+DELETE FROM ducklake_files_scheduled_for_deletion
+WHERE data_file_id = $1 AND path = $2;
@@ -0,0 +1,2 @@
+DELETE FROM ducklake_snapshot
+WHERE snapshot_id = $1;
@@ -0,0 +1,2 @@
+DELETE FROM ducklake_snapshot_changes
+WHERE snapshot_id = $1;
@@ -0,0 +1,2 @@
+DELETE FROM duckpond_snapshot_ref
+WHERE name = $1;
@@ -0,0 +1,2 @@
+DELETE FROM ducklake_table_column_stats
+WHERE $1 IS NULL OR table_id = $1;
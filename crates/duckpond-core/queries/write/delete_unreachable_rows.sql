@@ -0,0 +1,9 @@
+DELETE FROM {table}
+WHERE
+    end_snapshot IS NOT NULL AND
+    NOT EXISTS (
+        SELECT 1 FROM ducklake_snapshot
+        WHERE
+            ducklake_snapshot.snapshot_id >= {table}.begin_snapshot AND
+            ducklake_snapshot.snapshot_id < {table}.end_snapshot
+    );
@@ -0,0 +1,7 @@
+UPDATE ducklake_column_tag SET
+    end_snapshot = $4
+WHERE
+    table_id = $1 AND
+    column_id = $2 AND
+    "key" = $3 AND
+    {live};
@@ -0,0 +1,6 @@
+UPDATE {table}
+SET end_snapshot = $1
+WHERE
+    row_id >= $2 AND
+    row_id < $3 AND
+    {live};
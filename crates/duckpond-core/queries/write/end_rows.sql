@@ -0,0 +1,5 @@
+UPDATE {table}
+SET end_snapshot = $2
+WHERE
+    {column} = $1 AND
+    {live};
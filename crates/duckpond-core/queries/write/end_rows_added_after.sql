@@ -0,0 +1,5 @@
+UPDATE {table}
+SET end_snapshot = $1
+WHERE
+    begin_snapshot > $2 AND
+    {live}{filter};
@@ -0,0 +1,6 @@
+UPDATE ducklake_tag SET
+    end_snapshot = $3
+WHERE
+    object_id = $1 AND
+    "key" = $2 AND
+    {live};
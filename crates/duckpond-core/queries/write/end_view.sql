@@ -0,0 +1,3 @@
+UPDATE ducklake_view
+SET end_snapshot = $2
+WHERE view_id = $1 AND {live};
@@ -0,0 +1,2 @@
+INSERT INTO duckpond_branch_snapshot (snapshot_id, branch_name)
+VALUES ($1, $2);
@@ -0,0 +1,9 @@
+INSERT INTO ducklake_column_tag (
+    table_id,
+    column_id,
+    begin_snapshot,
+    end_snapshot,
+    "key",
+    value
+)
+VALUES ($1, $2, $3, NULL, $4, $5);
@@ -0,0 +1,2 @@
+INSERT INTO ducklake_inlined_data_tables (table_id, table_name, schema_snapshot)
+VALUES ($1, $2, $3);
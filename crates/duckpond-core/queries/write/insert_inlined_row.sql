@@ -0,0 +1,2 @@
+INSERT INTO {table} (row_id, begin_snapshot, end_snapshot{columns})
+VALUES ($1, $2, NULL{values});
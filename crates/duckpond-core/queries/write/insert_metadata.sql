@@ -0,0 +1,5 @@
+INSERT INTO ducklake_metadata (
+    "key",
+    value
+)
+VALUES ($1, $2);
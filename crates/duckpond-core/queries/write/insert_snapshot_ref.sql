@@ -0,0 +1,7 @@
+INSERT INTO duckpond_snapshot_ref (
+    name,
+    ref_type,
+    snapshot_id,
+    base_snapshot
+)
+VALUES ($1, $2, $3, $4);
@@ -0,0 +1,8 @@
+INSERT INTO ducklake_tag (
+    object_id,
+    begin_snapshot,
+    end_snapshot,
+    "key",
+    value
+)
+VALUES ($1, $2, NULL, $3, $4);
@@ -0,0 +1,21 @@
+INSERT INTO ducklake_table_column_stats (
+    table_id,
+    column_id,
+    contains_null,
+    contains_nan,
+    min_value,
+    max_value
+)
+SELECT
+    s.table_id,
+    s.column_id,
+    SUM(s.null_count) > 0,
+    MAX(CASE WHEN s.contains_nan THEN 1 ELSE 0 END) > 0,
+    MIN(s.min_value),
+    MAX(s.max_value)
+FROM ducklake_file_column_statistics AS s
+JOIN ducklake_data_file AS f ON f.data_file_id = s.data_file_id
+WHERE
+    f.end_snapshot IS NULL AND
+    ($1 IS NULL OR s.table_id = $1)
+GROUP BY s.table_id, s.column_id;
@@ -0,0 +1,13 @@
+UPDATE ducklake_table_stats
+SET
+    record_count = (
+        SELECT COALESCE(SUM(f.record_count), 0)
+        FROM ducklake_data_file AS f
+        WHERE f.table_id = ducklake_table_stats.table_id AND f.end_snapshot IS NULL
+    ),
+    file_size_bytes = (
+        SELECT COALESCE(SUM(f.file_size_bytes), 0)
+        FROM ducklake_data_file AS f
+        WHERE f.table_id = ducklake_table_stats.table_id AND f.end_snapshot IS NULL
+    )
+WHERE $1 IS NULL OR table_id = $1;
@@ -0,0 +1,5 @@
+UPDATE ducklake_table_stats
+SET
+    record_count = record_count - $2,
+    file_size_bytes = file_size_bytes - $3
+WHERE table_id = $1;
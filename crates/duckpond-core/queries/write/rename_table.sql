@@ -0,0 +1,13 @@
+INSERT INTO ducklake_table (
+    table_id,
+    table_uuid,
+    begin_snapshot,
+    end_snapshot,
+    schema_id,
+    table_name,
+    path,
+    path_is_relative
+)
+SELECT table_id, table_uuid, $2, NULL, schema_id, $3, path, path_is_relative
+FROM ducklake_table
+WHERE table_id = $1 AND end_snapshot = $2;
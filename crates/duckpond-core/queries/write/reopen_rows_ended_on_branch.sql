@@ -0,0 +1,5 @@
+UPDATE {table}
+SET end_snapshot = NULL
+WHERE end_snapshot IN (
+    SELECT snapshot_id FROM duckpond_branch_snapshot WHERE branch_name = $1
+);
@@ -0,0 +1,8 @@
+INSERT INTO {table} ({columns}, begin_snapshot, end_snapshot)
+SELECT {columns}, $1, NULL
+FROM {table}
+WHERE
+    begin_snapshot <= $2 AND
+    begin_snapshot NOT IN ({hidden}) AND
+    end_snapshot > $2 AND
+    end_snapshot NOT IN ({hidden}){filter};
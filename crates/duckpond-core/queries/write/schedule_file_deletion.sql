@@ -0,0 +1,7 @@
+INSERT INTO ducklake_files_scheduled_for_deletion (
+    data_file_id,
+    path,
+    path_is_relative,
+    schedule_start
+)
+VALUES ($1, $2, $3, $4);
@@ -0,0 +1,3 @@
+UPDATE {table}
+SET path = $2, path_is_relative = $3
+WHERE {column} = $1;
@@ -0,0 +1,3 @@
+UPDATE duckpond_snapshot_ref
+SET snapshot_id = $2, base_snapshot = $3
+WHERE name = $1;
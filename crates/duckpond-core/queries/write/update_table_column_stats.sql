@@ -1,4 +1,4 @@
-INSERT OR REPLACE INTO ducklake_table_column_stats (
+INSERT INTO ducklake_table_column_stats (
     table_id,
     column_id,
     contains_null,
@@ -6,27 +6,20 @@ INSERT OR REPLACE INTO ducklake_table_column_stats (
     min_value,
     max_value
 )
-WITH existing AS (
-    SELECT contains_null, contains_nan, min_value, max_value 
-    FROM ducklake_table_column_stats 
-    WHERE table_id = $1 AND column_id = $2
-)
-SELECT 
-    $1,
-    $2,
-    COALESCE(existing.contains_null, false) OR ($3 > 0),
-    COALESCE(existing.contains_nan, false) OR ($4 > 0),
-    CASE 
-        WHEN $5 IS NULL THEN existing.min_value
-        WHEN existing.min_value IS NULL THEN $5
-        WHEN existing.min_value < $5 THEN existing.min_value
-        ELSE $5
+VALUES ($1, $2, ($3 > 0), ($4 > 0), $5, $6)
+ON CONFLICT (table_id, column_id) DO UPDATE SET
+    contains_null = ducklake_table_column_stats.contains_null OR excluded.contains_null,
+    contains_nan = COALESCE(ducklake_table_column_stats.contains_nan, false)
+        OR COALESCE(excluded.contains_nan, false),
+    min_value = CASE
+        WHEN excluded.min_value IS NULL THEN ducklake_table_column_stats.min_value
+        WHEN ducklake_table_column_stats.min_value IS NULL THEN excluded.min_value
+        WHEN excluded.min_value < ducklake_table_column_stats.min_value THEN excluded.min_value
+        ELSE ducklake_table_column_stats.min_value
     END,
-    CASE 
-        WHEN $6 IS NULL THEN existing.max_value
-        WHEN existing.max_value IS NULL THEN $6
-        WHEN existing.max_value > $6 THEN existing.max_value
-        ELSE $6
-    END
-FROM (SELECT 1) dummy
-LEFT JOIN existing ON 1=1; 
\ No newline at end of file
+    max_value = CASE
+        WHEN excluded.max_value IS NULL THEN ducklake_table_column_stats.max_value
+        WHEN ducklake_table_column_stats.max_value IS NULL THEN excluded.max_value
+        WHEN excluded.max_value > ducklake_table_column_stats.max_value THEN excluded.max_value
+        ELSE ducklake_table_column_stats.max_value
+    END;
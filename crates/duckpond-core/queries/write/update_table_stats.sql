@@ -1,5 +1,11 @@
-UPDATE ducklake_table_stats SET
-    record_count = record_count + $2,
-    next_row_id = next_row_id + $2,
-    file_size_bytes = file_size_bytes + $3
-WHERE table_id = $1; 
\ No newline at end of file
+INSERT INTO ducklake_table_stats (
+    table_id,
+    record_count,
+    next_row_id,
+    file_size_bytes
+)
+VALUES ($1, $2, $2, $3)
+ON CONFLICT (table_id) DO UPDATE SET
+    record_count = ducklake_table_stats.record_count + excluded.record_count,
+    next_row_id = ducklake_table_stats.next_row_id + excluded.next_row_id,
+    file_size_bytes = ducklake_table_stats.file_size_bytes + excluded.file_size_bytes;
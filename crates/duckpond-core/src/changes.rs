@@ -0,0 +1,315 @@
+//! Structured records of the changes made by a snapshot
+//!
+//! `ducklake_snapshot_changes.changes_made` holds a comma-separated list of
+//! entries as described by the DuckLake specification, e.g.
+//! `created_table:"main"."events",inserted_into_table:12`. Objects that are
+//! created are referred to by their quoted name, all others by their ID.
+
+use crate::error::{DuckPondError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A single change made by a snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnapshotChange {
+    CreatedSchema {
+        schema: String,
+    },
+    CreatedTable {
+        schema: String,
+        table: String,
+    },
+    CreatedView {
+        schema: String,
+        view: String,
+    },
+    InsertedIntoTable {
+        table_id: i64,
+    },
+    DeletedFromTable {
+        table_id: i64,
+    },
+    CompactedTable {
+        table_id: i64,
+    },
+    AlteredTable {
+        table_id: i64,
+    },
+    AlteredView {
+        view_id: i64,
+    },
+    DroppedSchema {
+        schema_id: i64,
+    },
+    DroppedTable {
+        table_id: i64,
+    },
+    DroppedView {
+        view_id: i64,
+    },
+    /// A change log that does not follow the spec format, such as the
+    /// free-form descriptions written by earlier versions
+    Other(String),
+}
+
+impl SnapshotChange {
+    /// ID of the table this change touches, if it refers to one by ID
+    pub fn table_id(&self) -> Option<i64> {
+        match self {
+            SnapshotChange::InsertedIntoTable { table_id }
+            | SnapshotChange::DeletedFromTable { table_id }
+            | SnapshotChange::CompactedTable { table_id }
+            | SnapshotChange::AlteredTable { table_id }
+            | SnapshotChange::DroppedTable { table_id } => Some(*table_id),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SnapshotChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotChange::CreatedSchema { schema } => {
+                write!(f, "created_schema:{}", quote(schema))
+            }
+            SnapshotChange::CreatedTable { schema, table } => {
+                write!(f, "created_table:{}.{}", quote(schema), quote(table))
+            }
+            SnapshotChange::CreatedView { schema, view } => {
+                write!(f, "created_view:{}.{}", quote(schema), quote(view))
+            }
+            SnapshotChange::InsertedIntoTable { table_id } => {
+                write!(f, "inserted_into_table:{}", table_id)
+            }
+            SnapshotChange::DeletedFromTable { table_id } => {
+                write!(f, "deleted_from_table:{}", table_id)
+            }
+            SnapshotChange::CompactedTable { table_id } => {
+                write!(f, "compacted_table:{}", table_id)
+            }
+            SnapshotChange::AlteredTable { table_id } => write!(f, "altered_table:{}", table_id),
+            SnapshotChange::AlteredView { view_id } => write!(f, "altered_view:{}", view_id),
+            SnapshotChange::DroppedSchema { schema_id } => {
+                write!(f, "dropped_schema:{}", schema_id)
+            }
+            SnapshotChange::DroppedTable { table_id } => write!(f, "dropped_table:{}", table_id),
+            SnapshotChange::DroppedView { view_id } => write!(f, "dropped_view:{}", view_id),
+            SnapshotChange::Other(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+/// Format changes as a `changes_made` value
+pub fn format_changes(changes: &[SnapshotChange]) -> String {
+    changes
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Parse a `changes_made` value
+///
+/// Values that do not follow the spec format are returned whole as a single
+/// [`SnapshotChange::Other`].
+pub fn parse_changes(changes_made: &str) -> Vec<SnapshotChange> {
+    if changes_made.is_empty() {
+        return Vec::new();
+    }
+    parse_entries(changes_made)
+        .unwrap_or_else(|_| vec![SnapshotChange::Other(changes_made.to_string())])
+}
+
+impl FromStr for SnapshotChange {
+    type Err = DuckPondError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match parse_entries(s)?.as_slice() {
+            [change] => Ok(change.clone()),
+            _ => Err(invalid_change(s)),
+        }
+    }
+}
+
+fn parse_entries(s: &str) -> Result<Vec<SnapshotChange>> {
+    let mut changes = Vec::new();
+    let mut rest = s;
+
+    loop {
+        let (kind, value) = rest.split_once(':').ok_or_else(|| invalid_change(s))?;
+        let (names, id, remaining) = parse_value(value).ok_or_else(|| invalid_change(s))?;
+
+        let change = match (kind, names.as_slice(), id) {
+            ("created_schema", [schema], None) => SnapshotChange::CreatedSchema {
+                schema: schema.clone(),
+            },
+            ("created_table", [schema, table], None) => SnapshotChange::CreatedTable {
+                schema: schema.clone(),
+                table: table.clone(),
+            },
+            ("created_view", [schema, view], None) => SnapshotChange::CreatedView {
+                schema: schema.clone(),
+                view: view.clone(),
+            },
+            ("inserted_into_table", [], Some(table_id)) => {
+                SnapshotChange::InsertedIntoTable { table_id }
+            }
+            ("deleted_from_table", [], Some(table_id)) => {
+                SnapshotChange::DeletedFromTable { table_id }
+            }
+            ("compacted_table", [], Some(table_id)) => SnapshotChange::CompactedTable { table_id },
+            ("altered_table", [], Some(table_id)) => SnapshotChange::AlteredTable { table_id },
+            ("altered_view", [], Some(view_id)) => SnapshotChange::AlteredView { view_id },
+            ("dropped_schema", [], Some(schema_id)) => SnapshotChange::DroppedSchema { schema_id },
+            ("dropped_table", [], Some(table_id)) => SnapshotChange::DroppedTable { table_id },
+            ("dropped_view", [], Some(view_id)) => SnapshotChange::DroppedView { view_id },
+            _ => return Err(invalid_change(s)),
+        };
+        changes.push(change);
+
+        match remaining.strip_prefix(',') {
+            Some(next) => rest = next,
+            None if remaining.is_empty() => return Ok(changes),
+            None => return Err(invalid_change(s)),
+        }
+    }
+}
+
+/// Parse either an ID or a dot-separated list of quoted names, returning the
+/// unparsed remainder
+fn parse_value(value: &str) -> Option<(Vec<String>, Option<i64>, &str)> {
+    if !value.starts_with('"') {
+        let end = value.find(',').unwrap_or(value.len());
+        let id = value[..end].parse().ok()?;
+        return Some((Vec::new(), Some(id), &value[end..]));
+    }
+
+    let mut names = Vec::new();
+    let mut rest = value;
+    loop {
+        let (name, remaining) = parse_quoted(rest)?;
+        names.push(name);
+        match remaining.strip_prefix('.') {
+            Some(next) => rest = next,
+            None => return Some((names, None, remaining)),
+        }
+    }
+}
+
+/// Parse a double-quoted name in which `""` stands for a literal quote
+fn parse_quoted(s: &str) -> Option<(String, &str)> {
+    let mut chars = s.strip_prefix('"')?.char_indices().peekable();
+    let mut name = String::new();
+    while let Some((i, c)) = chars.next() {
+        if c == '"' {
+            if chars.peek().map(|(_, next)| *next) == Some('"') {
+                chars.next();
+                name.push('"');
+            } else {
+                return Some((name, &s[i + 2..]));
+            }
+        } else {
+            name.push(c);
+        }
+    }
+    None
+}
+
+/// Format names as a comma-separated list of quoted names, as used for view
+/// column aliases
+pub(crate) fn format_quoted_list(names: &[String]) -> String {
+    names.iter().map(|n| quote(n)).collect::<Vec<_>>().join(",")
+}
+
+/// Parse a list written by [`format_quoted_list`]
+pub(crate) fn parse_quoted_list(s: &str) -> Option<Vec<String>> {
+    let mut names = Vec::new();
+    let mut rest = s.trim();
+    while !rest.is_empty() {
+        let (name, remaining) = parse_quoted(rest)?;
+        names.push(name);
+        let remaining = remaining.trim_start();
+        rest = match remaining.strip_prefix(',') {
+            Some(next) => next.trim_start(),
+            None if remaining.is_empty() => remaining,
+            None => return None,
+        };
+    }
+    Some(names)
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn invalid_change(s: &str) -> DuckPondError {
+    DuckPondError::InvalidSnapshotChange {
+        message: format!("cannot parse snapshot change '{}'", s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quoted_list() {
+        let names = vec!["a".to_string(), "b, \"c\"".to_string()];
+        let formatted = format_quoted_list(&names);
+        assert_eq!(formatted, r#""a","b, ""c""""#);
+        assert_eq!(parse_quoted_list(&formatted), Some(names));
+        assert_eq!(parse_quoted_list(""), Some(vec![]));
+        assert_eq!(
+            parse_quoted_list("\"a\", \"b\""),
+            Some(vec!["a".into(), "b".into()])
+        );
+        assert_eq!(parse_quoted_list("a"), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let changes = vec![
+            SnapshotChange::CreatedSchema {
+                schema: "main".to_string(),
+            },
+            SnapshotChange::CreatedTable {
+                schema: "main".to_string(),
+                table: "odd, \"name\"".to_string(),
+            },
+            SnapshotChange::InsertedIntoTable { table_id: 12 },
+            SnapshotChange::DroppedTable { table_id: 3 },
+        ];
+
+        let text = format_changes(&changes);
+        assert_eq!(
+            text,
+            r#"created_schema:"main",created_table:"main"."odd, ""name""",inserted_into_table:12,dropped_table:3"#
+        );
+        assert_eq!(parse_changes(&text), changes);
+    }
+
+    #[test]
+    fn test_legacy_free_form_changes() {
+        assert_eq!(
+            parse_changes("INSERT DATA FILE a/b.parquet"),
+            vec![SnapshotChange::Other(
+                "INSERT DATA FILE a/b.parquet".to_string()
+            )]
+        );
+        assert!(parse_changes("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_single_change() {
+        let change: SnapshotChange = "altered_table:7".parse().unwrap();
+        assert_eq!(change, SnapshotChange::AlteredTable { table_id: 7 });
+        assert_eq!(change.table_id(), Some(7));
+
+        assert!("altered_table:x".parse::<SnapshotChange>().is_err());
+        assert!("created_table:\"main\"".parse::<SnapshotChange>().is_err());
+        assert!("altered_table:1,altered_table:2"
+            .parse::<SnapshotChange>()
+            .is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::dialect::Dialect;
 use crate::{config::DuckPondConfig, error::Result};
 use sqlx::any::AnyPoolOptions;
 use sqlx::{Any, AnyPool, Pool};
@@ -6,12 +7,14 @@ use std::time::Duration;
 /// Database connection manager for DuckPond catalog
 pub struct Database {
     pool: Pool<Any>,
+    backend: BackendInfo,
 }
 
 impl Database {
     /// Create a new database connection from configuration
     pub async fn new(config: &DuckPondConfig) -> Result<Self> {
         config.validate()?;
+        let database_type = config.detect_database_type()?;
 
         // Install drivers for AnyPool to work
         sqlx::any::install_default_drivers();
@@ -22,7 +25,9 @@ impl Database {
             .connect(&config.database_url)
             .await?;
 
-        Ok(Self { pool })
+        let backend = BackendInfo::detect(&pool, database_type).await?;
+
+        Ok(Self { pool, backend })
     }
 
     /// Get a reference to the connection pool
@@ -30,49 +35,25 @@ impl Database {
         &self.pool
     }
 
-    /// Check if the database is healthy
-    pub async fn health_check(&self) -> Result<()> {
-        sqlx::query("SELECT 1").execute(&self.pool).await?;
-        Ok(())
+    /// Get the backend this database connects to
+    pub fn backend(&self) -> &BackendInfo {
+        &self.backend
     }
 
-    /// Get the current database version/state
-    pub async fn get_version(&self) -> Result<String> {
-        // Use database-specific version query
-        let version_query = match self.get_database_type().await? {
-            DatabaseType::PostgreSQL => "SELECT version()",
-            DatabaseType::Postgres => "SELECT version()",
-            DatabaseType::MySQL => "SELECT VERSION()",
-            DatabaseType::SQLite => "SELECT sqlite_version()",
-        };
-
-        let row = sqlx::query_scalar::<_, String>(version_query)
-            .fetch_one(&self.pool)
-            .await?;
-        Ok(row)
+    /// Get the database type, as detected from the connection URL
+    pub fn database_type(&self) -> DatabaseType {
+        self.backend.database_type
     }
 
-    /// Detect the database type from the connection
-    pub async fn get_database_type(&self) -> Result<DatabaseType> {
-        // Try to determine database type by attempting database-specific queries
-        if sqlx::query("SELECT 1::INTEGER")
-            .execute(&self.pool)
-            .await
-            .is_ok()
-        {
-            return Ok(DatabaseType::PostgreSQL);
-        }
-
-        if sqlx::query("SELECT 1 FROM DUAL")
-            .execute(&self.pool)
-            .await
-            .is_ok()
-        {
-            return Ok(DatabaseType::MySQL);
-        }
+    /// Get the version reported by the database server
+    pub fn server_version(&self) -> &str {
+        &self.backend.server_version
+    }
 
-        // Default to SQLite if other checks fail
-        Ok(DatabaseType::SQLite)
+    /// Check if the database is healthy
+    pub async fn health_check(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
     }
 }
 
@@ -95,7 +76,102 @@ pub async fn create_pool(database_url: &str, db_type: DatabaseType) -> Result<An
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DatabaseType {
     PostgreSQL,
-    Postgres, // Alias for PostgreSQL
     MySQL,
     SQLite,
 }
+
+/// A catalog database backend and the features its server supports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackendInfo {
+    pub database_type: DatabaseType,
+    pub server_version: String,
+}
+
+impl BackendInfo {
+    /// Query the server version of a connected catalog database
+    pub async fn detect(pool: &AnyPool, database_type: DatabaseType) -> Result<Self> {
+        let version_query = match database_type {
+            DatabaseType::PostgreSQL => "SELECT current_setting('server_version')",
+            DatabaseType::MySQL => "SELECT VERSION()",
+            DatabaseType::SQLite => "SELECT sqlite_version()",
+        };
+
+        let server_version = sqlx::query_scalar::<_, String>(version_query)
+            .fetch_one(pool)
+            .await?;
+
+        Ok(Self {
+            database_type,
+            server_version,
+        })
+    }
+
+    /// SQL dialect spoken by this backend
+    pub fn dialect(&self) -> Dialect {
+        self.database_type.into()
+    }
+
+    /// Whether `INSERT`/`UPDATE ... RETURNING` is available
+    pub fn supports_returning(&self) -> bool {
+        match self.database_type {
+            DatabaseType::PostgreSQL => true,
+            // RETURNING was added in SQLite 3.35
+            DatabaseType::SQLite => self.version_at_least(3, 35),
+            DatabaseType::MySQL => false,
+        }
+    }
+
+    /// Whether rows can be locked with `SELECT ... FOR UPDATE`
+    pub fn supports_row_locks(&self) -> bool {
+        match self.database_type {
+            DatabaseType::PostgreSQL | DatabaseType::MySQL => true,
+            // SQLite locks the whole database file instead
+            DatabaseType::SQLite => false,
+        }
+    }
+
+    /// Leading `major.minor` of the server version, e.g. `(15, 8)` for
+    /// "15.8 (Debian 15.8-1)"
+    fn version(&self) -> Option<(u32, u32)> {
+        let mut parts = self
+            .server_version
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|part| !part.is_empty())
+            .map(str::parse::<u32>);
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().and_then(|part| part.ok()).unwrap_or(0);
+        Some((major, minor))
+    }
+
+    fn version_at_least(&self, major: u32, minor: u32) -> bool {
+        self.version()
+            .is_some_and(|version| version >= (major, minor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backend(database_type: DatabaseType, server_version: &str) -> BackendInfo {
+        BackendInfo {
+            database_type,
+            server_version: server_version.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let postgres = backend(DatabaseType::PostgreSQL, "15.8 (Debian 15.8-1.pgdg120+1)");
+        assert!(postgres.supports_returning());
+        assert!(postgres.supports_row_locks());
+
+        let mysql = backend(DatabaseType::MySQL, "8.0.36");
+        assert!(!mysql.supports_returning());
+        assert!(mysql.supports_row_locks());
+
+        assert!(backend(DatabaseType::SQLite, "3.45.1").supports_returning());
+        assert!(!backend(DatabaseType::SQLite, "3.34.0").supports_returning());
+        assert!(!backend(DatabaseType::SQLite, "3.45.1").supports_row_locks());
+    }
+}
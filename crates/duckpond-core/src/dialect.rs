@@ -0,0 +1,335 @@
+//! SQL dialects of the supported catalog databases
+//!
+//! Catalog statements under `queries/` are written once in a portable form:
+//! Postgres-style `$N` placeholders (which may repeat) and ANSI double-quoted
+//! identifiers. A [`Dialect`] renders such a statement for its backend and
+//! binds the arguments in the order the backend expects. Statements whose
+//! syntax genuinely differs between backends (upserts, timestamp casts) have
+//! per-backend variants under `queries/<backend>/`.
+
+use crate::database::DatabaseType;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sqlx::any::{AnyArguments, AnyQueryResult, AnyRow};
+use sqlx::migrate::Migrator;
+use sqlx::query::Query;
+use sqlx::{Any, Executor};
+
+static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+static MYSQL_MIGRATOR: Migrator = sqlx::migrate!("./migrations/mysql");
+
+/// SQL dialect spoken by a catalog database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    SQLite,
+    PostgreSQL,
+    MySQL,
+}
+
+impl From<DatabaseType> for Dialect {
+    fn from(database_type: DatabaseType) -> Self {
+        match database_type {
+            DatabaseType::SQLite => Dialect::SQLite,
+            DatabaseType::PostgreSQL => Dialect::PostgreSQL,
+            DatabaseType::MySQL => Dialect::MySQL,
+        }
+    }
+}
+
+impl Dialect {
+    /// Migrations creating the catalog tables for this backend
+    pub fn migrator(&self) -> &'static Migrator {
+        match self {
+            Dialect::SQLite => &SQLITE_MIGRATOR,
+            Dialect::PostgreSQL => &POSTGRES_MIGRATOR,
+            Dialect::MySQL => &MYSQL_MIGRATOR,
+        }
+    }
+
+    /// Format a timestamp for binding to a snapshot time column
+    pub fn format_timestamp(&self, timestamp: DateTime<Utc>) -> String {
+        match self {
+            // DATETIME(6) has no zone; timestamps are always stored as UTC
+            Dialect::MySQL => timestamp.format("%Y-%m-%d %H:%M:%S%.6f").to_string(),
+            Dialect::SQLite | Dialect::PostgreSQL => timestamp.to_rfc3339(),
+        }
+    }
+
+    /// Parse a timestamp column selected as text
+    ///
+    /// SQLite returns the RFC 3339 text it was given, Postgres renders
+    /// `TIMESTAMPTZ` with a short offset (`+00`) and `TIMESTAMP`/`DATETIME`
+    /// columns have no offset at all; those are stored as UTC.
+    pub fn parse_timestamp(&self, value: &str) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(value)
+            .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%#z"))
+            .map(|timestamp| timestamp.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f")
+                    .ok()
+                    .map(|timestamp| timestamp.and_utc())
+            })
+    }
+
+    /// Start building a catalog statement written in the portable form
+    pub fn query(self, sql: &str) -> CatalogQuery<'_> {
+        CatalogQuery {
+            dialect: self,
+            sql,
+            args: Vec::new(),
+        }
+    }
+
+    /// Render portable SQL for this dialect
+    ///
+    /// Returns the SQL together with the indices of `args` to bind, in
+    /// order. NULL arguments are inlined as literals: the `Any` driver sends
+    /// NULLs as integers, which Postgres refuses to compare with or store
+    /// into text columns.
+    fn render(&self, sql: &str, args: &[QueryArg]) -> (String, Vec<usize>) {
+        let mut rendered = String::with_capacity(sql.len());
+        let mut bind_order = Vec::new();
+        let mut numbering: Vec<Option<usize>> = vec![None; args.len()];
+        let mut in_literal = false;
+        let mut chars = sql.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\'' => {
+                    in_literal = !in_literal;
+                    rendered.push(c);
+                }
+                '"' if !in_literal && *self == Dialect::MySQL => rendered.push('`'),
+                '$' if !in_literal && chars.peek().is_some_and(char::is_ascii_digit) => {
+                    let mut digits = String::new();
+                    while let Some(d) = chars.peek().copied().filter(char::is_ascii_digit) {
+                        digits.push(d);
+                        chars.next();
+                    }
+                    let index = digits.parse::<usize>().unwrap_or(0).wrapping_sub(1);
+
+                    match args.get(index) {
+                        None | Some(QueryArg::Null) => rendered.push_str("NULL"),
+                        Some(_) if *self == Dialect::MySQL => {
+                            rendered.push('?');
+                            bind_order.push(index);
+                        }
+                        Some(_) => {
+                            let number = *numbering[index].get_or_insert_with(|| {
+                                bind_order.push(index);
+                                bind_order.len()
+                            });
+                            rendered.push('$');
+                            rendered.push_str(&number.to_string());
+                        }
+                    }
+                }
+                _ => rendered.push(c),
+            }
+        }
+
+        (rendered, bind_order)
+    }
+}
+
+/// A value bound to a catalog statement
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryArg {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl From<bool> for QueryArg {
+    fn from(value: bool) -> Self {
+        QueryArg::Bool(value)
+    }
+}
+
+impl From<i64> for QueryArg {
+    fn from(value: i64) -> Self {
+        QueryArg::Int(value)
+    }
+}
+
+impl From<f64> for QueryArg {
+    fn from(value: f64) -> Self {
+        QueryArg::Float(value)
+    }
+}
+
+impl From<&str> for QueryArg {
+    fn from(value: &str) -> Self {
+        QueryArg::Text(value.to_string())
+    }
+}
+
+impl From<String> for QueryArg {
+    fn from(value: String) -> Self {
+        QueryArg::Text(value)
+    }
+}
+
+impl<T: Into<QueryArg>> From<Option<T>> for QueryArg {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or(QueryArg::Null)
+    }
+}
+
+/// A catalog statement and its arguments, rendered for a dialect on execution
+pub struct CatalogQuery<'q> {
+    dialect: Dialect,
+    sql: &'q str,
+    args: Vec<QueryArg>,
+}
+
+impl<'q> CatalogQuery<'q> {
+    /// Bind the next `$N` argument
+    pub fn bind(mut self, value: impl Into<QueryArg>) -> Self {
+        self.args.push(value.into());
+        self
+    }
+
+    /// Execute the statement, returning the number of rows affected
+    pub async fn execute<'c, E>(self, executor: E) -> Result<AnyQueryResult, sqlx::Error>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let (sql, bind_order) = self.dialect.render(self.sql, &self.args);
+        build_query(&sql, &self.args, &bind_order)
+            .execute(executor)
+            .await
+    }
+
+    /// Fetch exactly one row
+    pub async fn fetch_one<'c, E>(self, executor: E) -> Result<AnyRow, sqlx::Error>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let (sql, bind_order) = self.dialect.render(self.sql, &self.args);
+        build_query(&sql, &self.args, &bind_order)
+            .fetch_one(executor)
+            .await
+    }
+
+    /// Fetch at most one row
+    pub async fn fetch_optional<'c, E>(self, executor: E) -> Result<Option<AnyRow>, sqlx::Error>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let (sql, bind_order) = self.dialect.render(self.sql, &self.args);
+        build_query(&sql, &self.args, &bind_order)
+            .fetch_optional(executor)
+            .await
+    }
+
+    /// Fetch all rows
+    pub async fn fetch_all<'c, E>(self, executor: E) -> Result<Vec<AnyRow>, sqlx::Error>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let (sql, bind_order) = self.dialect.render(self.sql, &self.args);
+        build_query(&sql, &self.args, &bind_order)
+            .fetch_all(executor)
+            .await
+    }
+}
+
+fn build_query<'a>(
+    sql: &'a str,
+    args: &[QueryArg],
+    bind_order: &[usize],
+) -> Query<'a, Any, AnyArguments<'a>> {
+    bind_order
+        .iter()
+        .fold(sqlx::query(sql), |query, &index| match &args[index] {
+            QueryArg::Null => query,
+            QueryArg::Bool(value) => query.bind(*value),
+            QueryArg::Int(value) => query.bind(*value),
+            QueryArg::Float(value) => query.bind(*value),
+            QueryArg::Text(value) => query.bind(value.clone()),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args() -> Vec<QueryArg> {
+        vec![QueryArg::Int(7), QueryArg::Int(3)]
+    }
+
+    #[test]
+    fn test_render_postgres_and_sqlite_keep_numbered_placeholders() {
+        let sql = "SELECT \"key\" FROM t WHERE a = $1 AND $2 >= b AND ($2 < c)";
+        for dialect in [Dialect::PostgreSQL, Dialect::SQLite] {
+            let (rendered, order) = dialect.render(sql, &args());
+            assert_eq!(rendered, sql);
+            assert_eq!(order, vec![0, 1]);
+        }
+    }
+
+    #[test]
+    fn test_render_mysql_repeats_positional_arguments() {
+        let sql = "SELECT \"key\" FROM t WHERE a = $1 AND $2 >= b AND ($2 < c)";
+        let (rendered, order) = Dialect::MySQL.render(sql, &args());
+        assert_eq!(
+            rendered,
+            "SELECT `key` FROM t WHERE a = ? AND ? >= b AND (? < c)"
+        );
+        assert_eq!(order, vec![0, 1, 1]);
+    }
+
+    #[test]
+    fn test_render_inlines_nulls_and_renumbers() {
+        let args = vec![QueryArg::Int(1), QueryArg::Null, QueryArg::Text("x".into())];
+        let sql = "INSERT INTO t VALUES ($1, $2, $3, $3)";
+
+        let (rendered, order) = Dialect::PostgreSQL.render(sql, &args);
+        assert_eq!(rendered, "INSERT INTO t VALUES ($1, NULL, $2, $2)");
+        assert_eq!(order, vec![0, 2]);
+
+        let (rendered, order) = Dialect::MySQL.render(sql, &args);
+        assert_eq!(rendered, "INSERT INTO t VALUES (?, NULL, ?, ?)");
+        assert_eq!(order, vec![0, 2, 2]);
+    }
+
+    #[test]
+    fn test_parse_timestamp_formats() {
+        let expected = "2026-10-18T12:30:00.250Z".parse::<DateTime<Utc>>().unwrap();
+        for value in [
+            "2026-10-18T12:30:00.250+00:00",
+            "2026-10-18 14:30:00.25+02",
+            "2026-10-18 12:30:00.250000",
+        ] {
+            assert_eq!(Dialect::SQLite.parse_timestamp(value), Some(expected));
+        }
+        assert_eq!(Dialect::SQLite.parse_timestamp("yesterday"), None);
+    }
+
+    #[test]
+    fn test_first_migration_matches_baseline() {
+        // Catalogs created from the original single migrations directory
+        // recorded its checksum; the SQLite and PostgreSQL copies keep it
+        let first = |migrator: &Migrator| {
+            migrator
+                .iter()
+                .find(|m| m.version == 20250624030102 && m.migration_type.is_up_migration())
+                .unwrap()
+                .checksum
+                .clone()
+        };
+        assert_eq!(first(&SQLITE_MIGRATOR), first(&POSTGRES_MIGRATOR));
+    }
+
+    #[test]
+    fn test_render_leaves_string_literals_alone() {
+        let sql = "SELECT '$1 \"x\"' FROM t WHERE a = $1";
+        let (rendered, order) = Dialect::MySQL.render(sql, &args());
+        assert_eq!(rendered, "SELECT '$1 \"x\"' FROM t WHERE a = ?");
+        assert_eq!(order, vec![0]);
+    }
+}
@@ -3,23 +3,191 @@
 //! This module provides a transaction-aware interface for DuckPond operations,
 //! implementing the patterns described in the DuckPond specification.
 
-use crate::error::Result;
+use crate::changes::{format_changes, parse_changes, SnapshotChange};
+use crate::database::BackendInfo;
+use crate::dialect::Dialect;
+use crate::error::{DuckPondError, Result};
+use crate::inlining::{
+    FlushedDataFile, InlinedColumnKind, InlinedDataTable, InlinedRow, InlinedValue,
+    DATA_INLINING_ROW_LIMIT,
+};
 use crate::models::*;
-use crate::queries::{ReadQueries, WriteQueries};
-use chrono::Utc;
-use sqlx::{Any, AnyPool, Transaction};
+use crate::queries::{Lineage, ReadQueries, WriteQueries};
+use crate::sort_order::SortOrder;
+use crate::types::DuckLakeType;
+use chrono::{DateTime, Utc};
+use sqlx::{Any, AnyConnection, AnyPool, Transaction};
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 /// High-level DuckPond interface for managing lakehouse operations
 #[derive(Clone)]
 pub struct DuckPond {
     pool: AnyPool,
+    backend: BackendInfo,
+    dialect: Dialect,
+    branch: Option<String>,
+    /// Snapshot reads are pinned to; such a handle cannot write
+    as_of: Option<i64>,
 }
 
 impl DuckPond {
     /// Create a new DuckPond instance with the given database pool
-    pub fn new(pool: AnyPool) -> Self {
-        Self { pool }
+    pub fn new(pool: AnyPool, backend: BackendInfo) -> Self {
+        Self {
+            pool,
+            dialect: backend.dialect(),
+            backend,
+            branch: None,
+            as_of: None,
+        }
+    }
+
+    /// Open an existing catalog, refusing spec versions this release does not
+    /// understand
+    ///
+    /// Catalogs that were migrated but never initialized carry no version and
+    /// are accepted.
+    pub async fn open(pool: AnyPool, backend: BackendInfo) -> Result<Self> {
+        let duckpond = Self::new(pool, backend);
+        if let Some(version) = duckpond.spec_version().await? {
+            check_spec_version(&version)?;
+        }
+        Ok(duckpond)
+    }
+
+    /// Get the DuckLake spec version recorded in `ducklake_metadata`
+    pub async fn spec_version(&self) -> Result<Option<String>> {
+        ReadQueries::get_metadata(&self.pool, self.dialect, "version").await
+    }
+
+    /// Initialize a migrated catalog
+    ///
+    /// Writes the `version`, `created_by` and `data_path` metadata and creates
+    /// snapshot 0 holding the default `main` schema. An already initialized
+    /// catalog is only reinitialized with `force`, which rewrites the metadata
+    /// and recreates the default schema if it was dropped, keeping all
+    /// existing snapshots, tables and data files.
+    pub async fn initialize(&self, data_path: &str, force: bool) -> Result<()> {
+        if let Some(version) = self.spec_version().await? {
+            check_spec_version(&version)?;
+            if !force {
+                return Err(DuckPondError::CatalogAlreadyInitialized { version });
+            }
+        }
+
+        let created_by = format!("DuckPond {}", env!("CARGO_PKG_VERSION"));
+        let mut tx = self.pool.begin().await?;
+        for (key, value) in [
+            ("version", DUCKLAKE_SPEC_VERSION),
+            ("created_by", created_by.as_str()),
+            ("data_path", data_path),
+        ] {
+            WriteQueries::set_metadata(&mut tx, self.dialect, key, value).await?;
+        }
+
+        if ReadQueries::get_current_snapshot(&mut *tx, self.dialect)
+            .await?
+            .is_none()
+        {
+            // Snapshot 0 holds the default schema; catalog IDs continue from 1
+            WriteQueries::create_snapshot(&mut *tx, self.dialect, 0, Utc::now(), 0, 1, 0).await?;
+            WriteQueries::create_schema(
+                &mut *tx,
+                self.dialect,
+                0,
+                Uuid::new_v4(),
+                0,
+                DEFAULT_SCHEMA,
+                Some(&format!("{}/", DEFAULT_SCHEMA)),
+            )
+            .await?;
+            WriteQueries::log_snapshot_changes(
+                &mut *tx,
+                self.dialect,
+                0,
+                &format_changes(&[SnapshotChange::CreatedSchema {
+                    schema: DEFAULT_SCHEMA.to_string(),
+                }]),
+            )
+            .await?;
+            tx.commit().await?;
+        } else {
+            tx.commit().await?;
+            let schemas = self.list_schemas().await?;
+            if !schemas.iter().any(|s| s.schema_name == DEFAULT_SCHEMA) {
+                self.create_schema(DEFAULT_SCHEMA).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Upgrade the catalog to the latest DuckLake spec version
+    ///
+    /// The catalog tables must already be migrated to this release. Each step
+    /// converts the existing metadata and rewrites `ducklake_metadata.version`
+    /// in its own transaction; 0.1 to 0.2 gives schemas and tables their
+    /// paths and makes relative file paths relative to their table's.
+    /// Returns the `(from, to)` steps applied.
+    pub async fn upgrade_spec(&self) -> Result<Vec<(String, String)>> {
+        let mut version = self
+            .spec_version()
+            .await?
+            .ok_or(DuckPondError::CatalogNotInitialized)?;
+        check_spec_version(&version)?;
+
+        let mut applied = Vec::new();
+        while version != DUCKLAKE_SPEC_VERSION {
+            let (from, to) = SPEC_UPGRADES
+                .iter()
+                .copied()
+                .find(|(from, _)| *from == version)
+                .ok_or_else(|| DuckPondError::UnsupportedSpecVersion {
+                    version: version.clone(),
+                })?;
+
+            let mut tx = self.pool.begin().await?;
+            // 0.2 gave schemas and tables their own paths, against which
+            // relative file paths are resolved
+            if to == "0.2" {
+                WriteQueries::backfill_object_paths(&mut tx, self.dialect).await?;
+                let paths = FilePaths::load(&mut tx, self.dialect).await?;
+                for (kind, file_id, table_id, path) in
+                    ReadQueries::list_relative_file_paths(&mut tx, self.dialect).await?
+                {
+                    let (stored, relative) = paths.to_catalog(table_id, &path, true)?;
+                    WriteQueries::set_file_path(
+                        &mut tx,
+                        self.dialect,
+                        kind,
+                        file_id,
+                        &stored,
+                        relative,
+                    )
+                    .await?;
+                }
+            }
+            WriteQueries::set_metadata(&mut tx, self.dialect, "version", to).await?;
+            tx.commit().await?;
+
+            applied.push((from.to_string(), to.to_string()));
+            version = to.to_string();
+        }
+
+        Ok(applied)
+    }
+
+    /// The path a new schema or table gets, relative to its parent; objects
+    /// of catalogs still on spec 0.1 have none
+    async fn object_path(&self, name: &str) -> Result<Option<String>> {
+        let version = self.spec_version().await?;
+        Ok((version.as_deref() != Some("0.1")).then(|| format!("{}/", name)))
+    }
+
+    async fn file_paths(&self) -> Result<FilePaths> {
+        let mut conn = self.pool.acquire().await?;
+        FilePaths::load(&mut conn, self.dialect).await
     }
 
     /// Get the database pool (for advanced use cases)
@@ -27,14 +195,61 @@ impl DuckPond {
         &self.pool
     }
 
-    /// Get the current snapshot ID
+    /// Get the catalog database backend and its capabilities
+    pub fn backend(&self) -> &BackendInfo {
+        &self.backend
+    }
+
+    /// Get the SQL dialect of the catalog database
+    pub fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    /// Get the branch this handle reads and writes, `None` for main
+    pub fn branch(&self) -> Option<&str> {
+        self.branch.as_deref()
+    }
+
+    /// Get the current snapshot ID: the pinned snapshot, the head of the
+    /// branch, or on main the newest snapshot not pending on a branch
     pub async fn current_snapshot(&self) -> Result<Option<i64>> {
-        ReadQueries::get_current_snapshot(&self.pool).await
+        if let Some(snapshot_id) = self.as_of {
+            return Ok(Some(snapshot_id));
+        }
+        match &self.branch {
+            Some(name) => Ok(Some(self.snapshot_ref(name).await?.snapshot_id)),
+            None => ReadQueries::get_current_snapshot(&self.pool, self.dialect).await,
+        }
+    }
+
+    /// Get the latest snapshot taken at or before `timestamp`
+    pub async fn snapshot_at_time(&self, timestamp: DateTime<Utc>) -> Result<i64> {
+        let current = self.current_snapshot().await?.unwrap_or(0);
+        let lineage = self.lineage().await?;
+        ReadQueries::get_snapshot_at_time(&self.pool, self.dialect, timestamp, current, &lineage)
+            .await?
+            .ok_or(DuckPondError::NoSnapshotAtTime { timestamp })
+    }
+
+    /// Get a read-only handle on which every read sees `snapshot_id` as the
+    /// current snapshot
+    pub async fn as_of(&self, snapshot_id: i64) -> Result<DuckPond> {
+        if !ReadQueries::list_snapshots(&self.pool, self.dialect)
+            .await?
+            .iter()
+            .any(|s| s.snapshot_id == snapshot_id)
+        {
+            return Err(DuckPondError::InvalidSnapshot { snapshot_id });
+        }
+        Ok(DuckPond {
+            as_of: Some(snapshot_id),
+            ..self.clone()
+        })
     }
 
     /// Get the maximum snapshot ID
     pub async fn max_snapshot_id(&self) -> Result<i64> {
-        ReadQueries::get_max_snapshot_id(&self.pool).await
+        ReadQueries::get_max_snapshot_id(&self.pool, self.dialect).await
     }
 
     /// List all schemas at the current snapshot
@@ -45,7 +260,8 @@ impl DuckPond {
 
     /// List all schemas at a specific snapshot
     pub async fn list_schemas_at_snapshot(&self, snapshot_id: i64) -> Result<Vec<SchemaInfo>> {
-        ReadQueries::list_schemas(&self.pool, snapshot_id).await
+        let lineage = self.lineage().await?;
+        ReadQueries::list_schemas(&self.pool, self.dialect, snapshot_id, &lineage).await
     }
 
     /// List all tables in a schema at the current snapshot
@@ -60,7 +276,8 @@ impl DuckPond {
         schema_id: i64,
         snapshot_id: i64,
     ) -> Result<Vec<TableInfo>> {
-        ReadQueries::list_tables(&self.pool, schema_id, snapshot_id).await
+        let lineage = self.lineage().await?;
+        ReadQueries::list_tables(&self.pool, self.dialect, schema_id, snapshot_id, &lineage).await
     }
 
     /// Get table structure (columns) at the current snapshot
@@ -76,7 +293,23 @@ impl DuckPond {
         table_id: i64,
         snapshot_id: i64,
     ) -> Result<Vec<ColumnInfo>> {
-        ReadQueries::show_table_structure(&self.pool, table_id, snapshot_id).await
+        let lineage = self.lineage().await?;
+        ReadQueries::show_table_structure(&self.pool, self.dialect, table_id, snapshot_id, &lineage)
+            .await
+    }
+
+    /// Get the directory new data files of a table are written to, relative
+    /// to the data path unless absolute
+    ///
+    /// `None` for tables of catalogs still on spec 0.1, which have no path;
+    /// their files are placed relative to the data path.
+    pub async fn table_path(&self, table_id: i64) -> Result<Option<String>> {
+        Ok(self
+            .file_paths()
+            .await?
+            .tables
+            .remove(&table_id)
+            .map(|(path, _)| path))
     }
 
     /// List data files for a table at the current snapshot
@@ -92,7 +325,22 @@ impl DuckPond {
         table_id: i64,
         snapshot_id: i64,
     ) -> Result<Vec<DataFileInfo>> {
-        ReadQueries::list_data_files(&self.pool, table_id, snapshot_id).await
+        let paths = self.file_paths().await?;
+        let lineage = self.lineage().await?;
+        let mut files =
+            ReadQueries::list_data_files(&self.pool, self.dialect, table_id, snapshot_id, &lineage)
+                .await?;
+        for file in &mut files {
+            (file.data_file_path, file.path_is_relative) =
+                paths.resolve(table_id, &file.data_file_path, file.path_is_relative);
+            if let Some(path) = &file.delete_file_path {
+                let (path, relative) =
+                    paths.resolve(table_id, path, file.delete_file_path_is_relative);
+                file.delete_file_path = Some(path);
+                file.delete_file_path_is_relative = relative;
+            }
+        }
+        Ok(files)
     }
 
     /// Prune files by column statistics for efficient querying
@@ -102,7 +350,30 @@ impl DuckPond {
         column_id: i64,
         value: &str,
     ) -> Result<Vec<i64>> {
-        ReadQueries::prune_files_by_column_stats(&self.pool, table_id, column_id, value).await
+        ReadQueries::prune_files_by_column_stats(
+            &self.pool,
+            self.dialect,
+            table_id,
+            column_id,
+            value,
+        )
+        .await
+    }
+
+    /// Get the record count and size of a table over its data files, if any
+    /// were written
+    pub async fn table_stats(&self, table_id: i64) -> Result<Option<TableStats>> {
+        ReadQueries::get_table_stats(&self.pool, self.dialect, table_id).await
+    }
+
+    /// Get the statistics of a table's columns over its data files
+    pub async fn table_column_stats(&self, table_id: i64) -> Result<Vec<TableColumnStats>> {
+        ReadQueries::list_table_column_stats(&self.pool, self.dialect, table_id).await
+    }
+
+    /// Get the statistics of a table's columns in each of its data files
+    pub async fn file_column_stats(&self, table_id: i64) -> Result<Vec<DataFileColumnStats>> {
+        ReadQueries::list_file_column_stats(&self.pool, self.dialect, table_id).await
     }
 
     /// Create a new schema in a transaction
@@ -119,20 +390,28 @@ impl DuckPond {
         _tx: &mut Transaction<'_, Any>,
         schema_name: &str,
     ) -> Result<SchemaInfo> {
-        let snapshot_context = SnapshotContext::new(&self.pool).await?;
+        let mut snapshot_context = self.snapshot_context().await?;
         let schema_id = snapshot_context.next_catalog_id;
+        snapshot_context.next_catalog_id += 1;
 
         WriteQueries::create_schema(
             &self.pool,
+            self.dialect,
             schema_id,
             Uuid::new_v4(),
             snapshot_context.snapshot_id,
             schema_name,
+            self.object_path(schema_name).await?.as_deref(),
         )
         .await?;
 
         snapshot_context
-            .commit_with_changes(&self.pool, &format!("CREATE SCHEMA {}", schema_name))
+            .commit_with_changes(
+                &self.pool,
+                &[SnapshotChange::CreatedSchema {
+                    schema: schema_name.to_string(),
+                }],
+            )
             .await?;
 
         Ok(SchemaInfo {
@@ -141,6 +420,49 @@ impl DuckPond {
         })
     }
 
+    /// Drop an empty schema; it stays visible at earlier snapshots
+    pub async fn drop_schema(&self, schema_id: i64) -> Result<()> {
+        let schema = self
+            .list_schemas()
+            .await?
+            .into_iter()
+            .find(|s| s.schema_id == schema_id)
+            .ok_or_else(|| DuckPondError::SchemaNotFound {
+                schema_name: schema_id.to_string(),
+            })?;
+        if !self.list_tables(schema_id).await?.is_empty()
+            || !self.list_views(schema_id).await?.is_empty()
+        {
+            return Err(DuckPondError::SchemaNotEmpty {
+                schema_name: schema.schema_name,
+            });
+        }
+
+        let snapshot_context = self.snapshot_context().await?;
+        let mut tx = self.pool.begin().await?;
+        for (table, column) in [
+            ("ducklake_schema", "schema_id"),
+            ("ducklake_tag", "object_id"),
+        ] {
+            WriteQueries::end_rows(
+                &mut tx,
+                self.dialect,
+                table,
+                column,
+                schema_id,
+                snapshot_context.snapshot_id,
+                &snapshot_context.lineage,
+            )
+            .await?;
+        }
+        snapshot_context
+            .commit_in(&mut tx, &[SnapshotChange::DroppedSchema { schema_id }])
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
     /// Create a new table in a transaction
     pub async fn create_table(
         &self,
@@ -164,37 +486,57 @@ impl DuckPond {
         table_name: &str,
         columns: Vec<ColumnDefinition>,
     ) -> Result<TableInfo> {
-        let mut snapshot_context = SnapshotContext::new(&self.pool).await?;
+        let schema_name = self
+            .list_schemas()
+            .await?
+            .into_iter()
+            .find(|s| s.schema_id == schema_id)
+            .map(|s| s.schema_name)
+            .ok_or_else(|| DuckPondError::SchemaNotFound {
+                schema_name: schema_id.to_string(),
+            })?;
+
+        let mut snapshot_context = self.snapshot_context().await?;
         let table_id = snapshot_context.next_catalog_id;
         snapshot_context.next_catalog_id += 1;
 
         WriteQueries::create_table(
             &self.pool,
+            self.dialect,
             table_id,
             Uuid::new_v4(),
             snapshot_context.snapshot_id,
             schema_id,
             table_name,
+            self.object_path(table_name).await?.as_deref(),
         )
         .await?;
 
-        // Create columns
-        for (index, column) in columns.into_iter().enumerate() {
+        // Create columns, children following their parent
+        for (index, column) in place_columns(&columns, 1)?.into_iter().enumerate() {
             WriteQueries::create_column(
                 &self.pool,
-                column.column_id.unwrap_or(index as i64 + 1),
+                self.dialect,
+                column.column_id,
                 snapshot_context.snapshot_id,
                 table_id,
                 index as i64,
-                &column.name,
-                &column.data_type,
-                column.nullable,
+                &column.definition.name,
+                &column.column_type.to_string(),
+                column.definition.nullable,
+                column.parent_column,
             )
             .await?;
         }
 
         snapshot_context
-            .commit_with_changes(&self.pool, &format!("CREATE TABLE {}", table_name))
+            .commit_with_changes(
+                &self.pool,
+                &[SnapshotChange::CreatedTable {
+                    schema: schema_name,
+                    table: table_name.to_string(),
+                }],
+            )
             .await?;
 
         Ok(TableInfo {
@@ -203,6 +545,290 @@ impl DuckPond {
         })
     }
 
+    /// Drop a table with its columns, files and tags
+    ///
+    /// The table stays visible at earlier snapshots, and its files stay in
+    /// storage until those snapshots are expired.
+    pub async fn drop_table(&self, schema_id: i64, table_name: &str) -> Result<()> {
+        let table = self.get_table(schema_id, table_name).await?;
+        let snapshot_context = self.snapshot_context().await?;
+
+        let mut tx = self.pool.begin().await?;
+        WriteQueries::end_table_rows(
+            &mut tx,
+            self.dialect,
+            table.table_id,
+            snapshot_context.snapshot_id,
+            &snapshot_context.lineage,
+        )
+        .await?;
+        snapshot_context
+            .commit_in(
+                &mut tx,
+                &[SnapshotChange::DroppedTable {
+                    table_id: table.table_id,
+                }],
+            )
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Rename a table; earlier snapshots keep the old name
+    pub async fn rename_table(
+        &self,
+        schema_id: i64,
+        table_name: &str,
+        new_name: &str,
+    ) -> Result<TableInfo> {
+        let table = self.get_table(schema_id, table_name).await?;
+        if self
+            .list_tables(schema_id)
+            .await?
+            .iter()
+            .any(|t| t.table_name == new_name)
+            || self
+                .list_views(schema_id)
+                .await?
+                .iter()
+                .any(|v| v.view_name == new_name)
+        {
+            return Err(DuckPondError::NameAlreadyExists {
+                name: new_name.to_string(),
+            });
+        }
+        let snapshot_context = self.snapshot_context().await?;
+
+        let mut tx = self.pool.begin().await?;
+        WriteQueries::end_rows(
+            &mut tx,
+            self.dialect,
+            "ducklake_table",
+            "table_id",
+            table.table_id,
+            snapshot_context.snapshot_id,
+            &snapshot_context.lineage,
+        )
+        .await?;
+        WriteQueries::rename_table(
+            &mut *tx,
+            self.dialect,
+            table.table_id,
+            snapshot_context.snapshot_id,
+            new_name,
+        )
+        .await?;
+        snapshot_context
+            .commit_in(
+                &mut tx,
+                &[SnapshotChange::AlteredTable {
+                    table_id: table.table_id,
+                }],
+            )
+            .await?;
+        tx.commit().await?;
+
+        Ok(TableInfo {
+            table_id: table.table_id,
+            table_name: new_name.to_string(),
+        })
+    }
+
+    /// Add a column to a table
+    ///
+    /// The column must be nullable: rows written before it was added read
+    /// as null.
+    pub async fn add_column(&self, table_id: i64, column: ColumnDefinition) -> Result<()> {
+        let columns = self.table_structure(table_id).await?;
+        if columns.is_empty() {
+            return Err(DuckPondError::TableNotFound {
+                table_name: table_id.to_string(),
+            });
+        }
+        if !column.nullable {
+            return Err(invalid_column(format!(
+                "column '{}' must be nullable to be added to an existing table",
+                column.name
+            )));
+        }
+        if columns.iter().any(|c| c.column_name == column.name) {
+            return Err(invalid_column(format!(
+                "column '{}' already exists",
+                column.name
+            )));
+        }
+
+        // Column IDs and orders continue after those of every existing
+        // column, nested ones included
+        fn flatten(columns: &[ColumnInfo]) -> Vec<&ColumnInfo> {
+            columns
+                .iter()
+                .flat_map(|c| std::iter::once(c).chain(flatten(&c.children)))
+                .collect()
+        }
+        let existing = flatten(&columns);
+        let first_id = existing.iter().map(|c| c.column_id).max().unwrap_or(0) + 1;
+        let column = ColumnDefinition {
+            column_id: None,
+            ..column
+        };
+        let placed = place_columns(std::slice::from_ref(&column), first_id)?;
+
+        let snapshot_context = self.snapshot_context().await?;
+        let mut tx = self.pool.begin().await?;
+        for (index, placed) in placed.into_iter().enumerate() {
+            WriteQueries::create_column(
+                &mut *tx,
+                self.dialect,
+                placed.column_id,
+                snapshot_context.snapshot_id,
+                table_id,
+                (existing.len() + index) as i64,
+                &placed.definition.name,
+                &placed.column_type.to_string(),
+                placed.definition.nullable,
+                placed.parent_column,
+            )
+            .await?;
+        }
+        snapshot_context
+            .commit_in(&mut tx, &[SnapshotChange::AlteredTable { table_id }])
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Get a table of a schema by name at the current snapshot
+    async fn get_table(&self, schema_id: i64, table_name: &str) -> Result<TableInfo> {
+        self.list_tables(schema_id)
+            .await?
+            .into_iter()
+            .find(|t| t.table_name == table_name)
+            .ok_or_else(|| DuckPondError::TableNotFound {
+                table_name: table_name.to_string(),
+            })
+    }
+
+    /// List the views of a schema at the current snapshot
+    pub async fn list_views(&self, schema_id: i64) -> Result<Vec<ViewInfo>> {
+        let snapshot_id = self.current_snapshot().await?.unwrap_or(0);
+        self.list_views_at_snapshot(schema_id, snapshot_id).await
+    }
+
+    /// List the views of a schema at a specific snapshot
+    pub async fn list_views_at_snapshot(
+        &self,
+        schema_id: i64,
+        snapshot_id: i64,
+    ) -> Result<Vec<ViewInfo>> {
+        let lineage = self.lineage().await?;
+        ReadQueries::list_views(&self.pool, self.dialect, schema_id, snapshot_id, &lineage).await
+    }
+
+    /// Get a view of a schema by name at the current snapshot
+    pub async fn get_view(&self, schema_id: i64, view_name: &str) -> Result<ViewInfo> {
+        self.list_views(schema_id)
+            .await?
+            .into_iter()
+            .find(|v| v.view_name == view_name)
+            .ok_or_else(|| DuckPondError::ViewNotFound {
+                view_name: view_name.to_string(),
+            })
+    }
+
+    /// Create a view defined by a query in the given SQL dialect
+    ///
+    /// `column_aliases` renames the query's output columns; pass an empty
+    /// list to keep them.
+    pub async fn create_view(
+        &self,
+        schema_id: i64,
+        view_name: &str,
+        dialect: &str,
+        sql: &str,
+        column_aliases: Vec<String>,
+    ) -> Result<ViewInfo> {
+        let schema_name = self
+            .list_schemas()
+            .await?
+            .into_iter()
+            .find(|s| s.schema_id == schema_id)
+            .map(|s| s.schema_name)
+            .ok_or_else(|| DuckPondError::SchemaNotFound {
+                schema_name: schema_id.to_string(),
+            })?;
+        let tables = self.list_tables(schema_id).await?;
+        let views = self.list_views(schema_id).await?;
+        if tables.iter().any(|t| t.table_name == view_name)
+            || views.iter().any(|v| v.view_name == view_name)
+        {
+            return Err(DuckPondError::NameAlreadyExists {
+                name: format!("{}.{}", schema_name, view_name),
+            });
+        }
+
+        let mut snapshot_context = self.snapshot_context().await?;
+        let view = ViewInfo {
+            view_id: snapshot_context.next_catalog_id,
+            view_uuid: Uuid::new_v4().to_string(),
+            schema_id,
+            view_name: view_name.to_string(),
+            dialect: dialect.to_string(),
+            sql: sql.to_string(),
+            column_aliases,
+        };
+        snapshot_context.next_catalog_id += 1;
+
+        WriteQueries::create_view(
+            &self.pool,
+            self.dialect,
+            &view,
+            snapshot_context.snapshot_id,
+        )
+        .await?;
+
+        snapshot_context
+            .commit_with_changes(
+                &self.pool,
+                &[SnapshotChange::CreatedView {
+                    schema: schema_name,
+                    view: view_name.to_string(),
+                }],
+            )
+            .await?;
+
+        Ok(view)
+    }
+
+    /// Drop a view; it stays visible at earlier snapshots
+    pub async fn drop_view(&self, schema_id: i64, view_name: &str) -> Result<()> {
+        let view = self.get_view(schema_id, view_name).await?;
+        let snapshot_context = self.snapshot_context().await?;
+
+        let mut tx = self.pool.begin().await?;
+        WriteQueries::end_view(
+            &mut tx,
+            self.dialect,
+            view.view_id,
+            snapshot_context.snapshot_id,
+            &snapshot_context.lineage,
+        )
+        .await?;
+        snapshot_context
+            .commit_in(
+                &mut tx,
+                &[SnapshotChange::DroppedView {
+                    view_id: view.view_id,
+                }],
+            )
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
     /// Insert data file and update statistics in a transaction
     pub async fn insert_data_file(
         &self,
@@ -230,30 +856,33 @@ impl DuckPond {
     /// Insert data file within an existing transaction
     pub async fn insert_data_file_tx(
         &self,
-        _tx: &mut Transaction<'_, Any>,
+        tx: &mut Transaction<'_, Any>,
         table_id: i64,
         file_path: &str,
         record_count: i64,
         file_size_bytes: i64,
         column_statistics: Vec<FileColumnStatistics>,
     ) -> Result<i64> {
-        let mut snapshot_context = SnapshotContext::new(&self.pool).await?;
+        let mut snapshot_context = self.snapshot_context().await?;
         let data_file_id = snapshot_context.next_file_id;
         snapshot_context.next_file_id += 1;
 
-        // Get the next row ID for this table
-        let row_id_start = ReadQueries::get_table_next_row_id(&self.pool, table_id)
+        let (file_path, path_is_relative) = FilePaths::load(tx, self.dialect)
             .await?
-            .unwrap_or(0);
+            .to_catalog(table_id, file_path, true)?;
+        let row_id_start = self
+            .allocate_row_ids(tx, table_id, record_count, file_size_bytes)
+            .await?;
 
         // Insert the data file record
         WriteQueries::insert_data_file(
-            &self.pool,
+            &mut **tx,
+            self.dialect,
             data_file_id,
             table_id,
             snapshot_context.snapshot_id,
-            file_path,
-            true, // path_is_relative
+            &file_path,
+            path_is_relative,
             "parquet",
             record_count,
             file_size_bytes,
@@ -262,60 +891,1481 @@ impl DuckPond {
         )
         .await?;
 
-        // Update table statistics
-        WriteQueries::update_table_stats(&self.pool, table_id, record_count, file_size_bytes)
+        insert_file_stats(tx, self.dialect, table_id, data_file_id, &column_statistics).await?;
+
+        snapshot_context
+            .commit_in(tx, &[SnapshotChange::InsertedIntoTable { table_id }])
             .await?;
 
-        // Insert file-level column statistics
-        for stat in column_statistics {
-            WriteQueries::insert_file_column_stats(
+        Ok(data_file_id)
+    }
+
+    /// Get the largest write, in rows, that is inlined into the catalog
+    /// instead of written to a data file; 0 disables inlining
+    pub async fn data_inlining_row_limit(&self) -> Result<usize> {
+        let value =
+            ReadQueries::get_metadata(&self.pool, self.dialect, DATA_INLINING_ROW_LIMIT).await?;
+        match value {
+            Some(value) => value.parse().map_err(|_| {
+                DuckPondError::ConfigError(format!(
+                    "invalid {}: {}",
+                    DATA_INLINING_ROW_LIMIT, value
+                ))
+            }),
+            None => Ok(0),
+        }
+    }
+
+    /// Set the largest write, in rows, that is inlined into the catalog
+    pub async fn set_data_inlining_row_limit(&self, limit: usize) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        WriteQueries::set_metadata(
+            &mut tx,
+            self.dialect,
+            DATA_INLINING_ROW_LIMIT,
+            &limit.to_string(),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// List the inlined rows of a table at the current snapshot
+    pub async fn list_inlined_rows(&self, table_id: i64) -> Result<Vec<InlinedRow>> {
+        let snapshot_id = self.current_snapshot().await?.unwrap_or(0);
+        self.list_inlined_rows_at_snapshot(table_id, snapshot_id)
+            .await
+    }
+
+    /// List the inlined rows of a table at a specific snapshot, ordered by
+    /// row ID, with values in the order of the table's columns then
+    pub async fn list_inlined_rows_at_snapshot(
+        &self,
+        table_id: i64,
+        snapshot_id: i64,
+    ) -> Result<Vec<InlinedRow>> {
+        let lineage = self.lineage().await?;
+        let columns = ReadQueries::show_table_structure(
+            &self.pool,
+            self.dialect,
+            table_id,
+            snapshot_id,
+            &lineage,
+        )
+        .await?;
+        let inlined_tables = ReadQueries::list_inlined_data_tables(
+            &self.pool,
+            self.dialect,
+            Some(table_id),
+            Some(&lineage),
+        )
+        .await?;
+
+        let mut rows = Vec::new();
+        for inlined in inlined_tables {
+            if inlined.schema_snapshot > snapshot_id {
+                continue;
+            }
+            // Tables created for earlier columns are matched by column ID
+            let inlined_columns = ReadQueries::show_table_structure(
                 &self.pool,
-                data_file_id,
+                self.dialect,
                 table_id,
-                stat.column_id,
-                stat.value_count,
-                stat.null_count,
-                stat.nan_count,
-                stat.min_value.as_deref(),
-                stat.max_value.as_deref(),
+                inlined.schema_snapshot,
+                &lineage,
             )
             .await?;
+            let positions: Vec<Option<usize>> = columns
+                .iter()
+                .map(|c| {
+                    inlined_columns
+                        .iter()
+                        .position(|i| i.column_id == c.column_id)
+                })
+                .collect();
 
-            // Update table-level column statistics
-            WriteQueries::update_table_column_stats(
+            let inlined_rows = ReadQueries::list_inlined_rows(
                 &self.pool,
-                table_id,
-                stat.column_id,
-                stat.null_count,
-                stat.nan_count,
-                stat.min_value.as_deref(),
-                stat.max_value.as_deref(),
+                self.dialect,
+                &inlined,
+                &inlined_columns,
+                snapshot_id,
+                &lineage,
             )
             .await?;
+            for row in inlined_rows {
+                let values = positions
+                    .iter()
+                    .map(|p| p.map_or(InlinedValue::Null, |p| row.values[p].clone()))
+                    .collect();
+                rows.push(InlinedRow {
+                    row_id: row.row_id,
+                    begin_snapshot: row.begin_snapshot,
+                    values,
+                });
+            }
         }
+        rows.sort_by_key(|r| r.row_id);
 
-        snapshot_context
-            .commit_with_changes(&self.pool, &format!("INSERT DATA FILE {}", file_path))
-            .await?;
-
-        Ok(data_file_id)
+        Ok(rows)
     }
 
-    /// Query data from a table with basic filtering and formatting
-    pub async fn query_data(
+    /// Insert rows into the catalog instead of a data file, with values in
+    /// the order of the table's columns
+    ///
+    /// Returns the row ID allocated to the first row; the others follow.
+    pub async fn insert_inlined_rows(
         &self,
         table_id: i64,
-        limit: usize,
-        _columns: Option<String>,
-        format: String,
-    ) -> Result<Vec<String>> {
-        // Get table structure to understand columns
-        let column_info = self.table_structure(table_id).await?;
-
-        // Get data files for this table
-        let data_files = self.list_data_files(table_id).await?;
+        rows: &[Vec<InlinedValue>],
+    ) -> Result<i64> {
+        let snapshot_context = self.snapshot_context().await?;
+        let snapshot_id = snapshot_context.snapshot_id;
+        let columns = self.table_structure(table_id).await?;
+        if columns.is_empty() {
+            return Err(DuckPondError::TableNotFound {
+                table_name: table_id.to_string(),
+            });
+        }
+        check_inlined_rows(&columns, rows)?;
 
-        if data_files.is_empty() {
+        let mut tx = self.pool.begin().await?;
+        // Not a table another lineage created: dropping a branch drops those
+        let current = ReadQueries::list_inlined_data_tables(
+            &mut *tx,
+            self.dialect,
+            Some(table_id),
+            Some(&snapshot_context.lineage),
+        )
+        .await?
+        .pop();
+        let inlined = match current {
+            Some(inlined)
+                if same_columns(
+                    &ReadQueries::show_table_structure(
+                        &mut *tx,
+                        self.dialect,
+                        table_id,
+                        inlined.schema_snapshot,
+                        &snapshot_context.lineage,
+                    )
+                    .await?,
+                    &columns,
+                ) =>
+            {
+                inlined
+            }
+            _ => {
+                let inlined = InlinedDataTable::new(table_id, snapshot_id);
+                WriteQueries::create_inlined_data_table(&mut tx, self.dialect, &inlined, &columns)
+                    .await?;
+                inlined
+            }
+        };
+
+        let row_id_start = self
+            .allocate_row_ids(&mut tx, table_id, rows.len() as i64, 0)
+            .await?;
+
+        for (row_id, values) in (row_id_start..).zip(rows) {
+            WriteQueries::insert_inlined_row(
+                &mut *tx,
+                self.dialect,
+                &inlined,
+                &columns,
+                row_id,
+                snapshot_id,
+                values,
+            )
+            .await?;
+        }
+
+        snapshot_context
+            .commit_in(&mut tx, &[SnapshotChange::InsertedIntoTable { table_id }])
+            .await?;
+        tx.commit().await?;
+
+        Ok(row_id_start)
+    }
+
+    /// Move inlined rows of a table into data files
+    ///
+    /// Each file holds the inlined rows with row IDs from its `row_id_start`
+    /// on, in row ID order; the rows keep their row IDs. Commits a single
+    /// snapshot and returns the IDs of the new data files.
+    pub async fn flush_inlined_rows(
+        &self,
+        table_id: i64,
+        files: &[FlushedDataFile],
+    ) -> Result<Vec<i64>> {
+        let mut snapshot_context = self.snapshot_context().await?;
+        let snapshot_id = snapshot_context.snapshot_id;
+
+        let mut tx = self.pool.begin().await?;
+        let paths = FilePaths::load(&mut tx, self.dialect).await?;
+        let inlined_tables = ReadQueries::list_inlined_data_tables(
+            &mut *tx,
+            self.dialect,
+            Some(table_id),
+            Some(&snapshot_context.lineage),
+        )
+        .await?;
+        let mut data_file_ids = Vec::new();
+        for file in files {
+            let mut ended = 0;
+            for inlined in &inlined_tables {
+                ended += WriteQueries::end_inlined_rows(
+                    &mut tx,
+                    self.dialect,
+                    inlined,
+                    snapshot_id,
+                    file.row_id_start,
+                    file.row_id_start + file.record_count,
+                    &snapshot_context.lineage,
+                )
+                .await?;
+            }
+            if ended != file.record_count as u64 {
+                return Err(DuckPondError::TransactionConflict {
+                    message: format!(
+                        "only {} of the {} rows written to {} are still inlined",
+                        ended, file.record_count, file.path
+                    ),
+                });
+            }
+
+            let data_file_id = snapshot_context.next_file_id;
+            snapshot_context.next_file_id += 1;
+            let (path, path_is_relative) = paths.to_catalog(table_id, &file.path, true)?;
+            WriteQueries::insert_data_file(
+                &mut *tx,
+                self.dialect,
+                data_file_id,
+                table_id,
+                snapshot_id,
+                &path,
+                path_is_relative,
+                "parquet",
+                file.record_count,
+                file.file_size_bytes,
+                Some(4096), // footer_size - default parquet footer size
+                file.row_id_start,
+            )
+            .await?;
+            insert_file_stats(
+                &mut tx,
+                self.dialect,
+                table_id,
+                data_file_id,
+                &file.column_statistics,
+            )
+            .await?;
+            // The rows are already counted; only the file size is new
+            WriteQueries::update_table_stats(
+                &mut *tx,
+                self.dialect,
+                table_id,
+                0,
+                file.file_size_bytes,
+            )
+            .await?;
+            data_file_ids.push(data_file_id);
+        }
+
+        snapshot_context
+            .commit_in(&mut tx, &[SnapshotChange::CompactedTable { table_id }])
+            .await?;
+        tx.commit().await?;
+
+        Ok(data_file_ids)
+    }
+
+    /// Register data files that already exist in storage with a table, in a
+    /// single snapshot
+    ///
+    /// The files are not read; row IDs are allocated to them in order.
+    /// Returns the IDs of the new data files.
+    pub async fn add_data_files(&self, table_id: i64, files: &[NewDataFile]) -> Result<Vec<i64>> {
+        if self.table_structure(table_id).await?.is_empty() {
+            return Err(DuckPondError::TableNotFound {
+                table_name: table_id.to_string(),
+            });
+        }
+        let mut registered: HashSet<String> = self
+            .list_data_files(table_id)
+            .await?
+            .into_iter()
+            .map(|f| f.data_file_path)
+            .collect();
+        for file in files {
+            if !registered.insert(file.path.clone()) {
+                return Err(DuckPondError::InvalidDataFile {
+                    path: file.path.clone(),
+                    message: format!("already registered with table {}", table_id),
+                });
+            }
+        }
+        if files.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut snapshot_context = self.snapshot_context().await?;
+        let mut tx = self.pool.begin().await?;
+        let paths = FilePaths::load(&mut tx, self.dialect).await?;
+        let mut data_file_ids = Vec::new();
+        for file in files {
+            let data_file_id = snapshot_context.next_file_id;
+            snapshot_context.next_file_id += 1;
+            let (path, path_is_relative) =
+                paths.to_catalog(table_id, &file.path, file.path_is_relative)?;
+            let row_id_start = self
+                .allocate_row_ids(&mut tx, table_id, file.record_count, file.file_size_bytes)
+                .await?;
+            WriteQueries::insert_data_file(
+                &mut *tx,
+                self.dialect,
+                data_file_id,
+                table_id,
+                snapshot_context.snapshot_id,
+                &path,
+                path_is_relative,
+                "parquet",
+                file.record_count,
+                file.file_size_bytes,
+                file.footer_size,
+                row_id_start,
+            )
+            .await?;
+            insert_file_stats(
+                &mut tx,
+                self.dialect,
+                table_id,
+                data_file_id,
+                &file.column_statistics,
+            )
+            .await?;
+            data_file_ids.push(data_file_id);
+        }
+
+        snapshot_context
+            .commit_in(&mut tx, &[SnapshotChange::InsertedIntoTable { table_id }])
+            .await?;
+        tx.commit().await?;
+
+        Ok(data_file_ids)
+    }
+
+    /// Replace data files of a table with one file holding their live rows,
+    /// in a single snapshot
+    ///
+    /// The replaced files and their delete files are ended; like any other
+    /// removed file they are scheduled for deletion once their snapshots
+    /// expire. The rows of the new file get new row IDs. Returns the ID of
+    /// the new data file.
+    pub async fn replace_data_files(
+        &self,
+        table_id: i64,
+        replaced: &[i64],
+        file: &NewDataFile,
+    ) -> Result<i64> {
+        let live = self.list_data_files(table_id).await?;
+        let mut snapshot_context = self.snapshot_context().await?;
+        let snapshot_id = snapshot_context.snapshot_id;
+
+        let mut tx = self.pool.begin().await?;
+        let (mut record_count, mut file_size_bytes) = (0, 0);
+        for data_file_id in replaced {
+            let Some(info) = live.iter().find(|f| f.data_file_id == *data_file_id) else {
+                return Err(DuckPondError::TransactionConflict {
+                    message: format!(
+                        "data file {} is not part of table {}",
+                        data_file_id, table_id
+                    ),
+                });
+            };
+            let ended = WriteQueries::end_rows(
+                &mut tx,
+                self.dialect,
+                "ducklake_data_file",
+                "data_file_id",
+                *data_file_id,
+                snapshot_id,
+                &snapshot_context.lineage,
+            )
+            .await?;
+            if ended != 1 {
+                return Err(DuckPondError::TransactionConflict {
+                    message: format!("data file {} was already removed", info.data_file_path),
+                });
+            }
+            WriteQueries::end_rows(
+                &mut tx,
+                self.dialect,
+                "ducklake_delete_file",
+                "data_file_id",
+                *data_file_id,
+                snapshot_id,
+                &snapshot_context.lineage,
+            )
+            .await?;
+            record_count += info.record_count;
+            file_size_bytes += info.file_size_bytes;
+        }
+
+        let data_file_id = snapshot_context.next_file_id;
+        snapshot_context.next_file_id += 1;
+        let (path, path_is_relative) = FilePaths::load(&mut tx, self.dialect).await?.to_catalog(
+            table_id,
+            &file.path,
+            file.path_is_relative,
+        )?;
+        let row_id_start = self
+            .allocate_row_ids(&mut tx, table_id, file.record_count, file.file_size_bytes)
+            .await?;
+        WriteQueries::remove_table_stats(
+            &mut *tx,
+            self.dialect,
+            table_id,
+            record_count,
+            file_size_bytes,
+        )
+        .await?;
+        WriteQueries::insert_data_file(
+            &mut *tx,
+            self.dialect,
+            data_file_id,
+            table_id,
+            snapshot_id,
+            &path,
+            path_is_relative,
+            "parquet",
+            file.record_count,
+            file.file_size_bytes,
+            file.footer_size,
+            row_id_start,
+        )
+        .await?;
+        insert_file_stats(
+            &mut tx,
+            self.dialect,
+            table_id,
+            data_file_id,
+            &file.column_statistics,
+        )
+        .await?;
+
+        snapshot_context
+            .commit_in(&mut tx, &[SnapshotChange::CompactedTable { table_id }])
+            .await?;
+        tx.commit().await?;
+
+        Ok(data_file_id)
+    }
+
+    /// Allocate row IDs for new rows of a table on `conn`, adding the rows
+    /// and their size to the table's statistics
+    async fn allocate_row_ids(
+        &self,
+        conn: &mut AnyConnection,
+        table_id: i64,
+        record_count: i64,
+        file_size_bytes: i64,
+    ) -> Result<i64> {
+        if self.backend.supports_returning() {
+            return WriteQueries::allocate_table_row_ids(
+                &mut *conn,
+                self.dialect,
+                table_id,
+                record_count,
+                file_size_bytes,
+            )
+            .await;
+        }
+        let row_id_start = if self.backend.supports_row_locks() {
+            ReadQueries::get_table_next_row_id_for_update(&mut *conn, self.dialect, table_id)
+                .await?
+        } else {
+            ReadQueries::get_table_next_row_id(&mut *conn, self.dialect, table_id).await?
+        };
+        WriteQueries::update_table_stats(
+            &mut *conn,
+            self.dialect,
+            table_id,
+            record_count,
+            file_size_bytes,
+        )
+        .await?;
+        Ok(row_id_start.unwrap_or(0))
+    }
+
+    /// Expire old snapshots
+    ///
+    /// Removes snapshots taken before `older_than`, always keeping the newest
+    /// `retain_last` (and at least the current snapshot) as well as tagged
+    /// snapshots and the snapshots of branches. Catalog rows that no
+    /// remaining snapshot can see are dropped, and their data and delete files
+    /// are scheduled for deletion; a vacuum removes them from storage later.
+    pub async fn expire_snapshots(
+        &self,
+        older_than: DateTime<Utc>,
+        retain_last: usize,
+    ) -> Result<SnapshotExpiration> {
+        let snapshots = ReadQueries::list_snapshots(&self.pool, self.dialect).await?;
+        let refs = ReadQueries::list_snapshot_refs(&self.pool, self.dialect).await?;
+        let mut protected: HashSet<i64> = refs
+            .iter()
+            .flat_map(|r| std::iter::once(r.snapshot_id).chain(r.base_snapshot))
+            .collect();
+        for branch in refs.iter().filter(|r| r.kind == RefKind::Branch) {
+            protected.extend(self.branch_snapshots(&branch.name).await?);
+        }
+        // Main's head need not be the newest snapshot while branches commit
+        protected.extend(ReadQueries::get_current_snapshot(&self.pool, self.dialect).await?);
+        let retained_from = snapshots.len().saturating_sub(retain_last.max(1));
+        let expired: Vec<i64> = snapshots[..retained_from]
+            .iter()
+            .filter(|s| s.snapshot_time < older_than && !protected.contains(&s.snapshot_id))
+            .map(|s| s.snapshot_id)
+            .collect();
+        if expired.is_empty() {
+            return Ok(SnapshotExpiration::default());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for snapshot_id in &expired {
+            WriteQueries::delete_snapshot(&mut tx, self.dialect, *snapshot_id).await?;
+        }
+
+        // Scheduled files have no table to resolve their paths against later
+        let paths = FilePaths::load(&mut tx, self.dialect).await?;
+        let mut scheduled_files = Vec::new();
+        let now = Utc::now();
+        for (file_id, table_id, path, path_is_relative) in
+            ReadQueries::list_unreachable_files(&mut *tx, self.dialect).await?
+        {
+            let (path, path_is_relative) = paths.resolve(table_id, &path, path_is_relative);
+            WriteQueries::schedule_file_deletion(
+                &mut *tx,
+                self.dialect,
+                file_id,
+                &path,
+                path_is_relative,
+                now,
+            )
+            .await?;
+            scheduled_files.push(path);
+        }
+        WriteQueries::delete_unreachable_rows(&mut tx, self.dialect).await?;
+        tx.commit().await?;
+
+        Ok(SnapshotExpiration {
+            expired_snapshots: expired,
+            scheduled_files,
+        })
+    }
+
+    /// List files waiting to be removed from storage
+    pub async fn scheduled_deletions(&self) -> Result<Vec<ScheduledDeletion>> {
+        ReadQueries::list_scheduled_deletions(&self.pool, self.dialect).await
+    }
+
+    /// Drop a file from the deletion schedule after it was removed from storage
+    pub async fn remove_scheduled_deletion(&self, data_file_id: i64, path: &str) -> Result<()> {
+        WriteQueries::delete_scheduled_deletion(&self.pool, self.dialect, data_file_id, path).await
+    }
+
+    /// Get the paths of all data and delete files referenced by any snapshot
+    /// or still scheduled for deletion
+    pub async fn referenced_file_paths(&self) -> Result<HashSet<String>> {
+        let mut conn = self.pool.acquire().await?;
+        referenced_file_paths(&mut conn, self.dialect).await
+    }
+
+    /// List every snapshot with the changes it made, oldest first
+    pub async fn list_snapshots(&self) -> Result<Vec<SnapshotInfo>> {
+        let snapshots = ReadQueries::list_snapshots(&self.pool, self.dialect).await?;
+        let mut changes: HashMap<i64, String> =
+            ReadQueries::list_snapshot_changes(&self.pool, self.dialect)
+                .await?
+                .into_iter()
+                .collect();
+
+        Ok(snapshots
+            .into_iter()
+            .map(|s| SnapshotInfo {
+                changes: changes
+                    .remove(&s.snapshot_id)
+                    .map(|c| parse_changes(&c))
+                    .unwrap_or_default(),
+                snapshot_id: s.snapshot_id,
+                snapshot_time: s.snapshot_time,
+                schema_version: s.schema_version,
+            })
+            .collect())
+    }
+
+    /// Get a single snapshot with the changes it made
+    pub async fn snapshot_info(&self, snapshot_id: i64) -> Result<SnapshotInfo> {
+        self.list_snapshots()
+            .await?
+            .into_iter()
+            .find(|s| s.snapshot_id == snapshot_id)
+            .ok_or(DuckPondError::InvalidSnapshot { snapshot_id })
+    }
+
+    /// Get the files added and removed per table after snapshot `from` up to
+    /// and including snapshot `to`
+    ///
+    /// A file both added and removed within the range is left out.
+    pub async fn diff(&self, from: i64, to: i64) -> Result<Vec<TableDiff>> {
+        if from > to {
+            return Err(DuckPondError::InvalidSnapshotRange { from, to });
+        }
+        let max_snapshot_id = self.max_snapshot_id().await?;
+        for snapshot_id in [from, to] {
+            if snapshot_id < 0 || snapshot_id > max_snapshot_id {
+                return Err(DuckPondError::InvalidSnapshot { snapshot_id });
+            }
+        }
+
+        let paths = self.file_paths().await?;
+        let lineage = self.lineage().await?;
+        let changes =
+            ReadQueries::list_file_changes(&self.pool, self.dialect, from, to, &lineage).await?;
+        let mut diffs: Vec<TableDiff> = Vec::new();
+        for (added, mut file) in changes {
+            (file.path, file.path_is_relative) =
+                paths.resolve(file.table_id, &file.path, file.path_is_relative);
+            let diff = match diffs.iter_mut().position(|d| d.table_id == file.table_id) {
+                Some(i) => &mut diffs[i],
+                None => {
+                    diffs.push(TableDiff {
+                        table_id: file.table_id,
+                        ..Default::default()
+                    });
+                    diffs.last_mut().unwrap()
+                }
+            };
+            if added {
+                diff.added_files.push(file);
+            } else {
+                diff.removed_files.push(file);
+            }
+        }
+        Ok(diffs)
+    }
+
+    /// Get the data and delete files of a table visible in any snapshot from
+    /// `from` to `to`, the input for computing row-level changes
+    pub async fn table_file_history(
+        &self,
+        table_id: i64,
+        from: i64,
+        to: i64,
+    ) -> Result<TableFileHistory> {
+        if from > to {
+            return Err(DuckPondError::InvalidSnapshotRange { from, to });
+        }
+        let paths = self.file_paths().await?;
+        let lineage = self.lineage().await?;
+        let mut history = TableFileHistory {
+            data_files: ReadQueries::list_table_data_file_history(
+                &self.pool,
+                self.dialect,
+                table_id,
+                from,
+                to,
+                &lineage,
+            )
+            .await?,
+            delete_files: ReadQueries::list_table_delete_file_history(
+                &self.pool,
+                self.dialect,
+                table_id,
+                from,
+                to,
+                &lineage,
+            )
+            .await?,
+        };
+        for file in &mut history.data_files {
+            (file.path, file.path_is_relative) =
+                paths.resolve(table_id, &file.path, file.path_is_relative);
+        }
+        for file in &mut history.delete_files {
+            (file.path, file.path_is_relative) =
+                paths.resolve(table_id, &file.path, file.path_is_relative);
+        }
+        Ok(history)
+    }
+
+    /// Restore a table to its state at `to_snapshot`
+    ///
+    /// Commits a new snapshot in which the table's columns, tags and files
+    /// equal those visible at `to_snapshot`: rows added since are ended and
+    /// rows removed since are reopened. History and data files are left
+    /// untouched. Returns the ID of the new snapshot.
+    pub async fn restore_table(&self, table_id: i64, to_snapshot: i64) -> Result<i64> {
+        let time_travel = self.at_snapshot(to_snapshot);
+        let mut exists = false;
+        for schema in time_travel.list_schemas().await? {
+            let tables = time_travel.list_tables(schema.schema_id).await?;
+            exists |= tables.iter().any(|t| t.table_id == table_id);
+        }
+        if !exists {
+            return Err(DuckPondError::TableNotFound {
+                table_name: format!("{} at snapshot {}", table_id, to_snapshot),
+            });
+        }
+
+        self.restore_to(Some(table_id), to_snapshot).await
+    }
+
+    /// Restore the whole catalog to its state at `to_snapshot`
+    ///
+    /// Like [`Self::restore_table`] for every schema, table and view.
+    pub async fn restore(&self, to_snapshot: i64) -> Result<i64> {
+        self.restore_to(None, to_snapshot).await
+    }
+
+    async fn restore_to(&self, table_id: Option<i64>, to_snapshot: i64) -> Result<i64> {
+        let snapshots = ReadQueries::list_snapshots(&self.pool, self.dialect).await?;
+        if !snapshots.iter().any(|s| s.snapshot_id == to_snapshot) {
+            return Err(DuckPondError::InvalidSnapshot {
+                snapshot_id: to_snapshot,
+            });
+        }
+        if self.current_snapshot().await? == Some(to_snapshot) {
+            return Ok(to_snapshot);
+        }
+
+        let mut snapshot_context = self.snapshot_context().await?;
+        let snapshot_id = snapshot_context.snapshot_id;
+
+        let changes = match table_id {
+            Some(table_id) => vec![SnapshotChange::AlteredTable { table_id }],
+            None => {
+                let mut conn = self.pool.acquire().await?;
+                let changed_tables = ReadQueries::list_tables_changed_after(
+                    &mut conn,
+                    self.dialect,
+                    to_snapshot,
+                    None,
+                    &snapshot_context.lineage,
+                )
+                .await?;
+                drop(conn);
+                self.restore_changes(to_snapshot, changed_tables).await?
+            }
+        };
+
+        let mut tx = self.pool.begin().await?;
+        let removed_data_files = ReadQueries::list_data_files_removed_after(
+            &mut *tx,
+            self.dialect,
+            to_snapshot,
+            table_id,
+            &snapshot_context.lineage,
+        )
+        .await?;
+        let removed_delete_files = ReadQueries::list_delete_files_removed_after(
+            &mut *tx,
+            self.dialect,
+            to_snapshot,
+            table_id,
+            &snapshot_context.lineage,
+        )
+        .await?;
+
+        // End first: the reopened copies are themselves newer than the target
+        WriteQueries::end_rows_added_after(
+            &mut tx,
+            self.dialect,
+            snapshot_id,
+            to_snapshot,
+            table_id,
+            &snapshot_context.lineage,
+        )
+        .await?;
+        WriteQueries::reopen_rows_removed_after(
+            &mut tx,
+            self.dialect,
+            snapshot_id,
+            to_snapshot,
+            table_id,
+            &snapshot_context.lineage,
+        )
+        .await?;
+
+        // Reopened files get new file IDs but keep their paths, which
+        // expiration only schedules once no reachable row uses them;
+        // delete files follow their data file
+        let mut new_data_file_ids = HashMap::new();
+        for data_file_id in removed_data_files {
+            let new_id = snapshot_context.next_file_id;
+            snapshot_context.next_file_id += 1;
+            WriteQueries::copy_data_file(&mut tx, self.dialect, new_id, snapshot_id, data_file_id)
+                .await?;
+            new_data_file_ids.insert(data_file_id, new_id);
+        }
+        for (delete_file_id, data_file_id) in removed_delete_files {
+            let new_id = snapshot_context.next_file_id;
+            snapshot_context.next_file_id += 1;
+            WriteQueries::copy_delete_file(
+                &mut *tx,
+                self.dialect,
+                new_id,
+                snapshot_id,
+                new_data_file_ids
+                    .get(&data_file_id)
+                    .copied()
+                    .unwrap_or(data_file_id),
+                delete_file_id,
+            )
+            .await?;
+        }
+
+        WriteQueries::refresh_table_stats(&mut tx, self.dialect, table_id).await?;
+
+        snapshot_context.commit_in(&mut tx, &changes).await?;
+        tx.commit().await?;
+
+        Ok(snapshot_id)
+    }
+
+    /// Describe a catalog restore to `to_snapshot`: schemas, tables and views
+    /// that come back are created, those that go away dropped, and any other
+    /// changed table altered
+    async fn restore_changes(
+        &self,
+        to_snapshot: i64,
+        changed_tables: Vec<i64>,
+    ) -> Result<Vec<SnapshotChange>> {
+        let current = self.current_snapshot().await?.unwrap_or(0);
+        let mut catalogs = Vec::new();
+        for snapshot_id in [to_snapshot, current] {
+            let time_travel = self.at_snapshot(snapshot_id);
+            let schemas = time_travel.list_schemas().await?;
+            let mut tables = Vec::new();
+            let mut views = Vec::new();
+            for schema in &schemas {
+                for table in time_travel.list_tables(schema.schema_id).await? {
+                    tables.push((schema.schema_name.clone(), table));
+                }
+                for view in time_travel.list_views(schema.schema_id).await? {
+                    views.push((schema.schema_name.clone(), view));
+                }
+            }
+            catalogs.push((schemas, tables, views));
+        }
+        let (target_schemas, target_tables, target_views) = &catalogs[0];
+        let (current_schemas, current_tables, current_views) = &catalogs[1];
+
+        let mut changes = Vec::new();
+        for schema in target_schemas {
+            if !current_schemas
+                .iter()
+                .any(|s| s.schema_id == schema.schema_id)
+            {
+                changes.push(SnapshotChange::CreatedSchema {
+                    schema: schema.schema_name.clone(),
+                });
+            }
+        }
+        for (schema_name, table) in target_tables {
+            if !current_tables
+                .iter()
+                .any(|(_, t)| t.table_id == table.table_id)
+            {
+                changes.push(SnapshotChange::CreatedTable {
+                    schema: schema_name.clone(),
+                    table: table.table_name.clone(),
+                });
+            }
+        }
+        for (schema_name, view) in target_views {
+            if !current_views.iter().any(|(_, v)| v.view_id == view.view_id) {
+                changes.push(SnapshotChange::CreatedView {
+                    schema: schema_name.clone(),
+                    view: view.view_name.clone(),
+                });
+            }
+        }
+        for (_, view) in current_views {
+            if !target_views.iter().any(|(_, v)| v.view_id == view.view_id) {
+                changes.push(SnapshotChange::DroppedView {
+                    view_id: view.view_id,
+                });
+            }
+        }
+        for table_id in changed_tables {
+            let in_target = target_tables.iter().any(|(_, t)| t.table_id == table_id);
+            let in_current = current_tables.iter().any(|(_, t)| t.table_id == table_id);
+            if in_target && in_current {
+                changes.push(SnapshotChange::AlteredTable { table_id });
+            } else if in_current {
+                changes.push(SnapshotChange::DroppedTable { table_id });
+            }
+        }
+        for schema in current_schemas {
+            if !target_schemas
+                .iter()
+                .any(|s| s.schema_id == schema.schema_id)
+            {
+                changes.push(SnapshotChange::DroppedSchema {
+                    schema_id: schema.schema_id,
+                });
+            }
+        }
+        Ok(changes)
+    }
+
+    /// List tags and branches
+    pub async fn list_refs(&self) -> Result<Vec<SnapshotRef>> {
+        ReadQueries::list_snapshot_refs(&self.pool, self.dialect).await
+    }
+
+    /// Get a tag or branch by name
+    pub async fn snapshot_ref(&self, name: &str) -> Result<SnapshotRef> {
+        self.list_refs()
+            .await?
+            .into_iter()
+            .find(|r| r.name == name)
+            .ok_or_else(|| DuckPondError::SnapshotRefNotFound {
+                name: name.to_string(),
+            })
+    }
+
+    /// Tag a snapshot, the current one by default
+    pub async fn create_tag(&self, name: &str, snapshot_id: Option<i64>) -> Result<SnapshotRef> {
+        let snapshot_id = match snapshot_id {
+            Some(snapshot_id) => snapshot_id,
+            None => self.current_snapshot().await?.unwrap_or(0),
+        };
+        if !ReadQueries::list_snapshots(&self.pool, self.dialect)
+            .await?
+            .iter()
+            .any(|s| s.snapshot_id == snapshot_id)
+        {
+            return Err(DuckPondError::InvalidSnapshot { snapshot_id });
+        }
+
+        self.insert_ref(SnapshotRef {
+            name: name.to_string(),
+            kind: RefKind::Tag,
+            snapshot_id,
+            base_snapshot: None,
+        })
+        .await
+    }
+
+    /// Remove a tag; the snapshot itself is kept until it expires
+    pub async fn drop_tag(&self, name: &str) -> Result<()> {
+        let tag = self.snapshot_ref(name).await?;
+        if tag.kind != RefKind::Tag {
+            return Err(DuckPondError::SnapshotRefNotFound {
+                name: name.to_string(),
+            });
+        }
+        WriteQueries::delete_snapshot_ref(&self.pool, self.dialect, name).await
+    }
+
+    /// Create a time travel interface at a tagged snapshot
+    pub async fn at_tag(&self, name: &str) -> Result<TimeTravel<'_>> {
+        let tag = self.snapshot_ref(name).await?;
+        if tag.kind != RefKind::Tag {
+            return Err(DuckPondError::SnapshotRefNotFound {
+                name: name.to_string(),
+            });
+        }
+        Ok(self.at_snapshot(tag.snapshot_id))
+    }
+
+    /// Create a branch from the current snapshot of main
+    ///
+    /// Main and other branches keep committing meanwhile; the branch does
+    /// not see their snapshots, nor they its own until it is merged.
+    pub async fn create_branch(&self, name: &str) -> Result<SnapshotRef> {
+        let snapshot_id = ReadQueries::get_current_snapshot(&self.pool, self.dialect)
+            .await?
+            .unwrap_or(0);
+        self.insert_ref(SnapshotRef {
+            name: name.to_string(),
+            kind: RefKind::Branch,
+            snapshot_id,
+            base_snapshot: Some(snapshot_id),
+        })
+        .await
+    }
+
+    /// Get a handle that reads the head of a branch and commits to it
+    pub async fn on_branch(&self, name: &str) -> Result<DuckPond> {
+        self.branch_ref(name).await?;
+        Ok(DuckPond {
+            branch: Some(name.to_string()),
+            ..self.clone()
+        })
+    }
+
+    /// List the snapshots committed to a branch that main does not see yet
+    pub async fn branch_snapshots(&self, name: &str) -> Result<Vec<i64>> {
+        ReadQueries::list_branch_snapshots(&self.pool, self.dialect, name).await
+    }
+
+    /// Fast-forward main to the head of a branch
+    ///
+    /// Refused with a conflict once main has committed since the branch's
+    /// base, as main would lose those snapshots. Returns the new current
+    /// snapshot of main. The branch stays and can keep collecting snapshots.
+    pub async fn merge_branch(&self, name: &str) -> Result<i64> {
+        let branch = self.branch_ref(name).await?;
+        let mut tx = self.pool.begin().await?;
+        let main = ReadQueries::get_current_snapshot(&mut *tx, self.dialect)
+            .await?
+            .unwrap_or(0);
+        let unmerged = ReadQueries::list_branch_snapshots(&mut *tx, self.dialect, name).await?;
+        let head = if unmerged.is_empty() {
+            // Nothing to merge; follow main instead
+            main
+        } else if branch.base_snapshot != Some(main) {
+            return Err(DuckPondError::BranchConflict {
+                message: format!(
+                    "main has moved to snapshot {} since branch '{}' was based on {}",
+                    main,
+                    name,
+                    branch.base_snapshot.unwrap_or(0)
+                ),
+            });
+        } else {
+            branch.snapshot_id
+        };
+        WriteQueries::delete_branch_snapshots(&mut *tx, self.dialect, name).await?;
+        WriteQueries::update_snapshot_ref(&mut *tx, self.dialect, name, head, Some(head)).await?;
+        tx.commit().await?;
+
+        Ok(head)
+    }
+
+    /// Remove a branch, discarding the snapshots it has not merged
+    ///
+    /// Files added by the discarded snapshots are scheduled for deletion.
+    /// Returns the IDs of the discarded snapshots.
+    pub async fn drop_branch(&self, name: &str) -> Result<Vec<i64>> {
+        self.branch_ref(name).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let discarded = ReadQueries::list_branch_snapshots(&mut *tx, self.dialect, name).await?;
+        if !discarded.is_empty() {
+            // Resolve paths before the tables created on the branch are gone
+            let paths = FilePaths::load(&mut tx, self.dialect).await?;
+            let added =
+                ReadQueries::list_files_added_on_branch(&mut *tx, self.dialect, name).await?;
+
+            WriteQueries::discard_branch_rows(&mut tx, self.dialect, name).await?;
+            for snapshot_id in &discarded {
+                WriteQueries::delete_snapshot(&mut tx, self.dialect, *snapshot_id).await?;
+            }
+            WriteQueries::delete_branch_snapshots(&mut *tx, self.dialect, name).await?;
+            WriteQueries::delete_unreachable_rows(&mut tx, self.dialect).await?;
+            WriteQueries::refresh_table_stats(&mut tx, self.dialect, None).await?;
+
+            // Restores and re-registered files share paths with rows that
+            // stay, so only paths nothing references any more are deleted
+            let mut referenced = referenced_file_paths(&mut tx, self.dialect).await?;
+            let now = Utc::now();
+            for (file_id, table_id, path, path_is_relative) in added {
+                let (path, path_is_relative) = paths.resolve(table_id, &path, path_is_relative);
+                if !referenced.insert(path.clone()) {
+                    continue;
+                }
+                WriteQueries::schedule_file_deletion(
+                    &mut *tx,
+                    self.dialect,
+                    file_id,
+                    &path,
+                    path_is_relative,
+                    now,
+                )
+                .await?;
+            }
+        }
+        WriteQueries::delete_snapshot_ref(&mut *tx, self.dialect, name).await?;
+        tx.commit().await?;
+
+        Ok(discarded)
+    }
+
+    async fn branch_ref(&self, name: &str) -> Result<SnapshotRef> {
+        let branch = self.snapshot_ref(name).await?;
+        if branch.kind != RefKind::Branch {
+            return Err(DuckPondError::SnapshotRefNotFound {
+                name: name.to_string(),
+            });
+        }
+        Ok(branch)
+    }
+
+    async fn insert_ref(&self, snapshot_ref: SnapshotRef) -> Result<SnapshotRef> {
+        if self
+            .list_refs()
+            .await?
+            .iter()
+            .any(|r| r.name == snapshot_ref.name)
+        {
+            return Err(DuckPondError::SnapshotRefExists {
+                name: snapshot_ref.name,
+            });
+        }
+        WriteQueries::insert_snapshot_ref(&self.pool, self.dialect, &snapshot_ref).await?;
+        Ok(snapshot_ref)
+    }
+
+    /// Start a new snapshot on this handle's branch, or on main
+    ///
+    /// Snapshot IDs are global, so main and its branches commit
+    /// interleaving snapshots; each sees only its own lineage.
+    async fn snapshot_context(&self) -> Result<SnapshotContext> {
+        if let Some(snapshot_id) = self.as_of {
+            return Err(DuckPondError::HistoricalSnapshot { snapshot_id });
+        }
+        let mut context = SnapshotContext::new(&self.pool, self.dialect).await?;
+        if let Some(name) = &self.branch {
+            context.branch = Some(self.branch_ref(name).await?);
+        }
+        context.lineage = self.lineage().await?;
+        Ok(context)
+    }
+
+    /// Get the snapshots this handle sees
+    async fn lineage(&self) -> Result<Lineage> {
+        let Some(name) = &self.branch else {
+            return Ok(Lineage::Main);
+        };
+        let branch = self.branch_ref(name).await?;
+        Ok(Lineage::Branch {
+            base_snapshot: branch.base_snapshot.unwrap_or(branch.snapshot_id),
+            snapshots: self.branch_snapshots(name).await?,
+        })
+    }
+
+    /// Get the sort order of a table at the current snapshot
+    pub async fn sort_order(&self, table_id: i64) -> Result<Option<SortOrder>> {
+        let snapshot_id = self.current_snapshot().await?.unwrap_or(0);
+        self.sort_order_at_snapshot(table_id, snapshot_id).await
+    }
+
+    /// Get the sort order of a table at a specific snapshot
+    pub async fn sort_order_at_snapshot(
+        &self,
+        table_id: i64,
+        snapshot_id: i64,
+    ) -> Result<Option<SortOrder>> {
+        ReadQueries::get_tag(
+            &self.pool,
+            self.dialect,
+            table_id,
+            SORT_ORDER_TAG,
+            snapshot_id,
+            &self.lineage().await?,
+        )
+        .await?
+        .map(|value| value.parse())
+        .transpose()
+    }
+
+    /// Set (or clear, with `None`) the sort order applied when writing a table
+    pub async fn set_sort_order(
+        &self,
+        table_id: i64,
+        sort_order: Option<&SortOrder>,
+    ) -> Result<()> {
+        if let Some(sort_order) = sort_order {
+            let columns = self.table_structure(table_id).await?;
+            for name in sort_order.columns() {
+                if !columns.iter().any(|c| c.column_name == name) {
+                    return Err(DuckPondError::InvalidSortOrder {
+                        message: format!("column '{}' does not exist in table {}", name, table_id),
+                    });
+                }
+            }
+        }
+
+        let snapshot_context = self.snapshot_context().await?;
+
+        let mut tx = self.pool.begin().await?;
+        WriteQueries::end_tag(
+            &mut tx,
+            self.dialect,
+            table_id,
+            SORT_ORDER_TAG,
+            snapshot_context.snapshot_id,
+            &snapshot_context.lineage,
+        )
+        .await?;
+
+        if let Some(sort_order) = sort_order {
+            WriteQueries::insert_tag(
+                &mut *tx,
+                self.dialect,
+                table_id,
+                snapshot_context.snapshot_id,
+                SORT_ORDER_TAG,
+                &sort_order.to_string(),
+            )
+            .await?;
+        }
+
+        snapshot_context
+            .commit_in(&mut tx, &[SnapshotChange::AlteredTable { table_id }])
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// List the tags of a schema, table or view at the current snapshot
+    pub async fn list_tags(&self, object_id: i64) -> Result<Vec<ObjectTag>> {
+        let snapshot_id = self.current_snapshot().await?.unwrap_or(0);
+        self.list_tags_at_snapshot(object_id, snapshot_id).await
+    }
+
+    /// List the tags of a schema, table or view at a specific snapshot
+    ///
+    /// Tags DuckPond sets itself, such as [`SORT_ORDER_TAG`], are left out.
+    pub async fn list_tags_at_snapshot(
+        &self,
+        object_id: i64,
+        snapshot_id: i64,
+    ) -> Result<Vec<ObjectTag>> {
+        let lineage = self.lineage().await?;
+        let mut tags =
+            ReadQueries::list_tags(&self.pool, self.dialect, object_id, snapshot_id, &lineage)
+                .await?;
+        tags.retain(|tag| !tag.key.starts_with(RESERVED_TAG_PREFIX));
+        Ok(tags)
+    }
+
+    /// Set a tag on a schema, table or view, replacing its previous value
+    pub async fn set_tag(&self, object_id: i64, key: &str, value: &str) -> Result<()> {
+        check_tag_key(key)?;
+        let changes = self.object_changes(object_id).await?;
+        let current = self.current_snapshot().await?.unwrap_or(0);
+        let snapshot_context = self.snapshot_context().await?;
+        let snapshot_id = snapshot_context.snapshot_id;
+
+        let mut tx = self.pool.begin().await?;
+        let previous = ReadQueries::get_tag(
+            &mut *tx,
+            self.dialect,
+            object_id,
+            key,
+            current,
+            &snapshot_context.lineage,
+        )
+        .await?;
+        if previous.as_deref() == Some(value) {
+            return Ok(());
+        }
+        WriteQueries::end_tag(
+            &mut tx,
+            self.dialect,
+            object_id,
+            key,
+            snapshot_id,
+            &snapshot_context.lineage,
+        )
+        .await?;
+        WriteQueries::insert_tag(&mut *tx, self.dialect, object_id, snapshot_id, key, value)
+            .await?;
+        snapshot_context.commit_in(&mut tx, &changes).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Remove a tag from a schema, table or view
+    pub async fn unset_tag(&self, object_id: i64, key: &str) -> Result<()> {
+        check_tag_key(key)?;
+        let changes = self.object_changes(object_id).await?;
+        let snapshot_context = self.snapshot_context().await?;
+
+        let mut tx = self.pool.begin().await?;
+        let ended = WriteQueries::end_tag(
+            &mut tx,
+            self.dialect,
+            object_id,
+            key,
+            snapshot_context.snapshot_id,
+            &snapshot_context.lineage,
+        )
+        .await?;
+        if ended == 0 {
+            return Err(DuckPondError::TagNotFound {
+                key: key.to_string(),
+            });
+        }
+        snapshot_context.commit_in(&mut tx, &changes).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// List the tags of a table's columns at the current snapshot
+    pub async fn list_column_tags(&self, table_id: i64) -> Result<Vec<ColumnTag>> {
+        let snapshot_id = self.current_snapshot().await?.unwrap_or(0);
+        self.list_column_tags_at_snapshot(table_id, snapshot_id)
+            .await
+    }
+
+    /// List the tags of a table's columns at a specific snapshot
+    pub async fn list_column_tags_at_snapshot(
+        &self,
+        table_id: i64,
+        snapshot_id: i64,
+    ) -> Result<Vec<ColumnTag>> {
+        let lineage = self.lineage().await?;
+        ReadQueries::list_column_tags(&self.pool, self.dialect, table_id, snapshot_id, &lineage)
+            .await
+    }
+
+    /// Set a tag on a column, replacing its previous value
+    pub async fn set_column_tag(
+        &self,
+        table_id: i64,
+        column_id: i64,
+        key: &str,
+        value: &str,
+    ) -> Result<()> {
+        check_tag_key(key)?;
+        self.check_column(table_id, column_id).await?;
+        let current = self.current_snapshot().await?.unwrap_or(0);
+        let snapshot_context = self.snapshot_context().await?;
+        let snapshot_id = snapshot_context.snapshot_id;
+
+        let mut tx = self.pool.begin().await?;
+        let tags = ReadQueries::list_column_tags(
+            &mut *tx,
+            self.dialect,
+            table_id,
+            current,
+            &snapshot_context.lineage,
+        )
+        .await?;
+        if tags
+            .iter()
+            .any(|t| t.column_id == column_id && t.key == key && t.value == value)
+        {
+            return Ok(());
+        }
+        WriteQueries::end_column_tag(
+            &mut tx,
+            self.dialect,
+            table_id,
+            column_id,
+            key,
+            snapshot_id,
+            &snapshot_context.lineage,
+        )
+        .await?;
+        WriteQueries::insert_column_tag(
+            &mut *tx,
+            self.dialect,
+            table_id,
+            column_id,
+            snapshot_id,
+            key,
+            value,
+        )
+        .await?;
+        snapshot_context
+            .commit_in(&mut tx, &[SnapshotChange::AlteredTable { table_id }])
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Remove a tag from a column
+    pub async fn unset_column_tag(&self, table_id: i64, column_id: i64, key: &str) -> Result<()> {
+        check_tag_key(key)?;
+        self.check_column(table_id, column_id).await?;
+        let snapshot_context = self.snapshot_context().await?;
+
+        let mut tx = self.pool.begin().await?;
+        let ended = WriteQueries::end_column_tag(
+            &mut tx,
+            self.dialect,
+            table_id,
+            column_id,
+            key,
+            snapshot_context.snapshot_id,
+            &snapshot_context.lineage,
+        )
+        .await?;
+        if ended == 0 {
+            return Err(DuckPondError::TagNotFound {
+                key: key.to_string(),
+            });
+        }
+        snapshot_context
+            .commit_in(&mut tx, &[SnapshotChange::AlteredTable { table_id }])
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Find the object a tag is set on and describe the change as a snapshot
+    /// change; the spec has no change type for altering a schema
+    async fn object_changes(&self, object_id: i64) -> Result<Vec<SnapshotChange>> {
+        for schema in self.list_schemas().await? {
+            if schema.schema_id == object_id {
+                return Ok(Vec::new());
+            }
+            let tables = self.list_tables(schema.schema_id).await?;
+            if tables.iter().any(|t| t.table_id == object_id) {
+                return Ok(vec![SnapshotChange::AlteredTable {
+                    table_id: object_id,
+                }]);
+            }
+            let views = self.list_views(schema.schema_id).await?;
+            if views.iter().any(|v| v.view_id == object_id) {
+                return Ok(vec![SnapshotChange::AlteredView { view_id: object_id }]);
+            }
+        }
+        Err(DuckPondError::ObjectNotFound { object_id })
+    }
+
+    /// Check that a column exists in a table before tagging it
+    async fn check_column(&self, table_id: i64, column_id: i64) -> Result<()> {
+        if !self
+            .table_structure(table_id)
+            .await?
+            .iter()
+            .any(|c| c.column_id == column_id)
+        {
+            return Err(DuckPondError::InvalidTag {
+                message: format!("column {} does not exist in table {}", column_id, table_id),
+            });
+        }
+        Ok(())
+    }
+
+    /// Query data from a table with basic filtering and formatting
+    pub async fn query_data(
+        &self,
+        table_id: i64,
+        limit: usize,
+        _columns: Option<String>,
+        format: String,
+    ) -> Result<Vec<String>> {
+        // Get table structure to understand columns
+        let column_info = self.table_structure(table_id).await?;
+
+        // Get data files for this table
+        let data_files = self.list_data_files(table_id).await?;
+
+        if data_files.is_empty() {
             return Ok(vec!["No data files found for this table.".to_string()]);
         }
 
@@ -371,6 +2421,225 @@ impl DuckPond {
     }
 }
 
+/// DuckLake specification version written to newly initialized catalogs
+pub const DUCKLAKE_SPEC_VERSION: &str = "0.2";
+
+/// Specification versions this release can open
+pub const SUPPORTED_SPEC_VERSIONS: &[&str] = &["0.1", "0.2"];
+
+/// Upgrade steps between consecutive spec versions, oldest first
+const SPEC_UPGRADES: &[(&str, &str)] = &[("0.1", "0.2")];
+
+/// Name of the schema every catalog starts with
+pub const DEFAULT_SCHEMA: &str = "main";
+
+/// Tag key under which a table's sort order is stored in `ducklake_tag`
+pub const SORT_ORDER_TAG: &str = "duckpond.sort_order";
+
+/// Prefix of tag keys DuckPond sets itself, such as [`SORT_ORDER_TAG`]
+const RESERVED_TAG_PREFIX: &str = "duckpond.";
+
+fn check_tag_key(key: &str) -> Result<()> {
+    if key.is_empty() || key.starts_with(RESERVED_TAG_PREFIX) {
+        return Err(DuckPondError::InvalidTag {
+            message: format!("tag key '{}' is empty or reserved", key),
+        });
+    }
+    Ok(())
+}
+
+/// Translates data and delete file paths between the catalog, which since
+/// spec 0.2 stores relative paths against their table's path, and the API,
+/// where they are relative to the data path
+struct FilePaths {
+    /// The catalog's `data_path`, under which absolute paths are given
+    /// relative again
+    data_path: Option<String>,
+    /// Each table's path, resolved through its schema's, relative to the
+    /// data path unless absolute, as `(path, path_is_relative)`
+    tables: HashMap<i64, (String, bool)>,
+}
+
+impl FilePaths {
+    async fn load(conn: &mut AnyConnection, dialect: Dialect) -> Result<Self> {
+        let data_path = ReadQueries::get_metadata(&mut *conn, dialect, "data_path").await?;
+        let mut tables = HashMap::new();
+        for (table_id, schema, table) in ReadQueries::list_table_paths(&mut *conn, dialect).await? {
+            let Some((path, relative)) = table else {
+                continue;
+            };
+            let path = match schema {
+                Some((schema_path, schema_relative)) if relative => {
+                    (format!("{}{}", schema_path, path), schema_relative)
+                }
+                _ => (path, relative),
+            };
+            tables.insert(table_id, path);
+        }
+        Ok(Self { data_path, tables })
+    }
+
+    /// A catalog file path of a table, relative to the data path unless
+    /// absolute
+    fn resolve(&self, table_id: i64, path: &str, path_is_relative: bool) -> (String, bool) {
+        if !path_is_relative {
+            return match self.under_data_path(path) {
+                Some(relative) => (relative.to_string(), true),
+                None => (path.to_string(), false),
+            };
+        }
+        match self.tables.get(&table_id) {
+            Some((table_path, relative)) => (format!("{}{}", table_path, path), *relative),
+            None => (path.to_string(), true),
+        }
+    }
+
+    /// A file path relative to the data path unless absolute, as the catalog
+    /// stores it for a table: relative to the table's path if it lies under
+    /// it, and absolute otherwise
+    fn to_catalog(
+        &self,
+        table_id: i64,
+        path: &str,
+        path_is_relative: bool,
+    ) -> Result<(String, bool)> {
+        let Some((table_path, table_path_is_relative)) = self.tables.get(&table_id) else {
+            return Ok((path.to_string(), path_is_relative));
+        };
+        if path_is_relative == *table_path_is_relative {
+            if let Some(relative) = path.strip_prefix(table_path.as_str()) {
+                return Ok((relative.to_string(), true));
+            }
+        }
+        if !path_is_relative {
+            return Ok((path.to_string(), false));
+        }
+        match &self.data_path {
+            Some(data_path) => Ok((
+                format!("{}/{}", data_path.trim_end_matches('/'), path),
+                false,
+            )),
+            None => Err(DuckPondError::InvalidDataFile {
+                path: path.to_string(),
+                message: format!(
+                    "lies outside the path of table {} and the catalog has no data path",
+                    table_id
+                ),
+            }),
+        }
+    }
+
+    fn under_data_path<'a>(&self, path: &'a str) -> Option<&'a str> {
+        let data_path = self.data_path.as_deref()?.trim_end_matches('/');
+        path.strip_prefix(data_path)?
+            .strip_prefix('/')
+            .filter(|relative| !relative.is_empty())
+    }
+}
+
+/// Get the paths of all data and delete files referenced by any snapshot or
+/// still scheduled for deletion, relative to the data path unless absolute
+async fn referenced_file_paths(
+    conn: &mut AnyConnection,
+    dialect: Dialect,
+) -> Result<HashSet<String>> {
+    let paths = FilePaths::load(conn, dialect).await?;
+    Ok(ReadQueries::list_referenced_file_paths(&mut *conn, dialect)
+        .await?
+        .into_iter()
+        .map(|(table_id, path, path_is_relative)| match table_id {
+            Some(table_id) => paths.resolve(table_id, &path, path_is_relative).0,
+            None => path,
+        })
+        .collect())
+}
+
+/// Insert the column statistics of a new data file and fold them into the
+/// table's column statistics
+async fn insert_file_stats(
+    conn: &mut AnyConnection,
+    dialect: Dialect,
+    table_id: i64,
+    data_file_id: i64,
+    column_statistics: &[FileColumnStatistics],
+) -> Result<()> {
+    for stat in column_statistics {
+        WriteQueries::insert_file_column_stats(
+            &mut *conn,
+            dialect,
+            data_file_id,
+            table_id,
+            stat.column_id,
+            stat.value_count,
+            stat.null_count,
+            stat.nan_count,
+            stat.min_value.as_deref(),
+            stat.max_value.as_deref(),
+        )
+        .await?;
+        WriteQueries::update_table_column_stats(
+            &mut *conn,
+            dialect,
+            table_id,
+            stat.column_id,
+            stat.null_count,
+            stat.nan_count,
+            stat.min_value.as_deref(),
+            stat.max_value.as_deref(),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Check that rows to inline match the table's columns
+fn check_inlined_rows(columns: &[ColumnInfo], rows: &[Vec<InlinedValue>]) -> Result<()> {
+    for values in rows {
+        if values.len() != columns.len() {
+            return Err(DuckPondError::InvalidInlinedData {
+                message: format!(
+                    "row has {} values for {} columns",
+                    values.len(),
+                    columns.len()
+                ),
+            });
+        }
+        for (column, value) in columns.iter().zip(values) {
+            let accepted = InlinedColumnKind::of(&column.column_type)
+                .is_some_and(|kind| kind.accepts(value))
+                && (column.nulls_allowed || *value != InlinedValue::Null);
+            if !accepted {
+                return Err(DuckPondError::InvalidInlinedData {
+                    message: format!(
+                        "{:?} does not fit column {} of type {}",
+                        value, column.column_name, column.column_type
+                    ),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether two column lists have the same columns, names and types
+fn same_columns(a: &[ColumnInfo], b: &[ColumnInfo]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(a, b)| {
+            (a.column_id, &a.column_name, &a.column_type)
+                == (b.column_id, &b.column_name, &b.column_type)
+        })
+}
+
+fn check_spec_version(version: &str) -> Result<()> {
+    if SUPPORTED_SPEC_VERSIONS.contains(&version) {
+        Ok(())
+    } else {
+        Err(DuckPondError::UnsupportedSpecVersion {
+            version: version.to_string(),
+        })
+    }
+}
+
 /// Column definition for table creation
 #[derive(Debug, Clone)]
 pub struct ColumnDefinition {
@@ -378,6 +2647,87 @@ pub struct ColumnDefinition {
     pub name: String,
     pub data_type: String,
     pub nullable: bool,
+    /// Fields of a `struct`, the `element` of a `list`, or the `key` and
+    /// `value` of a `map`
+    pub children: Vec<ColumnDefinition>,
+}
+
+impl From<ColumnInfo> for ColumnDefinition {
+    fn from(column: ColumnInfo) -> Self {
+        Self {
+            column_id: Some(column.column_id),
+            name: column.column_name,
+            data_type: column.column_type,
+            nullable: column.nulls_allowed,
+            children: column.children.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// A column definition placed in the catalog, in depth-first order
+struct PlacedColumn<'a> {
+    column_id: i64,
+    parent_column: Option<i64>,
+    column_type: DuckLakeType,
+    definition: &'a ColumnDefinition,
+}
+
+/// Validate column definitions and assign column IDs depth first, from
+/// `first_id` on. Columns without an explicit ID take the next ID after the
+/// largest one assigned so far.
+fn place_columns(columns: &[ColumnDefinition], mut first_id: i64) -> Result<Vec<PlacedColumn<'_>>> {
+    fn place<'a>(
+        columns: &'a [ColumnDefinition],
+        parent_column: Option<i64>,
+        next_id: &mut i64,
+        placed: &mut Vec<PlacedColumn<'a>>,
+    ) -> Result<()> {
+        let mut names = HashSet::new();
+        for column in columns {
+            if !names.insert(column.name.as_str()) {
+                return Err(invalid_column(format!(
+                    "duplicate column name '{}'",
+                    column.name
+                )));
+            }
+            let column_type: DuckLakeType = column.data_type.parse()?;
+            let children = column.children.len();
+            if !column_type.accepts_children(children) {
+                return Err(invalid_column(format!(
+                    "column '{}' of type {} cannot have {} child columns \
+                     (struct needs at least one, list one and map two)",
+                    column.name, column.data_type, children
+                )));
+            }
+
+            let column_id = column.column_id.unwrap_or(*next_id);
+            *next_id = (*next_id).max(column_id + 1);
+            placed.push(PlacedColumn {
+                column_id,
+                parent_column,
+                column_type,
+                definition: column,
+            });
+            place(&column.children, Some(column_id), next_id, placed)?;
+        }
+        Ok(())
+    }
+
+    let mut placed = Vec::new();
+    place(columns, None, &mut first_id, &mut placed)?;
+
+    let mut ids = HashSet::new();
+    if let Some(column) = placed.iter().find(|c| !ids.insert(c.column_id)) {
+        return Err(invalid_column(format!(
+            "duplicate column ID {}",
+            column.column_id
+        )));
+    }
+    Ok(placed)
+}
+
+fn invalid_column(message: String) -> DuckPondError {
+    DuckPondError::InvalidColumnDefinition { message }
 }
 
 /// File-level column statistics for data insertion
@@ -391,41 +2741,91 @@ pub struct FileColumnStatistics {
     pub max_value: Option<String>,
 }
 
+/// A data file that already exists in storage, to register with a table
+#[derive(Debug, Clone)]
+pub struct NewDataFile {
+    pub path: String,
+    /// Whether `path` is relative to the catalog's data path
+    pub path_is_relative: bool,
+    pub record_count: i64,
+    pub file_size_bytes: i64,
+    pub footer_size: Option<i64>,
+    pub column_statistics: Vec<FileColumnStatistics>,
+}
+
 /// Internal helper for managing snapshot context within transactions
 struct SnapshotContext {
+    dialect: Dialect,
     snapshot_id: i64,
     schema_version: i64,
     next_catalog_id: i64,
     next_file_id: i64,
+    /// Branch the snapshot is committed to, `None` for main
+    branch: Option<SnapshotRef>,
+    /// Snapshots the writes see, and which rows they may end
+    lineage: Lineage,
 }
 
 impl SnapshotContext {
     /// Create a new snapshot context for a transaction
-    async fn new(pool: &AnyPool) -> Result<Self> {
-        let max_snapshot_id = ReadQueries::get_max_snapshot_id(pool).await?;
+    async fn new(pool: &AnyPool, dialect: Dialect) -> Result<Self> {
+        let max_snapshot_id = ReadQueries::get_max_snapshot_id(pool, dialect).await?;
         let snapshot_id = max_snapshot_id + 1;
 
         // Get the next IDs from the current snapshot
-        let next_catalog_id = ReadQueries::get_next_catalog_id(pool).await?.unwrap_or(1);
-        let next_file_id = ReadQueries::get_next_file_id(pool).await?.unwrap_or(1);
+        let next_catalog_id = ReadQueries::get_next_catalog_id(pool, dialect)
+            .await?
+            .unwrap_or(1);
+        let next_file_id = ReadQueries::get_next_file_id(pool, dialect)
+            .await?
+            .unwrap_or(1);
 
         // For now, we'll use a simple schema version increment
         // In a real implementation, this should track actual schema changes
         let schema_version = max_snapshot_id + 1;
 
         Ok(Self {
+            dialect,
             snapshot_id,
             schema_version,
             next_catalog_id,
             next_file_id,
+            branch: None,
+            lineage: Lineage::Main,
         })
     }
 
+    /// Commit the snapshot with changes on a connection, e.g. inside a
+    /// transaction doing the snapshot's writes
+    async fn commit_in(&self, conn: &mut AnyConnection, changes: &[SnapshotChange]) -> Result<()> {
+        WriteQueries::create_snapshot(
+            &mut *conn,
+            self.dialect,
+            self.snapshot_id,
+            Utc::now(),
+            self.schema_version,
+            self.next_catalog_id,
+            self.next_file_id,
+        )
+        .await?;
+        WriteQueries::log_snapshot_changes(
+            &mut *conn,
+            self.dialect,
+            self.snapshot_id,
+            &format_changes(changes),
+        )
+        .await?;
+        self.advance_branch(&mut *conn).await?;
+
+        Ok(())
+    }
+
     /// Commit the snapshot with changes
-    async fn commit_with_changes(&self, pool: &AnyPool, changes: &str) -> Result<()> {
+    async fn commit_with_changes(&self, pool: &AnyPool, changes: &[SnapshotChange]) -> Result<()> {
         // Create the snapshot
         WriteQueries::create_snapshot(
             pool,
+            self.dialect,
             self.snapshot_id,
             Utc::now(),
             self.schema_version,
@@ -435,10 +2835,40 @@ impl SnapshotContext {
         .await?;
 
         // Log the changes
-        WriteQueries::log_snapshot_changes(pool, self.snapshot_id, changes).await?;
+        WriteQueries::log_snapshot_changes(
+            pool,
+            self.dialect,
+            self.snapshot_id,
+            &format_changes(changes),
+        )
+        .await?;
+        self.advance_branch(&mut *pool.acquire().await?).await?;
 
         Ok(())
     }
+
+    /// Move the head of the branch the snapshot was committed to, and hide
+    /// the snapshot from main until the branch is merged
+    async fn advance_branch(&self, conn: &mut AnyConnection) -> Result<()> {
+        let Some(branch) = &self.branch else {
+            return Ok(());
+        };
+        WriteQueries::update_snapshot_ref(
+            &mut *conn,
+            self.dialect,
+            &branch.name,
+            self.snapshot_id,
+            branch.base_snapshot,
+        )
+        .await?;
+        WriteQueries::insert_branch_snapshot(
+            &mut *conn,
+            self.dialect,
+            self.snapshot_id,
+            &branch.name,
+        )
+        .await
+    }
 }
 
 /// Time travel operations for querying historical data
@@ -455,6 +2885,11 @@ impl<'a> TimeTravel<'a> {
         }
     }
 
+    /// Get the snapshot this interface reads
+    pub fn snapshot_id(&self) -> i64 {
+        self.snapshot_id
+    }
+
     /// List schemas at this snapshot
     pub async fn list_schemas(&self) -> Result<Vec<SchemaInfo>> {
         self.duckpond
@@ -469,6 +2904,27 @@ impl<'a> TimeTravel<'a> {
             .await
     }
 
+    /// List views in a schema at this snapshot
+    pub async fn list_views(&self, schema_id: i64) -> Result<Vec<ViewInfo>> {
+        self.duckpond
+            .list_views_at_snapshot(schema_id, self.snapshot_id)
+            .await
+    }
+
+    /// List the tags of a schema, table or view at this snapshot
+    pub async fn list_tags(&self, object_id: i64) -> Result<Vec<ObjectTag>> {
+        self.duckpond
+            .list_tags_at_snapshot(object_id, self.snapshot_id)
+            .await
+    }
+
+    /// List the tags of a table's columns at this snapshot
+    pub async fn list_column_tags(&self, table_id: i64) -> Result<Vec<ColumnTag>> {
+        self.duckpond
+            .list_column_tags_at_snapshot(table_id, self.snapshot_id)
+            .await
+    }
+
     /// Get table structure at this snapshot
     pub async fn table_structure(&self, table_id: i64) -> Result<Vec<ColumnInfo>> {
         self.duckpond
@@ -476,6 +2932,13 @@ impl<'a> TimeTravel<'a> {
             .await
     }
 
+    /// List the inlined rows of a table at this snapshot
+    pub async fn list_inlined_rows(&self, table_id: i64) -> Result<Vec<InlinedRow>> {
+        self.duckpond
+            .list_inlined_rows_at_snapshot(table_id, self.snapshot_id)
+            .await
+    }
+
     /// List data files for a table at this snapshot
     pub async fn list_data_files(&self, table_id: i64) -> Result<Vec<DataFileInfo>> {
         self.duckpond
@@ -489,4 +2952,11 @@ impl DuckPond {
     pub fn at_snapshot(&self, snapshot_id: i64) -> TimeTravel<'_> {
         TimeTravel::new(self, snapshot_id)
     }
+
+    /// Create a time travel interface at the latest snapshot taken at or
+    /// before `timestamp`
+    pub async fn at_timestamp(&self, timestamp: DateTime<Utc>) -> Result<TimeTravel<'_>> {
+        let snapshot_id = self.snapshot_at_time(timestamp).await?;
+        Ok(self.at_snapshot(snapshot_id))
+    }
 }
@@ -9,6 +9,9 @@ pub enum DuckPondError {
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
 
+    #[error("Migration error: {0}")]
+    Migration(#[from] sqlx::migrate::MigrateError),
+
     #[error("Configuration error: {message}")]
     Config { message: String },
 
@@ -21,12 +24,59 @@ pub enum DuckPondError {
     #[error("Invalid snapshot: {snapshot_id}")]
     InvalidSnapshot { snapshot_id: i64 },
 
+    #[error("No snapshot at or before {timestamp}")]
+    NoSnapshotAtTime {
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
+
+    #[error("Cannot write at historical snapshot {snapshot_id}")]
+    HistoricalSnapshot { snapshot_id: i64 },
+
+    #[error("Invalid snapshot range: {from} is after {to}")]
+    InvalidSnapshotRange { from: i64, to: i64 },
+
     #[error("Table not found: {table_name}")]
     TableNotFound { table_name: String },
 
     #[error("Schema not found: {schema_name}")]
     SchemaNotFound { schema_name: String },
 
+    #[error("View not found: {view_name}")]
+    ViewNotFound { view_name: String },
+
+    #[error("A table or view named {name} already exists")]
+    NameAlreadyExists { name: String },
+
+    #[error("Schema {schema_name} still holds tables or views")]
+    SchemaNotEmpty { schema_name: String },
+
+    #[error("Catalog object not found: {object_id}")]
+    ObjectNotFound { object_id: i64 },
+
+    #[error("Tag not found: {key}")]
+    TagNotFound { key: String },
+
+    #[error("Invalid tag: {message}")]
+    InvalidTag { message: String },
+
+    #[error("Invalid inlined data: {message}")]
+    InvalidInlinedData { message: String },
+
+    #[error("Invalid column definition: {message}")]
+    InvalidColumnDefinition { message: String },
+
+    #[error("Data does not match table schema: {message}")]
+    SchemaMismatch { message: String },
+
+    #[error("Invalid data file {path}: {message}")]
+    InvalidDataFile { path: String, message: String },
+
+    #[error("Invalid import data: {message}")]
+    InvalidImport { message: String },
+
+    #[error("Invalid filter: {message}")]
+    InvalidFilter { message: String },
+
     #[error("File operation error: {0}")]
     FileOperation(#[from] std::io::Error),
 
@@ -36,6 +86,33 @@ pub enum DuckPondError {
     #[error("UUID error: {0}")]
     Uuid(#[from] uuid::Error),
 
+    #[error("Invalid snapshot change: {message}")]
+    InvalidSnapshotChange { message: String },
+
+    #[error("Invalid sort order: {message}")]
+    InvalidSortOrder { message: String },
+
+    #[error("Unsupported DuckLake spec version: {version}")]
+    UnsupportedSpecVersion { version: String },
+
+    #[error("Catalog is already initialized (spec version {version})")]
+    CatalogAlreadyInitialized { version: String },
+
+    #[error("Catalog is not initialized; run `duckpond init` first")]
+    CatalogNotInitialized,
+
+    #[error("Migration {version} cannot be reverted")]
+    IrreversibleMigration { version: i64 },
+
+    #[error("Snapshot reference not found: {name}")]
+    SnapshotRefNotFound { name: String },
+
+    #[error("Snapshot reference already exists: {name}")]
+    SnapshotRefExists { name: String },
+
+    #[error("Branch conflict: {message}")]
+    BranchConflict { message: String },
+
     #[error("Transaction conflict: {message}")]
     TransactionConflict { message: String },
 }
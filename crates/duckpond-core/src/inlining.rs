@@ -0,0 +1,149 @@
+//! Data inlining: small inserts kept inside the catalog database
+//!
+//! Writing a Parquet file for every micro-batch leaves many tiny files
+//! behind. Writes up to the catalog's `data_inlining_row_limit` are instead
+//! stored as rows of a per-table catalog table registered in
+//! `ducklake_inlined_data_tables`. Inlined rows are versioned by
+//! `begin_snapshot`/`end_snapshot` like any other catalog row and keep the
+//! row IDs they were allocated, so flushing them into a data file later does
+//! not change their identity.
+
+use crate::duckpond::FileColumnStatistics;
+use crate::types::DuckLakeType;
+
+/// Metadata key holding the largest write, in rows, that is inlined
+pub const DATA_INLINING_ROW_LIMIT: &str = "data_inlining_row_limit";
+
+/// A single value of an inlined row
+#[derive(Debug, Clone, PartialEq)]
+pub enum InlinedValue {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    Text(String),
+}
+
+/// An inlined row, with values in the order of the table's columns
+#[derive(Debug, Clone, PartialEq)]
+pub struct InlinedRow {
+    pub row_id: i64,
+    /// Snapshot that inserted the row
+    pub begin_snapshot: i64,
+    pub values: Vec<InlinedValue>,
+}
+
+/// A catalog table holding inlined rows of a table, created for the table's
+/// columns as of `schema_snapshot`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlinedDataTable {
+    pub table_id: i64,
+    pub table_name: String,
+    pub schema_snapshot: i64,
+}
+
+impl InlinedDataTable {
+    pub fn new(table_id: i64, schema_snapshot: i64) -> Self {
+        Self {
+            table_id,
+            table_name: format!("ducklake_inlined_data_{}_{}", table_id, schema_snapshot),
+            schema_snapshot,
+        }
+    }
+}
+
+/// A data file holding a contiguous run of inlined rows, written by a flush
+#[derive(Debug, Clone)]
+pub struct FlushedDataFile {
+    pub path: String,
+    /// Row ID of the file's first row; rows follow in row ID order
+    pub row_id_start: i64,
+    pub record_count: i64,
+    pub file_size_bytes: i64,
+    pub column_statistics: Vec<FileColumnStatistics>,
+}
+
+/// How values of a column type are stored in an inlined data table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InlinedColumnKind {
+    Boolean,
+    Integer,
+    Float,
+    Text,
+}
+
+impl InlinedColumnKind {
+    /// Storage for a DuckLake column type, or `None` if columns of the type
+    /// cannot be inlined
+    pub fn of(column_type: &str) -> Option<Self> {
+        match column_type.parse().ok()? {
+            DuckLakeType::Boolean => Some(Self::Boolean),
+            DuckLakeType::Int8
+            | DuckLakeType::Int16
+            | DuckLakeType::Int32
+            | DuckLakeType::Int64
+            | DuckLakeType::UInt8
+            | DuckLakeType::UInt16
+            | DuckLakeType::UInt32 => Some(Self::Integer),
+            DuckLakeType::Float32 | DuckLakeType::Float64 => Some(Self::Float),
+            DuckLakeType::Varchar => Some(Self::Text),
+            _ => None,
+        }
+    }
+
+    /// Catalog column type storing values of this kind
+    pub(crate) fn sql_type(self) -> &'static str {
+        match self {
+            Self::Boolean => "BOOLEAN",
+            Self::Integer => "BIGINT",
+            Self::Float => "DOUBLE PRECISION",
+            Self::Text => "TEXT",
+        }
+    }
+
+    /// Whether a value can be stored in a column of this kind
+    pub(crate) fn accepts(self, value: &InlinedValue) -> bool {
+        matches!(
+            (self, value),
+            (_, InlinedValue::Null)
+                | (Self::Boolean, InlinedValue::Boolean(_))
+                | (Self::Integer, InlinedValue::Integer(_))
+                | (Self::Float, InlinedValue::Float(_))
+                | (Self::Text, InlinedValue::Text(_))
+        )
+    }
+}
+
+/// Quote a column name for use in an inlined data table statement
+pub(crate) fn quote_column(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_kinds() {
+        assert_eq!(
+            InlinedColumnKind::of("int32"),
+            Some(InlinedColumnKind::Integer)
+        );
+        assert_eq!(
+            InlinedColumnKind::of("VARCHAR"),
+            Some(InlinedColumnKind::Text)
+        );
+        assert_eq!(InlinedColumnKind::of("uint64"), None);
+        assert_eq!(InlinedColumnKind::of("blob"), None);
+
+        assert!(InlinedColumnKind::Float.accepts(&InlinedValue::Null));
+        assert!(InlinedColumnKind::Float.accepts(&InlinedValue::Float(1.5)));
+        assert!(!InlinedColumnKind::Float.accepts(&InlinedValue::Integer(1)));
+    }
+
+    #[test]
+    fn test_inlined_data_table_name() {
+        let table = InlinedDataTable::new(3, 12);
+        assert_eq!(table.table_name, "ducklake_inlined_data_3_12");
+    }
+}
@@ -3,16 +3,30 @@
 //! This crate implements the DuckPond specification for managing metadata
 //! in a SQL database while storing data as Parquet files in object storage.
 
+pub mod changes;
 pub mod config;
 pub mod database;
+pub mod dialect;
 pub mod duckpond;
 pub mod error;
+pub mod inlining;
+pub mod migration;
 pub mod models;
 pub mod queries;
+pub mod sort_order;
+pub mod types;
 
-pub use database::DatabaseType;
-pub use duckpond::{ColumnDefinition, DuckPond, FileColumnStatistics, TimeTravel};
+pub use changes::SnapshotChange;
+pub use database::{BackendInfo, DatabaseType};
+pub use dialect::Dialect;
+pub use duckpond::{
+    ColumnDefinition, DuckPond, FileColumnStatistics, NewDataFile, TimeTravel, DEFAULT_SCHEMA,
+    DUCKLAKE_SPEC_VERSION,
+};
 pub use error::{DuckPondError, Result};
+pub use inlining::{FlushedDataFile, InlinedRow, InlinedValue};
+pub use sort_order::{SortKey, SortOrder};
+pub use types::DuckLakeType;
 
 /// Re-export commonly used types
 pub use sqlx::{Any, AnyPool};
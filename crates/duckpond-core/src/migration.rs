@@ -0,0 +1,220 @@
+//! Status and reversal of catalog schema migrations
+//!
+//! Migrations are embedded per backend (see [`Dialect::migrator`]) and
+//! tracked by sqlx in the `_sqlx_migrations` table. Every migration ships a
+//! `.down.sql` counterpart so it can be reverted.
+
+use crate::dialect::Dialect;
+use crate::error::{DuckPondError, Result};
+use chrono::Utc;
+use sqlx::migrate::{AppliedMigration, Migrate};
+use sqlx::AnyPool;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Directories of a migrations directory holding each backend's migrations
+const BACKEND_DIRS: [&str; 3] = ["sqlite", "postgres", "mysql"];
+
+/// State of a migration in a catalog database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationStatus {
+    Applied,
+    Pending,
+    /// Applied, but the embedded script has changed since
+    ChecksumMismatch,
+}
+
+impl fmt::Display for MigrationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationStatus::Applied => write!(f, "applied"),
+            MigrationStatus::Pending => write!(f, "pending"),
+            MigrationStatus::ChecksumMismatch => write!(f, "checksum mismatch"),
+        }
+    }
+}
+
+/// A migration known to this release and its state in the catalog
+#[derive(Debug, Clone)]
+pub struct MigrationInfo {
+    pub version: i64,
+    pub description: String,
+    /// Hex-encoded SHA-384 of the up script
+    pub checksum: String,
+    pub reversible: bool,
+    pub status: MigrationStatus,
+}
+
+/// List the migrations of a backend with their applied/pending state
+pub async fn migration_info(pool: &AnyPool, dialect: Dialect) -> Result<Vec<MigrationInfo>> {
+    let applied: HashMap<i64, AppliedMigration> = applied_migrations(pool)
+        .await?
+        .into_iter()
+        .map(|m| (m.version, m))
+        .collect();
+
+    let reversible: Vec<i64> = dialect
+        .migrator()
+        .iter()
+        .filter(|m| m.migration_type.is_down_migration())
+        .map(|m| m.version)
+        .collect();
+
+    Ok(dialect
+        .migrator()
+        .iter()
+        .filter(|m| m.migration_type.is_up_migration())
+        .map(|m| MigrationInfo {
+            version: m.version,
+            description: m.description.to_string(),
+            checksum: hex(&m.checksum),
+            reversible: reversible.contains(&m.version),
+            status: match applied.get(&m.version) {
+                None => MigrationStatus::Pending,
+                Some(a) if a.checksum == m.checksum => MigrationStatus::Applied,
+                Some(_) => MigrationStatus::ChecksumMismatch,
+            },
+        })
+        .collect())
+}
+
+/// Revert applied migrations
+///
+/// Reverts every applied migration newer than `target`, or only the latest
+/// one when no target is given. Returns the reverted versions, newest first.
+pub async fn revert_migrations(
+    pool: &AnyPool,
+    dialect: Dialect,
+    target: Option<i64>,
+) -> Result<Vec<i64>> {
+    let mut applied: Vec<i64> = applied_migrations(pool)
+        .await?
+        .into_iter()
+        .map(|m| m.version)
+        .collect();
+    applied.sort_unstable_by(|a, b| b.cmp(a));
+
+    let target = match target {
+        Some(target) => target,
+        None => applied.get(1).copied().unwrap_or(0),
+    };
+    let reverted: Vec<i64> = applied.into_iter().filter(|v| *v > target).collect();
+
+    let migrator = dialect.migrator();
+    if let Some(version) = reverted.iter().copied().find(|version| {
+        !migrator
+            .iter()
+            .any(|m| m.version == *version && m.migration_type.is_down_migration())
+    }) {
+        return Err(DuckPondError::IrreversibleMigration { version });
+    }
+
+    migrator.undo(pool, target).await?;
+    Ok(reverted)
+}
+
+/// Add an empty reversible migration for every backend, as
+/// `<version>_<name>.up.sql` and `.down.sql` in each backend directory of
+/// `migrations_dir`
+///
+/// The version is the current UTC time as `YYYYMMDDHHMMSS`, or follows the
+/// latest existing migration if that is newer. Migrations are embedded at
+/// build time, so the new one is applied once DuckPond is rebuilt. Returns
+/// the created files.
+pub fn add_migration(migrations_dir: &Path, name: &str) -> Result<Vec<PathBuf>> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err(DuckPondError::Config {
+            message: format!(
+                "migration name '{}' must be lowercase letters, digits and underscores",
+                name
+            ),
+        });
+    }
+
+    let mut latest = 0;
+    for backend in BACKEND_DIRS {
+        let dir = migrations_dir.join(backend);
+        if !dir.is_dir() {
+            return Err(DuckPondError::Config {
+                message: format!("{} is not a migrations directory", dir.display()),
+            });
+        }
+        for entry in std::fs::read_dir(&dir)? {
+            let file_name = entry?.file_name();
+            let version = file_name
+                .to_string_lossy()
+                .split('_')
+                .next()
+                .and_then(|version| version.parse::<i64>().ok());
+            latest = latest.max(version.unwrap_or(0));
+        }
+    }
+    let now: i64 = Utc::now()
+        .format("%Y%m%d%H%M%S")
+        .to_string()
+        .parse()
+        .expect("timestamp is numeric");
+    let version = now.max(latest + 1);
+
+    let mut created = Vec::new();
+    for backend in BACKEND_DIRS {
+        for (direction, comment) in [
+            ("up", name.to_string()),
+            ("down", format!("Revert {}", name)),
+        ] {
+            let path = migrations_dir
+                .join(backend)
+                .join(format!("{}_{}.{}.sql", version, name, direction));
+            let mut file = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)?;
+            writeln!(file, "-- {}", comment)?;
+            created.push(path);
+        }
+    }
+    Ok(created)
+}
+
+async fn applied_migrations(pool: &AnyPool) -> Result<Vec<AppliedMigration>> {
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    Ok(conn.list_applied_migrations().await?)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_migration() {
+        let dir = std::env::temp_dir().join(format!("duckpond-migrations-{}", std::process::id()));
+        for backend in BACKEND_DIRS {
+            std::fs::create_dir_all(dir.join(backend)).unwrap();
+        }
+        std::fs::write(dir.join("sqlite/99990101000000_future.up.sql"), "").unwrap();
+
+        let created = add_migration(&dir, "add_owner").unwrap();
+        assert_eq!(created.len(), 6);
+        assert!(created[0].ends_with("sqlite/99990101000001_add_owner.up.sql"));
+        assert!(created[5].ends_with("mysql/99990101000001_add_owner.down.sql"));
+        assert_eq!(
+            std::fs::read_to_string(&created[1]).unwrap(),
+            "-- Revert add_owner\n"
+        );
+        assert!(add_migration(&dir, "Add Owner").is_err());
+        assert!(add_migration(&dir.join("missing"), "add_owner").is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,3 +1,4 @@
+use crate::changes::SnapshotChange;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -78,6 +79,31 @@ pub struct TableStats {
     pub file_size_bytes: i64,
 }
 
+/// Statistics of a table column over all of the table's data files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableColumnStats {
+    pub column_id: i64,
+    pub contains_null: bool,
+    /// Unknown for columns written without NaN counts
+    pub contains_nan: Option<bool>,
+    pub min_value: Option<String>,
+    pub max_value: Option<String>,
+}
+
+/// Statistics of a column in one data file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataFileColumnStats {
+    pub data_file_id: i64,
+    pub column_id: i64,
+    /// Number of non-NULL values
+    pub value_count: i64,
+    pub null_count: i64,
+    /// Unknown for columns written without NaN counts
+    pub contains_nan: Option<bool>,
+    pub min_value: Option<String>,
+    pub max_value: Option<String>,
+}
+
 /// Metadata key-value pairs
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Metadata {
@@ -101,6 +127,35 @@ pub struct TableInfo {
     pub table_name: String,
 }
 
+/// A SQL view
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewInfo {
+    pub view_id: i64,
+    pub view_uuid: String,
+    pub schema_id: i64,
+    pub view_name: String,
+    /// SQL dialect the definition is written in, e.g. `duckdb`
+    pub dialect: String,
+    pub sql: String,
+    /// Names given to the view's output columns, empty to keep the query's
+    pub column_aliases: Vec<String>,
+}
+
+/// A key/value tag on a schema, table or view
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectTag {
+    pub key: String,
+    pub value: String,
+}
+
+/// A key/value tag on a column
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnTag {
+    pub column_id: i64,
+    pub key: String,
+    pub value: String,
+}
+
 /// Simplified column information for queries
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ColumnInfo {
@@ -108,9 +163,15 @@ pub struct ColumnInfo {
     pub column_name: String,
     pub column_type: String,
     pub nulls_allowed: bool,
+    /// Child columns of a `struct`, `list` or `map` column
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ColumnInfo>,
 }
 
 /// Simplified data file information for queries
+///
+/// Paths are relative to the data path unless absolute; the catalog stores
+/// relative paths against the table's path.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataFileInfo {
     pub data_file_id: i64,
@@ -118,5 +179,125 @@ pub struct DataFileInfo {
     pub path_is_relative: bool,
     pub record_count: i64,
     pub file_size_bytes: i64,
+    /// Row ID of the file's first row
+    pub row_id_start: i64,
+    /// Snapshot that added the file
+    pub begin_snapshot: i64,
     pub delete_file_path: Option<String>,
+    pub delete_file_path_is_relative: bool,
+}
+
+/// A file waiting to be physically removed by a vacuum
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledDeletion {
+    pub data_file_id: i64,
+    pub path: String,
+    pub path_is_relative: bool,
+    pub schedule_start: DateTime<Utc>,
+}
+
+/// Outcome of expiring snapshots
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotExpiration {
+    pub expired_snapshots: Vec<i64>,
+    /// Paths of data and delete files no remaining snapshot can see, now
+    /// scheduled for deletion
+    pub scheduled_files: Vec<String>,
+}
+
+/// A snapshot and the changes it made
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub snapshot_id: i64,
+    pub snapshot_time: DateTime<Utc>,
+    pub schema_version: i64,
+    pub changes: Vec<SnapshotChange>,
+}
+
+/// Kind of file tracked by the catalog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FileKind {
+    Data,
+    Delete,
+}
+
+/// A data or delete file added or removed between two snapshots
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChange {
+    pub table_id: i64,
+    pub file_id: i64,
+    pub kind: FileKind,
+    pub path: String,
+    pub path_is_relative: bool,
+    /// Rows in a data file, or rows deleted by a delete file
+    pub record_count: i64,
+    pub file_size_bytes: i64,
+    /// Snapshot that added or removed the file
+    pub snapshot_id: i64,
+}
+
+/// Files added and removed in one table between two snapshots
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableDiff {
+    pub table_id: i64,
+    pub added_files: Vec<FileChange>,
+    pub removed_files: Vec<FileChange>,
+}
+
+/// A data file with the range of snapshots it is visible in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataFileEntry {
+    pub data_file_id: i64,
+    pub path: String,
+    pub path_is_relative: bool,
+    pub record_count: i64,
+    pub row_id_start: i64,
+    pub begin_snapshot: i64,
+    pub end_snapshot: Option<i64>,
+}
+
+/// A delete file with the range of snapshots it is visible in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteFileEntry {
+    pub delete_file_id: i64,
+    pub data_file_id: i64,
+    pub path: String,
+    pub path_is_relative: bool,
+    pub begin_snapshot: i64,
+    pub end_snapshot: Option<i64>,
+}
+
+/// Data and delete files of a table visible in any snapshot of a range
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableFileHistory {
+    pub data_files: Vec<DataFileEntry>,
+    pub delete_files: Vec<DeleteFileEntry>,
+}
+
+/// Kind of a named snapshot reference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RefKind {
+    Tag,
+    Branch,
+}
+
+impl RefKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RefKind::Tag => "tag",
+            RefKind::Branch => "branch",
+        }
+    }
+}
+
+/// A named snapshot: a tag pinning a snapshot, or a branch whose snapshots
+/// since `base_snapshot` are hidden from main until merged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotRef {
+    pub name: String,
+    pub kind: RefKind,
+    /// The tagged snapshot, or the head of the branch
+    pub snapshot_id: i64,
+    /// Snapshot the branch was created from or last merged at
+    pub base_snapshot: Option<i64>,
 }
@@ -1,31 +1,118 @@
+// Query wrappers mirror the column lists of their SQL statements.
+#![allow(clippy::too_many_arguments)]
+
+use crate::changes::{format_quoted_list, parse_quoted_list};
+use crate::dialect::{Dialect, QueryArg};
 use crate::error::DuckPondError;
+use crate::inlining::{
+    quote_column, InlinedColumnKind, InlinedDataTable, InlinedRow, InlinedValue,
+};
 use crate::models::*;
 use chrono::{DateTime, Utc};
-use sqlx::{Any, Executor, Row};
+use sqlx::any::AnyRow;
+use sqlx::{Any, AnyConnection, Executor, Row, ValueRef};
 use uuid::Uuid;
 
+/// A table's ID with its schema's path and its own, see
+/// [`ReadQueries::list_table_paths`]
+pub type TablePathRow = (i64, Option<(String, bool)>, Option<(String, bool)>);
+
+/// The snapshots a catalog handle reads and writes
+///
+/// Snapshot IDs are global, so the snapshots of main and of its branches
+/// interleave. A lineage sees every snapshot up to the one it reads except
+/// those it hides: on main the unmerged snapshots of branches, on a branch
+/// whatever was committed since its base outside the branch.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Lineage {
+    #[default]
+    Main,
+    Branch {
+        base_snapshot: i64,
+        /// Snapshots committed to the branch since its base
+        snapshots: Vec<i64>,
+    },
+}
+
+impl Lineage {
+    /// SQL selecting the snapshots this lineage hides, for a query's `{hidden}`
+    ///
+    /// A subquery rather than a list, so that a transaction sees snapshots
+    /// other lineages commit meanwhile.
+    pub fn hidden(&self) -> String {
+        match self {
+            Lineage::Main => "SELECT snapshot_id FROM duckpond_branch_snapshot".to_string(),
+            Lineage::Branch {
+                base_snapshot,
+                snapshots,
+            } => {
+                // -1 keeps the list valid when empty; no snapshot has it
+                let own: Vec<String> = std::iter::once(-1)
+                    .chain(snapshots.iter().copied())
+                    .map(|s| s.to_string())
+                    .collect();
+                format!(
+                    "SELECT snapshot_id FROM ducklake_snapshot \
+                     WHERE snapshot_id > {} AND snapshot_id NOT IN ({})",
+                    base_snapshot,
+                    own.join(", ")
+                )
+            }
+        }
+    }
+
+    /// SQL matching the rows this lineage may end, for a statement's `{live}`
+    ///
+    /// Main ends rows a branch ended too, which keeps that branch from being
+    /// merged anyway; a branch only ends rows nobody ended.
+    fn live(&self) -> String {
+        let hidden = self.hidden();
+        match self {
+            Lineage::Main => format!(
+                "begin_snapshot NOT IN ({hidden}) AND \
+                 (end_snapshot IS NULL OR end_snapshot IN ({hidden}))"
+            ),
+            Lineage::Branch { .. } => {
+                format!("begin_snapshot NOT IN ({hidden}) AND end_snapshot IS NULL")
+            }
+        }
+    }
+
+    /// SQL matching the rows this lineage sees live that another one ended
+    fn ended_elsewhere(&self) -> String {
+        let hidden = self.hidden();
+        format!("begin_snapshot NOT IN ({hidden}) AND end_snapshot IN ({hidden})")
+    }
+}
+
 /// Reading operations for DuckPond metadata
 pub struct ReadQueries;
 
 impl ReadQueries {
     /// Get the current (latest) snapshot ID
-    pub async fn get_current_snapshot<'c, E>(executor: E) -> Result<Option<i64>, DuckPondError>
+    pub async fn get_current_snapshot<'c, E>(
+        executor: E,
+        dialect: Dialect,
+    ) -> Result<Option<i64>, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
         let sql = include_str!("../queries/read/get_current_snapshot.sql");
-        let row = sqlx::query(sql).fetch_optional(executor).await?;
+        let row = dialect.query(sql).fetch_optional(executor).await?;
 
         Ok(row.map(|r| r.try_get("snapshot_id").unwrap_or_default()))
     }
 
     /// Get the maximum snapshot ID (for generating new snapshots)
-    pub async fn get_max_snapshot_id<'c, E>(executor: E) -> Result<i64, DuckPondError>
+    pub async fn get_max_snapshot_id<'c, E>(
+        executor: E,
+        dialect: Dialect,
+    ) -> Result<i64, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
         let sql = include_str!("../queries/read/get_max_snapshot_id.sql");
-        let row = sqlx::query(sql).fetch_one(executor).await?;
+        let row = dialect.query(sql).fetch_one(executor).await?;
 
         Ok(row.try_get("max_snapshot_id").unwrap_or(0))
     }
@@ -33,13 +120,16 @@ impl ReadQueries {
     /// List schemas for a given snapshot
     pub async fn list_schemas<'c, E>(
         executor: E,
+        dialect: Dialect,
         snapshot_id: i64,
+        lineage: &Lineage,
     ) -> Result<Vec<SchemaInfo>, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
         let sql = include_str!("../queries/read/list_schemas.sql");
-        let rows = sqlx::query(sql)
+        let rows = dialect
+            .query(&sql.replace("{hidden}", &lineage.hidden()))
             .bind(snapshot_id)
             .fetch_all(executor)
             .await?;
@@ -58,14 +148,17 @@ impl ReadQueries {
     /// List tables in a schema for a given snapshot
     pub async fn list_tables<'c, E>(
         executor: E,
+        dialect: Dialect,
         schema_id: i64,
         snapshot_id: i64,
+        lineage: &Lineage,
     ) -> Result<Vec<TableInfo>, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
         let sql = include_str!("../queries/read/list_tables.sql");
-        let rows = sqlx::query(sql)
+        let rows = dialect
+            .query(&sql.replace("{hidden}", &lineage.hidden()))
             .bind(schema_id)
             .bind(snapshot_id)
             .fetch_all(executor)
@@ -82,46 +175,94 @@ impl ReadQueries {
         Ok(tables)
     }
 
+    /// List the views of a schema at a snapshot
+    pub async fn list_views<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        schema_id: i64,
+        snapshot_id: i64,
+        lineage: &Lineage,
+    ) -> Result<Vec<ViewInfo>, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/read/list_views.sql");
+        let rows = dialect
+            .query(&sql.replace("{hidden}", &lineage.hidden()))
+            .bind(schema_id)
+            .bind(snapshot_id)
+            .fetch_all(executor)
+            .await?;
+
+        rows.iter()
+            .map(|r| {
+                let column_aliases: Option<String> = r.try_get("column_aliases").ok();
+                Ok(ViewInfo {
+                    view_id: r.try_get("view_id")?,
+                    view_uuid: r.try_get("view_uuid")?,
+                    schema_id: r.try_get("schema_id")?,
+                    view_name: r.try_get("view_name")?,
+                    dialect: r.try_get("dialect")?,
+                    sql: r.try_get("sql")?,
+                    column_aliases: column_aliases
+                        .as_deref()
+                        .and_then(parse_quoted_list)
+                        .unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
     /// Show the structure of a table (top-level columns)
     pub async fn show_table_structure<'c, E>(
         executor: E,
+        dialect: Dialect,
         table_id: i64,
         snapshot_id: i64,
+        lineage: &Lineage,
     ) -> Result<Vec<ColumnInfo>, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
         let sql = include_str!("../queries/read/show_table_structure.sql");
-        let rows = sqlx::query(sql)
+        let rows = dialect
+            .query(&sql.replace("{hidden}", &lineage.hidden()))
             .bind(table_id)
             .bind(snapshot_id)
             .fetch_all(executor)
             .await?;
 
-        let columns = rows
+        let columns: Vec<(Option<i64>, ColumnInfo)> = rows
             .into_iter()
-            .map(|row| ColumnInfo {
-                column_id: row.try_get("column_id").unwrap_or_default(),
-                column_name: row.try_get("column_name").unwrap_or_default(),
-                column_type: row.try_get("column_type").unwrap_or_default(),
-                nulls_allowed: row.try_get::<i32, _>("nulls_allowed").unwrap_or_default() != 0,
+            .map(|row| {
+                let column = ColumnInfo {
+                    column_id: row.try_get("column_id").unwrap_or_default(),
+                    column_name: row.try_get("column_name").unwrap_or_default(),
+                    column_type: row.try_get("column_type").unwrap_or_default(),
+                    nulls_allowed: get_bool(&row, "nulls_allowed"),
+                    children: Vec::new(),
+                };
+                (row.try_get("parent_column").ok(), column)
             })
             .collect();
 
-        Ok(columns)
+        Ok(column_tree(&columns, None))
     }
 
     /// List data files for a table
     pub async fn list_data_files<'c, E>(
         executor: E,
+        dialect: Dialect,
         table_id: i64,
         snapshot_id: i64,
+        lineage: &Lineage,
     ) -> Result<Vec<DataFileInfo>, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
         let sql = include_str!("../queries/read/list_data_files.sql");
-        let rows = sqlx::query(sql)
+        let rows = dialect
+            .query(&sql.replace("{hidden}", &lineage.hidden()))
             .bind(table_id)
             .bind(snapshot_id)
             .fetch_all(executor)
@@ -132,13 +273,13 @@ impl ReadQueries {
             .map(|row| DataFileInfo {
                 data_file_id: row.try_get("data_file_id").unwrap_or_default(),
                 data_file_path: row.try_get("data_file_path").unwrap_or_default(),
-                path_is_relative: row
-                    .try_get::<i32, _>("path_is_relative")
-                    .unwrap_or_default()
-                    != 0,
+                path_is_relative: get_bool(&row, "path_is_relative"),
                 record_count: row.try_get("record_count").unwrap_or_default(),
                 file_size_bytes: row.try_get("file_size_bytes").unwrap_or_default(),
+                row_id_start: row.try_get("row_id_start").unwrap_or_default(),
+                begin_snapshot: row.try_get("begin_snapshot").unwrap_or_default(),
                 delete_file_path: row.try_get("delete_file_path").ok(),
+                delete_file_path_is_relative: get_bool(&row, "delete_file_path_is_relative"),
             })
             .collect();
 
@@ -148,6 +289,7 @@ impl ReadQueries {
     /// Prune files by column statistics
     pub async fn prune_files_by_column_stats<'c, E>(
         executor: E,
+        dialect: Dialect,
         table_id: i64,
         column_id: i64,
         value: &str,
@@ -156,7 +298,8 @@ impl ReadQueries {
         E: Executor<'c, Database = Any>,
     {
         let sql = include_str!("../queries/read/prune_files_by_column_stats.sql");
-        let rows = sqlx::query(sql)
+        let rows = dialect
+            .query(sql)
             .bind(table_id)
             .bind(column_id)
             .bind(value)
@@ -171,23 +314,29 @@ impl ReadQueries {
     }
 
     /// Get next catalog ID from latest snapshot
-    pub async fn get_next_catalog_id<'c, E>(executor: E) -> Result<Option<i64>, DuckPondError>
+    pub async fn get_next_catalog_id<'c, E>(
+        executor: E,
+        dialect: Dialect,
+    ) -> Result<Option<i64>, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
         let sql = include_str!("../queries/read/get_next_catalog_id.sql");
-        let row = sqlx::query(sql).fetch_optional(executor).await?;
+        let row = dialect.query(sql).fetch_optional(executor).await?;
 
         Ok(row.map(|r| r.try_get("next_catalog_id").unwrap_or_default()))
     }
 
     /// Get next file ID from latest snapshot
-    pub async fn get_next_file_id<'c, E>(executor: E) -> Result<Option<i64>, DuckPondError>
+    pub async fn get_next_file_id<'c, E>(
+        executor: E,
+        dialect: Dialect,
+    ) -> Result<Option<i64>, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
         let sql = include_str!("../queries/read/get_next_file_id.sql");
-        let row = sqlx::query(sql).fetch_optional(executor).await?;
+        let row = dialect.query(sql).fetch_optional(executor).await?;
 
         Ok(row.map(|r| r.try_get("next_file_id").unwrap_or_default()))
     }
@@ -195,257 +344,2183 @@ impl ReadQueries {
     /// Get next row ID for a table
     pub async fn get_table_next_row_id<'c, E>(
         executor: E,
+        dialect: Dialect,
         table_id: i64,
     ) -> Result<Option<i64>, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
         let sql = include_str!("../queries/read/get_table_next_row_id.sql");
-        let row = sqlx::query(sql)
+        let row = dialect
+            .query(sql)
             .bind(table_id)
             .fetch_optional(executor)
             .await?;
 
         Ok(row.map(|r| r.try_get("next_row_id").unwrap_or_default()))
     }
-}
 
-/// Writing operations for DuckPond metadata
-pub struct WriteQueries;
+    /// Get the next row ID for a table, locking its statistics row until the
+    /// surrounding transaction ends
+    pub async fn get_table_next_row_id_for_update<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        table_id: i64,
+    ) -> Result<Option<i64>, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/read/get_table_next_row_id_for_update.sql");
+        let row = dialect
+            .query(sql)
+            .bind(table_id)
+            .fetch_optional(executor)
+            .await?;
 
-impl WriteQueries {
-    /// Create a new snapshot
-    pub async fn create_snapshot<'c, E>(
+        Ok(row.map(|r| r.try_get("next_row_id").unwrap_or_default()))
+    }
+
+    /// Get a catalog-wide metadata value
+    pub async fn get_metadata<'c, E>(
         executor: E,
-        snapshot_id: i64,
-        timestamp: DateTime<Utc>,
-        schema_version: i64,
-        next_catalog_id: i64,
-        next_file_id: i64,
-    ) -> Result<(), DuckPondError>
+        dialect: Dialect,
+        key: &str,
+    ) -> Result<Option<String>, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
-        let sql = include_str!("../queries/write/create_snapshot.sql");
-        sqlx::query(sql)
-            .bind(snapshot_id)
-            .bind(timestamp.to_rfc3339())
-            .bind(schema_version)
-            .bind(next_catalog_id)
-            .bind(next_file_id)
-            .execute(executor)
+        let sql = include_str!("../queries/read/get_metadata.sql");
+        let row = dialect
+            .query(sql)
+            .bind(key)
+            .fetch_optional(executor)
             .await?;
 
-        Ok(())
+        Ok(row.map(|r| r.try_get("value")).transpose()?)
     }
 
-    /// Log snapshot changes
-    pub async fn log_snapshot_changes<'c, E>(
+    /// Get the value of a tag on a catalog object at a given snapshot
+    pub async fn get_tag<'c, E>(
         executor: E,
+        dialect: Dialect,
+        object_id: i64,
+        key: &str,
         snapshot_id: i64,
-        changes: &str,
-    ) -> Result<(), DuckPondError>
+        lineage: &Lineage,
+    ) -> Result<Option<String>, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
-        let sql = include_str!("../queries/write/log_snapshot_changes.sql");
-        sqlx::query(sql)
+        let sql = include_str!("../queries/read/get_tag.sql");
+        let row = dialect
+            .query(&sql.replace("{hidden}", &lineage.hidden()))
+            .bind(object_id)
+            .bind(key)
             .bind(snapshot_id)
-            .bind(changes)
-            .execute(executor)
+            .fetch_optional(executor)
             .await?;
 
-        Ok(())
+        Ok(row.map(|r| r.try_get("value").unwrap_or_default()))
     }
 
-    /// Create a new schema
-    pub async fn create_schema<'c, E>(
+    /// List the tags of a catalog object at a snapshot
+    pub async fn list_tags<'c, E>(
         executor: E,
-        schema_id: i64,
-        schema_uuid: Uuid,
-        begin_snapshot: i64,
-        schema_name: &str,
-    ) -> Result<(), DuckPondError>
+        dialect: Dialect,
+        object_id: i64,
+        snapshot_id: i64,
+        lineage: &Lineage,
+    ) -> Result<Vec<ObjectTag>, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
-        let sql = include_str!("../queries/write/create_schema.sql");
-        sqlx::query(sql)
-            .bind(schema_id)
-            .bind(schema_uuid.to_string())
-            .bind(begin_snapshot)
-            .bind(schema_name)
-            .execute(executor)
+        let sql = include_str!("../queries/read/list_tags.sql");
+        let rows = dialect
+            .query(&sql.replace("{hidden}", &lineage.hidden()))
+            .bind(object_id)
+            .bind(snapshot_id)
+            .fetch_all(executor)
             .await?;
 
-        Ok(())
+        rows.iter()
+            .map(|r| {
+                Ok(ObjectTag {
+                    key: r.try_get("key")?,
+                    value: r.try_get("value")?,
+                })
+            })
+            .collect()
     }
 
-    /// Create a new table
-    pub async fn create_table<'c, E>(
+    /// List the tags of a table's columns at a snapshot
+    pub async fn list_column_tags<'c, E>(
         executor: E,
+        dialect: Dialect,
         table_id: i64,
-        table_uuid: Uuid,
-        begin_snapshot: i64,
-        schema_id: i64,
-        table_name: &str,
-    ) -> Result<(), DuckPondError>
+        snapshot_id: i64,
+        lineage: &Lineage,
+    ) -> Result<Vec<ColumnTag>, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
-        let sql = include_str!("../queries/write/create_table.sql");
-        sqlx::query(sql)
+        let sql = include_str!("../queries/read/list_column_tags.sql");
+        let rows = dialect
+            .query(&sql.replace("{hidden}", &lineage.hidden()))
             .bind(table_id)
-            .bind(table_uuid.to_string())
-            .bind(begin_snapshot)
-            .bind(schema_id)
-            .bind(table_name)
-            .execute(executor)
+            .bind(snapshot_id)
+            .fetch_all(executor)
             .await?;
 
-        Ok(())
+        rows.iter()
+            .map(|r| {
+                Ok(ColumnTag {
+                    column_id: r.try_get("column_id")?,
+                    key: r.try_get("key")?,
+                    value: r.try_get("value")?,
+                })
+            })
+            .collect()
     }
 
-    /// Create a new column
-    pub async fn create_column<'c, E>(
+    /// Get the statistics of a table
+    pub async fn get_table_stats<'c, E>(
         executor: E,
-        column_id: i64,
-        begin_snapshot: i64,
+        dialect: Dialect,
         table_id: i64,
-        column_order: i64,
-        column_name: &str,
-        column_type: &str,
-        nulls_allowed: bool,
-    ) -> Result<(), DuckPondError>
+    ) -> Result<Option<TableStats>, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
-        let sql = include_str!("../queries/write/create_column.sql");
-        sqlx::query(sql)
-            .bind(column_id)
-            .bind(begin_snapshot)
+        let sql = include_str!("../queries/read/get_table_stats.sql");
+        let row = dialect
+            .query(sql)
             .bind(table_id)
-            .bind(column_order)
-            .bind(column_name)
-            .bind(column_type)
-            .bind(nulls_allowed)
-            .execute(executor)
+            .fetch_optional(executor)
             .await?;
 
-        Ok(())
+        row.map(|r| {
+            Ok(TableStats {
+                table_id: r.try_get("table_id")?,
+                record_count: r.try_get("record_count")?,
+                next_row_id: r.try_get("next_row_id")?,
+                file_size_bytes: r.try_get("file_size_bytes")?,
+            })
+        })
+        .transpose()
     }
 
-    /// Insert a new data file
-    pub async fn insert_data_file<'c, E>(
+    /// List the column statistics of each data file of a table
+    pub async fn list_file_column_stats<'c, E>(
         executor: E,
-        data_file_id: i64,
+        dialect: Dialect,
         table_id: i64,
-        begin_snapshot: i64,
-        path: &str,
-        path_is_relative: bool,
-        file_format: &str,
-        record_count: i64,
-        file_size_bytes: i64,
-        footer_size: Option<i64>,
-        row_id_start: i64,
-    ) -> Result<(), DuckPondError>
+    ) -> Result<Vec<DataFileColumnStats>, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
-        let sql = include_str!("../queries/write/insert_data_file.sql");
-        sqlx::query(sql)
-            .bind(data_file_id)
+        let sql = include_str!("../queries/read/list_file_column_stats.sql");
+        let rows = dialect
+            .query(sql)
             .bind(table_id)
-            .bind(begin_snapshot)
-            .bind(path)
-            .bind(path_is_relative)
-            .bind(file_format)
-            .bind(record_count)
-            .bind(file_size_bytes)
-            .bind(footer_size)
-            .bind(row_id_start)
-            .execute(executor)
+            .fetch_all(executor)
             .await?;
 
-        Ok(())
+        rows.iter()
+            .map(|r| {
+                let contains_nan = if r.try_get_raw("contains_nan")?.is_null() {
+                    None
+                } else {
+                    Some(get_bool(r, "contains_nan"))
+                };
+                Ok(DataFileColumnStats {
+                    data_file_id: r.try_get("data_file_id")?,
+                    column_id: r.try_get("column_id")?,
+                    value_count: r.try_get("value_count")?,
+                    null_count: r.try_get("null_count")?,
+                    contains_nan,
+                    min_value: r.try_get("min_value").ok(),
+                    max_value: r.try_get("max_value").ok(),
+                })
+            })
+            .collect()
     }
 
-    /// Update table statistics
-    pub async fn update_table_stats<'c, E>(
+    /// List the column statistics of a table
+    pub async fn list_table_column_stats<'c, E>(
         executor: E,
+        dialect: Dialect,
         table_id: i64,
-        record_count: i64,
-        file_size_bytes: i64,
-    ) -> Result<(), DuckPondError>
+    ) -> Result<Vec<TableColumnStats>, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
-        let sql = include_str!("../queries/write/update_table_stats.sql");
-        sqlx::query(sql)
+        let sql = include_str!("../queries/read/list_table_column_stats.sql");
+        let rows = dialect
+            .query(sql)
             .bind(table_id)
-            .bind(record_count)
-            .bind(file_size_bytes)
-            .execute(executor)
+            .fetch_all(executor)
             .await?;
 
-        Ok(())
+        rows.iter()
+            .map(|r| {
+                let contains_nan = if r.try_get_raw("contains_nan")?.is_null() {
+                    None
+                } else {
+                    Some(get_bool(r, "contains_nan"))
+                };
+                Ok(TableColumnStats {
+                    column_id: r.try_get("column_id")?,
+                    contains_null: get_bool(r, "contains_null"),
+                    contains_nan,
+                    min_value: r.try_get("min_value").ok(),
+                    max_value: r.try_get("max_value").ok(),
+                })
+            })
+            .collect()
     }
 
-    /// Update table column statistics
-    pub async fn update_table_column_stats<'c, E>(
+    /// Get the latest snapshot up to `max_snapshot_id` taken at or before a
+    /// point in time
+    pub async fn get_snapshot_at_time<'c, E>(
         executor: E,
-        table_id: i64,
-        column_id: i64,
-        null_count: i64,
-        nan_count: i64,
-        min_value: Option<&str>,
-        max_value: Option<&str>,
-    ) -> Result<(), DuckPondError>
+        dialect: Dialect,
+        timestamp: DateTime<Utc>,
+        max_snapshot_id: i64,
+        lineage: &Lineage,
+    ) -> Result<Option<i64>, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
-        let sql = include_str!("../queries/write/update_table_column_stats.sql");
-        sqlx::query(sql)
-            .bind(table_id)
-            .bind(column_id)
-            .bind(null_count)
-            .bind(nan_count)
-            .bind(min_value)
-            .bind(max_value)
-            .execute(executor)
+        let sql = match dialect {
+            Dialect::PostgreSQL => {
+                include_str!("../queries/postgres/read/get_snapshot_at_time.sql")
+            }
+            Dialect::SQLite | Dialect::MySQL => {
+                include_str!("../queries/read/get_snapshot_at_time.sql")
+            }
+        };
+        let row = dialect
+            .query(&sql.replace("{hidden}", &lineage.hidden()))
+            .bind(dialect.format_timestamp(timestamp))
+            .bind(max_snapshot_id)
+            .fetch_optional(executor)
             .await?;
 
-        Ok(())
+        row.map(|r| r.try_get("snapshot_id"))
+            .transpose()
+            .map_err(Into::into)
     }
 
-    /// Insert file column statistics
-    pub async fn insert_file_column_stats<'c, E>(
+    /// List all snapshots, oldest first
+    pub async fn list_snapshots<'c, E>(
         executor: E,
-        data_file_id: i64,
-        table_id: i64,
-        column_id: i64,
-        value_count: i64,
-        null_count: i64,
-        nan_count: i64,
-        min_value: Option<&str>,
-        max_value: Option<&str>,
-    ) -> Result<(), DuckPondError>
+        dialect: Dialect,
+    ) -> Result<Vec<Snapshot>, DuckPondError>
     where
         E: Executor<'c, Database = Any>,
     {
-        let sql = include_str!("../queries/write/insert_file_column_stats.sql");
-        sqlx::query(sql)
-            .bind(data_file_id)
-            .bind(table_id)
-            .bind(column_id)
-            .bind(value_count)
-            .bind(null_count)
-            .bind(min_value)
-            .bind(max_value)
-            .bind(nan_count)
-            .execute(executor)
-            .await?;
+        let sql = match dialect {
+            Dialect::MySQL => include_str!("../queries/mysql/read/list_snapshots.sql"),
+            Dialect::SQLite | Dialect::PostgreSQL => {
+                include_str!("../queries/read/list_snapshots.sql")
+            }
+        };
+        let rows = dialect.query(sql).fetch_all(executor).await?;
 
-        Ok(())
+        rows.iter()
+            .map(|r| {
+                Ok(Snapshot {
+                    snapshot_id: r.try_get("snapshot_id")?,
+                    snapshot_time: get_timestamp(r, dialect, "snapshot_time")?,
+                    schema_version: r.try_get("schema_version")?,
+                    next_catalog_id: r.try_get("next_catalog_id")?,
+                    next_file_id: r.try_get("next_file_id")?,
+                })
+            })
+            .collect()
+    }
+
+    /// List data and delete files whose lifetime no longer overlaps any
+    /// snapshot and whose path no reachable file row still uses, as
+    /// `(file_id, table_id, path, path_is_relative)`
+    pub async fn list_unreachable_files<'c, E>(
+        executor: E,
+        dialect: Dialect,
+    ) -> Result<Vec<(i64, i64, String, bool)>, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/read/list_unreachable_files.sql");
+        let rows = dialect.query(sql).fetch_all(executor).await?;
+
+        rows.iter()
+            .map(|r| {
+                Ok((
+                    r.try_get("file_id")?,
+                    r.try_get("table_id")?,
+                    r.try_get("path")?,
+                    get_bool(r, "path_is_relative"),
+                ))
+            })
+            .collect()
+    }
+
+    /// List files scheduled for deletion, oldest first
+    pub async fn list_scheduled_deletions<'c, E>(
+        executor: E,
+        dialect: Dialect,
+    ) -> Result<Vec<ScheduledDeletion>, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = match dialect {
+            Dialect::MySQL => include_str!("../queries/mysql/read/list_scheduled_deletions.sql"),
+            Dialect::SQLite | Dialect::PostgreSQL => {
+                include_str!("../queries/read/list_scheduled_deletions.sql")
+            }
+        };
+        let rows = dialect.query(sql).fetch_all(executor).await?;
+
+        rows.iter()
+            .map(|r| {
+                Ok(ScheduledDeletion {
+                    data_file_id: r.try_get("data_file_id")?,
+                    path: r.try_get("path")?,
+                    path_is_relative: get_bool(r, "path_is_relative"),
+                    schedule_start: get_timestamp(r, dialect, "schedule_start")?,
+                })
+            })
+            .collect()
+    }
+
+    /// List the paths of every data and delete file the catalog knows about,
+    /// in any snapshot, including files waiting to be vacuumed, as
+    /// `(table_id, path, path_is_relative)`; files waiting to be vacuumed
+    /// have no table
+    pub async fn list_referenced_file_paths<'c, E>(
+        executor: E,
+        dialect: Dialect,
+    ) -> Result<Vec<(Option<i64>, String, bool)>, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/read/list_referenced_file_paths.sql");
+        let rows = dialect.query(sql).fetch_all(executor).await?;
+
+        rows.iter()
+            .map(|r| {
+                Ok((
+                    r.try_get("table_id").ok(),
+                    r.try_get("path")?,
+                    get_bool(r, "path_is_relative"),
+                ))
+            })
+            .collect()
+    }
+
+    /// List the path of every table and of its schema, in any snapshot, as
+    /// `(table_id, schema path, table path)` with each path as
+    /// `(path, path_is_relative)`; both are unset before spec 0.2
+    pub async fn list_table_paths<'c, E>(
+        executor: E,
+        dialect: Dialect,
+    ) -> Result<Vec<TablePathRow>, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/read/list_table_paths.sql");
+        let rows = dialect.query(sql).fetch_all(executor).await?;
+
+        rows.iter()
+            .map(|r| {
+                let path = |column: &str, relative: &str| {
+                    r.try_get::<String, _>(column)
+                        .ok()
+                        .map(|path| (path, get_bool(r, relative)))
+                };
+                Ok((
+                    r.try_get("table_id")?,
+                    path("schema_path", "schema_path_is_relative"),
+                    path("table_path", "table_path_is_relative"),
+                ))
+            })
+            .collect()
+    }
+
+    /// List every data and delete file row with a relative path as
+    /// `(kind, file_id, table_id, path)`
+    pub async fn list_relative_file_paths(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+    ) -> Result<Vec<(FileKind, i64, i64, String)>, DuckPondError> {
+        let sql = include_str!("../queries/read/list_relative_file_paths.sql");
+        let rows = dialect.query(sql).fetch_all(&mut *conn).await?;
+
+        rows.iter()
+            .map(|r| {
+                let kind: String = r.try_get("kind")?;
+                Ok((
+                    if kind == "delete" {
+                        FileKind::Delete
+                    } else {
+                        FileKind::Data
+                    },
+                    r.try_get("file_id")?,
+                    r.try_get("table_id")?,
+                    r.try_get("path")?,
+                ))
+            })
+            .collect()
+    }
+
+    /// List the change log of every snapshot as `(snapshot_id, changes_made)`
+    pub async fn list_snapshot_changes<'c, E>(
+        executor: E,
+        dialect: Dialect,
+    ) -> Result<Vec<(i64, String)>, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/read/list_snapshot_changes.sql");
+        let rows = dialect.query(sql).fetch_all(executor).await?;
+
+        rows.iter()
+            .map(|r| Ok((r.try_get("snapshot_id")?, r.try_get("changes_made")?)))
+            .collect()
     }
+
+    /// List files added (`true`) or removed (`false`) after snapshot `from`
+    /// up to and including snapshot `to`
+    pub async fn list_file_changes<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        from_snapshot: i64,
+        to_snapshot: i64,
+        lineage: &Lineage,
+    ) -> Result<Vec<(bool, FileChange)>, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/read/list_file_changes.sql");
+        let rows = dialect
+            .query(&sql.replace("{hidden}", &lineage.hidden()))
+            .bind(from_snapshot)
+            .bind(to_snapshot)
+            .fetch_all(executor)
+            .await?;
+
+        rows.iter()
+            .map(|r| {
+                let change: String = r.try_get("change")?;
+                let kind: String = r.try_get("kind")?;
+                Ok((
+                    change == "added",
+                    FileChange {
+                        table_id: r.try_get("table_id")?,
+                        file_id: r.try_get("file_id")?,
+                        kind: if kind == "delete" {
+                            FileKind::Delete
+                        } else {
+                            FileKind::Data
+                        },
+                        path: r.try_get("path")?,
+                        path_is_relative: get_bool(r, "path_is_relative"),
+                        record_count: r.try_get("record_count")?,
+                        file_size_bytes: r.try_get("file_size_bytes")?,
+                        snapshot_id: r.try_get("snapshot_id")?,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// List data files of a table visible in any snapshot from `from_snapshot`
+    /// to `to_snapshot`
+    pub async fn list_table_data_file_history<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        table_id: i64,
+        from_snapshot: i64,
+        to_snapshot: i64,
+        lineage: &Lineage,
+    ) -> Result<Vec<DataFileEntry>, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/read/list_table_data_file_history.sql");
+        let rows = dialect
+            .query(&sql.replace("{hidden}", &lineage.hidden()))
+            .bind(table_id)
+            .bind(from_snapshot)
+            .bind(to_snapshot)
+            .fetch_all(executor)
+            .await?;
+
+        rows.iter()
+            .map(|r| {
+                Ok(DataFileEntry {
+                    data_file_id: r.try_get("data_file_id")?,
+                    path: r.try_get("path")?,
+                    path_is_relative: get_bool(r, "path_is_relative"),
+                    record_count: r.try_get("record_count")?,
+                    row_id_start: r.try_get("row_id_start")?,
+                    begin_snapshot: r.try_get("begin_snapshot")?,
+                    end_snapshot: r.try_get("end_snapshot").ok(),
+                })
+            })
+            .collect()
+    }
+
+    /// List delete files of a table visible in any snapshot from
+    /// `from_snapshot` to `to_snapshot`
+    pub async fn list_table_delete_file_history<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        table_id: i64,
+        from_snapshot: i64,
+        to_snapshot: i64,
+        lineage: &Lineage,
+    ) -> Result<Vec<DeleteFileEntry>, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/read/list_table_delete_file_history.sql");
+        let rows = dialect
+            .query(&sql.replace("{hidden}", &lineage.hidden()))
+            .bind(table_id)
+            .bind(from_snapshot)
+            .bind(to_snapshot)
+            .fetch_all(executor)
+            .await?;
+
+        rows.iter()
+            .map(|r| {
+                Ok(DeleteFileEntry {
+                    delete_file_id: r.try_get("delete_file_id")?,
+                    data_file_id: r.try_get("data_file_id")?,
+                    path: r.try_get("path")?,
+                    path_is_relative: get_bool(r, "path_is_relative"),
+                    begin_snapshot: r.try_get("begin_snapshot")?,
+                    end_snapshot: r.try_get("end_snapshot").ok(),
+                })
+            })
+            .collect()
+    }
+
+    /// List the tables with catalog rows or files added after `to_snapshot`
+    /// that are still visible, or removed after it; a restore to
+    /// `to_snapshot` changes these tables. Limited to one table if given.
+    pub async fn list_tables_changed_after(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+        to_snapshot: i64,
+        table_id: Option<i64>,
+        lineage: &Lineage,
+    ) -> Result<Vec<i64>, DuckPondError> {
+        let sql = include_str!("../queries/read/list_tables_changed_after.sql");
+        let mut table_ids = Vec::new();
+        for (table, table_column, _) in RESTORED_TABLES {
+            if *table_column != Some("table_id") {
+                continue;
+            }
+            let rows = dialect
+                .query(
+                    &sql.replace("{table}", table)
+                        .replace("{hidden}", &lineage.hidden()),
+                )
+                .bind(to_snapshot)
+                .bind(table_id)
+                .fetch_all(&mut *conn)
+                .await?;
+            for row in rows {
+                let table_id: i64 = row.try_get("table_id")?;
+                if !table_ids.contains(&table_id) {
+                    table_ids.push(table_id);
+                }
+            }
+        }
+
+        let sql = include_str!("../queries/read/count_inlined_rows_changed_after.sql");
+        for inlined in Self::list_inlined_data_tables(&mut *conn, dialect, table_id, None).await? {
+            let row = dialect
+                .query(
+                    &sql.replace("{table}", &inlined.table_name)
+                        .replace("{hidden}", &lineage.hidden()),
+                )
+                .bind(to_snapshot)
+                .fetch_one(&mut *conn)
+                .await?;
+            let changed: i64 = row.try_get("changed")?;
+            if changed > 0 && !table_ids.contains(&inlined.table_id) {
+                table_ids.push(inlined.table_id);
+            }
+        }
+        table_ids.sort_unstable();
+        Ok(table_ids)
+    }
+
+    /// List data files visible at `to_snapshot` but removed since
+    pub async fn list_data_files_removed_after<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        to_snapshot: i64,
+        table_id: Option<i64>,
+        lineage: &Lineage,
+    ) -> Result<Vec<i64>, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/read/list_data_files_removed_after.sql");
+        let rows = dialect
+            .query(&sql.replace("{hidden}", &lineage.hidden()))
+            .bind(to_snapshot)
+            .bind(table_id)
+            .fetch_all(executor)
+            .await?;
+
+        rows.iter()
+            .map(|r| Ok(r.try_get("data_file_id")?))
+            .collect()
+    }
+
+    /// List delete files visible at `to_snapshot` but removed since, as
+    /// `(delete_file_id, data_file_id)`
+    pub async fn list_delete_files_removed_after<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        to_snapshot: i64,
+        table_id: Option<i64>,
+        lineage: &Lineage,
+    ) -> Result<Vec<(i64, i64)>, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/read/list_delete_files_removed_after.sql");
+        let rows = dialect
+            .query(&sql.replace("{hidden}", &lineage.hidden()))
+            .bind(to_snapshot)
+            .bind(table_id)
+            .fetch_all(executor)
+            .await?;
+
+        rows.iter()
+            .map(|r| Ok((r.try_get("delete_file_id")?, r.try_get("data_file_id")?)))
+            .collect()
+    }
+
+    /// List tags and branches
+    pub async fn list_snapshot_refs<'c, E>(
+        executor: E,
+        dialect: Dialect,
+    ) -> Result<Vec<SnapshotRef>, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/read/list_snapshot_refs.sql");
+        let rows = dialect.query(sql).fetch_all(executor).await?;
+
+        rows.iter()
+            .map(|r| {
+                let ref_type: String = r.try_get("ref_type")?;
+                Ok(SnapshotRef {
+                    name: r.try_get("name")?,
+                    kind: if ref_type == RefKind::Branch.as_str() {
+                        RefKind::Branch
+                    } else {
+                        RefKind::Tag
+                    },
+                    snapshot_id: r.try_get("snapshot_id")?,
+                    base_snapshot: r.try_get("base_snapshot").ok(),
+                })
+            })
+            .collect()
+    }
+
+    /// List the snapshots committed to a branch that main does not see yet
+    pub async fn list_branch_snapshots<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        branch: &str,
+    ) -> Result<Vec<i64>, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/read/list_branch_snapshots.sql");
+        let rows = dialect.query(sql).bind(branch).fetch_all(executor).await?;
+
+        rows.iter().map(|r| Ok(r.try_get("snapshot_id")?)).collect()
+    }
+
+    /// List the catalog tables holding inlined rows, of one table if given,
+    /// and only those created in a snapshot of `lineage` if given
+    pub async fn list_inlined_data_tables<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        table_id: Option<i64>,
+        lineage: Option<&Lineage>,
+    ) -> Result<Vec<InlinedDataTable>, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/read/list_inlined_data_tables.sql");
+        let hidden = lineage.map_or("-1".to_string(), Lineage::hidden);
+        let rows = dialect
+            .query(&sql.replace("{hidden}", &hidden))
+            .bind(table_id)
+            .fetch_all(executor)
+            .await?;
+
+        rows.iter()
+            .map(|r| {
+                Ok(InlinedDataTable {
+                    table_id: r.try_get("table_id")?,
+                    table_name: r.try_get("table_name")?,
+                    schema_snapshot: r.try_get("schema_snapshot")?,
+                })
+            })
+            .collect()
+    }
+
+    /// List the inlined rows of an inlined data table visible at a snapshot,
+    /// with values in the order of `columns`, the table's columns as of
+    /// the inlined data table's schema snapshot
+    pub async fn list_inlined_rows<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        inlined: &InlinedDataTable,
+        columns: &[ColumnInfo],
+        snapshot_id: i64,
+        lineage: &Lineage,
+    ) -> Result<Vec<InlinedRow>, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/read/list_inlined_rows.sql");
+        let selected: String = columns
+            .iter()
+            .map(|c| format!(", {}", quote_column(&c.column_name)))
+            .collect();
+        let rows = dialect
+            .query(
+                &sql.replace("{table}", &inlined.table_name)
+                    .replace("{columns}", &selected)
+                    .replace("{hidden}", &lineage.hidden()),
+            )
+            .bind(snapshot_id)
+            .fetch_all(executor)
+            .await?;
+
+        rows.iter()
+            .map(|r| {
+                let values = columns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| {
+                        get_inlined_value(r, i + 2, InlinedColumnKind::of(&c.column_type))
+                    })
+                    .collect();
+                Ok(InlinedRow {
+                    row_id: r.try_get("row_id")?,
+                    begin_snapshot: r.try_get("begin_snapshot")?,
+                    values,
+                })
+            })
+            .collect()
+    }
+
+    /// List data and delete files added by the unmerged snapshots of a
+    /// branch as `(file_id, table_id, path, path_is_relative)`
+    pub async fn list_files_added_on_branch<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        branch: &str,
+    ) -> Result<Vec<(i64, i64, String, bool)>, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/read/list_files_added_on_branch.sql");
+        let rows = dialect.query(sql).bind(branch).fetch_all(executor).await?;
+
+        rows.iter()
+            .map(|r| {
+                Ok((
+                    r.try_get("file_id")?,
+                    r.try_get("table_id")?,
+                    r.try_get("path")?,
+                    get_bool(r, "path_is_relative"),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Writing operations for DuckPond metadata
+pub struct WriteQueries;
+
+impl WriteQueries {
+    /// Set a catalog-wide metadata value, replacing any previous value
+    pub async fn set_metadata(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+        key: &str,
+        value: &str,
+    ) -> Result<(), DuckPondError> {
+        let sql = include_str!("../queries/write/delete_metadata.sql");
+        dialect.query(sql).bind(key).execute(&mut *conn).await?;
+
+        let sql = include_str!("../queries/write/insert_metadata.sql");
+        dialect
+            .query(sql)
+            .bind(key)
+            .bind(value)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Give schemas and tables created before spec 0.2 the default path: a
+    /// directory named after them, relative to their parent
+    pub async fn backfill_object_paths(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+    ) -> Result<(), DuckPondError> {
+        let (schema_sql, table_sql) = match dialect {
+            Dialect::MySQL => (
+                include_str!("../queries/mysql/write/backfill_schema_paths.sql"),
+                include_str!("../queries/mysql/write/backfill_table_paths.sql"),
+            ),
+            Dialect::SQLite | Dialect::PostgreSQL => (
+                include_str!("../queries/write/backfill_schema_paths.sql"),
+                include_str!("../queries/write/backfill_table_paths.sql"),
+            ),
+        };
+        dialect
+            .query(schema_sql)
+            .bind(true)
+            .execute(&mut *conn)
+            .await?;
+        dialect
+            .query(table_sql)
+            .bind(true)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Point a data or delete file row at a new path
+    pub async fn set_file_path(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+        kind: FileKind,
+        file_id: i64,
+        path: &str,
+        path_is_relative: bool,
+    ) -> Result<(), DuckPondError> {
+        let (table, column) = match kind {
+            FileKind::Data => ("ducklake_data_file", "data_file_id"),
+            FileKind::Delete => ("ducklake_delete_file", "delete_file_id"),
+        };
+        let sql = include_str!("../queries/write/set_file_path.sql");
+        dialect
+            .query(&sql.replace("{table}", table).replace("{column}", column))
+            .bind(file_id)
+            .bind(path)
+            .bind(path_is_relative)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Create a new snapshot
+    pub async fn create_snapshot<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        snapshot_id: i64,
+        timestamp: DateTime<Utc>,
+        schema_version: i64,
+        next_catalog_id: i64,
+        next_file_id: i64,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = match dialect {
+            Dialect::PostgreSQL => include_str!("../queries/postgres/write/create_snapshot.sql"),
+            Dialect::SQLite | Dialect::MySQL => {
+                include_str!("../queries/write/create_snapshot.sql")
+            }
+        };
+        dialect
+            .query(sql)
+            .bind(snapshot_id)
+            .bind(dialect.format_timestamp(timestamp))
+            .bind(schema_version)
+            .bind(next_catalog_id)
+            .bind(next_file_id)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Log snapshot changes
+    pub async fn log_snapshot_changes<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        snapshot_id: i64,
+        changes: &str,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/log_snapshot_changes.sql");
+        dialect
+            .query(sql)
+            .bind(snapshot_id)
+            .bind(changes)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Create a new schema
+    pub async fn create_schema<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        schema_id: i64,
+        schema_uuid: Uuid,
+        begin_snapshot: i64,
+        schema_name: &str,
+        path: Option<&str>,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/create_schema.sql");
+        dialect
+            .query(sql)
+            .bind(schema_id)
+            .bind(schema_uuid.to_string())
+            .bind(begin_snapshot)
+            .bind(schema_name)
+            .bind(path)
+            .bind(path.map(|_| true))
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Create a new table
+    pub async fn create_table<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        table_id: i64,
+        table_uuid: Uuid,
+        begin_snapshot: i64,
+        schema_id: i64,
+        table_name: &str,
+        path: Option<&str>,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/create_table.sql");
+        dialect
+            .query(sql)
+            .bind(table_id)
+            .bind(table_uuid.to_string())
+            .bind(begin_snapshot)
+            .bind(schema_id)
+            .bind(table_name)
+            .bind(path)
+            .bind(path.map(|_| true))
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Create a new view
+    pub async fn create_view<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        view: &ViewInfo,
+        begin_snapshot: i64,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/create_view.sql");
+        let column_aliases =
+            (!view.column_aliases.is_empty()).then(|| format_quoted_list(&view.column_aliases));
+        dialect
+            .query(sql)
+            .bind(view.view_id)
+            .bind(view.view_uuid.as_str())
+            .bind(begin_snapshot)
+            .bind(view.schema_id)
+            .bind(view.view_name.as_str())
+            .bind(view.dialect.as_str())
+            .bind(view.sql.as_str())
+            .bind(column_aliases)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Run a statement ending the rows `lineage` sees live, rendering its
+    /// `{live}` condition, and return how many rows it ended
+    ///
+    /// A branch must not end rows another lineage ended already, which
+    /// would take that end away from it. The statement first runs against
+    /// those rows to find them; the conflict then rolls back the caller's
+    /// transaction.
+    async fn end_live(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+        lineage: &Lineage,
+        sql: &str,
+        args: &[QueryArg],
+    ) -> Result<u64, DuckPondError> {
+        if let Lineage::Branch { .. } = lineage {
+            let sql = sql.replace("{live}", &lineage.ended_elsewhere());
+            let ended = args
+                .iter()
+                .fold(dialect.query(&sql), |query, arg| query.bind(arg.clone()))
+                .execute(&mut *conn)
+                .await?
+                .rows_affected();
+            if ended > 0 {
+                return Err(DuckPondError::BranchConflict {
+                    message: format!(
+                        "{} catalog row(s) this branch changes were changed since it was created",
+                        ended
+                    ),
+                });
+            }
+        }
+        let sql = sql.replace("{live}", &lineage.live());
+        let result = args
+            .iter()
+            .fold(dialect.query(&sql), |query, arg| query.bind(arg.clone()))
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// End a view's lifetime at a snapshot
+    pub async fn end_view(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+        view_id: i64,
+        end_snapshot: i64,
+        lineage: &Lineage,
+    ) -> Result<(), DuckPondError> {
+        let sql = include_str!("../queries/write/end_view.sql");
+        Self::end_live(
+            conn,
+            dialect,
+            lineage,
+            sql,
+            &[view_id.into(), end_snapshot.into()],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// End the rows of a versioned catalog table whose `column` is `id`,
+    /// returning how many were ended
+    pub async fn end_rows(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+        table: &str,
+        column: &str,
+        id: i64,
+        end_snapshot: i64,
+        lineage: &Lineage,
+    ) -> Result<u64, DuckPondError> {
+        let sql = include_str!("../queries/write/end_rows.sql");
+        Self::end_live(
+            conn,
+            dialect,
+            lineage,
+            &sql.replace("{table}", table).replace("{column}", column),
+            &[id.into(), end_snapshot.into()],
+        )
+        .await
+    }
+
+    /// End every catalog row of a table: the table itself, its columns,
+    /// files, tags and inlined rows
+    pub async fn end_table_rows(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+        table_id: i64,
+        end_snapshot: i64,
+        lineage: &Lineage,
+    ) -> Result<(), DuckPondError> {
+        for (table, table_column, _) in RESTORED_TABLES {
+            if let Some(column) = table_column {
+                Self::end_rows(
+                    &mut *conn,
+                    dialect,
+                    table,
+                    column,
+                    table_id,
+                    end_snapshot,
+                    lineage,
+                )
+                .await?;
+            }
+        }
+        for inlined in
+            ReadQueries::list_inlined_data_tables(&mut *conn, dialect, Some(table_id), None).await?
+        {
+            Self::end_inlined_rows(
+                &mut *conn,
+                dialect,
+                &inlined,
+                end_snapshot,
+                0,
+                i64::MAX,
+                lineage,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Give a table a new name from a snapshot on. The table's current row
+    /// must have been ended at that snapshot.
+    pub async fn rename_table<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        table_id: i64,
+        snapshot_id: i64,
+        table_name: &str,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/rename_table.sql");
+        dialect
+            .query(sql)
+            .bind(table_id)
+            .bind(snapshot_id)
+            .bind(table_name)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Create a new column
+    pub async fn create_column<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        column_id: i64,
+        begin_snapshot: i64,
+        table_id: i64,
+        column_order: i64,
+        column_name: &str,
+        column_type: &str,
+        nulls_allowed: bool,
+        parent_column: Option<i64>,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/create_column.sql");
+        dialect
+            .query(sql)
+            .bind(column_id)
+            .bind(begin_snapshot)
+            .bind(table_id)
+            .bind(column_order)
+            .bind(column_name)
+            .bind(column_type)
+            .bind(nulls_allowed)
+            .bind(parent_column)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Insert a new data file
+    pub async fn insert_data_file<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        data_file_id: i64,
+        table_id: i64,
+        begin_snapshot: i64,
+        path: &str,
+        path_is_relative: bool,
+        file_format: &str,
+        record_count: i64,
+        file_size_bytes: i64,
+        footer_size: Option<i64>,
+        row_id_start: i64,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/insert_data_file.sql");
+        dialect
+            .query(sql)
+            .bind(data_file_id)
+            .bind(table_id)
+            .bind(begin_snapshot)
+            .bind(path)
+            .bind(path_is_relative)
+            .bind(file_format)
+            .bind(record_count)
+            .bind(file_size_bytes)
+            .bind(footer_size)
+            .bind(row_id_start)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Update table statistics
+    pub async fn update_table_stats<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        table_id: i64,
+        record_count: i64,
+        file_size_bytes: i64,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = match dialect {
+            Dialect::MySQL => include_str!("../queries/mysql/write/update_table_stats.sql"),
+            Dialect::SQLite | Dialect::PostgreSQL => {
+                include_str!("../queries/write/update_table_stats.sql")
+            }
+        };
+        dialect
+            .query(sql)
+            .bind(table_id)
+            .bind(record_count)
+            .bind(file_size_bytes)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Take records and bytes of replaced files out of table statistics,
+    /// leaving the next row ID as it is
+    pub async fn remove_table_stats<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        table_id: i64,
+        record_count: i64,
+        file_size_bytes: i64,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/remove_table_stats.sql");
+        dialect
+            .query(sql)
+            .bind(table_id)
+            .bind(record_count)
+            .bind(file_size_bytes)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Update table statistics and return the first row ID allocated to the
+    /// new records, in a single `INSERT ... RETURNING` statement
+    pub async fn allocate_table_row_ids<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        table_id: i64,
+        record_count: i64,
+        file_size_bytes: i64,
+    ) -> Result<i64, DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/allocate_table_row_ids.sql");
+        let row = dialect
+            .query(sql)
+            .bind(table_id)
+            .bind(record_count)
+            .bind(file_size_bytes)
+            .fetch_one(executor)
+            .await?;
+
+        Ok(row.try_get("row_id_start")?)
+    }
+
+    /// Update table column statistics
+    pub async fn update_table_column_stats<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        table_id: i64,
+        column_id: i64,
+        null_count: i64,
+        nan_count: i64,
+        min_value: Option<&str>,
+        max_value: Option<&str>,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = match dialect {
+            Dialect::MySQL => include_str!("../queries/mysql/write/update_table_column_stats.sql"),
+            Dialect::SQLite | Dialect::PostgreSQL => {
+                include_str!("../queries/write/update_table_column_stats.sql")
+            }
+        };
+        dialect
+            .query(sql)
+            .bind(table_id)
+            .bind(column_id)
+            .bind(null_count)
+            .bind(nan_count)
+            .bind(min_value)
+            .bind(max_value)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Insert file column statistics
+    pub async fn insert_file_column_stats<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        data_file_id: i64,
+        table_id: i64,
+        column_id: i64,
+        value_count: i64,
+        null_count: i64,
+        nan_count: i64,
+        min_value: Option<&str>,
+        max_value: Option<&str>,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/insert_file_column_stats.sql");
+        dialect
+            .query(sql)
+            .bind(data_file_id)
+            .bind(table_id)
+            .bind(column_id)
+            .bind(value_count)
+            .bind(null_count)
+            .bind(min_value)
+            .bind(max_value)
+            .bind(nan_count)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Set a tag on a catalog object starting at the given snapshot
+    pub async fn insert_tag<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        object_id: i64,
+        begin_snapshot: i64,
+        key: &str,
+        value: &str,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/insert_tag.sql");
+        dialect
+            .query(sql)
+            .bind(object_id)
+            .bind(begin_snapshot)
+            .bind(key)
+            .bind(value)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// End the currently active value of a tag on a catalog object,
+    /// returning how many values were ended
+    pub async fn end_tag(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+        object_id: i64,
+        key: &str,
+        end_snapshot: i64,
+        lineage: &Lineage,
+    ) -> Result<u64, DuckPondError> {
+        let sql = include_str!("../queries/write/end_tag.sql");
+        Self::end_live(
+            conn,
+            dialect,
+            lineage,
+            sql,
+            &[object_id.into(), key.into(), end_snapshot.into()],
+        )
+        .await
+    }
+
+    /// Add a tag to a column
+    pub async fn insert_column_tag<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        table_id: i64,
+        column_id: i64,
+        begin_snapshot: i64,
+        key: &str,
+        value: &str,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/insert_column_tag.sql");
+        dialect
+            .query(sql)
+            .bind(table_id)
+            .bind(column_id)
+            .bind(begin_snapshot)
+            .bind(key)
+            .bind(value)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// End the currently active value of a tag on a column, returning how
+    /// many values were ended
+    pub async fn end_column_tag(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+        table_id: i64,
+        column_id: i64,
+        key: &str,
+        end_snapshot: i64,
+        lineage: &Lineage,
+    ) -> Result<u64, DuckPondError> {
+        let sql = include_str!("../queries/write/end_column_tag.sql");
+        Self::end_live(
+            conn,
+            dialect,
+            lineage,
+            sql,
+            &[
+                table_id.into(),
+                column_id.into(),
+                key.into(),
+                end_snapshot.into(),
+            ],
+        )
+        .await
+    }
+
+    /// Remove a snapshot and its change log
+    pub async fn delete_snapshot(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+        snapshot_id: i64,
+    ) -> Result<(), DuckPondError> {
+        let sql = include_str!("../queries/write/delete_snapshot_changes.sql");
+        dialect
+            .query(sql)
+            .bind(snapshot_id)
+            .execute(&mut *conn)
+            .await?;
+
+        let sql = include_str!("../queries/write/delete_snapshot.sql");
+        dialect
+            .query(sql)
+            .bind(snapshot_id)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remove catalog rows no remaining snapshot can see, along with the
+    /// statistics of removed files and tables
+    pub async fn delete_unreachable_rows(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+    ) -> Result<(), DuckPondError> {
+        let sql = include_str!("../queries/write/delete_unreachable_rows.sql");
+        for table in VERSIONED_TABLES {
+            dialect
+                .query(&sql.replace("{table}", table))
+                .execute(&mut *conn)
+                .await?;
+        }
+        for inlined in
+            ReadQueries::list_inlined_data_tables(&mut *conn, dialect, None, None).await?
+        {
+            dialect
+                .query(&sql.replace("{table}", &inlined.table_name))
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        let sql = include_str!("../queries/write/delete_orphaned_file_rows.sql");
+        for table in [
+            "ducklake_file_column_statistics",
+            "ducklake_file_partition_value",
+        ] {
+            dialect
+                .query(&sql.replace("{table}", table))
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        let sql = include_str!("../queries/write/delete_orphaned_table_rows.sql");
+        for table in ["ducklake_table_stats", "ducklake_table_column_stats"] {
+            dialect
+                .query(&sql.replace("{table}", table))
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Schedule a file for physical deletion
+    pub async fn schedule_file_deletion<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        data_file_id: i64,
+        path: &str,
+        path_is_relative: bool,
+        schedule_start: DateTime<Utc>,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = match dialect {
+            Dialect::PostgreSQL => {
+                include_str!("../queries/postgres/write/schedule_file_deletion.sql")
+            }
+            Dialect::SQLite | Dialect::MySQL => {
+                include_str!("../queries/write/schedule_file_deletion.sql")
+            }
+        };
+        dialect
+            .query(sql)
+            .bind(data_file_id)
+            .bind(path)
+            .bind(path_is_relative)
+            .bind(dialect.format_timestamp(schedule_start))
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remove a file from the deletion schedule once it is gone
+    pub async fn delete_scheduled_deletion<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        data_file_id: i64,
+        path: &str,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/delete_scheduled_deletion.sql");
+        dialect
+            .query(sql)
+            .bind(data_file_id)
+            .bind(path)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// End the catalog rows added after `to_snapshot` that are still
+    /// visible, as of `snapshot_id`. Limited to one table if given.
+    pub async fn end_rows_added_after(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+        snapshot_id: i64,
+        to_snapshot: i64,
+        table_id: Option<i64>,
+        lineage: &Lineage,
+    ) -> Result<(), DuckPondError> {
+        let sql = include_str!("../queries/write/end_rows_added_after.sql");
+        for (table, table_column, _) in RESTORED_TABLES {
+            let Some(filter) = restore_filter(*table_column, table_id) else {
+                continue;
+            };
+            Self::end_live(
+                &mut *conn,
+                dialect,
+                lineage,
+                &sql.replace("{table}", table).replace("{filter}", &filter),
+                &[snapshot_id.into(), to_snapshot.into(), table_id.into()],
+            )
+            .await?;
+        }
+        for inlined in
+            ReadQueries::list_inlined_data_tables(&mut *conn, dialect, table_id, None).await?
+        {
+            Self::end_live(
+                &mut *conn,
+                dialect,
+                lineage,
+                &sql.replace("{table}", &inlined.table_name)
+                    .replace("{filter}", ""),
+                &[snapshot_id.into(), to_snapshot.into()],
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Copy the catalog rows visible at `to_snapshot` but removed since into
+    /// new rows visible from `snapshot_id`. Data and delete files are not
+    /// copied here as they need new file IDs, see [`Self::copy_data_file`].
+    pub async fn reopen_rows_removed_after(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+        snapshot_id: i64,
+        to_snapshot: i64,
+        table_id: Option<i64>,
+        lineage: &Lineage,
+    ) -> Result<(), DuckPondError> {
+        let sql = include_str!("../queries/write/reopen_rows_removed_after.sql");
+        for (table, table_column, columns) in RESTORED_TABLES {
+            let Some(filter) = restore_filter(*table_column, table_id) else {
+                continue;
+            };
+            if columns.is_empty() {
+                continue;
+            }
+            dialect
+                .query(
+                    &sql.replace("{table}", table)
+                        .replace("{columns}", columns)
+                        .replace("{filter}", &filter)
+                        .replace("{hidden}", &lineage.hidden()),
+                )
+                .bind(snapshot_id)
+                .bind(to_snapshot)
+                .bind(table_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        for inlined in
+            ReadQueries::list_inlined_data_tables(&mut *conn, dialect, table_id, None).await?
+        {
+            let columns = ReadQueries::show_table_structure(
+                &mut *conn,
+                dialect,
+                inlined.table_id,
+                inlined.schema_snapshot,
+                lineage,
+            )
+            .await?;
+            let columns: String = std::iter::once("row_id".to_string())
+                .chain(columns.iter().map(|c| quote_column(&c.column_name)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            dialect
+                .query(
+                    &sql.replace("{table}", &inlined.table_name)
+                        .replace("{columns}", &columns)
+                        .replace("{filter}", "")
+                        .replace("{hidden}", &lineage.hidden()),
+                )
+                .bind(snapshot_id)
+                .bind(to_snapshot)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Copy a data file, its column statistics and partition values under a
+    /// new file ID visible from `snapshot_id`
+    pub async fn copy_data_file(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+        new_data_file_id: i64,
+        snapshot_id: i64,
+        data_file_id: i64,
+    ) -> Result<(), DuckPondError> {
+        let sql = include_str!("../queries/write/copy_data_file.sql");
+        dialect
+            .query(sql)
+            .bind(new_data_file_id)
+            .bind(snapshot_id)
+            .bind(data_file_id)
+            .execute(&mut *conn)
+            .await?;
+
+        for sql in [
+            include_str!("../queries/write/copy_file_column_statistics.sql"),
+            include_str!("../queries/write/copy_file_partition_value.sql"),
+        ] {
+            dialect
+                .query(sql)
+                .bind(new_data_file_id)
+                .bind(data_file_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Copy a delete file under a new file ID visible from `snapshot_id`,
+    /// pointing it at `data_file_id`
+    pub async fn copy_delete_file<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        new_delete_file_id: i64,
+        snapshot_id: i64,
+        data_file_id: i64,
+        delete_file_id: i64,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/copy_delete_file.sql");
+        dialect
+            .query(sql)
+            .bind(new_delete_file_id)
+            .bind(snapshot_id)
+            .bind(data_file_id)
+            .bind(delete_file_id)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Recompute table and table column statistics from the currently
+    /// visible data files and inlined rows, keeping row ID allocation as is
+    pub async fn refresh_table_stats(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+        table_id: Option<i64>,
+    ) -> Result<(), DuckPondError> {
+        for sql in [
+            include_str!("../queries/write/refresh_table_stats.sql"),
+            include_str!("../queries/write/delete_table_column_stats.sql"),
+            include_str!("../queries/write/rebuild_table_column_stats.sql"),
+        ] {
+            dialect
+                .query(sql)
+                .bind(table_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        let sql = include_str!("../queries/write/add_inlined_record_count.sql");
+        for inlined in
+            ReadQueries::list_inlined_data_tables(&mut *conn, dialect, table_id, None).await?
+        {
+            dialect
+                .query(&sql.replace("{table}", &inlined.table_name))
+                .bind(inlined.table_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Create a tag or branch
+    pub async fn insert_snapshot_ref<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        snapshot_ref: &SnapshotRef,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/insert_snapshot_ref.sql");
+        dialect
+            .query(sql)
+            .bind(snapshot_ref.name.as_str())
+            .bind(snapshot_ref.kind.as_str())
+            .bind(snapshot_ref.snapshot_id)
+            .bind(snapshot_ref.base_snapshot)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Move a tag or branch
+    pub async fn update_snapshot_ref<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        name: &str,
+        snapshot_id: i64,
+        base_snapshot: Option<i64>,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/update_snapshot_ref.sql");
+        dialect
+            .query(sql)
+            .bind(name)
+            .bind(snapshot_id)
+            .bind(base_snapshot)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Delete a tag or branch
+    pub async fn delete_snapshot_ref<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        name: &str,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/delete_snapshot_ref.sql");
+        dialect.query(sql).bind(name).execute(executor).await?;
+
+        Ok(())
+    }
+
+    /// Record that a snapshot was committed to a branch
+    pub async fn insert_branch_snapshot<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        snapshot_id: i64,
+        branch: &str,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/insert_branch_snapshot.sql");
+        dialect
+            .query(sql)
+            .bind(snapshot_id)
+            .bind(branch)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Forget the snapshots of a branch, which main then sees
+    pub async fn delete_branch_snapshots<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        branch: &str,
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/delete_branch_snapshots.sql");
+        dialect.query(sql).bind(branch).execute(executor).await?;
+
+        Ok(())
+    }
+
+    /// Undo everything a branch committed since its base: delete the catalog
+    /// rows it added and make the rows it removed visible again. The
+    /// snapshots themselves are deleted separately.
+    pub async fn discard_branch_rows(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+        branch: &str,
+    ) -> Result<(), DuckPondError> {
+        // Inlined data tables created on the branch go away entirely
+        let created = ReadQueries::list_branch_snapshots(&mut *conn, dialect, branch).await?;
+        let mut tables: Vec<String> = VERSIONED_TABLES.iter().map(|t| t.to_string()).collect();
+        let sql = include_str!("../queries/write/drop_inlined_data_table.sql");
+        for inlined in
+            ReadQueries::list_inlined_data_tables(&mut *conn, dialect, None, None).await?
+        {
+            if created.contains(&inlined.schema_snapshot) {
+                dialect
+                    .query(&sql.replace("{table}", &inlined.table_name))
+                    .execute(&mut *conn)
+                    .await?;
+            } else {
+                tables.push(inlined.table_name);
+            }
+        }
+        let sql = include_str!("../queries/write/delete_inlined_data_tables_on_branch.sql");
+        dialect.query(sql).bind(branch).execute(&mut *conn).await?;
+
+        for sql in [
+            include_str!("../queries/write/delete_rows_added_on_branch.sql"),
+            include_str!("../queries/write/reopen_rows_ended_on_branch.sql"),
+        ] {
+            for table in &tables {
+                dialect
+                    .query(&sql.replace("{table}", table))
+                    .bind(branch)
+                    .execute(&mut *conn)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Create a catalog table for inlined rows of a table with `columns`,
+    /// and register it in `ducklake_inlined_data_tables`
+    pub async fn create_inlined_data_table(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+        inlined: &InlinedDataTable,
+        columns: &[ColumnInfo],
+    ) -> Result<(), DuckPondError> {
+        let definitions: String = columns
+            .iter()
+            .map(|c| {
+                let kind = InlinedColumnKind::of(&c.column_type).ok_or_else(|| {
+                    DuckPondError::InvalidInlinedData {
+                        message: format!(
+                            "column {} of type {} cannot be inlined",
+                            c.column_name, c.column_type
+                        ),
+                    }
+                })?;
+                Ok(format!(
+                    ",\n    {} {}",
+                    quote_column(&c.column_name),
+                    kind.sql_type()
+                ))
+            })
+            .collect::<Result<_, DuckPondError>>()?;
+        let sql = include_str!("../queries/write/create_inlined_data_table.sql");
+        dialect
+            .query(
+                &sql.replace("{table}", &inlined.table_name)
+                    .replace("{columns}", &definitions),
+            )
+            .execute(&mut *conn)
+            .await?;
+
+        let sql = include_str!("../queries/write/insert_inlined_data_table.sql");
+        dialect
+            .query(sql)
+            .bind(inlined.table_id)
+            .bind(inlined.table_name.as_str())
+            .bind(inlined.schema_snapshot)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Insert an inlined row visible from `snapshot_id`, with values in the
+    /// order of `columns`
+    pub async fn insert_inlined_row<'c, E>(
+        executor: E,
+        dialect: Dialect,
+        inlined: &InlinedDataTable,
+        columns: &[ColumnInfo],
+        row_id: i64,
+        snapshot_id: i64,
+        values: &[InlinedValue],
+    ) -> Result<(), DuckPondError>
+    where
+        E: Executor<'c, Database = Any>,
+    {
+        let sql = include_str!("../queries/write/insert_inlined_row.sql");
+        let names: String = columns
+            .iter()
+            .map(|c| format!(", {}", quote_column(&c.column_name)))
+            .collect();
+        let placeholders: String = (0..columns.len())
+            .map(|i| format!(", ${}", i + 3))
+            .collect();
+        let sql = sql
+            .replace("{table}", &inlined.table_name)
+            .replace("{columns}", &names)
+            .replace("{values}", &placeholders);
+        let query = dialect.query(&sql).bind(row_id).bind(snapshot_id);
+        values
+            .iter()
+            .fold(query, |query, value| match value {
+                InlinedValue::Null => query.bind(None::<i64>),
+                InlinedValue::Boolean(value) => query.bind(*value),
+                InlinedValue::Integer(value) => query.bind(*value),
+                InlinedValue::Float(value) => query.bind(*value),
+                InlinedValue::Text(value) => query.bind(value.as_str()),
+            })
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// End the inlined rows with row IDs in `row_id_start..row_id_end` as of
+    /// `snapshot_id`, returning how many were ended
+    pub async fn end_inlined_rows(
+        conn: &mut AnyConnection,
+        dialect: Dialect,
+        inlined: &InlinedDataTable,
+        snapshot_id: i64,
+        row_id_start: i64,
+        row_id_end: i64,
+        lineage: &Lineage,
+    ) -> Result<u64, DuckPondError> {
+        let sql = include_str!("../queries/write/end_inlined_rows.sql");
+        Self::end_live(
+            conn,
+            dialect,
+            lineage,
+            &sql.replace("{table}", &inlined.table_name),
+            &[snapshot_id.into(), row_id_start.into(), row_id_end.into()],
+        )
+        .await
+    }
+}
+
+/// Catalog tables whose rows are versioned by `begin_snapshot`/`end_snapshot`
+const VERSIONED_TABLES: &[&str] = &[
+    "ducklake_schema",
+    "ducklake_table",
+    "ducklake_view",
+    "ducklake_column",
+    "ducklake_data_file",
+    "ducklake_delete_file",
+    "ducklake_partition_info",
+    "ducklake_tag",
+    "ducklake_column_tag",
+];
+
+/// Versioned catalog tables rewound by a restore: the column linking a row
+/// to its table (`None` for schema-level objects) and the columns copied when
+/// a removed row is reopened. Data and delete files are reopened separately.
+const RESTORED_TABLES: &[(&str, Option<&str>, &str)] = &[
+    (
+        "ducklake_schema",
+        None,
+        "schema_id, schema_uuid, schema_name, path, path_is_relative",
+    ),
+    (
+        "ducklake_table",
+        Some("table_id"),
+        "table_id, table_uuid, schema_id, table_name, path, path_is_relative",
+    ),
+    (
+        "ducklake_view",
+        None,
+        "view_id, view_uuid, schema_id, view_name, dialect, \"sql\", column_aliases",
+    ),
+    (
+        "ducklake_column",
+        Some("table_id"),
+        "column_id, table_id, column_order, column_name, column_type, initial_default, \
+         default_value, nulls_allowed, parent_column",
+    ),
+    ("ducklake_data_file", Some("table_id"), ""),
+    ("ducklake_delete_file", Some("table_id"), ""),
+    (
+        "ducklake_partition_info",
+        Some("table_id"),
+        "partition_id, table_id",
+    ),
+    (
+        "ducklake_tag",
+        Some("object_id"),
+        "object_id, \"key\", value",
+    ),
+    (
+        "ducklake_column_tag",
+        Some("table_id"),
+        "table_id, column_id, \"key\", value",
+    ),
+];
+
+/// Filter limiting a restore statement to one table (bound as `$3`), or
+/// `None` if the catalog table has no rows belonging to a table
+fn restore_filter(table_column: Option<&str>, table_id: Option<i64>) -> Option<String> {
+    match (table_column, table_id) {
+        (_, None) => Some(String::new()),
+        (Some(column), Some(_)) => Some(format!(" AND {} = $3", column)),
+        (None, Some(_)) => None,
+    }
+}
+
+/// Read a boolean column portably
+///
+/// Depending on the backend a `BOOLEAN` column decodes as a bool (Postgres)
+/// or as a small integer (SQLite, MySQL's `TINYINT(1)`).
+fn get_bool(row: &AnyRow, column: &str) -> bool {
+    row.try_get::<bool, _>(column)
+        .or_else(|_| row.try_get::<i64, _>(column).map(|v| v != 0))
+        .or_else(|_| row.try_get::<i32, _>(column).map(|v| v != 0))
+        .or_else(|_| row.try_get::<i16, _>(column).map(|v| v != 0))
+        .unwrap_or_default()
+}
+
+/// Nest columns under their `parent_column`, keeping catalog order
+fn column_tree(columns: &[(Option<i64>, ColumnInfo)], parent: Option<i64>) -> Vec<ColumnInfo> {
+    columns
+        .iter()
+        .filter(|(parent_column, _)| *parent_column == parent)
+        .map(|(_, column)| ColumnInfo {
+            children: column_tree(columns, Some(column.column_id)),
+            ..column.clone()
+        })
+        .collect()
+}
+
+/// Read a value of an inlined data table column, by position
+///
+/// The `Any` driver refuses to decode a NULL into any type, so a value that
+/// does not decode is NULL.
+fn get_inlined_value(row: &AnyRow, index: usize, kind: Option<InlinedColumnKind>) -> InlinedValue {
+    let value = match kind {
+        Some(InlinedColumnKind::Boolean) => row
+            .try_get::<bool, _>(index)
+            .or_else(|_| row.try_get::<i64, _>(index).map(|v| v != 0))
+            .or_else(|_| row.try_get::<i32, _>(index).map(|v| v != 0))
+            .or_else(|_| row.try_get::<i16, _>(index).map(|v| v != 0))
+            .map(InlinedValue::Boolean),
+        Some(InlinedColumnKind::Integer) => row.try_get(index).map(InlinedValue::Integer),
+        Some(InlinedColumnKind::Float) => row.try_get(index).map(InlinedValue::Float),
+        Some(InlinedColumnKind::Text) | None => row.try_get(index).map(InlinedValue::Text),
+    };
+    value.unwrap_or(InlinedValue::Null)
+}
+
+/// Read a timestamp column selected as text
+fn get_timestamp(
+    row: &AnyRow,
+    dialect: Dialect,
+    column: &str,
+) -> Result<DateTime<Utc>, DuckPondError> {
+    let value: String = row.try_get(column)?;
+    dialect.parse_timestamp(&value).ok_or_else(|| {
+        sqlx::Error::Decode(format!("invalid timestamp in {}: {}", column, value).into()).into()
+    })
 }
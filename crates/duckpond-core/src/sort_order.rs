@@ -0,0 +1,253 @@
+//! Table sort orders used to cluster data files on write
+//!
+//! A sort order is stored as table metadata and applied to record batches
+//! before they are written to Parquet. Keeping rows ordered (or Z-ordered)
+//! across writes keeps the per-file min/max statistics tight, which makes
+//! file pruning effective.
+
+use crate::error::{DuckPondError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A single key of a linear sort order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SortKey {
+    pub column: String,
+    pub descending: bool,
+    pub nulls_first: bool,
+}
+
+impl SortKey {
+    /// Create an ascending sort key with nulls last
+    pub fn asc(column: impl Into<String>) -> Self {
+        Self {
+            column: column.into(),
+            descending: false,
+            nulls_first: false,
+        }
+    }
+
+    /// Create a descending sort key with nulls first
+    pub fn desc(column: impl Into<String>) -> Self {
+        Self {
+            column: column.into(),
+            descending: true,
+            nulls_first: true,
+        }
+    }
+}
+
+/// Ordering applied to rows before they are written to a data file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortOrder {
+    /// Lexicographic ordering, e.g. `event_time, user_id DESC`
+    Linear(Vec<SortKey>),
+    /// Z-order curve over several columns, e.g. `ZORDER(lat, lon)`
+    ZOrder(Vec<String>),
+}
+
+impl SortOrder {
+    /// Names of the columns referenced by this sort order
+    pub fn columns(&self) -> Vec<&str> {
+        match self {
+            SortOrder::Linear(keys) => keys.iter().map(|k| k.column.as_str()).collect(),
+            SortOrder::ZOrder(columns) => columns.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.column)?;
+        if self.descending {
+            write!(f, " DESC")?;
+        }
+        // Only spell out the null ordering when it differs from the default
+        // for the direction (ASC -> NULLS LAST, DESC -> NULLS FIRST)
+        if self.nulls_first != self.descending {
+            if self.nulls_first {
+                write!(f, " NULLS FIRST")?;
+            } else {
+                write!(f, " NULLS LAST")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortOrder::Linear(keys) => {
+                let keys: Vec<String> = keys.iter().map(ToString::to_string).collect();
+                write!(f, "{}", keys.join(", "))
+            }
+            SortOrder::ZOrder(columns) => write!(f, "ZORDER({})", columns.join(", ")),
+        }
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = DuckPondError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let tokens: Vec<String> = s.split_whitespace().map(str::to_uppercase).collect();
+        let column = s
+            .split_whitespace()
+            .next()
+            .ok_or_else(|| invalid_sort_order("empty sort key"))?;
+
+        let mut key = SortKey::asc(column);
+        let mut nulls = None;
+        let mut rest = tokens[1..].iter().map(String::as_str);
+        while let Some(token) = rest.next() {
+            match token {
+                "ASC" => key.descending = false,
+                "DESC" => key.descending = true,
+                "NULLS" => match rest.next() {
+                    Some("FIRST") => nulls = Some(true),
+                    Some("LAST") => nulls = Some(false),
+                    _ => return Err(invalid_sort_order("expected FIRST or LAST after NULLS")),
+                },
+                other => {
+                    return Err(invalid_sort_order(&format!(
+                        "unexpected token '{}' in sort key '{}'",
+                        other, s
+                    )))
+                }
+            }
+        }
+        key.nulls_first = nulls.unwrap_or(key.descending);
+
+        Ok(key)
+    }
+}
+
+impl FromStr for SortOrder {
+    type Err = DuckPondError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let trimmed = s.trim();
+        let trimmed = strip_prefix_ignore_case(trimmed, "ORDER BY").unwrap_or(trimmed);
+
+        if let Some(inner) = strip_prefix_ignore_case(trimmed, "ZORDER")
+            .filter(|rest| rest.trim_start().starts_with('('))
+        {
+            let inner = inner
+                .trim()
+                .strip_prefix('(')
+                .and_then(|rest| rest.strip_suffix(')'))
+                .ok_or_else(|| invalid_sort_order("expected ZORDER(column, ...)"))?;
+            let columns = split_list(inner)?
+                .into_iter()
+                .map(|column| {
+                    if column.contains(char::is_whitespace) {
+                        Err(invalid_sort_order(&format!(
+                            "ZORDER columns cannot have a direction: '{}'",
+                            column
+                        )))
+                    } else {
+                        Ok(column.to_string())
+                    }
+                })
+                .collect::<Result<Vec<_>>>()?;
+            return Ok(SortOrder::ZOrder(columns));
+        }
+
+        let keys = split_list(trimmed)?
+            .into_iter()
+            .map(SortKey::from_str)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(SortOrder::Linear(keys))
+    }
+}
+
+fn split_list(s: &str) -> Result<Vec<&str>> {
+    let items: Vec<&str> = s.split(',').map(str::trim).collect();
+    if items.iter().any(|item| item.is_empty()) {
+        return Err(invalid_sort_order(
+            "sort order must list at least one column",
+        ));
+    }
+    Ok(items)
+}
+
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = s.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &s[prefix.len()..])
+}
+
+fn invalid_sort_order(message: &str) -> DuckPondError {
+    DuckPondError::InvalidSortOrder {
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_linear() {
+        let order: SortOrder = "event_time, user_id DESC".parse().unwrap();
+        assert_eq!(
+            order,
+            SortOrder::Linear(vec![SortKey::asc("event_time"), SortKey::desc("user_id")])
+        );
+        assert_eq!(order.columns(), vec!["event_time", "user_id"]);
+    }
+
+    #[test]
+    fn test_parse_order_by_prefix_and_nulls() {
+        let order: SortOrder = "order by a asc nulls first".parse().unwrap();
+        assert_eq!(
+            order,
+            SortOrder::Linear(vec![SortKey {
+                column: "a".to_string(),
+                descending: false,
+                nulls_first: true,
+            }])
+        );
+    }
+
+    #[test]
+    fn test_parse_zorder() {
+        let order: SortOrder = "zorder(lat, lon)".parse().unwrap();
+        assert_eq!(
+            order,
+            SortOrder::ZOrder(vec!["lat".to_string(), "lon".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!("".parse::<SortOrder>().is_err());
+        assert!("a,,b".parse::<SortOrder>().is_err());
+        assert!("a SIDEWAYS".parse::<SortOrder>().is_err());
+        assert!("a NULLS".parse::<SortOrder>().is_err());
+        assert!("ZORDER(a DESC)".parse::<SortOrder>().is_err());
+        assert!("ZORDER a".parse::<SortOrder>().is_err());
+        assert!("ZORDER(a, b".parse::<SortOrder>().is_err());
+    }
+
+    #[test]
+    fn test_column_named_like_keyword() {
+        let order: SortOrder = "zorder_rank DESC".parse().unwrap();
+        assert_eq!(order, SortOrder::Linear(vec![SortKey::desc("zorder_rank")]));
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for text in [
+            "event_time, user_id DESC",
+            "a NULLS FIRST, b DESC NULLS LAST",
+            "ZORDER(lat, lon)",
+        ] {
+            let order: SortOrder = text.parse().unwrap();
+            assert_eq!(order.to_string(), text);
+            assert_eq!(order.to_string().parse::<SortOrder>().unwrap(), order);
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! DuckLake column types
+//!
+//! Column types are stored in `ducklake_column.column_type` by their
+//! DuckLake names (`int64`, `decimal(18,3)`, `timestamptz`, ...). Nested
+//! types only name the kind of nesting; their fields are child columns.
+
+use crate::error::DuckPondError;
+use std::fmt;
+use std::str::FromStr;
+
+/// Largest precision of a `decimal` column
+pub const MAX_DECIMAL_PRECISION: u8 = 38;
+
+/// A DuckLake column type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DuckLakeType {
+    Boolean,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
+    Float32,
+    Float64,
+    Decimal {
+        precision: u8,
+        scale: u8,
+    },
+    Varchar,
+    Blob,
+    Date,
+    Time,
+    TimeTz,
+    /// Timestamp in microseconds
+    Timestamp,
+    TimestampS,
+    TimestampMs,
+    TimestampNs,
+    /// Timestamp with time zone, in microseconds since the epoch in UTC
+    TimestampTz,
+    Interval,
+    Uuid,
+    Json,
+    Struct,
+    List,
+    Map,
+}
+
+impl DuckLakeType {
+    /// Whether columns of the type have child columns
+    pub fn is_nested(self) -> bool {
+        matches!(self, Self::Struct | Self::List | Self::Map)
+    }
+
+    /// Whether `children` child columns are valid for the type: a struct
+    /// needs at least one, a list one and a map two (key and value)
+    pub fn accepts_children(self, children: usize) -> bool {
+        match self {
+            Self::Struct => children > 0,
+            Self::List => children == 1,
+            Self::Map => children == 2,
+            _ => children == 0,
+        }
+    }
+}
+
+impl fmt::Display for DuckLakeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Boolean => "boolean",
+            Self::Int8 => "int8",
+            Self::Int16 => "int16",
+            Self::Int32 => "int32",
+            Self::Int64 => "int64",
+            Self::UInt8 => "uint8",
+            Self::UInt16 => "uint16",
+            Self::UInt32 => "uint32",
+            Self::UInt64 => "uint64",
+            Self::Float32 => "float32",
+            Self::Float64 => "float64",
+            Self::Decimal { precision, scale } => {
+                return write!(f, "decimal({},{})", precision, scale)
+            }
+            Self::Varchar => "varchar",
+            Self::Blob => "blob",
+            Self::Date => "date",
+            Self::Time => "time",
+            Self::TimeTz => "timetz",
+            Self::Timestamp => "timestamp",
+            Self::TimestampS => "timestamp_s",
+            Self::TimestampMs => "timestamp_ms",
+            Self::TimestampNs => "timestamp_ns",
+            Self::TimestampTz => "timestamptz",
+            Self::Interval => "interval",
+            Self::Uuid => "uuid",
+            Self::Json => "json",
+            Self::Struct => "struct",
+            Self::List => "list",
+            Self::Map => "map",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Parse a type name, case-insensitively. Common SQL spellings (`bigint`,
+/// `string`, `double`, ...) are accepted as aliases of the DuckLake names.
+impl FromStr for DuckLakeType {
+    type Err = DuckPondError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        if let Some(args) = name
+            .strip_prefix("decimal")
+            .or_else(|| name.strip_prefix("numeric"))
+            .and_then(|rest| rest.trim_start().strip_prefix('('))
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            return parse_decimal(args).ok_or_else(|| unknown_type(s));
+        }
+
+        Ok(match name.as_str() {
+            "boolean" | "bool" => Self::Boolean,
+            "int8" | "tinyint" => Self::Int8,
+            "int16" | "smallint" => Self::Int16,
+            "int32" | "int" | "integer" => Self::Int32,
+            "int64" | "bigint" | "long" => Self::Int64,
+            "uint8" | "utinyint" => Self::UInt8,
+            "uint16" | "usmallint" => Self::UInt16,
+            "uint32" | "uinteger" => Self::UInt32,
+            "uint64" | "ubigint" => Self::UInt64,
+            "float32" | "float" | "real" => Self::Float32,
+            "float64" | "double" => Self::Float64,
+            "varchar" | "string" | "text" => Self::Varchar,
+            "blob" | "bytea" | "binary" => Self::Blob,
+            "date" => Self::Date,
+            "time" => Self::Time,
+            "timetz" => Self::TimeTz,
+            "timestamp" | "datetime" | "timestamp_us" => Self::Timestamp,
+            "timestamp_s" => Self::TimestampS,
+            "timestamp_ms" => Self::TimestampMs,
+            "timestamp_ns" => Self::TimestampNs,
+            "timestamptz" => Self::TimestampTz,
+            "interval" => Self::Interval,
+            "uuid" => Self::Uuid,
+            "json" => Self::Json,
+            "struct" => Self::Struct,
+            "list" => Self::List,
+            "map" => Self::Map,
+            _ => return Err(unknown_type(s)),
+        })
+    }
+}
+
+/// Parse the `p,s` arguments of a decimal type
+fn parse_decimal(args: &str) -> Option<DuckLakeType> {
+    let (precision, scale) = args.split_once(',')?;
+    let precision: u8 = precision.trim().parse().ok()?;
+    let scale: u8 = scale.trim().parse().ok()?;
+    if precision == 0 || precision > MAX_DECIMAL_PRECISION || scale > precision {
+        return None;
+    }
+    Some(DuckLakeType::Decimal { precision, scale })
+}
+
+fn unknown_type(name: &str) -> DuckPondError {
+    DuckPondError::InvalidColumnDefinition {
+        message: format!("unknown column type '{}'", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_print() {
+        for name in [
+            "boolean",
+            "int8",
+            "uint64",
+            "float64",
+            "decimal(18,3)",
+            "varchar",
+            "timestamp_ns",
+            "timestamptz",
+            "interval",
+            "uuid",
+            "json",
+            "map",
+        ] {
+            let parsed: DuckLakeType = name.parse().unwrap();
+            assert_eq!(parsed.to_string(), name);
+        }
+
+        assert_eq!(
+            "BIGINT".parse::<DuckLakeType>().unwrap(),
+            DuckLakeType::Int64
+        );
+        assert_eq!(
+            "string".parse::<DuckLakeType>().unwrap(),
+            DuckLakeType::Varchar
+        );
+        assert_eq!(
+            "DECIMAL(10, 2)".parse::<DuckLakeType>().unwrap(),
+            DuckLakeType::Decimal {
+                precision: 10,
+                scale: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_types() {
+        for name in [
+            "",
+            "varchar2",
+            "decimal(40,2)",
+            "decimal(5,6)",
+            "decimal(5)",
+        ] {
+            assert!(matches!(
+                name.parse::<DuckLakeType>(),
+                Err(DuckPondError::InvalidColumnDefinition { .. })
+            ));
+        }
+    }
+}
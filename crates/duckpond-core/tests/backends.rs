@@ -0,0 +1,1720 @@
+//! Catalog scenarios run against every supported backend
+//!
+//! SQLite always runs against a temporary file. The Postgres and MySQL
+//! scenarios are ignored by default; point `DUCKPOND_TEST_POSTGRES_URL` /
+//! `DUCKPOND_TEST_MYSQL_URL` at a server and run them with
+//!
+//! ```text
+//! DUCKPOND_TEST_POSTGRES_URL=postgres://postgres@localhost/postgres \
+//!     cargo test -p duckpond-core --test backends postgres -- --ignored
+//! DUCKPOND_TEST_MYSQL_URL=mysql://root@localhost/mysql \
+//!     cargo test -p duckpond-core --test backends mysql -- --ignored
+//! ```
+//!
+//! Each test creates (and afterwards drops) its own database there.
+
+use duckpond_core::migration::{self, MigrationStatus};
+use duckpond_core::models::{ColumnTag, FileKind, ObjectTag, RefKind, TableInfo};
+use duckpond_core::{
+    BackendInfo, ColumnDefinition, DatabaseType, Dialect, DuckPond, DuckPondError,
+    FileColumnStatistics, FlushedDataFile, InlinedValue, NewDataFile, SnapshotChange,
+    DEFAULT_SCHEMA, DUCKLAKE_SPEC_VERSION,
+};
+use sqlx::any::AnyPoolOptions;
+use sqlx::{AnyPool, Row};
+use std::future::Future;
+use std::path::PathBuf;
+
+struct TestCatalog {
+    pool: AnyPool,
+    database_type: DatabaseType,
+    admin: Option<(AnyPool, String)>,
+    file: Option<PathBuf>,
+}
+
+impl TestCatalog {
+    async fn create(database_type: DatabaseType) -> Self {
+        sqlx::any::install_default_drivers();
+        let name = format!("duckpond_test_{}", uuid::Uuid::new_v4().simple());
+
+        let (url, admin, file) = match database_type {
+            DatabaseType::SQLite => {
+                let path = std::env::temp_dir().join(format!("{}.db", name));
+                (
+                    format!("sqlite://{}?mode=rwc", path.display()),
+                    None,
+                    Some(path),
+                )
+            }
+            _ => {
+                let var = match database_type {
+                    DatabaseType::MySQL => "DUCKPOND_TEST_MYSQL_URL",
+                    _ => "DUCKPOND_TEST_POSTGRES_URL",
+                };
+                let server_url = std::env::var(var)
+                    .unwrap_or_else(|_| panic!("{} must point at a test server", var));
+                let admin = AnyPoolOptions::new()
+                    .max_connections(1)
+                    .connect(&server_url)
+                    .await
+                    .expect("connect to test server");
+                sqlx::query(&format!("CREATE DATABASE {}", name))
+                    .execute(&admin)
+                    .await
+                    .expect("create test database");
+
+                let base = server_url.split('?').next().unwrap();
+                let base = &base[..base.rfind('/').unwrap()];
+                (format!("{}/{}", base, name), Some((admin, name)), None)
+            }
+        };
+
+        let pool = AnyPoolOptions::new()
+            .max_connections(5)
+            .connect(&url)
+            .await
+            .expect("connect to test catalog");
+        Dialect::from(database_type)
+            .migrator()
+            .run(&pool)
+            .await
+            .expect("run migrations");
+
+        Self {
+            pool,
+            database_type,
+            admin,
+            file,
+        }
+    }
+
+    async fn drop(self) {
+        self.pool.close().await;
+        if let Some((admin, name)) = self.admin {
+            let _ = sqlx::query(&format!("DROP DATABASE {}", name))
+                .execute(&admin)
+                .await;
+        }
+        if let Some(file) = self.file {
+            let _ = std::fs::remove_file(file);
+        }
+    }
+}
+
+async fn run<F, Fut>(database_type: DatabaseType, scenario: F)
+where
+    F: FnOnce(DuckPond) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let catalog = TestCatalog::create(database_type).await;
+    let backend = BackendInfo::detect(&catalog.pool, catalog.database_type)
+        .await
+        .expect("detect backend");
+    scenario(DuckPond::new(catalog.pool.clone(), backend)).await;
+    catalog.drop().await;
+}
+
+macro_rules! backend_tests {
+    ($($scenario:ident),* $(,)?) => {
+        mod sqlite {
+            $(
+                #[tokio::test]
+                async fn $scenario() {
+                    super::run(duckpond_core::DatabaseType::SQLite, super::$scenario).await;
+                }
+            )*
+        }
+
+        mod postgres {
+            $(
+                #[tokio::test]
+                #[ignore = "needs DUCKPOND_TEST_POSTGRES_URL"]
+                async fn $scenario() {
+                    super::run(duckpond_core::DatabaseType::PostgreSQL, super::$scenario).await;
+                }
+            )*
+        }
+
+        mod mysql {
+            $(
+                #[tokio::test]
+                #[ignore = "needs DUCKPOND_TEST_MYSQL_URL"]
+                async fn $scenario() {
+                    super::run(duckpond_core::DatabaseType::MySQL, super::$scenario).await;
+                }
+            )*
+        }
+    };
+}
+
+backend_tests!(
+    schemas_and_tables,
+    data_files_and_statistics,
+    time_travel,
+    sort_order_tags,
+    initialize_catalog,
+    migrations_and_spec_upgrade,
+    expire_snapshots,
+    history_and_diff,
+    restore_snapshot,
+    tags_and_branches,
+    views,
+    object_and_column_tags,
+    data_inlining,
+    nested_columns,
+    column_types,
+    add_data_files,
+    drop_and_alter_tables,
+);
+
+fn columns() -> Vec<ColumnDefinition> {
+    vec![
+        ColumnDefinition {
+            column_id: None,
+            name: "id".to_string(),
+            data_type: "int64".to_string(),
+            nullable: false,
+            children: Vec::new(),
+        },
+        ColumnDefinition {
+            column_id: None,
+            name: "name".to_string(),
+            data_type: "varchar".to_string(),
+            nullable: true,
+            children: Vec::new(),
+        },
+    ]
+}
+
+fn stats(column_id: i64, min: Option<&str>, max: Option<&str>) -> FileColumnStatistics {
+    FileColumnStatistics {
+        column_id,
+        value_count: 10,
+        null_count: if min.is_none() { 10 } else { 0 },
+        nan_count: 0,
+        min_value: min.map(str::to_string),
+        max_value: max.map(str::to_string),
+    }
+}
+
+async fn schemas_and_tables(pond: DuckPond) {
+    let schema = pond.create_schema("analytics").await.unwrap();
+    let table = pond
+        .create_table(schema.schema_id, "events", columns())
+        .await
+        .unwrap();
+
+    let schemas = pond.list_schemas().await.unwrap();
+    assert_eq!(schemas.len(), 1);
+    assert_eq!(schemas[0].schema_name, "analytics");
+
+    let tables = pond.list_tables(schema.schema_id).await.unwrap();
+    assert_eq!(tables.len(), 1);
+    assert_eq!(tables[0].table_name, "events");
+
+    let structure = pond.table_structure(table.table_id).await.unwrap();
+    let shape: Vec<(&str, bool)> = structure
+        .iter()
+        .map(|c| (c.column_name.as_str(), c.nulls_allowed))
+        .collect();
+    assert_eq!(shape, vec![("id", false), ("name", true)]);
+}
+
+async fn data_files_and_statistics(pond: DuckPond) {
+    let schema = pond.create_schema("analytics").await.unwrap();
+    let table = pond
+        .create_table(schema.schema_id, "events", columns())
+        .await
+        .unwrap();
+
+    pond.insert_data_file(
+        table.table_id,
+        "analytics/events/a.parquet",
+        10,
+        1000,
+        vec![stats(1, Some("5"), Some("7")), stats(2, None, None)],
+    )
+    .await
+    .unwrap();
+    pond.insert_data_file(
+        table.table_id,
+        "analytics/events/b.parquet",
+        10,
+        2000,
+        vec![
+            stats(1, Some("3"), Some("6")),
+            stats(2, Some("a"), Some("z")),
+        ],
+    )
+    .await
+    .unwrap();
+
+    let files = pond.list_data_files(table.table_id).await.unwrap();
+    assert_eq!(files.len(), 2);
+    assert!(files.iter().all(|f| f.path_is_relative));
+    assert!(files.iter().all(|f| f.delete_file_path.is_none()));
+    let row_id_starts: Vec<i64> = files.iter().map(|f| f.row_id_start).collect();
+    assert_eq!(row_id_starts, vec![0, 10]);
+
+    // Table statistics are upserted and row IDs allocated sequentially
+    let row =
+        sqlx::query("SELECT record_count, next_row_id, file_size_bytes FROM ducklake_table_stats")
+            .fetch_all(pond.pool())
+            .await
+            .unwrap();
+    assert_eq!(row.len(), 1);
+    assert_eq!(row[0].get::<i64, _>("record_count"), 20);
+    assert_eq!(row[0].get::<i64, _>("next_row_id"), 20);
+    assert_eq!(row[0].get::<i64, _>("file_size_bytes"), 3000);
+
+    let row_id_starts: Vec<i64> =
+        sqlx::query("SELECT row_id_start FROM ducklake_data_file ORDER BY data_file_id")
+            .fetch_all(pond.pool())
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| r.get("row_id_start"))
+            .collect();
+    assert_eq!(row_id_starts, vec![0, 10]);
+
+    // Column statistics are merged into one row per column
+    let rows = sqlx::query(
+        "SELECT column_id, min_value, max_value FROM ducklake_table_column_stats ORDER BY column_id",
+    )
+    .fetch_all(pond.pool())
+    .await
+    .unwrap();
+    let merged: Vec<(i64, Option<String>, Option<String>)> = rows
+        .iter()
+        .map(|r| (r.get(0), r.try_get(1).ok(), r.try_get(2).ok()))
+        .collect();
+    assert_eq!(
+        merged,
+        vec![
+            (1, Some("3".to_string()), Some("7".to_string())),
+            (2, Some("a".to_string()), Some("z".to_string())),
+        ]
+    );
+
+    let pruned = pond
+        .prune_files_by_column_stats(table.table_id, 1, "4")
+        .await
+        .unwrap();
+    assert_eq!(pruned.len(), 1);
+
+    // Every write of a file inserted in a caller's transaction rolls back with it
+    let snapshot = pond.current_snapshot().await.unwrap();
+    let mut tx = pond.pool().begin().await.unwrap();
+    pond.insert_data_file_tx(
+        &mut tx,
+        table.table_id,
+        "analytics/events/c.parquet",
+        10,
+        500,
+        vec![stats(1, Some("1"), Some("9"))],
+    )
+    .await
+    .unwrap();
+    tx.rollback().await.unwrap();
+    assert_eq!(pond.current_snapshot().await.unwrap(), snapshot);
+    assert_eq!(pond.list_data_files(table.table_id).await.unwrap().len(), 2);
+    let row = sqlx::query("SELECT record_count, next_row_id FROM ducklake_table_stats")
+        .fetch_one(pond.pool())
+        .await
+        .unwrap();
+    assert_eq!(row.get::<i64, _>("record_count"), 20);
+    assert_eq!(row.get::<i64, _>("next_row_id"), 20);
+    let row = sqlx::query("SELECT min_value FROM ducklake_table_column_stats WHERE column_id = 1")
+        .fetch_one(pond.pool())
+        .await
+        .unwrap();
+    assert_eq!(row.get::<String, _>("min_value"), "3");
+}
+
+async fn time_travel(pond: DuckPond) {
+    let schema = pond.create_schema("analytics").await.unwrap();
+    let before_table = pond.current_snapshot().await.unwrap().unwrap();
+    pond.create_table(schema.schema_id, "events", columns())
+        .await
+        .unwrap();
+
+    assert!(pond
+        .at_snapshot(before_table)
+        .list_tables(schema.schema_id)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(pond.list_tables(schema.schema_id).await.unwrap().len(), 1);
+
+    // Timestamps resolve to the latest snapshot at or before them
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    let between = chrono::Utc::now();
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    pond.create_table(schema.schema_id, "sessions", columns())
+        .await
+        .unwrap();
+    let time_travel = pond.at_timestamp(between).await.unwrap();
+    assert_eq!(time_travel.snapshot_id(), before_table + 1);
+    assert_eq!(
+        time_travel
+            .list_tables(schema.schema_id)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    let long_ago = chrono::Utc::now() - chrono::Duration::days(365);
+    assert!(matches!(
+        pond.at_timestamp(long_ago).await,
+        Err(DuckPondError::NoSnapshotAtTime { .. })
+    ));
+
+    // A pinned handle reads the past and refuses to write
+    let pinned = pond.as_of(before_table).await.unwrap();
+    assert!(pinned
+        .list_tables(schema.schema_id)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(matches!(
+        pinned.create_schema("late").await,
+        Err(DuckPondError::HistoricalSnapshot { .. })
+    ));
+    assert!(pond.as_of(before_table + 100).await.is_err());
+}
+
+async fn sort_order_tags(pond: DuckPond) {
+    let schema = pond.create_schema("analytics").await.unwrap();
+    let table = pond
+        .create_table(schema.schema_id, "events", columns())
+        .await
+        .unwrap();
+
+    let order = "id, name DESC".parse().unwrap();
+    pond.set_sort_order(table.table_id, Some(&order))
+        .await
+        .unwrap();
+    let set_at = pond.current_snapshot().await.unwrap().unwrap();
+    assert_eq!(
+        pond.sort_order(table.table_id).await.unwrap(),
+        Some(order.clone())
+    );
+
+    pond.set_sort_order(table.table_id, None).await.unwrap();
+    assert_eq!(pond.sort_order(table.table_id).await.unwrap(), None);
+    assert_eq!(
+        pond.sort_order_at_snapshot(table.table_id, set_at)
+            .await
+            .unwrap(),
+        Some(order)
+    );
+
+    let unknown = "missing".parse().unwrap();
+    assert!(pond
+        .set_sort_order(table.table_id, Some(&unknown))
+        .await
+        .is_err());
+}
+
+async fn initialize_catalog(pond: DuckPond) {
+    pond.initialize("s3://bucket/lake", false).await.unwrap();
+
+    assert_eq!(
+        pond.spec_version().await.unwrap().as_deref(),
+        Some(DUCKLAKE_SPEC_VERSION)
+    );
+    assert_eq!(pond.current_snapshot().await.unwrap(), Some(0));
+    let schemas = pond.list_schemas().await.unwrap();
+    assert_eq!(schemas.len(), 1);
+    assert_eq!(schemas[0].schema_name, DEFAULT_SCHEMA);
+    assert_eq!(schemas[0].schema_id, 0);
+
+    // Catalog IDs continue after the default schema
+    let schema = pond.create_schema("analytics").await.unwrap();
+    assert_eq!(schema.schema_id, 1);
+
+    assert!(pond.initialize("s3://bucket/lake", false).await.is_err());
+
+    // Forcing keeps existing history and rewrites the metadata
+    pond.initialize("s3://bucket/other", true).await.unwrap();
+    assert_eq!(pond.list_schemas().await.unwrap().len(), 2);
+    let data_path: String =
+        sqlx::query_scalar("SELECT value FROM ducklake_metadata WHERE value LIKE 's3://%'")
+            .fetch_one(pond.pool())
+            .await
+            .unwrap();
+    assert_eq!(data_path, "s3://bucket/other");
+
+    set_spec_version(&pond, "9.9").await;
+    assert!(DuckPond::open(pond.pool().clone(), pond.backend().clone())
+        .await
+        .is_err());
+}
+
+async fn set_spec_version(pond: &DuckPond, version: &str) {
+    pond.dialect()
+        .query("UPDATE ducklake_metadata SET value = $1 WHERE \"key\" = 'version'")
+        .bind(version)
+        .execute(pond.pool())
+        .await
+        .unwrap();
+}
+
+async fn migrations_and_spec_upgrade(pond: DuckPond) {
+    let migrations = migration::migration_info(pond.pool(), pond.dialect())
+        .await
+        .unwrap();
+    assert!(migrations
+        .iter()
+        .all(|m| m.status == MigrationStatus::Applied && m.reversible));
+
+    pond.initialize("data", false).await.unwrap();
+    let schema = pond.create_schema("analytics").await.unwrap();
+    let table = pond
+        .create_table(schema.schema_id, "events", columns())
+        .await
+        .unwrap();
+
+    // Reverting the 0.2 migration takes the catalog back to spec 0.1
+    let spec_0_2 = 20261018000100;
+    let reverted = migration::revert_migrations(pond.pool(), pond.dialect(), Some(spec_0_2 - 1))
+        .await
+        .unwrap();
+    let newer: Vec<i64> = migrations
+        .iter()
+        .rev()
+        .map(|m| m.version)
+        .take_while(|v| *v >= spec_0_2)
+        .collect();
+    assert_eq!(reverted, newer);
+    assert_eq!(pond.spec_version().await.unwrap().as_deref(), Some("0.1"));
+    let migrations = migration::migration_info(pond.pool(), pond.dialect())
+        .await
+        .unwrap();
+    assert_eq!(migrations.last().unwrap().status, MigrationStatus::Pending);
+
+    // Files of a 0.1 catalog are relative to the data path
+    pond.dialect().migrator().run(pond.pool()).await.unwrap();
+    for path in ["analytics/events/a.parquet", "external/b.parquet"] {
+        pond.insert_data_file(table.table_id, path, 10, 1000, Vec::new())
+            .await
+            .unwrap();
+    }
+    assert_eq!(pond.table_path(table.table_id).await.unwrap(), None);
+
+    let steps = pond.upgrade_spec().await.unwrap();
+    assert_eq!(steps, vec![("0.1".to_string(), "0.2".to_string())]);
+    assert_eq!(
+        pond.spec_version().await.unwrap().as_deref(),
+        Some(DUCKLAKE_SPEC_VERSION)
+    );
+    assert!(pond.upgrade_spec().await.unwrap().is_empty());
+
+    let mut paths: Vec<String> = sqlx::query_scalar(
+        "SELECT path FROM ducklake_schema UNION ALL SELECT path FROM ducklake_table",
+    )
+    .fetch_all(pond.pool())
+    .await
+    .unwrap();
+    paths.sort();
+    assert_eq!(paths, vec!["analytics/", "events/", "main/"]);
+
+    // In 0.2 a relative file path is resolved against its table's path, and
+    // that against its schema's; files outside the table's path are absolute
+    let rows = sqlx::query(
+        "SELECT s.path AS schema_path, t.path AS table_path, f.path, \
+         CASE WHEN f.path_is_relative THEN 1 ELSE 0 END AS path_is_relative \
+         FROM ducklake_data_file AS f \
+         JOIN ducklake_table AS t ON t.table_id = f.table_id \
+         JOIN ducklake_schema AS s ON s.schema_id = t.schema_id \
+         ORDER BY f.data_file_id",
+    )
+    .fetch_all(pond.pool())
+    .await
+    .unwrap();
+    let resolved: Vec<String> = rows
+        .iter()
+        .map(|r| {
+            let path: String = r.get("path");
+            let relative = r
+                .try_get::<i64, _>("path_is_relative")
+                .or_else(|_| r.try_get::<i32, _>("path_is_relative").map(i64::from));
+            if relative.unwrap() == 1 {
+                let schema_path: String = r.get("schema_path");
+                let table_path: String = r.get("table_path");
+                format!("data/{}{}{}", schema_path, table_path, path)
+            } else {
+                path
+            }
+        })
+        .collect();
+    assert_eq!(
+        resolved,
+        vec!["data/analytics/events/a.parquet", "data/external/b.parquet"]
+    );
+    let files: Vec<String> = pond
+        .list_data_files(table.table_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|f| f.data_file_path)
+        .collect();
+    assert_eq!(
+        files,
+        vec!["analytics/events/a.parquet", "external/b.parquet"]
+    );
+    assert_eq!(
+        pond.table_path(table.table_id).await.unwrap().as_deref(),
+        Some("analytics/events/")
+    );
+
+    // Reverting makes file paths relative to the data path again
+    migration::revert_migrations(pond.pool(), pond.dialect(), Some(spec_0_2 - 1))
+        .await
+        .unwrap();
+    let mut paths: Vec<String> = sqlx::query_scalar("SELECT path FROM ducklake_data_file")
+        .fetch_all(pond.pool())
+        .await
+        .unwrap();
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec!["analytics/events/a.parquet", "data/external/b.parquet"]
+    );
+}
+
+async fn expire_snapshots(pond: DuckPond) {
+    let schema = pond.create_schema("analytics").await.unwrap();
+    let table = pond
+        .create_table(schema.schema_id, "events", columns())
+        .await
+        .unwrap();
+    let old_file = pond
+        .insert_data_file(
+            table.table_id,
+            "analytics/events/old.parquet",
+            10,
+            1000,
+            vec![stats(1, Some("1"), Some("9"))],
+        )
+        .await
+        .unwrap();
+    pond.insert_data_file(
+        table.table_id,
+        "analytics/events/new.parquet",
+        10,
+        1000,
+        vec![stats(1, Some("1"), Some("9"))],
+    )
+    .await
+    .unwrap();
+
+    // The old file was replaced in the latest snapshot
+    let latest = pond.current_snapshot().await.unwrap().unwrap();
+    pond.dialect()
+        .query("UPDATE ducklake_data_file SET end_snapshot = $1 WHERE data_file_id = $2")
+        .bind(latest)
+        .bind(old_file)
+        .execute(pond.pool())
+        .await
+        .unwrap();
+
+    // Nothing is old enough yet
+    let cutoff = chrono::Utc::now() - chrono::Duration::hours(1);
+    let expiration = pond.expire_snapshots(cutoff, 1).await.unwrap();
+    assert!(expiration.expired_snapshots.is_empty());
+
+    let cutoff = chrono::Utc::now() + chrono::Duration::hours(1);
+    let expiration = pond.expire_snapshots(cutoff, 2).await.unwrap();
+    assert_eq!(expiration.expired_snapshots.len(), 2);
+    assert!(expiration.scheduled_files.is_empty());
+
+    let expiration = pond.expire_snapshots(cutoff, 1).await.unwrap();
+    assert_eq!(expiration.expired_snapshots, vec![latest - 1]);
+    assert_eq!(
+        expiration.scheduled_files,
+        vec!["analytics/events/old.parquet".to_string()]
+    );
+    assert_eq!(pond.current_snapshot().await.unwrap(), Some(latest));
+
+    // The table is still visible, the old file and its statistics are gone
+    assert_eq!(pond.list_tables(schema.schema_id).await.unwrap().len(), 1);
+    let files = pond.list_data_files(table.table_id).await.unwrap();
+    assert_eq!(files.len(), 1);
+    let stats_rows: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM ducklake_file_column_statistics")
+            .fetch_one(pond.pool())
+            .await
+            .unwrap();
+    assert_eq!(stats_rows, 1);
+
+    let scheduled = pond.scheduled_deletions().await.unwrap();
+    assert_eq!(scheduled.len(), 1);
+    assert_eq!(scheduled[0].data_file_id, old_file);
+    assert!(scheduled[0].path_is_relative);
+    assert!((chrono::Utc::now() - scheduled[0].schedule_start).num_minutes() < 5);
+
+    pond.remove_scheduled_deletion(old_file, &scheduled[0].path)
+        .await
+        .unwrap();
+    assert!(pond.scheduled_deletions().await.unwrap().is_empty());
+
+    // A path still used by a live row is not scheduled with an ended row
+    for _ in 0..2 {
+        pond.insert_data_file(
+            table.table_id,
+            "analytics/events/shared.parquet",
+            10,
+            1000,
+            vec![stats(1, Some("1"), Some("9"))],
+        )
+        .await
+        .unwrap();
+    }
+    let latest = pond.current_snapshot().await.unwrap().unwrap();
+    pond.dialect()
+        .query("UPDATE ducklake_data_file SET end_snapshot = $1 WHERE path = $2 AND begin_snapshot < $1")
+        .bind(latest)
+        .bind("shared.parquet")
+        .execute(pond.pool())
+        .await
+        .unwrap();
+    let expiration = pond.expire_snapshots(cutoff, 1).await.unwrap();
+    assert!(!expiration.expired_snapshots.is_empty());
+    assert!(expiration.scheduled_files.is_empty());
+    assert_eq!(pond.list_data_files(table.table_id).await.unwrap().len(), 2);
+}
+
+async fn history_and_diff(pond: DuckPond) {
+    let schema = pond.create_schema("analytics").await.unwrap();
+    let table = pond
+        .create_table(schema.schema_id, "events", columns())
+        .await
+        .unwrap();
+    let before_insert = pond.current_snapshot().await.unwrap().unwrap();
+    let old_file = pond
+        .insert_data_file(
+            table.table_id,
+            "analytics/events/a.parquet",
+            10,
+            1000,
+            vec![],
+        )
+        .await
+        .unwrap();
+    pond.insert_data_file(table.table_id, "analytics/events/b.parquet", 5, 500, vec![])
+        .await
+        .unwrap();
+    let latest = pond.current_snapshot().await.unwrap().unwrap();
+    pond.dialect()
+        .query("UPDATE ducklake_data_file SET end_snapshot = $1 WHERE data_file_id = $2")
+        .bind(latest)
+        .bind(old_file)
+        .execute(pond.pool())
+        .await
+        .unwrap();
+
+    let history = pond.list_snapshots().await.unwrap();
+    let changes: Vec<Vec<SnapshotChange>> = history.iter().map(|s| s.changes.clone()).collect();
+    assert_eq!(
+        changes,
+        vec![
+            vec![SnapshotChange::CreatedSchema {
+                schema: "analytics".to_string()
+            }],
+            vec![SnapshotChange::CreatedTable {
+                schema: "analytics".to_string(),
+                table: "events".to_string()
+            }],
+            vec![SnapshotChange::InsertedIntoTable {
+                table_id: table.table_id
+            }],
+            vec![SnapshotChange::InsertedIntoTable {
+                table_id: table.table_id
+            }],
+        ]
+    );
+    let info = pond.snapshot_info(latest).await.unwrap();
+    assert_eq!(info.snapshot_id, latest);
+    assert!(pond.snapshot_info(latest + 1).await.is_err());
+
+    // a.parquet was added and removed within the range, so it is left out
+    let diff = pond.diff(before_insert, latest).await.unwrap();
+    assert_eq!(diff.len(), 1);
+    assert_eq!(diff[0].table_id, table.table_id);
+    let added: Vec<&str> = diff[0]
+        .added_files
+        .iter()
+        .map(|f| f.path.as_str())
+        .collect();
+    assert_eq!(added, vec!["analytics/events/b.parquet"]);
+    assert!(diff[0].removed_files.is_empty());
+
+    let diff = pond.diff(latest - 1, latest).await.unwrap();
+    assert_eq!(diff[0].added_files.len(), 1);
+    assert_eq!(diff[0].removed_files.len(), 1);
+    assert_eq!(diff[0].removed_files[0].file_id, old_file);
+    assert_eq!(diff[0].removed_files[0].kind, FileKind::Data);
+    assert_eq!(diff[0].removed_files[0].snapshot_id, latest);
+
+    assert!(pond.diff(latest, before_insert).await.is_err());
+
+    let history = pond
+        .table_file_history(table.table_id, before_insert, latest)
+        .await
+        .unwrap();
+    let lifetimes: Vec<(i64, i64, Option<i64>)> = history
+        .data_files
+        .iter()
+        .map(|f| (f.row_id_start, f.begin_snapshot, f.end_snapshot))
+        .collect();
+    assert_eq!(
+        lifetimes,
+        vec![(0, latest - 1, Some(latest)), (10, latest, None)]
+    );
+    assert!(history.delete_files.is_empty());
+}
+
+async fn restore_snapshot(pond: DuckPond) {
+    let schema = pond.create_schema("analytics").await.unwrap();
+    let table = pond
+        .create_table(schema.schema_id, "events", columns())
+        .await
+        .unwrap();
+    let kept_file = pond
+        .insert_data_file(
+            table.table_id,
+            "analytics/events/a.parquet",
+            10,
+            1000,
+            vec![stats(1, Some("1"), Some("5"))],
+        )
+        .await
+        .unwrap();
+    let target = pond.current_snapshot().await.unwrap().unwrap();
+
+    // A bad batch, a sort order, a new table and the good file removed
+    pond.insert_data_file(
+        table.table_id,
+        "analytics/events/bad.parquet",
+        5,
+        500,
+        vec![stats(1, Some("90"), Some("99"))],
+    )
+    .await
+    .unwrap();
+    pond.set_sort_order(table.table_id, Some(&"id".parse().unwrap()))
+        .await
+        .unwrap();
+    pond.create_table(schema.schema_id, "scratch", columns())
+        .await
+        .unwrap();
+    let before_restore = pond.current_snapshot().await.unwrap().unwrap();
+    pond.dialect()
+        .query("UPDATE ducklake_data_file SET end_snapshot = $1 WHERE data_file_id = $2")
+        .bind(before_restore)
+        .bind(kept_file)
+        .execute(pond.pool())
+        .await
+        .unwrap();
+
+    let restored = pond.restore_table(table.table_id, target).await.unwrap();
+    assert_eq!(restored, before_restore + 1);
+    let files = pond.list_data_files(table.table_id).await.unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].data_file_path, "analytics/events/a.parquet");
+    assert_ne!(files[0].data_file_id, kept_file);
+    assert_eq!(files[0].row_id_start, 0);
+    assert!(pond.sort_order(table.table_id).await.unwrap().is_none());
+    assert_eq!(pond.list_tables(schema.schema_id).await.unwrap().len(), 2);
+
+    // History is preserved
+    let old_files = pond
+        .list_data_files_at_snapshot(table.table_id, before_restore - 1)
+        .await
+        .unwrap();
+    assert_eq!(old_files.len(), 2);
+    assert_eq!(
+        pond.snapshot_info(restored).await.unwrap().changes,
+        vec![SnapshotChange::AlteredTable {
+            table_id: table.table_id
+        }]
+    );
+
+    // Statistics follow the visible files; row IDs are not reused
+    let row = sqlx::query("SELECT record_count, next_row_id FROM ducklake_table_stats")
+        .fetch_one(pond.pool())
+        .await
+        .unwrap();
+    assert_eq!(row.get::<i64, _>("record_count"), 10);
+    assert_eq!(row.get::<i64, _>("next_row_id"), 15);
+    let max_value: String = sqlx::query_scalar("SELECT max_value FROM ducklake_table_column_stats")
+        .fetch_one(pond.pool())
+        .await
+        .unwrap();
+    assert_eq!(max_value, "5");
+
+    // The copies made by the table restore are newer than the target too
+    let restored = pond.restore(target).await.unwrap();
+    assert_eq!(
+        pond.snapshot_info(restored).await.unwrap().changes,
+        vec![
+            SnapshotChange::AlteredTable {
+                table_id: table.table_id
+            },
+            SnapshotChange::DroppedTable {
+                table_id: table.table_id + 1
+            },
+        ]
+    );
+    let tables = pond.list_tables(schema.schema_id).await.unwrap();
+    assert_eq!(tables.len(), 1);
+    assert_eq!(tables[0].table_name, "events");
+    assert_eq!(pond.list_data_files(table.table_id).await.unwrap().len(), 1);
+
+    assert!(pond.restore(before_restore + 100).await.is_err());
+    let current = pond.current_snapshot().await.unwrap().unwrap();
+    assert_eq!(pond.restore(current).await.unwrap(), current);
+}
+
+async fn tags_and_branches(pond: DuckPond) {
+    let schema = pond.create_schema("analytics").await.unwrap();
+    let table = pond
+        .create_table(schema.schema_id, "events", columns())
+        .await
+        .unwrap();
+    let tagged = pond.current_snapshot().await.unwrap().unwrap();
+    let tag = pond.create_tag("2026-Q3-close", None).await.unwrap();
+    assert_eq!(tag.snapshot_id, tagged);
+    assert!(pond.create_tag("2026-Q3-close", None).await.is_err());
+    assert!(pond
+        .create_tag("missing", Some(tagged + 100))
+        .await
+        .is_err());
+
+    pond.insert_data_file(
+        table.table_id,
+        "analytics/events/main.parquet",
+        10,
+        1000,
+        vec![stats(1, Some("1"), Some("9"))],
+    )
+    .await
+    .unwrap();
+    let at_tag = pond.at_tag("2026-Q3-close").await.unwrap();
+    assert!(at_tag
+        .list_data_files(table.table_id)
+        .await
+        .unwrap()
+        .is_empty());
+    let main_head = pond.current_snapshot().await.unwrap().unwrap();
+
+    // Writes on a branch are invisible to main and to other branches
+    let branch = pond.create_branch("experiment").await.unwrap();
+    assert_eq!(branch.base_snapshot, Some(main_head));
+    let experiment = pond.on_branch("experiment").await.unwrap();
+    experiment
+        .insert_data_file(
+            table.table_id,
+            "analytics/events/experiment.parquet",
+            5,
+            500,
+            vec![stats(1, Some("50"), Some("99"))],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        experiment.current_snapshot().await.unwrap(),
+        Some(main_head + 1)
+    );
+    assert_eq!(
+        experiment
+            .list_data_files(table.table_id)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(pond.current_snapshot().await.unwrap(), Some(main_head));
+    assert_eq!(pond.list_data_files(table.table_id).await.unwrap().len(), 1);
+    pond.create_branch("second").await.unwrap();
+    let second = pond.on_branch("second").await.unwrap();
+    assert_eq!(second.current_snapshot().await.unwrap(), Some(main_head));
+    assert_eq!(
+        second.list_data_files(table.table_id).await.unwrap().len(),
+        1
+    );
+
+    // Tagged and branch snapshots survive expiration
+    let cutoff = chrono::Utc::now() + chrono::Duration::hours(1);
+    let expiration = pond.expire_snapshots(cutoff, 1).await.unwrap();
+    assert!(!expiration.expired_snapshots.contains(&tagged));
+    assert!(!expiration.expired_snapshots.contains(&main_head));
+    assert!(!expiration.expired_snapshots.contains(&(main_head + 1)));
+
+    // Merging fast-forwards main
+    assert_eq!(
+        pond.merge_branch("experiment").await.unwrap(),
+        main_head + 1
+    );
+    assert_eq!(pond.current_snapshot().await.unwrap(), Some(main_head + 1));
+    assert_eq!(pond.list_data_files(table.table_id).await.unwrap().len(), 2);
+    assert!(pond
+        .branch_snapshots("experiment")
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Main keeps committing while the branch collects snapshots
+    experiment
+        .insert_data_file(
+            table.table_id,
+            "analytics/events/discarded.parquet",
+            5,
+            500,
+            vec![stats(1, Some("1"), Some("2"))],
+        )
+        .await
+        .unwrap();
+    let concurrent = pond.create_schema("concurrent").await.unwrap();
+    // A file main still reads, registered again on the branch
+    experiment
+        .insert_data_file(
+            table.table_id,
+            "analytics/events/main.parquet",
+            10,
+            1000,
+            vec![stats(1, Some("1"), Some("9"))],
+        )
+        .await
+        .unwrap();
+    assert_eq!(pond.current_snapshot().await.unwrap(), Some(main_head + 3));
+    assert_eq!(pond.list_data_files(table.table_id).await.unwrap().len(), 2);
+    assert_eq!(
+        experiment.current_snapshot().await.unwrap(),
+        Some(main_head + 4)
+    );
+    assert_eq!(
+        experiment
+            .list_data_files(table.table_id)
+            .await
+            .unwrap()
+            .len(),
+        4
+    );
+    assert!(!experiment
+        .list_schemas()
+        .await
+        .unwrap()
+        .iter()
+        .any(|s| s.schema_id == concurrent.schema_id));
+
+    // Main has moved past the branch's base, so it can only be dropped
+    assert!(matches!(
+        pond.merge_branch("experiment").await,
+        Err(DuckPondError::BranchConflict { .. })
+    ));
+    assert_eq!(pond.current_snapshot().await.unwrap(), Some(main_head + 3));
+
+    // Dropping a branch discards its unmerged snapshots and files
+    assert_eq!(
+        pond.drop_branch("experiment").await.unwrap(),
+        vec![main_head + 2, main_head + 4]
+    );
+    assert_eq!(pond.max_snapshot_id().await.unwrap(), main_head + 3);
+    assert_eq!(pond.list_data_files(table.table_id).await.unwrap().len(), 2);
+    let scheduled = pond.scheduled_deletions().await.unwrap();
+    assert_eq!(scheduled.len(), 1);
+    assert_eq!(scheduled[0].path, "analytics/events/discarded.parquet");
+    let row = sqlx::query("SELECT record_count FROM ducklake_table_stats")
+        .fetch_one(pond.pool())
+        .await
+        .unwrap();
+    assert_eq!(row.get::<i64, _>("record_count"), 15);
+
+    // A branch cannot change what main has changed since its base
+    pond.set_tag(table.table_id, "owner", "data").await.unwrap();
+    pond.create_branch("rival").await.unwrap();
+    let rival = pond.on_branch("rival").await.unwrap();
+    pond.set_tag(table.table_id, "owner", "platform")
+        .await
+        .unwrap();
+    let rival_head = rival.current_snapshot().await.unwrap();
+    assert!(matches!(
+        rival.set_tag(table.table_id, "owner", "ml").await,
+        Err(DuckPondError::BranchConflict { .. })
+    ));
+    assert_eq!(rival.current_snapshot().await.unwrap(), rival_head);
+    assert_eq!(
+        rival.list_tags(table.table_id).await.unwrap()[0].value,
+        "data"
+    );
+    assert_eq!(
+        pond.list_tags(table.table_id).await.unwrap()[0].value,
+        "platform"
+    );
+    assert!(pond.drop_branch("rival").await.unwrap().is_empty());
+    assert!(pond.drop_branch("second").await.unwrap().is_empty());
+
+    let refs = pond.list_refs().await.unwrap();
+    assert_eq!(refs.len(), 1);
+    assert_eq!(refs[0].kind, RefKind::Tag);
+    pond.drop_tag("2026-Q3-close").await.unwrap();
+    assert!(pond.at_tag("2026-Q3-close").await.is_err());
+}
+
+async fn views(pond: DuckPond) {
+    let schema = pond.create_schema("analytics").await.unwrap();
+    pond.create_table(schema.schema_id, "events", columns())
+        .await
+        .unwrap();
+    let view = pond
+        .create_view(
+            schema.schema_id,
+            "recent_events",
+            "duckdb",
+            "SELECT id, name FROM analytics.events WHERE id > 100",
+            vec!["event_id".to_string(), "event \"name\"".to_string()],
+        )
+        .await
+        .unwrap();
+    let created = pond.current_snapshot().await.unwrap().unwrap();
+    assert!(matches!(
+        pond.create_view(schema.schema_id, "events", "duckdb", "SELECT 1", vec![])
+            .await,
+        Err(DuckPondError::NameAlreadyExists { .. })
+    ));
+
+    let stored = pond
+        .get_view(schema.schema_id, "recent_events")
+        .await
+        .unwrap();
+    assert_eq!(stored.view_id, view.view_id);
+    assert_eq!(stored.sql, view.sql);
+    assert_eq!(stored.dialect, "duckdb");
+    assert_eq!(stored.column_aliases, view.column_aliases);
+    assert_eq!(
+        pond.snapshot_info(created).await.unwrap().changes,
+        vec![SnapshotChange::CreatedView {
+            schema: "analytics".to_string(),
+            view: "recent_events".to_string(),
+        }]
+    );
+
+    pond.drop_view(schema.schema_id, "recent_events")
+        .await
+        .unwrap();
+    assert!(pond.list_views(schema.schema_id).await.unwrap().is_empty());
+    assert!(matches!(
+        pond.drop_view(schema.schema_id, "recent_events").await,
+        Err(DuckPondError::ViewNotFound { .. })
+    ));
+    let at_created = pond.at_snapshot(created);
+    assert_eq!(
+        at_created.list_views(schema.schema_id).await.unwrap()[0].view_name,
+        "recent_events"
+    );
+
+    // Restoring brings the view back
+    let restored = pond.restore(created).await.unwrap();
+    assert_eq!(pond.list_views(schema.schema_id).await.unwrap().len(), 1);
+    assert_eq!(
+        pond.snapshot_info(restored).await.unwrap().changes,
+        vec![SnapshotChange::CreatedView {
+            schema: "analytics".to_string(),
+            view: "recent_events".to_string(),
+        }]
+    );
+}
+
+async fn object_and_column_tags(pond: DuckPond) {
+    let schema = pond.create_schema("analytics").await.unwrap();
+    let table = pond
+        .create_table(schema.schema_id, "users", columns())
+        .await
+        .unwrap();
+    // Schemas, tables and views share one ID space
+    assert_ne!(schema.schema_id, table.table_id);
+
+    pond.set_tag(table.table_id, "owner", "data-team")
+        .await
+        .unwrap();
+    pond.set_tag(schema.schema_id, "comment", "Product analytics")
+        .await
+        .unwrap();
+    pond.set_column_tag(table.table_id, 2, "pii", "true")
+        .await
+        .unwrap();
+    let tagged = pond.current_snapshot().await.unwrap().unwrap();
+
+    // Setting the same value again does not commit a snapshot
+    pond.set_tag(table.table_id, "owner", "data-team")
+        .await
+        .unwrap();
+    assert_eq!(pond.current_snapshot().await.unwrap(), Some(tagged));
+
+    pond.set_tag(table.table_id, "owner", "platform-team")
+        .await
+        .unwrap();
+    // The sort order is kept as a reserved tag, which is not listed
+    pond.set_sort_order(table.table_id, Some(&"id".parse().unwrap()))
+        .await
+        .unwrap();
+    pond.unset_column_tag(table.table_id, 2, "pii")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        pond.list_tags(table.table_id).await.unwrap(),
+        vec![ObjectTag {
+            key: "owner".to_string(),
+            value: "platform-team".to_string(),
+        }]
+    );
+    assert_eq!(pond.list_tags(schema.schema_id).await.unwrap().len(), 1);
+    assert!(pond
+        .list_column_tags(table.table_id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Earlier values stay visible through time travel
+    let at_tagged = pond.at_snapshot(tagged);
+    assert_eq!(
+        at_tagged.list_tags(table.table_id).await.unwrap()[0].value,
+        "data-team"
+    );
+    assert_eq!(
+        at_tagged.list_column_tags(table.table_id).await.unwrap(),
+        vec![ColumnTag {
+            column_id: 2,
+            key: "pii".to_string(),
+            value: "true".to_string(),
+        }]
+    );
+
+    assert!(matches!(
+        pond.unset_tag(table.table_id, "missing").await,
+        Err(DuckPondError::TagNotFound { .. })
+    ));
+    assert!(matches!(
+        pond.set_tag(table.table_id + 100, "owner", "nobody").await,
+        Err(DuckPondError::ObjectNotFound { .. })
+    ));
+    assert!(matches!(
+        pond.set_tag(table.table_id, "duckpond.sort_order", "id")
+            .await,
+        Err(DuckPondError::InvalidTag { .. })
+    ));
+    assert!(pond
+        .set_column_tag(table.table_id, 99, "pii", "true")
+        .await
+        .is_err());
+}
+
+async fn table_stats(pond: &DuckPond, table_id: i64) -> (i64, i64, i64) {
+    let row = sqlx::query(&format!(
+        "SELECT record_count, next_row_id, file_size_bytes FROM ducklake_table_stats \
+         WHERE table_id = {}",
+        table_id
+    ))
+    .fetch_one(pond.pool())
+    .await
+    .unwrap();
+    (
+        row.get("record_count"),
+        row.get("next_row_id"),
+        row.get("file_size_bytes"),
+    )
+}
+
+async fn data_inlining(pond: DuckPond) {
+    assert_eq!(pond.data_inlining_row_limit().await.unwrap(), 0);
+    pond.set_data_inlining_row_limit(10).await.unwrap();
+    assert_eq!(pond.data_inlining_row_limit().await.unwrap(), 10);
+
+    let schema = pond.create_schema("analytics").await.unwrap();
+    let table = pond
+        .create_table(schema.schema_id, "events", columns())
+        .await
+        .unwrap();
+    let row = |id: i64, name: Option<&str>| {
+        vec![
+            InlinedValue::Integer(id),
+            name.map_or(InlinedValue::Null, |n| InlinedValue::Text(n.to_string())),
+        ]
+    };
+
+    // Inlined rows are allocated row IDs like rows in data files
+    let first = pond
+        .insert_inlined_rows(table.table_id, &[row(1, Some("a")), row(2, None)])
+        .await
+        .unwrap();
+    assert_eq!(first, 0);
+    let second = pond
+        .insert_inlined_rows(table.table_id, &[row(3, Some("c"))])
+        .await
+        .unwrap();
+    assert_eq!(second, 2);
+    assert!(matches!(
+        pond.insert_inlined_rows(
+            table.table_id,
+            &[vec![InlinedValue::Null, InlinedValue::Null]]
+        )
+        .await,
+        Err(DuckPondError::InvalidInlinedData { .. })
+    ));
+    assert!(matches!(
+        pond.insert_inlined_rows(table.table_id, &[vec![InlinedValue::Integer(1)]])
+            .await,
+        Err(DuckPondError::InvalidInlinedData { .. })
+    ));
+
+    let rows = pond.list_inlined_rows(table.table_id).await.unwrap();
+    let row_ids: Vec<i64> = rows.iter().map(|r| r.row_id).collect();
+    assert_eq!(row_ids, vec![0, 1, 2]);
+    assert_eq!(rows[0].values, row(1, Some("a")));
+    assert_eq!(rows[1].values, row(2, None));
+    assert_eq!(rows[2].begin_snapshot, rows[0].begin_snapshot + 1);
+    assert_eq!(table_stats(&pond, table.table_id).await, (3, 3, 0));
+
+    pond.insert_data_file(
+        table.table_id,
+        "analytics/events/a.parquet",
+        10,
+        1000,
+        vec![stats(1, Some("10"), Some("19"))],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        pond.list_data_files(table.table_id).await.unwrap()[0].row_id_start,
+        3
+    );
+    let before_flush = pond.current_snapshot().await.unwrap().unwrap();
+
+    // Flushing ends the inlined rows and adds a file holding them
+    let flushed = FlushedDataFile {
+        path: "analytics/events/flushed.parquet".to_string(),
+        row_id_start: 0,
+        record_count: 3,
+        file_size_bytes: 300,
+        column_statistics: vec![stats(1, Some("1"), Some("3"))],
+    };
+    let data_file_ids = pond
+        .flush_inlined_rows(table.table_id, std::slice::from_ref(&flushed))
+        .await
+        .unwrap();
+    assert_eq!(data_file_ids.len(), 1);
+    assert!(pond
+        .list_inlined_rows(table.table_id)
+        .await
+        .unwrap()
+        .is_empty());
+    let files = pond.list_data_files(table.table_id).await.unwrap();
+    assert!(files
+        .iter()
+        .any(|f| f.data_file_path == flushed.path && f.row_id_start == 0));
+    assert_eq!(table_stats(&pond, table.table_id).await, (13, 13, 1300));
+    assert_eq!(
+        pond.at_snapshot(before_flush)
+            .list_inlined_rows(table.table_id)
+            .await
+            .unwrap()
+            .len(),
+        3
+    );
+    assert!(matches!(
+        pond.flush_inlined_rows(table.table_id, &[flushed]).await,
+        Err(DuckPondError::TransactionConflict { .. })
+    ));
+
+    // A restore brings inlined rows back
+    pond.restore_table(table.table_id, before_flush)
+        .await
+        .unwrap();
+    assert_eq!(
+        pond.list_inlined_rows(table.table_id).await.unwrap().len(),
+        3
+    );
+    assert_eq!(pond.list_data_files(table.table_id).await.unwrap().len(), 1);
+    assert_eq!(table_stats(&pond, table.table_id).await.0, 13);
+
+    // Dropping a branch drops the inlined data tables it created
+    let other = pond
+        .create_table(schema.schema_id, "other", columns())
+        .await
+        .unwrap();
+    pond.create_branch("experiment").await.unwrap();
+    let experiment = pond.on_branch("experiment").await.unwrap();
+    experiment
+        .insert_inlined_rows(other.table_id, &[row(1, None)])
+        .await
+        .unwrap();
+    assert!(pond
+        .list_inlined_rows(other.table_id)
+        .await
+        .unwrap()
+        .is_empty());
+    pond.drop_branch("experiment").await.unwrap();
+    pond.insert_inlined_rows(other.table_id, &[row(2, None)])
+        .await
+        .unwrap();
+    let rows = pond.list_inlined_rows(other.table_id).await.unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].values, row(2, None));
+}
+
+async fn nested_columns(pond: DuckPond) {
+    let column = |name: &str, data_type: &str, children: Vec<ColumnDefinition>| ColumnDefinition {
+        column_id: None,
+        name: name.to_string(),
+        data_type: data_type.to_string(),
+        nullable: true,
+        children,
+    };
+    let payload = column(
+        "payload",
+        "struct",
+        vec![
+            column("user_id", "int64", vec![]),
+            column("tags", "list", vec![column("element", "varchar", vec![])]),
+            column(
+                "attributes",
+                "map",
+                vec![
+                    column("key", "varchar", vec![]),
+                    column("value", "float64", vec![]),
+                ],
+            ),
+        ],
+    );
+
+    let schema = pond.create_schema("analytics").await.unwrap();
+    let table = pond
+        .create_table(
+            schema.schema_id,
+            "events",
+            vec![
+                column("id", "int64", vec![]),
+                payload,
+                column("source", "varchar", vec![]),
+            ],
+        )
+        .await
+        .unwrap();
+
+    // Child columns get their own IDs, depth first, and nest under their parent
+    fn shape(columns: &[duckpond_core::models::ColumnInfo]) -> Vec<(i64, String, usize)> {
+        columns
+            .iter()
+            .flat_map(|c| {
+                std::iter::once((c.column_id, c.column_name.clone(), c.children.len()))
+                    .chain(shape(&c.children))
+            })
+            .collect()
+    }
+    let structure = pond.table_structure(table.table_id).await.unwrap();
+    assert_eq!(structure.len(), 3);
+    assert_eq!(
+        shape(&structure),
+        vec![
+            (1, "id".to_string(), 0),
+            (2, "payload".to_string(), 3),
+            (3, "user_id".to_string(), 0),
+            (4, "tags".to_string(), 1),
+            (5, "element".to_string(), 0),
+            (6, "attributes".to_string(), 2),
+            (7, "key".to_string(), 0),
+            (8, "value".to_string(), 0),
+            (9, "source".to_string(), 0),
+        ]
+    );
+
+    // Nested types need the right number of children
+    for invalid in [
+        column("tags", "list", vec![]),
+        column("m", "map", vec![column("key", "varchar", vec![])]),
+        column("id", "int64", vec![column("x", "int64", vec![])]),
+        column(
+            "s",
+            "struct",
+            vec![column("a", "int64", vec![]), column("a", "int64", vec![])],
+        ),
+    ] {
+        assert!(matches!(
+            pond.create_table(schema.schema_id, "invalid", vec![invalid])
+                .await,
+            Err(DuckPondError::InvalidColumnDefinition { .. })
+        ));
+    }
+    assert_eq!(pond.list_tables(schema.schema_id).await.unwrap().len(), 1);
+}
+
+async fn column_types(pond: DuckPond) {
+    let schema = pond.create_schema("analytics").await.unwrap();
+    let column = |name: &str, data_type: &str| ColumnDefinition {
+        column_id: None,
+        name: name.to_string(),
+        data_type: data_type.to_string(),
+        nullable: true,
+        children: Vec::new(),
+    };
+
+    // Types are stored by their DuckLake names
+    let table = pond
+        .create_table(
+            schema.schema_id,
+            "events",
+            vec![
+                column("id", "BIGINT"),
+                column("name", "string"),
+                column("amount", "Decimal(18, 2)"),
+                column("at", "timestamptz"),
+            ],
+        )
+        .await
+        .unwrap();
+    let types: Vec<String> = pond
+        .table_structure(table.table_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|c| c.column_type)
+        .collect();
+    assert_eq!(
+        types,
+        vec!["int64", "varchar", "decimal(18,2)", "timestamptz"]
+    );
+
+    assert!(matches!(
+        pond.create_table(schema.schema_id, "invalid", vec![column("id", "bigserial")])
+            .await,
+        Err(DuckPondError::InvalidColumnDefinition { .. })
+    ));
+}
+
+async fn add_data_files(pond: DuckPond) {
+    let schema = pond.create_schema("analytics").await.unwrap();
+    let table = pond
+        .create_table(schema.schema_id, "events", columns())
+        .await
+        .unwrap();
+    let file = |path: &str, path_is_relative: bool| NewDataFile {
+        path: path.to_string(),
+        path_is_relative,
+        record_count: 10,
+        file_size_bytes: 2048,
+        footer_size: Some(512),
+        column_statistics: vec![stats(1, Some("1"), Some("10")), stats(2, None, None)],
+    };
+
+    // All files are registered in one snapshot, with consecutive row IDs
+    let before = pond.current_snapshot().await.unwrap().unwrap();
+    let ids = pond
+        .add_data_files(
+            table.table_id,
+            &[
+                file("analytics/events/part-0.parquet", true),
+                file("/data/part-1.parquet", false),
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(ids.len(), 2);
+    assert_eq!(pond.current_snapshot().await.unwrap(), Some(before + 1));
+
+    let files = pond.list_data_files(table.table_id).await.unwrap();
+    assert_eq!(files.len(), 2);
+    assert!(files[0].path_is_relative);
+    assert!(!files[1].path_is_relative);
+    assert_eq!(files[0].data_file_path, "analytics/events/part-0.parquet");
+    assert_eq!(files[0].row_id_start, 0);
+    assert_eq!(files[1].row_id_start, 10);
+    let (record_count, next_row_id, _) = table_stats(&pond, table.table_id).await;
+    assert_eq!((record_count, next_row_id), (20, 20));
+
+    // The catalog stores relative paths against the table's path
+    let stored: String =
+        sqlx::query_scalar("SELECT path FROM ducklake_data_file ORDER BY data_file_id")
+            .fetch_one(pond.pool())
+            .await
+            .unwrap();
+    assert_eq!(stored, "part-0.parquet");
+
+    // A path can only be registered once
+    assert!(matches!(
+        pond.add_data_files(
+            table.table_id,
+            &[file("analytics/events/part-0.parquet", true)]
+        )
+        .await,
+        Err(DuckPondError::InvalidDataFile { .. })
+    ));
+    assert!(matches!(
+        pond.add_data_files(
+            table.table_id,
+            &[
+                file("analytics/events/part-2.parquet", true),
+                file("analytics/events/part-2.parquet", true)
+            ],
+        )
+        .await,
+        Err(DuckPondError::InvalidDataFile { .. })
+    ));
+
+    // Relative paths outside the table's path need the catalog's data path
+    // to be stored absolute
+    assert!(matches!(
+        pond.add_data_files(table.table_id, &[file("elsewhere/part-3.parquet", true)])
+            .await,
+        Err(DuckPondError::InvalidDataFile { .. })
+    ));
+    assert_eq!(pond.list_data_files(table.table_id).await.unwrap().len(), 2);
+}
+
+async fn drop_and_alter_tables(pond: DuckPond) {
+    let schema = pond.create_schema("analytics").await.unwrap();
+    let table = pond
+        .create_table(schema.schema_id, "events", columns())
+        .await
+        .unwrap();
+    pond.insert_data_file(
+        table.table_id,
+        "analytics/events/a.parquet",
+        10,
+        1000,
+        vec![stats(1, Some("1"), Some("5")), stats(2, None, None)],
+    )
+    .await
+    .unwrap();
+    let stats = pond.table_stats(table.table_id).await.unwrap().unwrap();
+    assert_eq!((stats.record_count, stats.file_size_bytes), (10, 1000));
+    let column_stats = pond.table_column_stats(table.table_id).await.unwrap();
+    assert_eq!(column_stats.len(), 2);
+    assert_eq!(column_stats[0].max_value.as_deref(), Some("5"));
+    assert!(!column_stats[0].contains_null);
+    assert!(column_stats[1].contains_null);
+
+    // Renaming keeps the table ID; the old name stays at earlier snapshots
+    let before_rename = pond.current_snapshot().await.unwrap().unwrap();
+    pond.create_table(schema.schema_id, "sessions", columns())
+        .await
+        .unwrap();
+    assert!(matches!(
+        pond.rename_table(schema.schema_id, "events", "sessions")
+            .await,
+        Err(DuckPondError::NameAlreadyExists { .. })
+    ));
+    let renamed = pond
+        .rename_table(schema.schema_id, "events", "clicks")
+        .await
+        .unwrap();
+    assert_eq!(renamed.table_id, table.table_id);
+    let names = |tables: Vec<TableInfo>| {
+        let mut names: Vec<_> = tables.into_iter().map(|t| t.table_name).collect();
+        names.sort();
+        names
+    };
+    assert_eq!(
+        names(pond.list_tables(schema.schema_id).await.unwrap()),
+        ["clicks", "sessions"]
+    );
+    assert_eq!(
+        names(
+            pond.list_tables_at_snapshot(schema.schema_id, before_rename)
+                .await
+                .unwrap()
+        ),
+        ["events"]
+    );
+    assert_eq!(pond.list_data_files(table.table_id).await.unwrap().len(), 1);
+
+    // Added columns follow the existing ones, nested fields included
+    pond.add_column(
+        table.table_id,
+        ColumnDefinition {
+            column_id: None,
+            name: "tags".to_string(),
+            data_type: "list".to_string(),
+            nullable: true,
+            children: vec![ColumnDefinition {
+                column_id: None,
+                name: "element".to_string(),
+                data_type: "varchar".to_string(),
+                nullable: true,
+                children: Vec::new(),
+            }],
+        },
+    )
+    .await
+    .unwrap();
+    let structure = pond.table_structure(table.table_id).await.unwrap();
+    assert_eq!(structure.len(), 3);
+    assert_eq!(structure[2].column_name, "tags");
+    assert_eq!(structure[2].column_id, 3);
+    assert_eq!(structure[2].children[0].column_id, 4);
+    assert!(matches!(
+        pond.add_column(table.table_id, columns().remove(1)).await,
+        Err(DuckPondError::InvalidColumnDefinition { .. })
+    ));
+    assert!(matches!(
+        pond.add_column(
+            table.table_id,
+            ColumnDefinition {
+                name: "required".to_string(),
+                ..columns().remove(0)
+            }
+        )
+        .await,
+        Err(DuckPondError::InvalidColumnDefinition { .. })
+    ));
+
+    // Dropping ends the table with its columns and files
+    assert!(matches!(
+        pond.drop_schema(schema.schema_id).await,
+        Err(DuckPondError::SchemaNotEmpty { .. })
+    ));
+    let before_drop = pond.current_snapshot().await.unwrap().unwrap();
+    pond.drop_table(schema.schema_id, "clicks").await.unwrap();
+    pond.drop_table(schema.schema_id, "sessions").await.unwrap();
+    assert!(pond.list_tables(schema.schema_id).await.unwrap().is_empty());
+    assert!(pond
+        .table_structure(table.table_id)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(pond
+        .list_data_files(table.table_id)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        pond.list_data_files_at_snapshot(table.table_id, before_drop)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(matches!(
+        pond.drop_table(schema.schema_id, "clicks").await,
+        Err(DuckPondError::TableNotFound { .. })
+    ));
+    let dropped = pond.current_snapshot().await.unwrap().unwrap();
+    assert_eq!(
+        pond.snapshot_info(dropped).await.unwrap().changes,
+        vec![SnapshotChange::DroppedTable {
+            table_id: table.table_id + 1
+        }]
+    );
+
+    pond.drop_schema(schema.schema_id).await.unwrap();
+    assert!(pond.list_schemas().await.unwrap().is_empty());
+    assert_eq!(
+        pond.list_schemas_at_snapshot(before_drop)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
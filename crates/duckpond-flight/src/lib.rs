@@ -0,0 +1,635 @@
+//! Arrow Flight SQL server for DuckPond lakehouses
+//!
+//! Serves the catalog's metadata (catalogs, schemas, tables and their Arrow
+//! schemas), scans of single tables (see [`query`]) and bulk ingestion, so
+//! that Flight SQL clients such as the ADBC drivers and JDBC can read from
+//! and write to a lakehouse.
+
+// `tonic::Status` is the error type of every Flight SQL handler
+#![allow(clippy::result_large_err)]
+
+pub mod query;
+
+use arrow::array::RecordBatch;
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::ipc::writer::IpcWriteOptions;
+use arrow_flight::decode::FlightRecordBatchStream;
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::error::FlightError;
+use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
+use arrow_flight::sql::metadata::{SqlInfoData, SqlInfoDataBuilder};
+use arrow_flight::sql::server::{FlightSqlService, PeekableFlightDataStream};
+use arrow_flight::sql::{
+    ActionClosePreparedStatementRequest, ActionCreatePreparedStatementRequest,
+    ActionCreatePreparedStatementResult, Any, CommandGetCatalogs, CommandGetDbSchemas,
+    CommandGetSqlInfo, CommandGetTableTypes, CommandGetTables, CommandPreparedStatementQuery,
+    CommandStatementIngest, CommandStatementQuery, ProstMessageExt, SqlInfo, TableExistsOption,
+    TableNotExistOption, TicketStatementQuery,
+};
+use arrow_flight::{
+    Action, FlightDescriptor, FlightEndpoint, FlightInfo, HandshakeRequest, HandshakeResponse,
+    IpcMessage, SchemaAsIpc, Ticket,
+};
+use duckpond::error::DuckPondError;
+use duckpond::{Lakehouse, DEFAULT_SCHEMA};
+use futures::{stream, Stream, TryStreamExt};
+use prost::Message;
+use query::ScanQuery;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock};
+use tonic::{Request, Response, Status, Streaming};
+
+/// The name the lakehouse is served under as a Flight SQL catalog
+pub const CATALOG_NAME: &str = "duckpond";
+
+/// The only table type
+const TABLE_TYPE: &str = "TABLE";
+
+/// Serve a lakehouse over Arrow Flight SQL until the server fails
+pub async fn serve(
+    lakehouse: Arc<Lakehouse>,
+    addr: SocketAddr,
+) -> Result<(), tonic::transport::Error> {
+    tracing::info!("Serving Arrow Flight SQL on {}", addr);
+    tonic::transport::Server::builder()
+        .add_service(FlightSqlServer::new(lakehouse).into_service())
+        .serve(addr)
+        .await
+}
+
+/// A [`FlightSqlService`] backed by a lakehouse
+///
+/// Statements are scans of one table (see [`ScanQuery`]); their handles,
+/// including those of prepared statements, are the SQL itself, so the server
+/// keeps no state between requests. There is no authentication.
+#[derive(Clone)]
+pub struct FlightSqlServer {
+    lakehouse: Arc<Lakehouse>,
+}
+
+impl FlightSqlServer {
+    pub fn new(lakehouse: Arc<Lakehouse>) -> Self {
+        Self { lakehouse }
+    }
+
+    pub fn into_service(self) -> FlightServiceServer<Self> {
+        FlightServiceServer::new(self)
+    }
+
+    /// Parse a statement and check it against the catalog
+    async fn plan(&self, sql: &str) -> Result<(ScanQuery, SchemaRef), Status> {
+        let query = ScanQuery::parse(sql).map_err(|e| Status::invalid_argument(e.to_string()))?;
+        check_catalog(query.catalog.as_deref())?;
+        let table_schema = self
+            .lakehouse
+            .table_schema(&query.schema, &query.table)
+            .await
+            .map_err(status)?;
+        if let Some(filter) = &query.filter {
+            filter
+                .evaluate(&RecordBatch::new_empty(table_schema.clone()))
+                .map_err(status)?;
+        }
+        let schema = match &query.columns {
+            Some(columns) => Arc::new(
+                table_schema
+                    .project(&column_indices(&table_schema, columns)?)
+                    .map_err(|e| Status::invalid_argument(e.to_string()))?,
+            ),
+            None => table_schema,
+        };
+        Ok((query, schema))
+    }
+
+    /// Run a statement, streaming its rows as Flight data
+    async fn execute(&self, sql: &str) -> Result<Response<DoGetStream>, Status> {
+        let (query, schema) = self.plan(sql).await?;
+        let (table_schema, batches) = self
+            .lakehouse
+            .scan_table(&query.schema, &query.table, query.filter.as_ref())
+            .await
+            .map_err(status)?;
+        let indices = match &query.columns {
+            Some(columns) => Some(column_indices(&table_schema, columns)?),
+            None => None,
+        };
+
+        let mut remaining = query.limit.unwrap_or(usize::MAX);
+        let mut rows = Vec::new();
+        for batch in batches {
+            if remaining == 0 {
+                break;
+            }
+            let batch = match &indices {
+                Some(indices) => batch
+                    .project(indices)
+                    .map_err(|e| Status::internal(e.to_string()))?,
+                None => batch,
+            };
+            let batch = batch.slice(0, batch.num_rows().min(remaining));
+            remaining -= batch.num_rows();
+            rows.push(Ok(batch));
+        }
+
+        let stream = FlightDataEncoderBuilder::new()
+            .with_schema(schema)
+            .build(stream::iter(rows))
+            .map_err(Status::from);
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+type DoGetStream = <FlightSqlServer as FlightService>::DoGetStream;
+
+#[tonic::async_trait]
+impl FlightSqlService for FlightSqlServer {
+    type FlightService = Self;
+
+    async fn do_handshake(
+        &self,
+        _request: Request<Streaming<HandshakeRequest>>,
+    ) -> Result<
+        Response<Pin<Box<dyn Stream<Item = Result<HandshakeResponse, Status>> + Send>>>,
+        Status,
+    > {
+        let response = HandshakeResponse::default();
+        Ok(Response::new(Box::pin(stream::once(async {
+            Ok(response)
+        }))))
+    }
+
+    async fn get_flight_info_statement(
+        &self,
+        query: CommandStatementQuery,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let (_, schema) = self.plan(&query.query).await?;
+        let ticket = TicketStatementQuery {
+            statement_handle: query.query.into_bytes().into(),
+        };
+        flight_info(&schema, ticket.as_any(), request)
+    }
+
+    async fn get_flight_info_prepared_statement(
+        &self,
+        query: CommandPreparedStatementQuery,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let (_, schema) = self
+            .plan(&handle_sql(&query.prepared_statement_handle)?)
+            .await?;
+        flight_info(&schema, query.as_any(), request)
+    }
+
+    async fn get_flight_info_catalogs(
+        &self,
+        query: CommandGetCatalogs,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let schema = query.into_builder().schema();
+        flight_info(&schema, query.as_any(), request)
+    }
+
+    async fn get_flight_info_schemas(
+        &self,
+        query: CommandGetDbSchemas,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let schema = query.clone().into_builder().schema();
+        flight_info(&schema, query.as_any(), request)
+    }
+
+    async fn get_flight_info_tables(
+        &self,
+        query: CommandGetTables,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let schema = query.clone().into_builder().schema();
+        flight_info(&schema, query.as_any(), request)
+    }
+
+    async fn get_flight_info_table_types(
+        &self,
+        query: CommandGetTableTypes,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let schema = query.into_builder().schema();
+        flight_info(&schema, query.as_any(), request)
+    }
+
+    async fn get_flight_info_sql_info(
+        &self,
+        query: CommandGetSqlInfo,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let schema = query.clone().into_builder(sql_info()).schema();
+        flight_info(&schema, query.as_any(), request)
+    }
+
+    async fn do_get_statement(
+        &self,
+        ticket: TicketStatementQuery,
+        _request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        self.execute(&handle_sql(&ticket.statement_handle)?).await
+    }
+
+    async fn do_get_prepared_statement(
+        &self,
+        query: CommandPreparedStatementQuery,
+        _request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        self.execute(&handle_sql(&query.prepared_statement_handle)?)
+            .await
+    }
+
+    async fn do_get_catalogs(
+        &self,
+        query: CommandGetCatalogs,
+        _request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        let mut builder = query.into_builder();
+        builder.append(CATALOG_NAME);
+        let schema = builder.schema();
+        batch_stream(schema, builder.build())
+    }
+
+    async fn do_get_schemas(
+        &self,
+        query: CommandGetDbSchemas,
+        _request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        let mut builder = query.into_builder();
+        for schema_name in self.lakehouse.list_schemas().await.map_err(status)? {
+            builder.append(CATALOG_NAME, schema_name);
+        }
+        let schema = builder.schema();
+        batch_stream(schema, builder.build())
+    }
+
+    async fn do_get_tables(
+        &self,
+        query: CommandGetTables,
+        _request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        let include_schema = query.include_schema;
+        let mut builder = query.into_builder();
+        for schema_name in self.lakehouse.list_schemas().await.map_err(status)? {
+            for table_name in self
+                .lakehouse
+                .list_tables(&schema_name)
+                .await
+                .map_err(status)?
+            {
+                let table_schema = if include_schema {
+                    self.lakehouse
+                        .table_schema(&schema_name, &table_name)
+                        .await
+                        .map_err(status)?
+                } else {
+                    Arc::new(Schema::empty())
+                };
+                builder
+                    .append(
+                        CATALOG_NAME,
+                        &schema_name,
+                        &table_name,
+                        TABLE_TYPE,
+                        &table_schema,
+                    )
+                    .map_err(Status::from)?;
+            }
+        }
+        let schema = builder.schema();
+        batch_stream(schema, builder.build())
+    }
+
+    async fn do_get_table_types(
+        &self,
+        query: CommandGetTableTypes,
+        _request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        let mut builder = query.into_builder();
+        builder.append(TABLE_TYPE);
+        let schema = builder.schema();
+        batch_stream(schema, builder.build())
+    }
+
+    async fn do_get_sql_info(
+        &self,
+        query: CommandGetSqlInfo,
+        _request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        let builder = query.into_builder(sql_info());
+        let schema = builder.schema();
+        batch_stream(schema, builder.build())
+    }
+
+    async fn do_put_statement_ingest(
+        &self,
+        command: CommandStatementIngest,
+        request: Request<PeekableFlightDataStream>,
+    ) -> Result<i64, Status> {
+        check_catalog(command.catalog.as_deref())?;
+        if command.temporary {
+            return Err(Status::unimplemented("Temporary tables are not supported"));
+        }
+        if command.transaction_id.is_some() {
+            return Err(Status::unimplemented("Transactions are not supported"));
+        }
+        let schema_name = command.schema.as_deref().unwrap_or(DEFAULT_SCHEMA);
+        let table_name = &command.table;
+
+        let mut stream =
+            FlightRecordBatchStream::new_from_flight_data(request.into_inner().map_err(Into::into));
+        let mut batches = Vec::new();
+        while let Some(batch) = stream.try_next().await.map_err(Status::from)? {
+            batches.push(batch);
+        }
+
+        let options = command.table_definition_options.unwrap_or_default();
+        let exists = self
+            .lakehouse
+            .list_tables(schema_name)
+            .await
+            .map_err(status)?
+            .iter()
+            .any(|table| table == table_name);
+        if exists {
+            match TableExistsOption::try_from(options.if_exists) {
+                Ok(TableExistsOption::Fail) => {
+                    return Err(Status::already_exists(format!(
+                        "Table '{}.{}' already exists",
+                        schema_name, table_name
+                    )))
+                }
+                Ok(TableExistsOption::Replace) => {
+                    return Err(Status::unimplemented("Replacing tables is not supported"))
+                }
+                _ => {}
+            }
+        } else if options.if_not_exist == TableNotExistOption::Create as i32 {
+            let Some(schema) = stream.schema() else {
+                return Err(Status::invalid_argument(
+                    "Cannot create a table without a schema",
+                ));
+            };
+            self.lakehouse
+                .create_table_from_arrow_schema(schema_name, table_name, schema)
+                .await
+                .map_err(status)?;
+        } else {
+            return Err(Status::not_found(format!(
+                "Table '{}.{}' not found",
+                schema_name, table_name
+            )));
+        }
+
+        let rows = batches.iter().map(RecordBatch::num_rows).sum::<usize>();
+        if rows > 0 {
+            self.lakehouse
+                .write_to_table(schema_name, table_name, batches)
+                .await
+                .map_err(status)?;
+        }
+        Ok(rows as i64)
+    }
+
+    async fn do_action_create_prepared_statement(
+        &self,
+        query: ActionCreatePreparedStatementRequest,
+        _request: Request<Action>,
+    ) -> Result<ActionCreatePreparedStatementResult, Status> {
+        let (_, schema) = self.plan(&query.query).await?;
+        let IpcMessage(dataset_schema) = SchemaAsIpc::new(&schema, &IpcWriteOptions::default())
+            .try_into()
+            .map_err(|e: arrow::error::ArrowError| Status::internal(e.to_string()))?;
+        Ok(ActionCreatePreparedStatementResult {
+            prepared_statement_handle: query.query.into_bytes().into(),
+            dataset_schema,
+            ..Default::default()
+        })
+    }
+
+    async fn do_action_close_prepared_statement(
+        &self,
+        _query: ActionClosePreparedStatementRequest,
+        _request: Request<Action>,
+    ) -> Result<(), Status> {
+        Ok(())
+    }
+
+    async fn register_sql_info(&self, _id: i32, _result: &SqlInfo) {}
+}
+
+fn sql_info() -> &'static SqlInfoData {
+    static SQL_INFO: OnceLock<SqlInfoData> = OnceLock::new();
+    SQL_INFO.get_or_init(|| {
+        let mut builder = SqlInfoDataBuilder::new();
+        builder.append(SqlInfo::FlightSqlServerName, "DuckPond");
+        builder.append(SqlInfo::FlightSqlServerVersion, env!("CARGO_PKG_VERSION"));
+        builder.append(SqlInfo::FlightSqlServerArrowVersion, "1.3");
+        builder.append(SqlInfo::FlightSqlServerReadOnly, false);
+        builder.build().expect("valid SQL info")
+    })
+}
+
+fn flight_info(
+    schema: &Schema,
+    ticket: Any,
+    request: Request<FlightDescriptor>,
+) -> Result<Response<FlightInfo>, Status> {
+    let endpoint = FlightEndpoint::new().with_ticket(Ticket::new(ticket.encode_to_vec()));
+    let info = FlightInfo::new()
+        .try_with_schema(schema)
+        .map_err(|e| Status::internal(e.to_string()))?
+        .with_endpoint(endpoint)
+        .with_descriptor(request.into_inner());
+    Ok(Response::new(info))
+}
+
+fn batch_stream<E: Into<FlightError> + Send + 'static>(
+    schema: SchemaRef,
+    batch: Result<RecordBatch, E>,
+) -> Result<Response<DoGetStream>, Status> {
+    let stream = FlightDataEncoderBuilder::new()
+        .with_schema(schema)
+        .build(stream::once(async { batch.map_err(Into::into) }))
+        .map_err(Status::from);
+    Ok(Response::new(Box::pin(stream)))
+}
+
+fn handle_sql(handle: &[u8]) -> Result<String, Status> {
+    String::from_utf8(handle.to_vec())
+        .map_err(|_| Status::invalid_argument("Invalid statement handle"))
+}
+
+fn check_catalog(catalog: Option<&str>) -> Result<(), Status> {
+    match catalog {
+        Some(catalog) if !catalog.is_empty() && catalog != CATALOG_NAME => Err(Status::not_found(
+            format!("Catalog '{}' not found", catalog),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn column_indices(schema: &Schema, columns: &[String]) -> Result<Vec<usize>, Status> {
+    columns
+        .iter()
+        .map(|column| {
+            schema
+                .index_of(column)
+                .map_err(|_| Status::invalid_argument(format!("Column '{}' not found", column)))
+        })
+        .collect()
+}
+
+fn status(error: DuckPondError) -> Status {
+    match &error {
+        DuckPondError::TableNotFound { .. }
+        | DuckPondError::SchemaNotFound { .. }
+        | DuckPondError::ViewNotFound { .. } => Status::not_found(error.to_string()),
+        DuckPondError::ConfigError(message) if message.contains("not found") => {
+            Status::not_found(error.to_string())
+        }
+        DuckPondError::SchemaMismatch { .. }
+        | DuckPondError::InvalidFilter { .. }
+        | DuckPondError::InvalidColumnDefinition { .. } => {
+            Status::invalid_argument(error.to_string())
+        }
+        _ => Status::internal(error.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Int64Array, StringArray};
+    use arrow::datatypes::{DataType, Field};
+    use arrow_flight::sql::client::FlightSqlServiceClient;
+    use arrow_flight::sql::{CommandGetTables, TableDefinitionOptions};
+    use duckpond::database::Database;
+    use duckpond::{DuckPond, DuckPondConfig};
+    use tonic::transport::Channel;
+
+    async fn collect(
+        client: &mut FlightSqlServiceClient<Channel>,
+        info: FlightInfo,
+    ) -> Vec<RecordBatch> {
+        let ticket = info.endpoint[0].ticket.clone().unwrap();
+        client
+            .do_get(ticket)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_flight_sql() {
+        let dir = std::env::temp_dir().join(format!("duckpond-flight-{}", std::process::id()));
+        let data_path = dir.join("data").to_string_lossy().into_owned();
+        std::fs::create_dir_all(&data_path).unwrap();
+        let database_url = format!("sqlite://{}?mode=rwc", dir.join("catalog.db").display());
+
+        let database = Database::new(&DuckPondConfig::new(
+            database_url.clone(),
+            data_path.clone(),
+        ))
+        .await
+        .unwrap();
+        database
+            .backend()
+            .dialect()
+            .migrator()
+            .run(database.pool())
+            .await
+            .unwrap();
+        DuckPond::new(database.pool().clone(), database.backend().clone())
+            .initialize(&data_path, false)
+            .await
+            .unwrap();
+        let lakehouse = Lakehouse::new_with_local_storage(database_url, data_path)
+            .await
+            .unwrap();
+
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        tokio::spawn(serve(Arc::new(lakehouse), addr));
+        let channel = loop {
+            match Channel::from_shared(format!("http://{}", addr))
+                .unwrap()
+                .connect()
+                .await
+            {
+                Ok(channel) => break channel,
+                Err(_) => tokio::time::sleep(std::time::Duration::from_millis(20)).await,
+            }
+        };
+        let mut client = FlightSqlServiceClient::new(channel);
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3])),
+                Arc::new(StringArray::from(vec![Some("a"), None, Some("c")])),
+            ],
+        )
+        .unwrap();
+        let ingest = |if_not_exist: TableNotExistOption| CommandStatementIngest {
+            table_definition_options: Some(TableDefinitionOptions {
+                if_not_exist: if_not_exist as i32,
+                if_exists: TableExistsOption::Append as i32,
+            }),
+            table: "events".to_string(),
+            ..Default::default()
+        };
+        let rows = client
+            .execute_ingest(
+                ingest(TableNotExistOption::Create),
+                stream::iter(vec![Ok(batch.clone())]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(rows, 3);
+
+        let info = client
+            .get_tables(CommandGetTables {
+                include_schema: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let tables = collect(&mut client, info).await;
+        assert_eq!(tables[0].num_rows(), 1);
+
+        let info = client
+            .execute(
+                "SELECT name FROM duckpond.main.events WHERE id >= 2 LIMIT 1".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        let batches = collect(&mut client, info).await;
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].num_rows(), 1);
+        assert_eq!(batches[0].schema().field(0).name(), "name");
+        assert!(batches[0].column(0).is_null(0));
+
+        let error = client
+            .execute("SELECT * FROM missing".to_string(), None)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("not found"), "{}", error);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,225 @@
+//! The SQL accepted by the server
+//!
+//! There is no query engine behind the server, so statements are limited to
+//! scans of one table:
+//!
+//! ```text
+//! SELECT * | column, ... FROM [catalog.][schema.]table [WHERE filter] [LIMIT n]
+//! ```
+//!
+//! The filter syntax is that of [`duckpond::filter`]. Without a schema the
+//! table is looked up in the default `main` schema.
+
+use duckpond::filter::Filter;
+use duckpond::DEFAULT_SCHEMA;
+use std::fmt;
+
+/// A parsed scan statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanQuery {
+    pub catalog: Option<String>,
+    pub schema: String,
+    pub table: String,
+    /// Columns to return, all of them if `None`
+    pub columns: Option<Vec<String>>,
+    pub filter: Option<Filter>,
+    pub limit: Option<usize>,
+}
+
+/// Why a statement is not a supported scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError(pub String);
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (only SELECT ... FROM table [WHERE ...] [LIMIT n] is supported)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+impl ScanQuery {
+    pub fn parse(sql: &str) -> Result<Self, QueryError> {
+        let sql = sql.trim().trim_end_matches(';').trim_end();
+        let select = keyword(sql, "SELECT", 0)
+            .filter(|&at| at == 0)
+            .ok_or_else(|| QueryError("expected SELECT".to_string()))?;
+        let from =
+            keyword(sql, "FROM", select).ok_or_else(|| QueryError("expected FROM".to_string()))?;
+        let where_at = keyword(sql, "WHERE", from);
+        let limit_at = keyword(sql, "LIMIT", where_at.unwrap_or(from));
+
+        let projection = sql[select + "SELECT".len()..from].trim();
+        let columns = match projection {
+            "*" => None,
+            "" => return Err(QueryError("expected columns to select".to_string())),
+            _ => Some(
+                split_outside_quotes(projection, ',')
+                    .into_iter()
+                    .map(|column| identifier(column.trim()))
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+
+        let table_end = where_at.or(limit_at).unwrap_or(sql.len());
+        let mut names = split_outside_quotes(sql[from + "FROM".len()..table_end].trim(), '.')
+            .into_iter()
+            .map(|name| identifier(name.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (catalog, schema, table) = match names.len() {
+            1 => (None, DEFAULT_SCHEMA.to_string(), names.remove(0)),
+            2 => {
+                let table = names.remove(1);
+                (None, names.remove(0), table)
+            }
+            3 => {
+                let table = names.remove(2);
+                let schema = names.remove(1);
+                (Some(names.remove(0)), schema, table)
+            }
+            _ => return Err(QueryError("expected one table name".to_string())),
+        };
+
+        let filter = match where_at {
+            Some(at) => {
+                let end = limit_at.unwrap_or(sql.len());
+                let filter = sql[at + "WHERE".len()..end].trim();
+                Some(
+                    filter
+                        .parse()
+                        .map_err(|e: duckpond::DuckPondError| QueryError(e.to_string()))?,
+                )
+            }
+            None => None,
+        };
+        let limit = match limit_at {
+            Some(at) => Some(
+                sql[at + "LIMIT".len()..]
+                    .trim()
+                    .parse()
+                    .map_err(|_| QueryError("expected a number after LIMIT".to_string()))?,
+            ),
+            None => None,
+        };
+
+        Ok(Self {
+            catalog,
+            schema,
+            table,
+            columns,
+            filter,
+            limit,
+        })
+    }
+}
+
+/// Byte offset of the first standalone `word`, ignoring case and quoted
+/// text, at or after `from`
+fn keyword(sql: &str, word: &str, from: usize) -> Option<usize> {
+    let bytes = sql.as_bytes();
+    let mut quote = None;
+    for (i, c) in sql.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None => {
+                let end = i + word.len();
+                if i >= from
+                    && sql.is_char_boundary(end)
+                    && end <= sql.len()
+                    && sql[i..end].eq_ignore_ascii_case(word)
+                    && (i == 0 || !is_word_byte(bytes[i - 1]))
+                    && (end == sql.len() || !is_word_byte(bytes[end]))
+                {
+                    return Some(i);
+                }
+            }
+        }
+    }
+    None
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+fn split_outside_quotes(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' => quote = Some(c),
+            None if c == separator => {
+                parts.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            None => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// An identifier, unquoted if it is in double quotes
+fn identifier(text: &str) -> Result<String, QueryError> {
+    if let Some(quoted) = text.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+        return Ok(quoted.replace("\"\"", "\""));
+    }
+    if !text.is_empty() && text.bytes().all(is_word_byte) {
+        Ok(text.to_string())
+    } else {
+        Err(QueryError(format!("unsupported name '{}'", text)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let query = ScanQuery::parse("SELECT * FROM events").unwrap();
+        assert_eq!(query.schema, "main");
+        assert_eq!(query.table, "events");
+        assert_eq!(query.columns, None);
+
+        let query = ScanQuery::parse(
+            "select id, \"Event Name\" from duckpond.analytics.\"events\" \
+             where name = 'limit from' and id > 3 limit 10;",
+        )
+        .unwrap();
+        assert_eq!(query.catalog.as_deref(), Some("duckpond"));
+        assert_eq!(query.schema, "analytics");
+        assert_eq!(query.table, "events");
+        assert_eq!(
+            query.columns,
+            Some(vec!["id".to_string(), "Event Name".to_string()])
+        );
+        assert_eq!(
+            query.filter,
+            Some("name = 'limit from' and id > 3".parse().unwrap())
+        );
+        assert_eq!(query.limit, Some(10));
+    }
+
+    #[test]
+    fn test_unsupported() {
+        for sql in [
+            "DELETE FROM events",
+            "SELECT count(*) FROM events",
+            "SELECT * FROM a JOIN b ON a.id = b.id",
+            "SELECT * FROM events LIMIT ten",
+            "SELECT FROM events",
+        ] {
+            assert!(ScanQuery::parse(sql).is_err(), "{}", sql);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Errors returned by the API as `{"error": "..."}` bodies
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use duckpond::error::DuckPondError;
+
+/// An error response
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, message)
+    }
+}
+
+impl From<DuckPondError> for ApiError {
+    fn from(error: DuckPondError) -> Self {
+        let status = match &error {
+            DuckPondError::TableNotFound { .. }
+            | DuckPondError::SchemaNotFound { .. }
+            | DuckPondError::ViewNotFound { .. }
+            | DuckPondError::ObjectNotFound { .. }
+            | DuckPondError::TagNotFound { .. }
+            | DuckPondError::SnapshotRefNotFound { .. }
+            | DuckPondError::NoSnapshotAtTime { .. }
+            | DuckPondError::InvalidSnapshot { .. } => StatusCode::NOT_FOUND,
+            DuckPondError::ConfigError(message) if message.contains("not found") => {
+                StatusCode::NOT_FOUND
+            }
+            DuckPondError::NameAlreadyExists { .. }
+            | DuckPondError::SchemaNotEmpty { .. }
+            | DuckPondError::SnapshotRefExists { .. }
+            | DuckPondError::BranchConflict { .. }
+            | DuckPondError::HistoricalSnapshot { .. } => StatusCode::CONFLICT,
+            DuckPondError::SchemaMismatch { .. }
+            | DuckPondError::InvalidColumnDefinition { .. }
+            | DuckPondError::InvalidDataFile { .. }
+            | DuckPondError::InvalidImport { .. }
+            | DuckPondError::InvalidFilter { .. }
+            | DuckPondError::InvalidSortOrder { .. }
+            | DuckPondError::InvalidTag { .. } => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self::new(status, error.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        if self.status.is_server_error() {
+            tracing::error!("{}", self.message);
+        }
+        let body = serde_json::json!({ "error": self.message });
+        (self.status, Json(body)).into_response()
+    }
+}
@@ -0,0 +1,1179 @@
+//! HTTP/JSON API for administering DuckPond lakehouses
+//!
+//! Lets tools without a DuckPond build browse and manage a lakehouse:
+//!
+//! | Method | Path | |
+//! |---|---|---|
+//! | `GET`, `PATCH` | `/catalog` | Spec version, current snapshot and settings; set `{"data_inlining_row_limit"}` |
+//! | `GET`, `POST` | `/schemas` | List or create (`{"name"}`) schemas |
+//! | `GET`, `DELETE` | `/schemas/{schema}` | Show or drop an empty schema |
+//! | `GET`, `POST` | `/schemas/{schema}/tables` | List or create (`{"name", "columns"}`) tables |
+//! | `GET`, `PATCH`, `DELETE` | `/schemas/{schema}/tables/{table}` | Show, alter (`{"add_columns", "rename_to"}`) or drop a table |
+//! | `GET` | `/schemas/{schema}/tables/{table}/columns` | Columns, nested ones under `children` |
+//! | `GET` | `/schemas/{schema}/tables/{table}/files` | Data files |
+//! | `GET` | `/schemas/{schema}/tables/{table}/stats` | Table and column statistics |
+//! | `GET`, `POST` | `/schemas/{schema}/tables/{table}/data` | Read or append rows |
+//! | `POST` | `/schemas/{schema}/tables/{table}/flush` | Move inlined rows into Parquet files |
+//! | `PUT`, `DELETE` | `/schemas/{schema}/tables/{table}/sort-order` | Set (`{"sort_order"}`, e.g. `"id DESC"`) or clear the sort order |
+//! | `GET` | `/schemas/{schema}/tables/{table}/columns/{column}/tags` | Column tags |
+//! | `PUT`, `DELETE` | `/schemas/{schema}/tables/{table}/columns/{column}/tags/{key}` | Set (`{"value"}`) or remove a column tag |
+//! | `GET`, `POST` | `/schemas/{schema}/views` | List or create (`{"name", "sql", "dialect", "column_aliases"}`) views |
+//! | `GET`, `DELETE` | `/schemas/{schema}/views/{view}` | Show or drop a view |
+//! | `GET` | `.../tags` | Tags of a schema, table or view |
+//! | `PUT`, `DELETE` | `.../tags/{key}` | Set (`{"value"}`) or remove a tag of a schema, table or view |
+//! | `GET`, `POST` | `/branches` | List or create (`{"name"}`) branches |
+//! | `DELETE` | `/branches/{branch}` | Drop a branch, discarding its unmerged snapshots |
+//! | `POST` | `/branches/{branch}/merge` | Fast-forward main to a branch |
+//! | `GET` | `/snapshots[/{id}]` | Snapshots with the changes they made |
+//!
+//! Columns are given as in the CLI: `{"name", "type", "nullable", "children"}`,
+//! and named in paths by their dotted path, such as `address.city`. Rows are
+//! read as NDJSON, an Arrow IPC stream, CSV or Parquet (`?format=`, by default
+//! taken from the `Accept` header), optionally with `?where=` (see
+//! [`duckpond::filter`]) and `?limit=`, and as of a past snapshot with
+//! `?snapshot=` or `?at=` (an RFC 3339 timestamp). Rows are appended from an
+//! Arrow IPC stream, NDJSON or CSV body, by `Content-Type`; a missing table is
+//! created from the data.
+
+pub mod error;
+
+use arrow::array::RecordBatch;
+use arrow::datatypes::SchemaRef;
+use arrow::ipc::reader::StreamReader;
+use arrow::ipc::writer::StreamWriter;
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post, put};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use duckpond::export::{self, ExportFormat};
+use duckpond::filter::Filter;
+use duckpond::models::{
+    ColumnInfo, ColumnTag, ObjectTag, RefKind, SchemaInfo, TableColumnStats, ViewInfo,
+};
+use duckpond::{ColumnDefinition, ImportOptions, Lakehouse, SortOrder};
+use error::ApiError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+type Result<T> = std::result::Result<T, ApiError>;
+type AppState = Arc<Lakehouse>;
+
+const ARROW_STREAM: &str = "application/vnd.apache.arrow.stream";
+const NDJSON: &str = "application/x-ndjson";
+const CSV: &str = "text/csv";
+
+/// Largest request body accepted when appending rows
+const MAX_UPLOAD_BYTES: usize = 256 * 1024 * 1024;
+
+/// Serve the API on an address until the server fails
+pub async fn serve(lakehouse: Arc<Lakehouse>, addr: SocketAddr) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!("Serving the HTTP API on {}", addr);
+    axum::serve(listener, router(lakehouse)).await
+}
+
+/// The API's routes over a lakehouse
+pub fn router(lakehouse: Arc<Lakehouse>) -> Router {
+    Router::new()
+        .route("/catalog", get(catalog).patch(alter_catalog))
+        .route("/schemas", get(list_schemas).post(create_schema))
+        .route("/schemas/:schema", get(show_schema).delete(drop_schema))
+        .route(
+            "/schemas/:schema/tables",
+            get(list_tables).post(create_table),
+        )
+        .route(
+            "/schemas/:schema/tables/:table",
+            get(show_table).patch(alter_table).delete(drop_table),
+        )
+        .route("/schemas/:schema/tables/:table/columns", get(list_columns))
+        .route("/schemas/:schema/tables/:table/files", get(list_files))
+        .route("/schemas/:schema/tables/:table/stats", get(table_stats))
+        .route(
+            "/schemas/:schema/tables/:table/data",
+            get(read_rows)
+                .post(append_rows)
+                .layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES)),
+        )
+        .route("/schemas/:schema/tables/:table/flush", post(flush_table))
+        .route(
+            "/schemas/:schema/tables/:table/sort-order",
+            put(set_sort_order).delete(clear_sort_order),
+        )
+        .route(
+            "/schemas/:schema/tables/:table/columns/:column/tags",
+            get(list_column_tags),
+        )
+        .route(
+            "/schemas/:schema/tables/:table/columns/:column/tags/:key",
+            put(set_column_tag).delete(unset_column_tag),
+        )
+        .route("/schemas/:schema/views", get(list_views).post(create_view))
+        .route(
+            "/schemas/:schema/views/:view",
+            get(show_view).delete(drop_view),
+        )
+        .route("/schemas/:schema/tags", get(list_tags))
+        .route("/schemas/:schema/tags/:key", put(set_tag).delete(unset_tag))
+        .route("/schemas/:schema/tables/:table/tags", get(list_tags))
+        .route(
+            "/schemas/:schema/tables/:table/tags/:key",
+            put(set_tag).delete(unset_tag),
+        )
+        .route("/schemas/:schema/views/:view/tags", get(list_tags))
+        .route(
+            "/schemas/:schema/views/:view/tags/:key",
+            put(set_tag).delete(unset_tag),
+        )
+        .route("/branches", get(list_branches).post(create_branch))
+        .route("/branches/:branch", delete(drop_branch))
+        .route("/branches/:branch/merge", post(merge_branch))
+        .route("/snapshots", get(list_snapshots))
+        .route("/snapshots/:snapshot_id", get(show_snapshot))
+        .with_state(lakehouse)
+}
+
+#[derive(Serialize)]
+struct Catalog {
+    spec_version: Option<String>,
+    current_snapshot: Option<i64>,
+    data_inlining_row_limit: usize,
+}
+
+async fn catalog(State(lakehouse): State<AppState>) -> Result<Json<Catalog>> {
+    let core = lakehouse.core();
+    Ok(Json(Catalog {
+        spec_version: core.spec_version().await?,
+        current_snapshot: core.current_snapshot().await?,
+        data_inlining_row_limit: core.data_inlining_row_limit().await?,
+    }))
+}
+
+#[derive(Deserialize)]
+struct AlterCatalog {
+    data_inlining_row_limit: Option<usize>,
+}
+
+async fn alter_catalog(
+    State(lakehouse): State<AppState>,
+    Json(request): Json<AlterCatalog>,
+) -> Result<Json<Catalog>> {
+    if let Some(limit) = request.data_inlining_row_limit {
+        lakehouse.set_data_inlining_row_limit(limit).await?;
+    }
+    catalog(State(lakehouse)).await
+}
+
+async fn list_schemas(State(lakehouse): State<AppState>) -> Result<Json<Vec<SchemaInfo>>> {
+    Ok(Json(lakehouse.core().list_schemas().await?))
+}
+
+#[derive(Deserialize)]
+struct CreateSchema {
+    name: String,
+}
+
+async fn create_schema(
+    State(lakehouse): State<AppState>,
+    Json(request): Json<CreateSchema>,
+) -> Result<(StatusCode, Json<SchemaInfo>)> {
+    let schema = lakehouse.core().create_schema(&request.name).await?;
+    Ok((StatusCode::CREATED, Json(schema)))
+}
+
+async fn show_schema(
+    State(lakehouse): State<AppState>,
+    Path(schema): Path<String>,
+) -> Result<Json<SchemaInfo>> {
+    let schema_id = lakehouse.get_schema_id(&schema).await?;
+    let schema = lakehouse
+        .core()
+        .list_schemas()
+        .await?
+        .into_iter()
+        .find(|s| s.schema_id == schema_id)
+        .ok_or(duckpond::error::DuckPondError::SchemaNotFound {
+            schema_name: schema,
+        })?;
+    Ok(Json(schema))
+}
+
+async fn drop_schema(
+    State(lakehouse): State<AppState>,
+    Path(schema): Path<String>,
+) -> Result<StatusCode> {
+    lakehouse.drop_schema(&schema).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_tables(
+    State(lakehouse): State<AppState>,
+    Path(schema): Path<String>,
+) -> Result<Json<Vec<duckpond::models::TableInfo>>> {
+    let schema_id = lakehouse.get_schema_id(&schema).await?;
+    Ok(Json(lakehouse.core().list_tables(schema_id).await?))
+}
+
+/// A column as given when creating or altering a table
+#[derive(Deserialize)]
+struct ColumnSpec {
+    name: String,
+    #[serde(rename = "type")]
+    data_type: String,
+    #[serde(default = "nullable_by_default")]
+    nullable: bool,
+    #[serde(default)]
+    children: Vec<ColumnSpec>,
+}
+
+fn nullable_by_default() -> bool {
+    true
+}
+
+impl From<ColumnSpec> for ColumnDefinition {
+    fn from(column: ColumnSpec) -> Self {
+        Self {
+            column_id: None,
+            name: column.name,
+            data_type: column.data_type,
+            nullable: column.nullable,
+            children: column.children.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Table {
+    table_id: i64,
+    table_name: String,
+    columns: Vec<ColumnInfo>,
+    sort_order: Option<String>,
+}
+
+async fn table(lakehouse: &Lakehouse, schema: &str, table: &str) -> Result<Table> {
+    let table_id = lakehouse.get_table_id(schema, table).await?;
+    Ok(Table {
+        table_id,
+        table_name: table.to_string(),
+        columns: lakehouse.core().table_structure(table_id).await?,
+        sort_order: lakehouse
+            .sort_order(schema, table)
+            .await?
+            .map(|order| order.to_string()),
+    })
+}
+
+#[derive(Deserialize)]
+struct CreateTable {
+    name: String,
+    columns: Vec<ColumnSpec>,
+}
+
+async fn create_table(
+    State(lakehouse): State<AppState>,
+    Path(schema): Path<String>,
+    Json(request): Json<CreateTable>,
+) -> Result<(StatusCode, Json<Table>)> {
+    let columns = request.columns.into_iter().map(Into::into).collect();
+    lakehouse
+        .create_table(&schema, &request.name, columns)
+        .await?;
+    let table = table(&lakehouse, &schema, &request.name).await?;
+    Ok((StatusCode::CREATED, Json(table)))
+}
+
+async fn show_table(
+    State(lakehouse): State<AppState>,
+    Path((schema, name)): Path<(String, String)>,
+) -> Result<Json<Table>> {
+    Ok(Json(table(&lakehouse, &schema, &name).await?))
+}
+
+/// Changes to a table, applied in field order, each in its own snapshot
+#[derive(Deserialize)]
+struct AlterTable {
+    #[serde(default)]
+    add_columns: Vec<ColumnSpec>,
+    rename_to: Option<String>,
+}
+
+async fn alter_table(
+    State(lakehouse): State<AppState>,
+    Path((schema, mut name)): Path<(String, String)>,
+    Json(request): Json<AlterTable>,
+) -> Result<Json<Table>> {
+    for column in request.add_columns {
+        lakehouse.add_column(&schema, &name, column.into()).await?;
+    }
+    if let Some(new_name) = request.rename_to {
+        lakehouse.rename_table(&schema, &name, &new_name).await?;
+        name = new_name;
+    }
+    Ok(Json(table(&lakehouse, &schema, &name).await?))
+}
+
+async fn drop_table(
+    State(lakehouse): State<AppState>,
+    Path((schema, table)): Path<(String, String)>,
+) -> Result<StatusCode> {
+    lakehouse.drop_table(&schema, &table).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_columns(
+    State(lakehouse): State<AppState>,
+    Path((schema, table)): Path<(String, String)>,
+) -> Result<Json<Vec<ColumnInfo>>> {
+    let table_id = lakehouse.get_table_id(&schema, &table).await?;
+    Ok(Json(lakehouse.core().table_structure(table_id).await?))
+}
+
+async fn list_files(
+    State(lakehouse): State<AppState>,
+    Path((schema, table)): Path<(String, String)>,
+) -> Result<Json<Vec<duckpond::models::DataFileInfo>>> {
+    let table_id = lakehouse.get_table_id(&schema, &table).await?;
+    Ok(Json(lakehouse.core().list_data_files(table_id).await?))
+}
+
+#[derive(Serialize)]
+struct TableStatistics {
+    record_count: i64,
+    file_size_bytes: i64,
+    next_row_id: i64,
+    columns: Vec<TableColumnStats>,
+}
+
+async fn table_stats(
+    State(lakehouse): State<AppState>,
+    Path((schema, table)): Path<(String, String)>,
+) -> Result<Json<TableStatistics>> {
+    let table_id = lakehouse.get_table_id(&schema, &table).await?;
+    let stats = lakehouse.core().table_stats(table_id).await?;
+    Ok(Json(TableStatistics {
+        record_count: stats.as_ref().map_or(0, |s| s.record_count),
+        file_size_bytes: stats.as_ref().map_or(0, |s| s.file_size_bytes),
+        next_row_id: stats.as_ref().map_or(0, |s| s.next_row_id),
+        columns: lakehouse.core().table_column_stats(table_id).await?,
+    }))
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum DataFormat {
+    Ndjson,
+    Arrow,
+    Csv,
+    Parquet,
+}
+
+impl DataFormat {
+    fn from_accept(headers: &HeaderMap) -> Self {
+        let accept = headers
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        if accept.contains(ARROW_STREAM) {
+            Self::Arrow
+        } else if accept.contains(CSV) {
+            Self::Csv
+        } else {
+            Self::Ndjson
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Ndjson => NDJSON,
+            Self::Arrow => ARROW_STREAM,
+            Self::Csv => CSV,
+            Self::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ReadRows {
+    format: Option<DataFormat>,
+    #[serde(rename = "where")]
+    filter: Option<String>,
+    limit: Option<usize>,
+    /// Read the table as of this snapshot
+    snapshot: Option<i64>,
+    /// Read the table as of the latest snapshot taken at or before this time
+    at: Option<DateTime<Utc>>,
+}
+
+async fn read_rows(
+    State(lakehouse): State<AppState>,
+    Path((schema, table)): Path<(String, String)>,
+    Query(params): Query<ReadRows>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let filter = params
+        .filter
+        .map(|filter| filter.parse::<Filter>())
+        .transpose()?;
+    let snapshot_id = match (params.snapshot, params.at) {
+        (Some(_), Some(_)) => {
+            return Err(ApiError::bad_request(
+                "Give either snapshot or at, not both",
+            ))
+        }
+        (Some(snapshot_id), None) => Some(snapshot_id),
+        (None, Some(at)) => Some(lakehouse.core().snapshot_at_time(at).await?),
+        (None, None) => None,
+    };
+    let lakehouse = match snapshot_id {
+        Some(snapshot_id) => Arc::new(Lakehouse::clone(&lakehouse).as_of(snapshot_id).await?),
+        None => lakehouse,
+    };
+    let (table_schema, batches) = lakehouse
+        .scan_table(&schema, &table, filter.as_ref())
+        .await?;
+    let batches = match params.limit {
+        Some(limit) => limit_rows(batches, limit),
+        None => batches,
+    };
+
+    let format = params
+        .format
+        .unwrap_or_else(|| DataFormat::from_accept(&headers));
+    let body = match format {
+        DataFormat::Arrow => arrow_stream(table_schema, &batches)
+            .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        DataFormat::Ndjson | DataFormat::Csv | DataFormat::Parquet => {
+            let export_format = match format {
+                DataFormat::Csv => ExportFormat::Csv,
+                DataFormat::Parquet => ExportFormat::Parquet,
+                _ => ExportFormat::Json,
+            };
+            let mut body = Vec::new();
+            export::write_batches(&mut body, export_format, table_schema, &batches)?;
+            body
+        }
+    };
+    Ok(([(header::CONTENT_TYPE, format.content_type())], body).into_response())
+}
+
+/// The first `limit` rows of batches
+fn limit_rows(batches: Vec<RecordBatch>, limit: usize) -> Vec<RecordBatch> {
+    let mut remaining = limit;
+    let mut limited = Vec::new();
+    for batch in batches {
+        if remaining == 0 {
+            break;
+        }
+        let batch = batch.slice(0, batch.num_rows().min(remaining));
+        remaining -= batch.num_rows();
+        limited.push(batch);
+    }
+    limited
+}
+
+fn arrow_stream(schema: SchemaRef, batches: &[RecordBatch]) -> arrow::error::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    let mut writer = StreamWriter::try_new(&mut body, &schema)?;
+    for batch in batches {
+        writer.write(batch)?;
+    }
+    writer.finish()?;
+    drop(writer);
+    Ok(body)
+}
+
+#[derive(Serialize)]
+struct RowsWritten {
+    rows_written: usize,
+}
+
+async fn append_rows(
+    State(lakehouse): State<AppState>,
+    Path((schema, table)): Path<(String, String)>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<RowsWritten>> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let media_type = content_type.split(';').next().unwrap_or_default().trim();
+
+    let rows_written = match media_type {
+        ARROW_STREAM => {
+            let reader = StreamReader::try_new(Cursor::new(body), None)
+                .map_err(|e| ApiError::bad_request(e.to_string()))?;
+            let arrow_schema = reader.schema();
+            let batches = reader
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|e| ApiError::bad_request(e.to_string()))?;
+            let exists = lakehouse
+                .list_tables(&schema)
+                .await?
+                .iter()
+                .any(|name| name == &table);
+            if !exists {
+                lakehouse
+                    .create_table_from_arrow_schema(&schema, &table, &arrow_schema)
+                    .await?;
+            }
+            let rows = batches.iter().map(RecordBatch::num_rows).sum();
+            if rows > 0 {
+                lakehouse.write_to_table(&schema, &table, batches).await?;
+            }
+            rows
+        }
+        NDJSON | "application/json" => {
+            lakehouse
+                .import_json(
+                    &schema,
+                    &table,
+                    Cursor::new(body),
+                    &ImportOptions::default(),
+                )
+                .await?
+                .records_imported
+        }
+        CSV => {
+            lakehouse
+                .import_csv(
+                    &schema,
+                    &table,
+                    Cursor::new(body),
+                    &ImportOptions::default(),
+                )
+                .await?
+                .records_imported
+        }
+        _ => {
+            return Err(ApiError::new(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                format!(
+                    "Send rows as {}, {} or {}, not '{}'",
+                    ARROW_STREAM, NDJSON, CSV, content_type
+                ),
+            ))
+        }
+    };
+    Ok(Json(RowsWritten { rows_written }))
+}
+
+#[derive(Serialize)]
+struct RowsFlushed {
+    rows_flushed: usize,
+}
+
+async fn flush_table(
+    State(lakehouse): State<AppState>,
+    Path((schema, table)): Path<(String, String)>,
+) -> Result<Json<RowsFlushed>> {
+    let rows_flushed = lakehouse.flush_inlined_data(&schema, &table).await?;
+    Ok(Json(RowsFlushed { rows_flushed }))
+}
+
+#[derive(Deserialize)]
+struct SetSortOrder {
+    sort_order: String,
+}
+
+async fn set_sort_order(
+    State(lakehouse): State<AppState>,
+    Path((schema, name)): Path<(String, String)>,
+    Json(request): Json<SetSortOrder>,
+) -> Result<Json<Table>> {
+    let sort_order = request.sort_order.parse::<SortOrder>()?;
+    lakehouse
+        .set_sort_order(&schema, &name, Some(sort_order))
+        .await?;
+    Ok(Json(table(&lakehouse, &schema, &name).await?))
+}
+
+async fn clear_sort_order(
+    State(lakehouse): State<AppState>,
+    Path((schema, name)): Path<(String, String)>,
+) -> Result<Json<Table>> {
+    lakehouse.set_sort_order(&schema, &name, None).await?;
+    Ok(Json(table(&lakehouse, &schema, &name).await?))
+}
+
+/// The ID of the schema, table or view a path names
+async fn object_id(lakehouse: &Lakehouse, path: &HashMap<String, String>) -> Result<i64> {
+    let schema = &path["schema"];
+    if let Some(table) = path.get("table") {
+        Ok(lakehouse.get_table_id(schema, table).await?)
+    } else if let Some(view) = path.get("view") {
+        Ok(lakehouse.get_view(schema, view).await?.view_id)
+    } else {
+        Ok(lakehouse.get_schema_id(schema).await?)
+    }
+}
+
+async fn list_tags(
+    State(lakehouse): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<Json<Vec<ObjectTag>>> {
+    let object_id = object_id(&lakehouse, &path).await?;
+    Ok(Json(lakehouse.core().list_tags(object_id).await?))
+}
+
+#[derive(Deserialize)]
+struct SetTag {
+    value: String,
+}
+
+async fn set_tag(
+    State(lakehouse): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+    Json(request): Json<SetTag>,
+) -> Result<Json<Vec<ObjectTag>>> {
+    let object_id = object_id(&lakehouse, &path).await?;
+    lakehouse
+        .core()
+        .set_tag(object_id, &path["key"], &request.value)
+        .await?;
+    Ok(Json(lakehouse.core().list_tags(object_id).await?))
+}
+
+async fn unset_tag(
+    State(lakehouse): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<StatusCode> {
+    let object_id = object_id(&lakehouse, &path).await?;
+    lakehouse.core().unset_tag(object_id, &path["key"]).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The IDs of a table and of one of its columns, named by its dotted path
+async fn column_id(
+    lakehouse: &Lakehouse,
+    schema: &str,
+    table: &str,
+    column: &str,
+) -> Result<(i64, i64)> {
+    let table_id = lakehouse.get_table_id(schema, table).await?;
+    let mut columns = lakehouse.core().table_structure(table_id).await?;
+    let mut names = column.split('.').peekable();
+    while let Some(name) = names.next() {
+        let found = columns
+            .into_iter()
+            .find(|c| c.column_name == name)
+            .ok_or_else(|| {
+                ApiError::new(
+                    StatusCode::NOT_FOUND,
+                    format!("Column '{}' not found in table '{}'", column, table),
+                )
+            })?;
+        if names.peek().is_none() {
+            return Ok((table_id, found.column_id));
+        }
+        columns = found.children;
+    }
+    Err(ApiError::bad_request("Name a column"))
+}
+
+async fn list_column_tags(
+    State(lakehouse): State<AppState>,
+    Path((schema, table, column)): Path<(String, String, String)>,
+) -> Result<Json<Vec<ColumnTag>>> {
+    let (table_id, column_id) = column_id(&lakehouse, &schema, &table, &column).await?;
+    let mut tags = lakehouse.core().list_column_tags(table_id).await?;
+    tags.retain(|tag| tag.column_id == column_id);
+    Ok(Json(tags))
+}
+
+async fn set_column_tag(
+    State(lakehouse): State<AppState>,
+    Path((schema, table, column, key)): Path<(String, String, String, String)>,
+    Json(request): Json<SetTag>,
+) -> Result<Json<Vec<ColumnTag>>> {
+    let (table_id, column_id) = column_id(&lakehouse, &schema, &table, &column).await?;
+    lakehouse
+        .core()
+        .set_column_tag(table_id, column_id, &key, &request.value)
+        .await?;
+    list_column_tags(State(lakehouse), Path((schema, table, column))).await
+}
+
+async fn unset_column_tag(
+    State(lakehouse): State<AppState>,
+    Path((schema, table, column, key)): Path<(String, String, String, String)>,
+) -> Result<StatusCode> {
+    let (table_id, column_id) = column_id(&lakehouse, &schema, &table, &column).await?;
+    lakehouse
+        .core()
+        .unset_column_tag(table_id, column_id, &key)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_views(
+    State(lakehouse): State<AppState>,
+    Path(schema): Path<String>,
+) -> Result<Json<Vec<ViewInfo>>> {
+    let schema_id = lakehouse.get_schema_id(&schema).await?;
+    Ok(Json(lakehouse.core().list_views(schema_id).await?))
+}
+
+#[derive(Deserialize)]
+struct CreateView {
+    name: String,
+    sql: String,
+    #[serde(default = "duckdb_dialect")]
+    dialect: String,
+    #[serde(default)]
+    column_aliases: Vec<String>,
+}
+
+fn duckdb_dialect() -> String {
+    "duckdb".to_string()
+}
+
+async fn create_view(
+    State(lakehouse): State<AppState>,
+    Path(schema): Path<String>,
+    Json(request): Json<CreateView>,
+) -> Result<(StatusCode, Json<ViewInfo>)> {
+    let schema_id = lakehouse.get_schema_id(&schema).await?;
+    let view = lakehouse
+        .core()
+        .create_view(
+            schema_id,
+            &request.name,
+            &request.dialect,
+            &request.sql,
+            request.column_aliases,
+        )
+        .await?;
+    Ok((StatusCode::CREATED, Json(view)))
+}
+
+async fn show_view(
+    State(lakehouse): State<AppState>,
+    Path((schema, view)): Path<(String, String)>,
+) -> Result<Json<ViewInfo>> {
+    Ok(Json(lakehouse.get_view(&schema, &view).await?))
+}
+
+async fn drop_view(
+    State(lakehouse): State<AppState>,
+    Path((schema, view)): Path<(String, String)>,
+) -> Result<StatusCode> {
+    let schema_id = lakehouse.get_schema_id(&schema).await?;
+    lakehouse.core().drop_view(schema_id, &view).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Serialize)]
+struct Branch {
+    name: String,
+    /// Head of the branch
+    snapshot_id: i64,
+    base_snapshot: Option<i64>,
+    /// Snapshots main does not see until the branch is merged
+    unmerged_snapshots: Vec<i64>,
+}
+
+async fn branch(lakehouse: &Lakehouse, name: &str) -> Result<Branch> {
+    let branch = lakehouse.core().snapshot_ref(name).await?;
+    Ok(Branch {
+        unmerged_snapshots: lakehouse.core().branch_snapshots(name).await?,
+        name: branch.name,
+        snapshot_id: branch.snapshot_id,
+        base_snapshot: branch.base_snapshot,
+    })
+}
+
+async fn list_branches(State(lakehouse): State<AppState>) -> Result<Json<Vec<Branch>>> {
+    let mut branches = Vec::new();
+    for branch_ref in lakehouse.core().list_refs().await? {
+        if branch_ref.kind == RefKind::Branch {
+            branches.push(branch(&lakehouse, &branch_ref.name).await?);
+        }
+    }
+    Ok(Json(branches))
+}
+
+#[derive(Deserialize)]
+struct CreateBranch {
+    name: String,
+}
+
+async fn create_branch(
+    State(lakehouse): State<AppState>,
+    Json(request): Json<CreateBranch>,
+) -> Result<(StatusCode, Json<Branch>)> {
+    lakehouse.core().create_branch(&request.name).await?;
+    let branch = branch(&lakehouse, &request.name).await?;
+    Ok((StatusCode::CREATED, Json(branch)))
+}
+
+#[derive(Serialize)]
+struct BranchMerged {
+    snapshot_id: i64,
+}
+
+async fn merge_branch(
+    State(lakehouse): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<BranchMerged>> {
+    let snapshot_id = lakehouse.core().merge_branch(&name).await?;
+    Ok(Json(BranchMerged { snapshot_id }))
+}
+
+#[derive(Serialize)]
+struct BranchDropped {
+    discarded_snapshots: Vec<i64>,
+}
+
+async fn drop_branch(
+    State(lakehouse): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<BranchDropped>> {
+    let discarded_snapshots = lakehouse.core().drop_branch(&name).await?;
+    Ok(Json(BranchDropped {
+        discarded_snapshots,
+    }))
+}
+
+async fn list_snapshots(
+    State(lakehouse): State<AppState>,
+) -> Result<Json<Vec<duckpond::models::SnapshotInfo>>> {
+    Ok(Json(lakehouse.core().list_snapshots().await?))
+}
+
+async fn show_snapshot(
+    State(lakehouse): State<AppState>,
+    Path(snapshot_id): Path<i64>,
+) -> Result<Json<duckpond::models::SnapshotInfo>> {
+    Ok(Json(lakehouse.core().snapshot_info(snapshot_id).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use duckpond::database::Database;
+    use duckpond::{DuckPond, DuckPondConfig};
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    async fn send(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        content_type: &str,
+        body: impl Into<Body>,
+    ) -> (StatusCode, Bytes) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, content_type)
+            .body(body.into())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, body)
+    }
+
+    async fn send_json(app: &Router, method: &str, uri: &str, body: Value) -> (StatusCode, Value) {
+        let (status, body) = send(app, method, uri, "application/json", body.to_string()).await;
+        let body = if body.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&body).unwrap()
+        };
+        (status, body)
+    }
+
+    #[tokio::test]
+    async fn test_http_api() {
+        let dir = std::env::temp_dir().join(format!("duckpond-http-{}", std::process::id()));
+        let data_path = dir.join("data").to_string_lossy().into_owned();
+        std::fs::create_dir_all(&data_path).unwrap();
+        let database_url = format!("sqlite://{}?mode=rwc", dir.join("catalog.db").display());
+
+        let database = Database::new(&DuckPondConfig::new(
+            database_url.clone(),
+            data_path.clone(),
+        ))
+        .await
+        .unwrap();
+        database
+            .backend()
+            .dialect()
+            .migrator()
+            .run(database.pool())
+            .await
+            .unwrap();
+        DuckPond::new(database.pool().clone(), database.backend().clone())
+            .initialize(&data_path, false)
+            .await
+            .unwrap();
+        let lakehouse = Lakehouse::new_with_local_storage(database_url, data_path)
+            .await
+            .unwrap();
+        let app = router(Arc::new(lakehouse));
+
+        let (status, _) = send_json(&app, "POST", "/schemas", json!({"name": "analytics"})).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let (status, schema) = send_json(&app, "GET", "/schemas/analytics", Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(schema["schema_name"], "analytics");
+        let (status, table) = send_json(
+            &app,
+            "POST",
+            "/schemas/analytics/tables",
+            json!({"name": "events", "columns": [
+                {"name": "id", "type": "bigint", "nullable": false},
+                {"name": "name", "type": "varchar"},
+            ]}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(table["columns"].as_array().unwrap().len(), 2);
+
+        let (status, body) = send(
+            &app,
+            "POST",
+            "/schemas/analytics/tables/events/data",
+            NDJSON,
+            "{\"id\": 1, \"name\": \"a\"}\n{\"id\": 2, \"name\": \"b\"}\n",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            serde_json::from_slice::<Value>(&body).unwrap()["rows_written"],
+            2
+        );
+        let (_, catalog) = send_json(&app, "GET", "/catalog", Value::Null).await;
+        let appended = catalog["current_snapshot"].as_i64().unwrap();
+
+        let (status, table) = send_json(
+            &app,
+            "PATCH",
+            "/schemas/analytics/tables/events",
+            json!({"add_columns": [{"name": "score", "type": "double"}], "rename_to": "clicks"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(table["table_name"], "clicks");
+        assert_eq!(table["columns"].as_array().unwrap().len(), 3);
+
+        let (status, body) = send(
+            &app,
+            "GET",
+            "/schemas/analytics/tables/clicks/data?where=id%20%3E%201",
+            "",
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let rows: Vec<Value> = body
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+        assert_eq!(rows, vec![json!({"id": 2, "name": "b"})]);
+
+        // Past snapshots are read under the names they had
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/schemas/analytics/tables/clicks/data",
+            NDJSON,
+            "{\"id\": 3, \"name\": \"c\", \"score\": 1.5}\n",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = send(
+            &app,
+            "GET",
+            &format!(
+                "/schemas/analytics/tables/events/data?snapshot={}",
+                appended
+            ),
+            "",
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body.split(|&b| b == b'\n')
+                .filter(|l| !l.is_empty())
+                .count(),
+            2
+        );
+        let (status, _) = send_json(
+            &app,
+            "GET",
+            "/schemas/analytics/tables/clicks/data?at=2000-01-01T00:00:00Z",
+            Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, body) = send(
+            &app,
+            "GET",
+            "/schemas/analytics/tables/clicks/data?format=arrow&limit=1",
+            "",
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let batches = StreamReader::try_new(Cursor::new(body), None)
+            .unwrap()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(batches.iter().map(RecordBatch::num_rows).sum::<usize>(), 1);
+        assert_eq!(batches[0].num_columns(), 3);
+
+        let (status, stats) = send_json(
+            &app,
+            "GET",
+            "/schemas/analytics/tables/clicks/stats",
+            Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(stats["record_count"], 3);
+
+        let (status, table) = send_json(
+            &app,
+            "PUT",
+            "/schemas/analytics/tables/clicks/sort-order",
+            json!({"sort_order": "id DESC"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(table["sort_order"].is_string());
+        let (_, table) = send_json(
+            &app,
+            "DELETE",
+            "/schemas/analytics/tables/clicks/sort-order",
+            Value::Null,
+        )
+        .await;
+        assert!(table["sort_order"].is_null());
+        let (status, _) = send_json(
+            &app,
+            "POST",
+            "/schemas/analytics/tables/clicks/flush",
+            Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, tags) = send_json(
+            &app,
+            "PUT",
+            "/schemas/analytics/tables/clicks/tags/owner",
+            json!({"value": "data"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(tags, json!([{"key": "owner", "value": "data"}]));
+        let (status, tags) = send_json(
+            &app,
+            "PUT",
+            "/schemas/analytics/tables/clicks/columns/name/tags/pii",
+            json!({"value": "true"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(tags.as_array().unwrap().len(), 1);
+        let (status, _) = send_json(
+            &app,
+            "DELETE",
+            "/schemas/analytics/tables/clicks/columns/name/tags/pii",
+            Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, _) = send_json(
+            &app,
+            "GET",
+            "/schemas/analytics/tables/clicks/columns/missing/tags",
+            Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, view) = send_json(
+            &app,
+            "POST",
+            "/schemas/analytics/views",
+            json!({"name": "recent", "sql": "SELECT * FROM analytics.clicks"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(view["dialect"], "duckdb");
+        let (status, _) = send_json(
+            &app,
+            "PUT",
+            "/schemas/analytics/views/recent/tags/owner",
+            json!({"value": "data"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send_json(
+            &app,
+            "DELETE",
+            "/schemas/analytics/views/recent",
+            Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, _) =
+            send_json(&app, "GET", "/schemas/analytics/views/recent", Value::Null).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, branch) =
+            send_json(&app, "POST", "/branches", json!({"name": "experiment"})).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(branch["unmerged_snapshots"], json!([]));
+        let (_, branches) = send_json(&app, "GET", "/branches", Value::Null).await;
+        assert_eq!(branches.as_array().unwrap().len(), 1);
+        let (status, _) = send_json(&app, "POST", "/branches/experiment/merge", Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, dropped) =
+            send_json(&app, "DELETE", "/branches/experiment", Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(dropped["discarded_snapshots"], json!([]));
+        let (status, _) = send_json(&app, "DELETE", "/branches/experiment", Value::Null).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, error) = send_json(&app, "DELETE", "/schemas/analytics", Value::Null).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(error["error"].is_string());
+        let (status, _) = send_json(
+            &app,
+            "DELETE",
+            "/schemas/analytics/tables/clicks",
+            Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, _) = send_json(&app, "DELETE", "/schemas/analytics", Value::Null).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, _) =
+            send_json(&app, "GET", "/schemas/analytics/tables/clicks", Value::Null).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, snapshots) = send_json(&app, "GET", "/snapshots", Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(snapshots.as_array().unwrap().len() > 5);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -4,15 +4,19 @@
 //! collecting statistics, and managing schema evolution.
 
 use arrow::array::RecordBatch;
+use duckpond_core::SortOrder;
 use duckpond_storage::FileSystem;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 pub mod reader;
 pub mod schema;
+pub mod sort;
 pub mod statistics;
 pub mod writer;
 
+pub use parquet::arrow::{ArrowWriter, PARQUET_FIELD_ID_META_KEY};
+
 #[derive(Error, Debug)]
 pub enum ParquetError {
     #[error("Schema mismatch: {message}")]
@@ -53,6 +57,8 @@ pub struct ParquetWriteConfig {
     pub compression: CompressionType,
     pub row_group_size: usize,
     pub enable_statistics: bool,
+    /// Ordering applied to the rows before they are written
+    pub sort_order: Option<SortOrder>,
 }
 
 impl Default for ParquetWriteConfig {
@@ -61,6 +67,7 @@ impl Default for ParquetWriteConfig {
             compression: CompressionType::Snappy,
             row_group_size: 100_000,
             enable_statistics: true,
+            sort_order: None,
         }
     }
 }
@@ -80,6 +87,8 @@ pub enum CompressionType {
 pub struct ParquetFileStats {
     pub file_path: String,
     pub file_size_bytes: u64,
+    /// Size of the footer metadata, if the file has a valid footer
+    pub footer_size: Option<u64>,
     pub record_count: u64,
     pub row_group_count: u32,
     pub column_stats: Vec<ParquetColumnStats>,
@@ -117,6 +126,16 @@ impl ParquetManager {
         reader::read_parquet_file(&*self.filesystem, path, config).await
     }
 
+    /// Read the Arrow schema of a Parquet file
+    pub async fn read_schema(&self, path: &str) -> Result<arrow::datatypes::SchemaRef> {
+        let data = self
+            .filesystem
+            .read_file(path)
+            .await
+            .map_err(|e| ParquetError::StorageError(e.to_string()))?;
+        reader::read_parquet_schema(data)
+    }
+
     /// Write RecordBatches to a Parquet file
     pub async fn write_file(
         &self,
@@ -2,6 +2,7 @@
 
 use crate::{ParquetError, ParquetReadConfig, Result};
 use arrow::array::RecordBatch;
+use arrow::datatypes::SchemaRef;
 use bytes::Bytes;
 use duckpond_storage::FileSystem;
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
@@ -56,3 +57,44 @@ pub async fn read_parquet_file(
 
     Ok(batches)
 }
+
+/// Read the Arrow schema of a Parquet file from its contents
+pub fn read_parquet_schema(data: impl Into<Bytes>) -> Result<SchemaRef> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(data.into())
+        .map_err(ParquetError::ParquetError)?;
+    Ok(builder.schema().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Array, ArrayRef, Int64Array, StringArray, StructArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use parquet::arrow::ArrowWriter;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_read_nested_schema() {
+        let payload = StructArray::from(vec![(
+            Arc::new(Field::new("user", DataType::Utf8, true)),
+            Arc::new(StringArray::from(vec!["a"])) as ArrayRef,
+        )]);
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("payload", payload.data_type().clone(), true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int64Array::from(vec![1])), Arc::new(payload)],
+        )
+        .unwrap();
+
+        let mut data = Vec::new();
+        let mut writer = ArrowWriter::try_new(&mut data, schema.clone(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let read = read_parquet_schema(data).unwrap();
+        assert_eq!(read.fields(), schema.fields());
+    }
+}
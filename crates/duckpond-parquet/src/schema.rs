@@ -8,6 +8,12 @@ pub struct SchemaEvolution {
     // TODO: Add schema mapping and evolution logic
 }
 
+impl Default for SchemaEvolution {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SchemaEvolution {
     pub fn new() -> Self {
         Self {}
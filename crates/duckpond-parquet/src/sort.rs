@@ -0,0 +1,187 @@
+//! Sorting and Z-order clustering of RecordBatches before they are written
+
+use crate::{ParquetError, Result};
+use arrow::array::{ArrayRef, RecordBatch, UInt32Array, UInt64Array};
+use arrow::compute::{
+    concat_batches, lexsort_to_indices, rank, sort_to_indices, take_record_batch, SortColumn,
+    SortOptions,
+};
+use duckpond_core::SortOrder;
+
+/// Sort RecordBatches according to a table sort order
+///
+/// The batches are concatenated and returned as a single sorted batch so
+/// that the ordering holds across the whole file, not just within a batch.
+pub fn sort_batches(batches: &[RecordBatch], sort_order: &SortOrder) -> Result<Vec<RecordBatch>> {
+    if batches.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let batch = concat_batches(&batches[0].schema(), batches)?;
+    let indices = match sort_order {
+        SortOrder::Linear(keys) => {
+            let columns = keys
+                .iter()
+                .map(|key| {
+                    Ok(SortColumn {
+                        values: column_by_name(&batch, &key.column)?,
+                        options: Some(SortOptions {
+                            descending: key.descending,
+                            nulls_first: key.nulls_first,
+                        }),
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            lexsort_to_indices(&columns, None)?
+        }
+        SortOrder::ZOrder(columns) => zorder_indices(&batch, columns)?,
+    };
+
+    Ok(vec![take_record_batch(&batch, &indices)?])
+}
+
+/// Compute the permutation that orders rows along a Z-order curve
+///
+/// Each column is first mapped to its rank so that columns with different
+/// types and value distributions contribute equally, then the ranks are
+/// scaled to a fixed number of bits and interleaved into a single key.
+fn zorder_indices(batch: &RecordBatch, columns: &[String]) -> Result<UInt32Array> {
+    let num_rows = batch.num_rows() as u64;
+    let bits = (u64::BITS as usize / columns.len().max(1)).min(32);
+
+    let scaled_ranks = columns
+        .iter()
+        .map(|name| {
+            let ranks = rank(column_by_name(batch, name)?.as_ref(), None)?;
+            // Ranks are 1-based; scale them into [0, 2^bits)
+            Ok(ranks
+                .into_iter()
+                .map(|r| ((r as u64 - 1) << bits) / num_rows.max(1))
+                .collect::<Vec<u64>>())
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let keys: UInt64Array = (0..batch.num_rows())
+        .map(|row| {
+            let mut key = 0u64;
+            for bit in (0..bits).rev() {
+                for ranks in &scaled_ranks {
+                    key = (key << 1) | ((ranks[row] >> bit) & 1);
+                }
+            }
+            key
+        })
+        .collect();
+
+    Ok(sort_to_indices(&keys, None, None)?)
+}
+
+fn column_by_name(batch: &RecordBatch, name: &str) -> Result<ArrayRef> {
+    batch
+        .column_by_name(name)
+        .cloned()
+        .ok_or_else(|| ParquetError::SchemaMismatch {
+            message: format!("Sort column '{}' not found in data", name),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Array, Int64Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    fn batch(a: Vec<i64>, b: Vec<&str>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, false),
+            Field::new("b", DataType::Utf8, false),
+        ]));
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(a)),
+                Arc::new(StringArray::from(b)),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn values(batch: &RecordBatch) -> Vec<i64> {
+        batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap()
+            .values()
+            .to_vec()
+    }
+
+    #[test]
+    fn test_linear_sort_across_batches() {
+        let batches = vec![
+            batch(vec![3, 1], vec!["x", "y"]),
+            batch(vec![2, 1], vec!["z", "x"]),
+        ];
+        let order: SortOrder = "a, b DESC".parse().unwrap();
+
+        let sorted = sort_batches(&batches, &order).unwrap();
+        assert_eq!(sorted.len(), 1);
+        assert_eq!(values(&sorted[0]), vec![1, 1, 2, 3]);
+
+        let b = sorted[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(b.value(0), "y");
+        assert_eq!(b.value(1), "x");
+    }
+
+    #[test]
+    fn test_zorder_groups_nearby_points() {
+        // A 4x4 grid in row-major order; Z-order visits each 2x2 quadrant in turn
+        let mut xs = Vec::new();
+        let mut ys = Vec::new();
+        for y in 0..4i64 {
+            for x in 0..4i64 {
+                xs.push(x);
+                ys.push(y);
+            }
+        }
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("x", DataType::Int64, false),
+            Field::new("y", DataType::Int64, false),
+        ]));
+        let grid = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(xs)),
+                Arc::new(Int64Array::from(ys)),
+            ],
+        )
+        .unwrap();
+
+        let order: SortOrder = "ZORDER(x, y)".parse().unwrap();
+        let sorted = sort_batches(&[grid], &order).unwrap();
+
+        let x = values(&sorted[0]);
+        let y = sorted[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap()
+            .values()
+            .to_vec();
+        // The first quadrant holds exactly the points with x < 2 and y < 2
+        for i in 0..4 {
+            assert!(x[i] < 2 && y[i] < 2, "row {} = ({}, {})", i, x[i], y[i]);
+        }
+    }
+
+    #[test]
+    fn test_unknown_column() {
+        let order: SortOrder = "missing".parse().unwrap();
+        assert!(sort_batches(&[batch(vec![1], vec!["x"])], &order).is_err());
+    }
+}
@@ -1,11 +1,19 @@
 //! Parquet file statistics collection
 
 use crate::{ParquetColumnStats, ParquetError, ParquetFileStats, Result};
+use arrow::array::{Array, ArrayRef, Float32Array, Float64Array, RecordBatch};
+use arrow::compute::{concat, sort_to_indices, SortOptions};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::util::display::array_value_to_string;
 use bytes::Bytes;
 use duckpond_storage::FileSystem;
+use parquet::arrow::arrow_reader::statistics::StatisticsConverter;
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
 
 /// Collect statistics from an existing Parquet file
+///
+/// Column statistics come from the row group statistics in the footer; the
+/// file's data pages are not decoded.
 pub async fn collect_file_statistics(
     filesystem: &dyn FileSystem,
     path: &str,
@@ -15,132 +23,162 @@ pub async fn collect_file_statistics(
         .read_file(path)
         .await
         .map_err(|e| ParquetError::StorageError(e.to_string()))?;
+    let file_size_bytes = data.len() as u64;
+    let footer_size = footer_size(&data).ok_or_else(|| ParquetError::FileCorruption {
+        path: path.to_string(),
+    })?;
 
-    // 2. Get file metadata first
-    let file_metadata = filesystem
-        .file_metadata(path)
-        .await
-        .map_err(|e| ParquetError::StorageError(e.to_string()))?;
-
-    // 3. Create ParquetRecordBatchReaderBuilder from the data
+    // 2. Parse the footer
     let bytes = Bytes::from(data);
-    let builder = ParquetRecordBatchReaderBuilder::try_new(bytes)
-        .map_err(|e| ParquetError::ParquetError(e))?;
-
-    // 4. Get the parquet metadata
+    let builder =
+        ParquetRecordBatchReaderBuilder::try_new(bytes).map_err(ParquetError::ParquetError)?;
     let parquet_metadata = builder.metadata();
     let schema = builder.schema();
+    let row_groups = parquet_metadata.row_groups();
+    let record_count: u64 = row_groups.iter().map(|rg| rg.num_rows() as u64).sum();
 
-    // 5. Collect basic file statistics
-    let row_group_count = parquet_metadata.num_row_groups() as u32;
-    let mut total_record_count = 0u64;
-
-    for rg_index in 0..row_group_count {
-        let row_group = parquet_metadata.row_group(rg_index as usize);
-        total_record_count += row_group.num_rows() as u64;
-    }
-
-    // 6. Collect column statistics
+    // 3. Combine the row group statistics of each column. Nested columns
+    // have no statistics of their own and only get counts.
     let mut column_stats = Vec::new();
     for (col_index, field) in schema.fields().iter().enumerate() {
-        let mut value_count = 0u64;
-        let mut null_count = 0u64;
-        let min_value: Option<String> = None;
-        let max_value: Option<String> = None;
-
-        // Aggregate statistics across all row groups
-        for rg_index in 0..row_group_count {
-            let row_group_metadata = parquet_metadata.row_group(rg_index as usize);
-            if col_index < row_group_metadata.num_columns() {
-                let column_metadata = row_group_metadata.column(col_index);
-                if let Some(_stats) = column_metadata.statistics() {
-                    // TODO: Extract statistics when API is stable
-                    value_count += total_record_count;
-                    null_count += 0;
-
-                    // TODO: Extract min/max values when API is stable
-                    // For now, we'll skip detailed statistics collection
-                }
-            }
-        }
+        let converter = StatisticsConverter::try_new(
+            field.name(),
+            schema,
+            parquet_metadata.file_metadata().schema_descr(),
+        )
+        .map_err(ParquetError::ParquetError)?;
+        let null_count: u64 = converter
+            .row_group_null_counts(row_groups)
+            .map_err(ParquetError::ParquetError)?
+            .iter()
+            .flatten()
+            .sum();
+        let mins = converter
+            .row_group_mins(row_groups)
+            .map_err(ParquetError::ParquetError)?;
+        let maxes = converter
+            .row_group_maxes(row_groups)
+            .map_err(ParquetError::ParquetError)?;
+        // A row group without statistics leaves the bounds unknown
+        let bounded = !row_groups.is_empty() && mins.null_count() == 0 && maxes.null_count() == 0;
+        let (min_value, max_value) = if bounded {
+            (min_max_strings(&mins, 0)?.0, min_max_strings(&maxes, 0)?.1)
+        } else {
+            (None, None)
+        };
 
         column_stats.push(ParquetColumnStats {
             column_name: field.name().clone(),
-            column_id: col_index as i64,
-            value_count,
+            column_id: field_column_id(field, col_index),
+            value_count: record_count.saturating_sub(null_count),
             null_count,
-            nan_count: 0, // TODO: Calculate NaN count for floating point types
+            nan_count: 0,
             min_value,
             max_value,
-            distinct_count: None, // TODO: Calculate distinct count if needed
+            distinct_count: None,
         });
     }
 
     Ok(ParquetFileStats {
         file_path: path.to_string(),
-        file_size_bytes: file_metadata.size,
-        record_count: total_record_count,
-        row_group_count,
+        file_size_bytes,
+        footer_size: Some(footer_size),
+        record_count,
+        row_group_count: row_groups.len() as u32,
         column_stats,
     })
 }
 
-/// Convert raw bytes to string representation based on physical type
-#[allow(dead_code)] // TODO: Will be used when statistics extraction is implemented
-fn bytes_to_string(bytes: &[u8], physical_type: parquet::basic::Type) -> String {
-    use parquet::basic::Type;
-
-    match physical_type {
-        Type::BOOLEAN => {
-            if !bytes.is_empty() {
-                (bytes[0] != 0).to_string()
-            } else {
-                "null".to_string()
-            }
-        }
-        Type::INT32 => {
-            if bytes.len() >= 4 {
-                let value = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
-                value.to_string()
-            } else {
-                "null".to_string()
-            }
-        }
-        Type::INT64 => {
-            if bytes.len() >= 8 {
-                let value = i64::from_le_bytes([
-                    bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
-                ]);
-                value.to_string()
-            } else {
-                "null".to_string()
-            }
-        }
-        Type::FLOAT => {
-            if bytes.len() >= 4 {
-                let value = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
-                value.to_string()
-            } else {
-                "null".to_string()
-            }
-        }
-        Type::DOUBLE => {
-            if bytes.len() >= 8 {
-                let value = f64::from_le_bytes([
-                    bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
-                ]);
-                value.to_string()
-            } else {
-                "null".to_string()
-            }
-        }
-        Type::BYTE_ARRAY | Type::FIXED_LEN_BYTE_ARRAY => {
-            // For string/binary data, convert to UTF-8 string if possible
-            String::from_utf8_lossy(bytes).to_string()
-        }
-        Type::INT96 => {
-            // INT96 is typically used for timestamps, convert to hex for now
-            hex::encode(bytes)
-        }
+/// Size of the footer metadata of a Parquet file, from the length stored
+/// before the trailing `PAR1` magic
+pub fn footer_size(data: &[u8]) -> Option<u64> {
+    let tail = data.len().checked_sub(8).map(|start| &data[start..])?;
+    if &tail[4..] != b"PAR1" {
+        return None;
     }
+    Some(u32::from_le_bytes([tail[0], tail[1], tail[2], tail[3]]) as u64)
+}
+
+/// Compute column statistics directly from the data being written
+///
+/// Min/max values use Arrow's display representation of the column type;
+/// they are left empty for types that have no total ordering (e.g. structs).
+pub fn column_stats_from_batches(
+    schema: &Schema,
+    batches: &[RecordBatch],
+) -> Result<Vec<ParquetColumnStats>> {
+    let mut column_stats = Vec::with_capacity(schema.fields().len());
+
+    for (col_index, field) in schema.fields().iter().enumerate() {
+        let arrays: Vec<&dyn Array> = batches
+            .iter()
+            .map(|batch| batch.column(col_index).as_ref())
+            .collect();
+        let column = concat(&arrays)?;
+
+        let null_count = column.null_count() as u64;
+        let nan_count = count_nans(&column);
+        let (min_value, max_value) = min_max_strings(&column, nan_count)?;
+
+        column_stats.push(ParquetColumnStats {
+            column_name: field.name().clone(),
+            column_id: field_column_id(field, col_index),
+            value_count: (column.len() as u64) - null_count,
+            null_count,
+            nan_count,
+            min_value,
+            max_value,
+            distinct_count: None,
+        });
+    }
+
+    Ok(column_stats)
+}
+
+/// Column ID of a field: its Parquet field ID or, for files written without
+/// field IDs, its position counting from 1 like default DuckLake column IDs
+fn field_column_id(field: &Field, col_index: usize) -> i64 {
+    field
+        .metadata()
+        .get(crate::PARQUET_FIELD_ID_META_KEY)
+        .and_then(|id| id.parse().ok())
+        .unwrap_or(col_index as i64 + 1)
+}
+
+fn count_nans(column: &ArrayRef) -> u64 {
+    match column.data_type() {
+        DataType::Float32 => column
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .map(|a| a.iter().flatten().filter(|v| v.is_nan()).count() as u64)
+            .unwrap_or(0),
+        DataType::Float64 => column
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .map(|a| a.iter().flatten().filter(|v| v.is_nan()).count() as u64)
+            .unwrap_or(0),
+        _ => 0,
+    }
+}
+
+fn min_max_strings(column: &ArrayRef, nan_count: u64) -> Result<(Option<String>, Option<String>)> {
+    // NaN sorts after every other value, so exclude it from the maximum
+    let non_null = column.len() - column.null_count() - nan_count as usize;
+    if non_null == 0 {
+        return Ok((None, None));
+    }
+
+    let options = SortOptions {
+        descending: false,
+        nulls_first: false,
+    };
+    let indices = match sort_to_indices(column, Some(options), None) {
+        Ok(indices) => indices,
+        // Types without an ordering simply don't get min/max statistics
+        Err(_) => return Ok((None, None)),
+    };
+
+    let min = array_value_to_string(column, indices.value(0) as usize)?;
+    let max = array_value_to_string(column, indices.value(non_null - 1) as usize)?;
+    Ok((Some(min), Some(max)))
 }
@@ -1,29 +1,48 @@
 //! Parquet file writing functionality
 
 use crate::{
-    CompressionType, ParquetColumnStats, ParquetError, ParquetFileStats, ParquetWriteConfig, Result,
+    sort, statistics, CompressionType, ParquetError, ParquetFileStats, ParquetWriteConfig, Result,
 };
 use arrow::array::RecordBatch;
+use arrow::datatypes::SchemaRef;
 use duckpond_storage::FileSystem;
 use parquet::arrow::ArrowWriter;
 use parquet::basic::Compression;
 use parquet::file::properties::WriterProperties;
-use std::io::Cursor;
+use std::io::{Cursor, Write};
 
-/// Write RecordBatches to a Parquet file and return statistics
-pub async fn write_parquet_file(
-    filesystem: &dyn FileSystem,
-    path: &str,
-    batches: Vec<RecordBatch>,
-    config: ParquetWriteConfig,
-) -> Result<ParquetFileStats> {
-    if batches.is_empty() {
-        return Err(ParquetError::SchemaMismatch {
-            message: "Cannot write empty batches".to_string(),
-        });
+/// Write RecordBatches as a Parquet file to `writer`, such as a local file
+/// outside of storage. Unlike [`write_parquet_file`], an empty file holding
+/// only the schema can be written.
+pub fn write_parquet<W: Write + Send>(
+    writer: W,
+    schema: SchemaRef,
+    batches: &[RecordBatch],
+    config: &ParquetWriteConfig,
+) -> Result<()> {
+    let mut writer = arrow_writer(writer, schema, config)?;
+    for batch in batches {
+        writer.write(batch)?;
     }
+    writer.close()?;
+    Ok(())
+}
 
-    // 1. Create WriterProperties with the specified configuration
+/// A Parquet writer to `writer` taking batches one at a time, for data too
+/// large to collect first
+pub fn arrow_writer<W: Write + Send>(
+    writer: W,
+    schema: SchemaRef,
+    config: &ParquetWriteConfig,
+) -> Result<ArrowWriter<W>> {
+    Ok(ArrowWriter::try_new(
+        writer,
+        schema,
+        Some(writer_properties(config)),
+    )?)
+}
+
+fn writer_properties(config: &ParquetWriteConfig) -> WriterProperties {
     let compression = match config.compression {
         CompressionType::None => Compression::UNCOMPRESSED,
         CompressionType::Snappy => Compression::SNAPPY,
@@ -32,7 +51,7 @@ pub async fn write_parquet_file(
         CompressionType::Zstd => Compression::ZSTD(Default::default()),
     };
 
-    let props = WriterProperties::builder()
+    WriterProperties::builder()
         .set_compression(compression)
         .set_max_row_group_size(config.row_group_size)
         .set_write_batch_size(1024)
@@ -41,7 +60,30 @@ pub async fn write_parquet_file(
         } else {
             parquet::file::properties::EnabledStatistics::None
         })
-        .build();
+        .build()
+}
+
+/// Write RecordBatches to a Parquet file and return statistics
+pub async fn write_parquet_file(
+    filesystem: &dyn FileSystem,
+    path: &str,
+    batches: Vec<RecordBatch>,
+    config: ParquetWriteConfig,
+) -> Result<ParquetFileStats> {
+    if batches.is_empty() {
+        return Err(ParquetError::SchemaMismatch {
+            message: "Cannot write empty batches".to_string(),
+        });
+    }
+
+    // Cluster the rows according to the table sort order, if any
+    let batches = match &config.sort_order {
+        Some(sort_order) => sort::sort_batches(&batches, sort_order)?,
+        None => batches,
+    };
+
+    // 1. Create WriterProperties with the specified configuration
+    let props = writer_properties(&config);
 
     // 2. Create an in-memory buffer to write to
     let mut buffer = Vec::new();
@@ -50,7 +92,7 @@ pub async fn write_parquet_file(
     // 3. Create ArrowWriter and write batches
     let schema = batches[0].schema();
     let mut writer = ArrowWriter::try_new(cursor, schema.clone(), Some(props))
-        .map_err(|e| ParquetError::ParquetError(e))?;
+        .map_err(ParquetError::ParquetError)?;
 
     let mut total_record_count = 0u64;
     for batch in &batches {
@@ -62,13 +104,11 @@ pub async fn write_parquet_file(
         }
 
         total_record_count += batch.num_rows() as u64;
-        writer
-            .write(&batch)
-            .map_err(|e| ParquetError::ParquetError(e))?;
+        writer.write(batch).map_err(ParquetError::ParquetError)?;
     }
 
     // 4. Close the writer to finalize the file
-    let _writer_metadata = writer.close().map_err(|e| ParquetError::ParquetError(e))?;
+    let writer_metadata = writer.close().map_err(ParquetError::ParquetError)?;
 
     // 5. Upload the file to storage
     filesystem
@@ -76,31 +116,20 @@ pub async fn write_parquet_file(
         .await
         .map_err(|e| ParquetError::StorageError(e.to_string()))?;
 
-    // 6. Collect basic statistics
+    // 6. Collect column statistics from the written data
     let file_size_bytes = buffer.len() as u64;
-    let row_group_count = 1; // Simplified for now
-
-    let mut column_stats = Vec::new();
-    if config.enable_statistics {
-        for (col_index, field) in schema.fields().iter().enumerate() {
-            // Create basic column statistics
-            // TODO: Extract real statistics from the parquet metadata when the API is more stable
-            column_stats.push(ParquetColumnStats {
-                column_name: field.name().clone(),
-                column_id: col_index as i64,
-                value_count: total_record_count,
-                null_count: 0, // TODO: Calculate from actual data
-                nan_count: 0,
-                min_value: None, // TODO: Calculate from actual data
-                max_value: None, // TODO: Calculate from actual data
-                distinct_count: None,
-            });
-        }
-    }
+    let row_group_count = writer_metadata.row_groups.len() as u32;
+
+    let column_stats = if config.enable_statistics {
+        statistics::column_stats_from_batches(&schema, &batches)?
+    } else {
+        Vec::new()
+    };
 
     Ok(ParquetFileStats {
         file_path: path.to_string(),
         file_size_bytes,
+        footer_size: statistics::footer_size(&buffer),
         record_count: total_record_count,
         row_group_count,
         column_stats,
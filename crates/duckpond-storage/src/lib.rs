@@ -76,7 +76,7 @@ pub enum StorageBackend {
 
 /// Trait for file system operations
 #[async_trait]
-pub trait FileSystem {
+pub trait FileSystem: Send + Sync {
     /// Read a file and return its contents
     async fn read_file(&self, path: &str) -> Result<Vec<u8>>;
 
@@ -121,31 +121,37 @@ impl FileSystem for LocalFileSystem {
             let metadata = self.file_metadata(prefix).await?;
             files.push(metadata);
         } else if full_path.is_dir() {
-            let mut entries =
-                fs::read_dir(&full_path)
-                    .await
-                    .map_err(|e| StorageError::BackendError {
-                        message: e.to_string(),
-                    })?;
-
-            while let Some(entry) =
-                entries
-                    .next_entry()
-                    .await
-                    .map_err(|e| StorageError::BackendError {
-                        message: e.to_string(),
-                    })?
-            {
-                let file_path = entry.path();
-                if file_path.is_file() {
-                    let relative_path = file_path
-                        .strip_prefix(&self.base_path)
-                        .unwrap_or(&file_path)
-                        .to_string_lossy()
-                        .to_string();
-
-                    if let Ok(metadata) = self.file_metadata(&relative_path).await {
-                        files.push(metadata);
+            // Walk subdirectories too, matching the prefix listing of object stores
+            let mut directories = vec![full_path];
+            while let Some(directory) = directories.pop() {
+                let mut entries =
+                    fs::read_dir(&directory)
+                        .await
+                        .map_err(|e| StorageError::BackendError {
+                            message: e.to_string(),
+                        })?;
+
+                while let Some(entry) =
+                    entries
+                        .next_entry()
+                        .await
+                        .map_err(|e| StorageError::BackendError {
+                            message: e.to_string(),
+                        })?
+                {
+                    let file_path = entry.path();
+                    if file_path.is_dir() {
+                        directories.push(file_path);
+                    } else if file_path.is_file() {
+                        let relative_path = file_path
+                            .strip_prefix(&self.base_path)
+                            .unwrap_or(&file_path)
+                            .to_string_lossy()
+                            .to_string();
+
+                        if let Ok(metadata) = self.file_metadata(&relative_path).await {
+                            files.push(metadata);
+                        }
                     }
                 }
             }
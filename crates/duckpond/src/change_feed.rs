@@ -0,0 +1,237 @@
+//! Row-level changes of a table between two snapshots
+//!
+//! Changes are derived from file lifetimes: a data file added in the range
+//! inserts its rows, a delete file added in the range deletes the positions it
+//! lists beyond those of the delete file it replaces, and a data file removed
+//! in the range deletes the rows still live in it. A row deleted and inserted
+//! again with the same row ID in one snapshot (a rewrite, e.g. compaction) is
+//! not reported.
+
+use duckpond_core::models::TableFileHistory;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Whether a row was inserted or deleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChangeType {
+    Insert,
+    Delete,
+}
+
+impl ChangeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeType::Insert => "insert",
+            ChangeType::Delete => "delete",
+        }
+    }
+}
+
+impl fmt::Display for ChangeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A row inserted or deleted by a snapshot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowChange {
+    pub snapshot_id: i64,
+    pub data_file_id: i64,
+    /// Position of the row within its data file
+    pub position: i64,
+    pub rowid: i64,
+    pub change_type: ChangeType,
+}
+
+/// Compute the rows changed after snapshot `from` up to and including `to`
+///
+/// `deleted_positions` holds the row positions listed by each delete file of
+/// the history, keyed by delete file ID. Changes are ordered by snapshot,
+/// data file, change type and position.
+pub fn row_changes(
+    history: &TableFileHistory,
+    deleted_positions: &HashMap<i64, Vec<i64>>,
+    from: i64,
+    to: i64,
+) -> Vec<RowChange> {
+    let in_range = |snapshot: i64| snapshot > from && snapshot <= to;
+    let deleted_at = |data_file_id: i64, snapshot: i64| -> HashSet<i64> {
+        history
+            .delete_files
+            .iter()
+            .filter(|d| {
+                d.data_file_id == data_file_id
+                    && d.begin_snapshot <= snapshot
+                    && d.end_snapshot.is_none_or(|end| end > snapshot)
+            })
+            .filter_map(|d| deleted_positions.get(&d.delete_file_id))
+            .flatten()
+            .copied()
+            .collect()
+    };
+
+    let mut changes = Vec::new();
+    for file in &history.data_files {
+        let mut live_rows = |snapshot: i64, change_type: ChangeType| {
+            let deleted = deleted_at(file.data_file_id, snapshot);
+            for position in (0..file.record_count).filter(|p| !deleted.contains(p)) {
+                changes.push(RowChange {
+                    snapshot_id: match change_type {
+                        ChangeType::Insert => snapshot,
+                        ChangeType::Delete => snapshot + 1,
+                    },
+                    data_file_id: file.data_file_id,
+                    position,
+                    rowid: file.row_id_start + position,
+                    change_type,
+                });
+            }
+        };
+        if in_range(file.begin_snapshot) {
+            live_rows(file.begin_snapshot, ChangeType::Insert);
+        }
+        if let Some(end) = file.end_snapshot.filter(|end| in_range(*end)) {
+            live_rows(end - 1, ChangeType::Delete);
+        }
+    }
+
+    for delete_file in history
+        .delete_files
+        .iter()
+        .filter(|d| in_range(d.begin_snapshot))
+    {
+        // Rows of a data file added in the same snapshot were never visible
+        let Some(file) = history.data_files.iter().find(|f| {
+            f.data_file_id == delete_file.data_file_id
+                && f.begin_snapshot < delete_file.begin_snapshot
+        }) else {
+            continue;
+        };
+        let already_deleted = deleted_at(file.data_file_id, delete_file.begin_snapshot - 1);
+        let positions = deleted_positions
+            .get(&delete_file.delete_file_id)
+            .into_iter()
+            .flatten()
+            .filter(|p| !already_deleted.contains(p));
+        for &position in positions {
+            changes.push(RowChange {
+                snapshot_id: delete_file.begin_snapshot,
+                data_file_id: file.data_file_id,
+                position,
+                rowid: file.row_id_start + position,
+                change_type: ChangeType::Delete,
+            });
+        }
+    }
+
+    let keys = |change_type: ChangeType| -> HashSet<(i64, i64)> {
+        changes
+            .iter()
+            .filter(|c| c.change_type == change_type)
+            .map(|c| (c.snapshot_id, c.rowid))
+            .collect()
+    };
+    let rewritten: HashSet<(i64, i64)> = keys(ChangeType::Insert)
+        .intersection(&keys(ChangeType::Delete))
+        .copied()
+        .collect();
+    changes.retain(|c| !rewritten.contains(&(c.snapshot_id, c.rowid)));
+
+    changes.sort_by_key(|c| (c.snapshot_id, c.data_file_id, c.change_type, c.position));
+    changes.dedup();
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use duckpond_core::models::{DataFileEntry, DeleteFileEntry};
+
+    fn data_file(
+        id: i64,
+        rows: i64,
+        row_id_start: i64,
+        begin: i64,
+        end: Option<i64>,
+    ) -> DataFileEntry {
+        DataFileEntry {
+            data_file_id: id,
+            path: format!("{}.parquet", id),
+            path_is_relative: true,
+            record_count: rows,
+            row_id_start,
+            begin_snapshot: begin,
+            end_snapshot: end,
+        }
+    }
+
+    fn delete_file(id: i64, data_file_id: i64, begin: i64, end: Option<i64>) -> DeleteFileEntry {
+        DeleteFileEntry {
+            delete_file_id: id,
+            data_file_id,
+            path: format!("delete_{}.parquet", id),
+            path_is_relative: true,
+            begin_snapshot: begin,
+            end_snapshot: end,
+        }
+    }
+
+    fn summary(changes: &[RowChange]) -> Vec<(i64, i64, ChangeType)> {
+        changes
+            .iter()
+            .map(|c| (c.snapshot_id, c.rowid, c.change_type))
+            .collect()
+    }
+
+    #[test]
+    fn test_inserts_and_deletes() {
+        use ChangeType::*;
+
+        // File 1 (rows 0-2) is added in snapshot 1, loses row 1 in snapshot 2
+        // and row 2 in snapshot 3 (delete file 11 replaces 10), and is
+        // dropped in snapshot 4. File 2 (rows 3-4) is added in snapshot 3.
+        let history = TableFileHistory {
+            data_files: vec![data_file(1, 3, 0, 1, Some(4)), data_file(2, 2, 3, 3, None)],
+            delete_files: vec![
+                delete_file(10, 1, 2, Some(3)),
+                delete_file(11, 1, 3, Some(4)),
+            ],
+        };
+        let positions = HashMap::from([(10, vec![1]), (11, vec![1, 2])]);
+
+        assert_eq!(
+            summary(&row_changes(&history, &positions, 0, 4)),
+            vec![
+                (1, 0, Insert),
+                (1, 1, Insert),
+                (1, 2, Insert),
+                (2, 1, Delete),
+                (3, 2, Delete),
+                (3, 3, Insert),
+                (3, 4, Insert),
+                (4, 0, Delete),
+            ]
+        );
+        assert_eq!(
+            summary(&row_changes(&history, &positions, 2, 3)),
+            vec![(3, 2, Delete), (3, 3, Insert), (3, 4, Insert)]
+        );
+        assert!(row_changes(&history, &positions, 4, 4).is_empty());
+    }
+
+    #[test]
+    fn test_rewrites_are_not_changes() {
+        // Snapshot 2 rewrites file 1 into file 2 keeping the row IDs
+        let history = TableFileHistory {
+            data_files: vec![data_file(1, 2, 0, 1, Some(2)), data_file(2, 2, 0, 2, None)],
+            delete_files: vec![],
+        };
+
+        let changes = row_changes(&history, &HashMap::new(), 0, 2);
+        assert_eq!(
+            summary(&changes),
+            vec![(1, 0, ChangeType::Insert), (1, 1, ChangeType::Insert)]
+        );
+    }
+}
@@ -0,0 +1,201 @@
+//! Table export to Parquet, CSV, newline-delimited JSON or Arrow IPC
+
+use crate::filter::Filter;
+use arrow::array::RecordBatch;
+use arrow::datatypes::SchemaRef;
+use duckpond_core::error::DuckPondError;
+use duckpond_core::Result;
+use duckpond_parquet::{ArrowWriter, ParquetWriteConfig};
+use std::io::Write;
+use std::path::Path;
+
+/// File formats of [`crate::Lakehouse::export`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Parquet,
+    /// CSV with a header line; nested columns cannot be exported as CSV
+    Csv,
+    /// Newline-delimited JSON
+    Json,
+    /// Arrow IPC file
+    Arrow,
+}
+
+impl ExportFormat {
+    /// The format matching a file extension, if any
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "parquet" => Some(Self::Parquet),
+            "csv" => Some(Self::Csv),
+            "json" | "jsonl" | "ndjson" => Some(Self::Json),
+            "arrow" | "ipc" | "feather" => Some(Self::Arrow),
+            _ => None,
+        }
+    }
+}
+
+/// Options of [`crate::Lakehouse::export`]
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Only export the rows matching the filter
+    pub filter: Option<Filter>,
+}
+
+/// Write batches, all of `schema`, to `writer` in `format`
+pub fn write_batches<W: Write + Send>(
+    writer: W,
+    format: ExportFormat,
+    schema: SchemaRef,
+    batches: &[RecordBatch],
+) -> Result<()> {
+    let mut writer = BatchWriter::try_new(writer, format, schema)?;
+    for batch in batches {
+        writer.write(batch)?;
+    }
+    writer.finish()
+}
+
+/// Writes batches of one schema to a writer in an [`ExportFormat`] as they
+/// come, so that they need not all be held at once
+pub struct BatchWriter<W: Write + Send> {
+    inner: Inner<W>,
+    schema: SchemaRef,
+}
+
+enum Inner<W: Write + Send> {
+    Parquet(ArrowWriter<W>),
+    Csv {
+        csv: Box<arrow::csv::Writer<W>>,
+        /// The header is written with the first batch
+        wrote_header: bool,
+    },
+    Json(arrow::json::LineDelimitedWriter<W>),
+    Arrow(arrow::ipc::writer::FileWriter<W>),
+}
+
+impl<W: Write + Send> BatchWriter<W> {
+    pub fn try_new(writer: W, format: ExportFormat, schema: SchemaRef) -> Result<Self> {
+        let inner = match format {
+            ExportFormat::Parquet => Inner::Parquet(
+                duckpond_parquet::writer::arrow_writer(
+                    writer,
+                    schema.clone(),
+                    &ParquetWriteConfig::default(),
+                )
+                .map_err(|e| export_error(e.to_string()))?,
+            ),
+            ExportFormat::Csv => Inner::Csv {
+                csv: Box::new(
+                    arrow::csv::WriterBuilder::new()
+                        .with_header(true)
+                        .build(writer),
+                ),
+                wrote_header: false,
+            },
+            ExportFormat::Json => Inner::Json(arrow::json::LineDelimitedWriter::new(writer)),
+            ExportFormat::Arrow => Inner::Arrow(
+                arrow::ipc::writer::FileWriter::try_new(writer, &schema)
+                    .map_err(|e| export_error(e.to_string()))?,
+            ),
+        };
+        Ok(Self { inner, schema })
+    }
+
+    pub fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        match &mut self.inner {
+            Inner::Parquet(parquet) => parquet
+                .write(batch)
+                .map_err(|e| export_error(e.to_string())),
+            Inner::Csv { csv, wrote_header } => {
+                *wrote_header = true;
+                csv.write(batch).map_err(|e| export_error(e.to_string()))
+            }
+            Inner::Json(json) => json.write(batch).map_err(|e| export_error(e.to_string())),
+            Inner::Arrow(ipc) => ipc.write(batch).map_err(|e| export_error(e.to_string())),
+        }
+    }
+
+    /// Write what the format needs after the last batch
+    pub fn finish(self) -> Result<()> {
+        match self.inner {
+            Inner::Parquet(parquet) => parquet
+                .close()
+                .map(|_| ())
+                .map_err(|e| export_error(e.to_string())),
+            Inner::Csv {
+                mut csv,
+                wrote_header,
+            } => {
+                if !wrote_header {
+                    csv.write(&RecordBatch::new_empty(self.schema))
+                        .map_err(|e| export_error(e.to_string()))?;
+                }
+                Ok(())
+            }
+            Inner::Json(mut json) => json.finish().map_err(|e| export_error(e.to_string())),
+            Inner::Arrow(mut ipc) => ipc.finish().map_err(|e| export_error(e.to_string())),
+        }
+    }
+}
+
+fn export_error(message: String) -> DuckPondError {
+    DuckPondError::ConfigError(format!("Export failed: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Int64Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_write_batches() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        let batches = vec![RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2])),
+                Arc::new(StringArray::from(vec![Some("a"), None])),
+            ],
+        )
+        .unwrap()];
+
+        let mut csv = Vec::new();
+        write_batches(&mut csv, ExportFormat::Csv, schema.clone(), &batches).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "id,name\n1,a\n2,\n");
+
+        let mut empty = Vec::new();
+        write_batches(&mut empty, ExportFormat::Csv, schema.clone(), &[]).unwrap();
+        assert_eq!(String::from_utf8(empty).unwrap(), "id,name\n");
+
+        let mut json = Vec::new();
+        write_batches(&mut json, ExportFormat::Json, schema.clone(), &batches).unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            "{\"id\":1,\"name\":\"a\"}\n{\"id\":2}\n"
+        );
+
+        let mut parquet = Vec::new();
+        write_batches(
+            &mut parquet,
+            ExportFormat::Parquet,
+            schema.clone(),
+            &batches,
+        )
+        .unwrap();
+        let read = duckpond_parquet::reader::read_parquet_schema(parquet).unwrap();
+        assert_eq!(read.fields().len(), 2);
+
+        let mut ipc = Vec::new();
+        write_batches(&mut ipc, ExportFormat::Arrow, schema, &batches).unwrap();
+        let reader =
+            arrow::ipc::reader::FileReader::try_new(std::io::Cursor::new(ipc), None).unwrap();
+        let read: Vec<_> = reader.map(|b| b.unwrap()).collect();
+        assert_eq!(read, batches);
+    }
+}
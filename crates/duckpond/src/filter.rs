@@ -0,0 +1,571 @@
+//! Row filters
+//!
+//! A filter is a boolean expression over a table's top-level columns, in a
+//! small SQL-like syntax:
+//!
+//! ```text
+//! status = 'active' AND (amount >= 100 OR vip IS NOT NULL)
+//! NOT event_time < '2026-01-01'
+//! ```
+//!
+//! Literals are converted to the type of the column they are compared with,
+//! so `'2026-01-01'` compares as a date against a date column. As in SQL,
+//! a comparison with NULL is unknown and the row is left out.
+
+use arrow::array::{Array, ArrayRef, BooleanArray, RecordBatch, Scalar, StringArray};
+use arrow::compute::kernels::cmp;
+use arrow::compute::{and_kleene, cast_with_options, filter_record_batch, is_not_null, is_null};
+use arrow::compute::{not, or_kleene, CastOptions};
+use arrow::datatypes::{DataType, Schema};
+use duckpond_core::error::DuckPondError;
+use duckpond_core::models::DataFileColumnStats;
+use duckpond_core::Result;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Comparison operators of a [`Filter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+impl CompareOp {
+    /// The operator true exactly where this one is false, NULLs aside
+    fn negate(self) -> Self {
+        match self {
+            Self::Eq => Self::NotEq,
+            Self::NotEq => Self::Eq,
+            Self::Lt => Self::GtEq,
+            Self::LtEq => Self::Gt,
+            Self::Gt => Self::LtEq,
+            Self::GtEq => Self::Lt,
+        }
+    }
+}
+
+impl fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Eq => "=",
+            Self::NotEq => "!=",
+            Self::Lt => "<",
+            Self::LtEq => "<=",
+            Self::Gt => ">",
+            Self::GtEq => ">=",
+        })
+    }
+}
+
+/// A boolean expression selecting rows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// Compare a column with a literal, given as text
+    Compare {
+        column: String,
+        op: CompareOp,
+        value: String,
+    },
+    IsNull {
+        column: String,
+        negated: bool,
+    },
+    Not(Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+}
+
+impl Filter {
+    /// Evaluate the filter for each row of a batch; unknown is NULL
+    pub fn evaluate(&self, batch: &RecordBatch) -> Result<BooleanArray> {
+        match self {
+            Self::Compare { column, op, value } => {
+                let array = column_array(batch, column)?;
+                let literal = cast_with_options(
+                    &StringArray::from(vec![value.as_str()]),
+                    array.data_type(),
+                    &CastOptions {
+                        safe: false,
+                        ..Default::default()
+                    },
+                )
+                .map_err(|_| {
+                    invalid_filter(format!(
+                        "'{}' is not a {} value, as column '{}' holds",
+                        value,
+                        array.data_type(),
+                        column
+                    ))
+                })?;
+                let literal = Scalar::new(literal);
+                let result = match op {
+                    CompareOp::Eq => cmp::eq(array, &literal),
+                    CompareOp::NotEq => cmp::neq(array, &literal),
+                    CompareOp::Lt => cmp::lt(array, &literal),
+                    CompareOp::LtEq => cmp::lt_eq(array, &literal),
+                    CompareOp::Gt => cmp::gt(array, &literal),
+                    CompareOp::GtEq => cmp::gt_eq(array, &literal),
+                };
+                result.map_err(|e| invalid_filter(format!("column '{}': {}", column, e)))
+            }
+            Self::IsNull { column, negated } => {
+                let array = column_array(batch, column)?;
+                let result = if *negated {
+                    is_not_null(array)
+                } else {
+                    is_null(array)
+                };
+                result.map_err(|e| invalid_filter(e.to_string()))
+            }
+            Self::Not(inner) => {
+                not(&inner.evaluate(batch)?).map_err(|e| invalid_filter(e.to_string()))
+            }
+            Self::And(left, right) => and_kleene(&left.evaluate(batch)?, &right.evaluate(batch)?)
+                .map_err(|e| invalid_filter(e.to_string())),
+            Self::Or(left, right) => or_kleene(&left.evaluate(batch)?, &right.evaluate(batch)?)
+                .map_err(|e| invalid_filter(e.to_string())),
+        }
+    }
+
+    /// Keep the rows of a batch for which the filter is true
+    pub fn apply(&self, batch: &RecordBatch) -> Result<RecordBatch> {
+        filter_record_batch(batch, &self.evaluate(batch)?)
+            .map_err(|e| invalid_filter(e.to_string()))
+    }
+
+    /// Whether any row of a data file may match, going by the statistics of
+    /// its columns, keyed by column name, against the table's schema
+    ///
+    /// Only `false` is certain: columns without statistics, with bounds
+    /// that cannot be compared, or of floating point types (whose bounds
+    /// leave NaN out) never rule a file out.
+    pub fn may_match(&self, schema: &Schema, stats: &HashMap<&str, DataFileColumnStats>) -> bool {
+        self.may_be(schema, stats, true)
+    }
+
+    /// Whether the filter may be `truth` for a row; NOT is pushed down to
+    /// the comparisons, as a row for which neither is true is left out
+    fn may_be(
+        &self,
+        schema: &Schema,
+        stats: &HashMap<&str, DataFileColumnStats>,
+        truth: bool,
+    ) -> bool {
+        match self {
+            Self::Compare { column, op, value } => {
+                let op = if truth { *op } else { op.negate() };
+                stats
+                    .get(column.as_str())
+                    .and_then(|stats| {
+                        let field = schema.field_with_name(column).ok()?;
+                        bounds_may_match(field.data_type(), stats, op, value)
+                    })
+                    .unwrap_or(true)
+            }
+            Self::IsNull { column, negated } => match stats.get(column.as_str()) {
+                Some(stats) if *negated == truth => stats.value_count > 0,
+                Some(stats) => stats.null_count > 0,
+                None => true,
+            },
+            Self::Not(inner) => inner.may_be(schema, stats, !truth),
+            // NOT (a AND b) is NOT a OR NOT b, and the other way around
+            Self::And(left, right) if truth => {
+                left.may_be(schema, stats, truth) && right.may_be(schema, stats, truth)
+            }
+            Self::Or(left, right) if !truth => {
+                left.may_be(schema, stats, truth) && right.may_be(schema, stats, truth)
+            }
+            Self::And(left, right) | Self::Or(left, right) => {
+                left.may_be(schema, stats, truth) || right.may_be(schema, stats, truth)
+            }
+        }
+    }
+}
+
+/// Whether a value between a column's bounds may compare true with a
+/// literal; `None` if that cannot be told
+fn bounds_may_match(
+    data_type: &DataType,
+    stats: &DataFileColumnStats,
+    op: CompareOp,
+    value: &str,
+) -> Option<bool> {
+    if data_type.is_floating() {
+        return None;
+    }
+    let cast = |values: Vec<&str>| {
+        cast_with_options(
+            &StringArray::from(values),
+            data_type,
+            &CastOptions {
+                safe: false,
+                ..Default::default()
+            },
+        )
+        .ok()
+    };
+    let bounds = cast(vec![
+        stats.min_value.as_deref()?,
+        stats.max_value.as_deref()?,
+    ])?;
+    let literal = Scalar::new(cast(vec![value])?);
+    // Each holds the comparison of [min, max] with the literal
+    let lt = cmp::lt(&bounds, &literal).ok()?;
+    let gt = cmp::gt(&bounds, &literal).ok()?;
+    let (min, max) = (0, 1);
+    Some(match op {
+        CompareOp::Eq => !gt.value(min) && !lt.value(max),
+        CompareOp::NotEq => lt.value(min) || gt.value(max),
+        CompareOp::Lt => lt.value(min),
+        CompareOp::LtEq => !gt.value(min),
+        CompareOp::Gt => gt.value(max),
+        CompareOp::GtEq => !lt.value(max),
+    })
+}
+
+impl FromStr for Filter {
+    type Err = DuckPondError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            position: 0,
+        };
+        let filter = parser.or()?;
+        match parser.peek() {
+            None => Ok(filter),
+            Some(token) => Err(invalid_filter(format!("unexpected {}", token))),
+        }
+    }
+}
+
+fn column_array<'a>(batch: &'a RecordBatch, column: &str) -> Result<&'a ArrayRef> {
+    batch
+        .column_by_name(column)
+        .ok_or_else(|| invalid_filter(format!("unknown column '{}'", column)))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    /// A keyword or operator
+    Symbol(String),
+    Literal(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ident(name) => write!(f, "'{}'", name),
+            Self::Symbol(symbol) => write!(f, "'{}'", symbol),
+            Self::Literal(value) => write!(f, "value '{}'", value),
+        }
+    }
+}
+
+const KEYWORDS: [&str; 7] = ["AND", "OR", "NOT", "IS", "NULL", "TRUE", "FALSE"];
+
+fn tokenize(s: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '\'' || c == '"' {
+            // 'literal' or "quoted identifier"; a doubled quote escapes it
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some(q) if q == c => {
+                        if chars.peek() == Some(&c) {
+                            chars.next();
+                            text.push(c);
+                        } else {
+                            break;
+                        }
+                    }
+                    Some(other) => text.push(other),
+                    None => return Err(invalid_filter("unterminated quote".to_string())),
+                }
+            }
+            tokens.push(if c == '\'' {
+                Token::Literal(text)
+            } else {
+                Token::Ident(text)
+            });
+        } else if c.is_ascii_digit() || c == '-' || c == '.' {
+            let mut number = String::new();
+            while let Some(&d) = chars.peek() {
+                if d.is_ascii_alphanumeric() || matches!(d, '-' | '+' | '.') {
+                    number.push(d);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Literal(number));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut word = String::new();
+            while let Some(&d) = chars.peek() {
+                if d.is_alphanumeric() || d == '_' {
+                    word.push(d);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let upper = word.to_ascii_uppercase();
+            tokens.push(match upper.as_str() {
+                "TRUE" | "FALSE" => Token::Literal(upper.to_ascii_lowercase()),
+                _ if KEYWORDS.contains(&upper.as_str()) => Token::Symbol(upper),
+                _ => Token::Ident(word),
+            });
+        } else {
+            chars.next();
+            let symbol = match (c, chars.peek()) {
+                ('<' | '>' | '!' | '=', Some('=')) | ('<', Some('>')) => {
+                    let next = chars.next().expect("peeked");
+                    format!("{}{}", c, next)
+                }
+                ('=' | '<' | '>' | '(' | ')', _) => c.to_string(),
+                _ => return Err(invalid_filter(format!("unexpected character '{}'", c))),
+            };
+            tokens.push(Token::Symbol(symbol));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser; OR binds loosest, then AND, then NOT
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol.to_string())) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<()> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("'{}'", symbol)))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> DuckPondError {
+        match self.peek() {
+            Some(token) => invalid_filter(format!("expected {}, found {}", expected, token)),
+            None => invalid_filter(format!("expected {}", expected)),
+        }
+    }
+
+    fn or(&mut self) -> Result<Filter> {
+        let mut filter = self.and()?;
+        while self.eat("OR") {
+            filter = Filter::Or(Box::new(filter), Box::new(self.and()?));
+        }
+        Ok(filter)
+    }
+
+    fn and(&mut self) -> Result<Filter> {
+        let mut filter = self.unary()?;
+        while self.eat("AND") {
+            filter = Filter::And(Box::new(filter), Box::new(self.unary()?));
+        }
+        Ok(filter)
+    }
+
+    fn unary(&mut self) -> Result<Filter> {
+        if self.eat("NOT") {
+            return Ok(Filter::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("(") {
+            let filter = self.or()?;
+            self.expect(")")?;
+            return Ok(filter);
+        }
+
+        let column = match self.next() {
+            Some(Token::Ident(column)) => column,
+            _ => {
+                self.position -= 1;
+                return Err(self.unexpected("a column name"));
+            }
+        };
+        if self.eat("IS") {
+            let negated = self.eat("NOT");
+            self.expect("NULL")?;
+            return Ok(Filter::IsNull { column, negated });
+        }
+
+        let op = match self.next() {
+            Some(Token::Symbol(symbol)) => match symbol.as_str() {
+                "=" | "==" => CompareOp::Eq,
+                "!=" | "<>" => CompareOp::NotEq,
+                "<" => CompareOp::Lt,
+                "<=" => CompareOp::LtEq,
+                ">" => CompareOp::Gt,
+                ">=" => CompareOp::GtEq,
+                _ => {
+                    self.position -= 1;
+                    return Err(self.unexpected("a comparison"));
+                }
+            },
+            _ => {
+                self.position -= 1;
+                return Err(self.unexpected("a comparison"));
+            }
+        };
+        match self.next() {
+            Some(Token::Literal(value)) => Ok(Filter::Compare { column, op, value }),
+            _ => {
+                self.position -= 1;
+                Err(self.unexpected("a value"))
+            }
+        }
+    }
+}
+
+fn invalid_filter(message: String) -> DuckPondError {
+    DuckPondError::InvalidFilter { message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Date32Array, Int64Array};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    fn batch() -> RecordBatch {
+        RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new("status", DataType::Utf8, true),
+                Field::new("day", DataType::Date32, true),
+            ])),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3, 4])),
+                Arc::new(StringArray::from(vec![
+                    Some("active"),
+                    Some("it's"),
+                    None,
+                    Some("active"),
+                ])),
+                Arc::new(Date32Array::from(vec![
+                    Some(20_000),
+                    None,
+                    Some(20_500),
+                    Some(20_744),
+                ])),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn ids(filter: &str) -> Vec<i64> {
+        let filtered = filter.parse::<Filter>().unwrap().apply(&batch()).unwrap();
+        filtered
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap()
+            .values()
+            .to_vec()
+    }
+
+    #[test]
+    fn test_filters() {
+        assert_eq!(ids("status = 'active'"), vec![1, 4]);
+        assert_eq!(ids("status <> 'active'"), vec![2]);
+        assert_eq!(ids("status = 'it''s'"), vec![2]);
+        assert_eq!(ids("id >= 2 and not id = 4"), vec![2, 3]);
+        assert_eq!(ids("id < 2 OR (status IS NULL AND id > 1)"), vec![1, 3]);
+        assert_eq!(ids("\"day\" >= '2026-10-18'"), vec![4]);
+        assert_eq!(ids("day IS NOT NULL AND id != -1"), vec![1, 3, 4]);
+    }
+
+    #[test]
+    fn test_may_match() {
+        let schema = batch().schema();
+        let stats = |column_id, min: &str, max: &str, null_count| DataFileColumnStats {
+            data_file_id: 1,
+            column_id,
+            value_count: 2,
+            null_count,
+            contains_nan: Some(false),
+            min_value: Some(min.to_string()),
+            max_value: Some(max.to_string()),
+        };
+        let file = HashMap::from([
+            ("id", stats(1, "10", "20", 0)),
+            ("status", stats(2, "active", "paused", 1)),
+        ]);
+        let may_match = |filter: &str| filter.parse::<Filter>().unwrap().may_match(&schema, &file);
+
+        assert!(may_match("id = 10"));
+        assert!(!may_match("id = 9"));
+        assert!(!may_match("id > 20"));
+        assert!(may_match("id >= 20"));
+        assert!(!may_match("id < 10"));
+        assert!(may_match("id != 15"));
+        assert!(!may_match("NOT id <= 20"));
+        assert!(!may_match("id > 20 OR status > 'zzz'"));
+        assert!(may_match("id > 20 OR status = 'done'"));
+        assert!(!may_match("NOT (id >= 10 AND id <= 20)"));
+        assert!(!may_match("id IS NULL"));
+        assert!(may_match("status IS NULL"));
+        // No statistics, or none that compare, rule nothing out
+        assert!(may_match("day < '2000-01-01'"));
+        let file = HashMap::from([("id", stats(1, "ten", "twenty", 0))]);
+        assert!("id = 1"
+            .parse::<Filter>()
+            .unwrap()
+            .may_match(&schema, &file));
+    }
+
+    #[test]
+    fn test_invalid_filters() {
+        for filter in ["", "id =", "id = 1 AND", "(id = 1", "id ~ 1", "'a' = id"] {
+            assert!(
+                matches!(
+                    filter.parse::<Filter>(),
+                    Err(DuckPondError::InvalidFilter { .. })
+                ),
+                "{}",
+                filter
+            );
+        }
+        for filter in ["missing = 1", "id = 'abc'", "day < 'yesterday'"] {
+            let filter: Filter = filter.parse().unwrap();
+            assert!(matches!(
+                filter.evaluate(&batch()),
+                Err(DuckPondError::InvalidFilter { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,548 @@
+//! CSV and newline-delimited JSON import
+//!
+//! Files are parsed in batches into the Arrow schema of the table they are
+//! imported into (see [`crate::schema::arrow_schema`]). CSV values are read
+//! as text and converted per column, so a value that does not parse makes
+//! its record a bad record; JSON records are decoded with the table's types
+//! directly. What happens to bad records is set by [`BadRecordPolicy`].
+
+use arrow::array::{Array, ArrayRef, AsArray, BooleanArray, Int32Array, Int64Array, RecordBatch};
+use arrow::compute::{cast, cast_with_options, concat_batches, filter, CastOptions};
+use arrow::csv::reader::Format;
+use arrow::datatypes::{DataType, Field, FieldRef, Schema, SchemaRef, TimeUnit};
+use arrow::error::ArrowError;
+use arrow::util::display::array_value_to_string;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use duckpond_core::error::DuckPondError;
+use duckpond_core::Result;
+use regex::Regex;
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Read};
+use std::sync::Arc;
+
+/// What to do with a record whose values do not fit the table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BadRecordPolicy {
+    /// Fail the import, naming the record
+    #[default]
+    Fail,
+    /// Leave the record out and count it in [`ImportSummary::records_skipped`]
+    Skip,
+}
+
+/// Options of [`crate::Lakehouse::import_csv`] and
+/// [`crate::Lakehouse::import_json`]
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Whether the first CSV line names the columns. Named columns are
+    /// matched to the table's by name; otherwise a CSV file must hold all of
+    /// the table's columns, in order.
+    pub has_header: bool,
+    /// CSV field delimiter
+    pub delimiter: u8,
+    /// CSV value read as NULL; by default empty values are NULL
+    pub null_string: Option<String>,
+    /// chrono format of date values, ISO 8601 (`2026-10-18`) by default
+    pub date_format: Option<String>,
+    /// chrono format of timestamp values, RFC 3339 by default. Timestamps
+    /// without an offset are taken to be in UTC.
+    pub timestamp_format: Option<String>,
+    pub bad_records: BadRecordPolicy,
+    /// Records read to infer the columns of a table that does not exist yet
+    pub infer_records: usize,
+    /// Records parsed at a time
+    pub batch_size: usize,
+    /// Records written to each Parquet file
+    pub records_per_file: usize,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            has_header: true,
+            delimiter: b',',
+            null_string: None,
+            date_format: None,
+            timestamp_format: None,
+            bad_records: BadRecordPolicy::Fail,
+            infer_records: 1000,
+            batch_size: 8192,
+            records_per_file: 1_000_000,
+        }
+    }
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Default)]
+pub struct ImportSummary {
+    pub records_imported: usize,
+    pub records_skipped: usize,
+    /// Data files written, all added in one snapshot
+    pub data_file_ids: Vec<i64>,
+}
+
+/// A parsed batch and the number of bad records left out of it
+pub type ImportBatch = (RecordBatch, usize);
+
+/// Infer the columns of a CSV file from its first records. Columns without
+/// any values become `varchar` columns; without a header they are named
+/// `column_1`, `column_2`, ...
+pub fn infer_csv_schema<R: Read>(reader: R, options: &ImportOptions) -> Result<Schema> {
+    let (schema, _) = csv_format(options)?
+        .infer_schema(reader, Some(options.infer_records))
+        .map_err(import_error)?;
+    Ok(without_null_types(&schema))
+}
+
+/// Infer the columns, including nested ones, of a newline-delimited JSON
+/// file from its first records
+pub fn infer_json_schema<R: Read>(reader: R, options: &ImportOptions) -> Result<Schema> {
+    let (schema, _) =
+        arrow::json::reader::infer_json_schema(BufReader::new(reader), Some(options.infer_records))
+            .map_err(import_error)?;
+    Ok(without_null_types(&schema))
+}
+
+/// Parse a CSV file into batches of the table's schema
+pub fn csv_batches<R: Read>(
+    reader: R,
+    table_schema: &Schema,
+    options: &ImportOptions,
+) -> Result<impl Iterator<Item = Result<ImportBatch>>> {
+    let mut reader = BufReader::new(reader);
+    let fields: Vec<FieldRef> = if options.has_header {
+        let mut header = Vec::new();
+        reader.read_until(b'\n', &mut header)?;
+        let (names, _) = csv_format(options)?
+            .infer_schema(header.as_slice(), Some(0))
+            .map_err(import_error)?;
+        let mut seen = HashSet::new();
+        names
+            .fields()
+            .iter()
+            .map(|name| {
+                if !seen.insert(name.name()) {
+                    return Err(mismatch(format!("column '{}' appears twice", name.name())));
+                }
+                table_schema
+                    .field_with_name(name.name())
+                    .map(|field| Arc::new(field.clone()))
+                    .map_err(|_| mismatch(format!("the table has no column '{}'", name.name())))
+            })
+            .collect::<Result<_>>()?
+    } else {
+        table_schema.fields().to_vec()
+    };
+
+    if let Some(field) = fields.iter().find(|f| f.data_type().is_nested()) {
+        return Err(mismatch(format!(
+            "nested column '{}' cannot be read from CSV",
+            field.name()
+        )));
+    }
+    let text_schema = Schema::new(
+        fields
+            .iter()
+            .map(|f| Field::new(f.name(), DataType::Utf8, true))
+            .collect::<Vec<_>>(),
+    );
+
+    let batches = arrow::csv::ReaderBuilder::new(Arc::new(text_schema))
+        .with_format(csv_format(options)?.with_header(false))
+        .with_batch_size(options.batch_size)
+        .build_buffered(reader)
+        .map_err(import_error)?;
+    let mut converter = Converter::new(fields, options);
+    Ok(batches.map(move |batch| converter.convert(batch.map_err(import_error)?)))
+}
+
+/// Parse a newline-delimited JSON file into batches of the table's schema.
+/// Keys that are not columns of the table are ignored.
+pub fn json_batches<R: Read>(
+    reader: R,
+    table_schema: &Schema,
+    options: &ImportOptions,
+) -> Result<impl Iterator<Item = Result<ImportBatch>>> {
+    let fields = table_schema.fields().to_vec();
+    // Values with a custom format are decoded as text and converted after,
+    // and NULLs in NOT NULL columns are reported per record
+    let decode_schema = Arc::new(Schema::new(
+        fields
+            .iter()
+            .map(|f| {
+                let data_type = if formatted(f.data_type(), options) {
+                    DataType::Utf8
+                } else {
+                    f.data_type().clone()
+                };
+                f.as_ref()
+                    .clone()
+                    .with_data_type(data_type)
+                    .with_nullable(true)
+            })
+            .collect::<Vec<_>>(),
+    ));
+
+    Ok(JsonBatches {
+        lines: BufReader::new(reader).split(b'\n'),
+        decode_schema,
+        batch_size: options.batch_size,
+        converter: Converter::new(fields, options),
+    })
+}
+
+struct JsonBatches<R> {
+    lines: std::io::Split<BufReader<R>>,
+    decode_schema: SchemaRef,
+    batch_size: usize,
+    converter: Converter,
+}
+
+impl<R: Read> JsonBatches<R> {
+    fn next_batch(&mut self) -> Result<Option<ImportBatch>> {
+        let mut records = Vec::new();
+        while records.len() < self.batch_size {
+            match self.lines.next() {
+                Some(line) => {
+                    let line = line?;
+                    if !line.trim_ascii().is_empty() {
+                        records.push(line);
+                    }
+                }
+                None => break,
+            }
+        }
+        if records.is_empty() {
+            return Ok(None);
+        }
+
+        let first_record = self.converter.next_record;
+        let (batch, skipped) = match decode_json(&self.decode_schema, &records) {
+            Ok(batch) => (batch, 0),
+            Err(_) => {
+                // Find the records that do not decode
+                let mut decoded = Vec::new();
+                for (i, record) in records.iter().enumerate() {
+                    match decode_json(&self.decode_schema, std::slice::from_ref(record)) {
+                        Ok(batch) => decoded.push(batch),
+                        Err(e) if self.converter.policy == BadRecordPolicy::Fail => {
+                            return Err(bad_record(first_record + i, &e.to_string()));
+                        }
+                        Err(_) => {}
+                    }
+                }
+                let batch = concat_batches(&self.decode_schema, &decoded).map_err(import_error)?;
+                (batch, records.len() - decoded.len())
+            }
+        };
+
+        let (batch, bad) = self.converter.convert(batch)?;
+        self.converter.next_record = first_record + records.len();
+        Ok(Some((batch, skipped + bad)))
+    }
+}
+
+impl<R: Read> Iterator for JsonBatches<R> {
+    type Item = Result<ImportBatch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_batch().transpose()
+    }
+}
+
+fn decode_json(
+    schema: &SchemaRef,
+    records: &[Vec<u8>],
+) -> std::result::Result<RecordBatch, ArrowError> {
+    let mut decoder = arrow::json::ReaderBuilder::new(schema.clone())
+        .with_batch_size(records.len() + 1)
+        .build_decoder()?;
+    for record in records {
+        decoder.decode(record)?;
+        decoder.decode(b"\n")?;
+    }
+    Ok(decoder
+        .flush()?
+        .unwrap_or_else(|| RecordBatch::new_empty(schema.clone())))
+}
+
+/// Converts parsed batches to the table's types, finding bad records
+struct Converter {
+    fields: Vec<FieldRef>,
+    date_format: Option<String>,
+    timestamp_format: Option<String>,
+    policy: BadRecordPolicy,
+    /// Number (0-based) of the next record to convert
+    next_record: usize,
+}
+
+impl Converter {
+    fn new(fields: Vec<FieldRef>, options: &ImportOptions) -> Self {
+        Self {
+            fields,
+            date_format: options.date_format.clone(),
+            timestamp_format: options.timestamp_format.clone(),
+            policy: options.bad_records,
+            next_record: 0,
+        }
+    }
+
+    fn convert(&mut self, batch: RecordBatch) -> Result<ImportBatch> {
+        let num_rows = batch.num_rows();
+        let mut problems: Vec<Option<String>> = vec![None; num_rows];
+        let mut arrays = Vec::new();
+        for (array, field) in batch.columns().iter().zip(&self.fields) {
+            let converted = if array.data_type() == field.data_type() {
+                array.clone()
+            } else {
+                self.convert_column(array, field.data_type())
+                    .map_err(|e| mismatch(format!("column '{}': {}", field.name(), e)))?
+            };
+            for (row, problem) in problems.iter_mut().enumerate() {
+                if problem.is_some() || converted.is_valid(row) {
+                    continue;
+                }
+                if array.is_valid(row) {
+                    let value = array_value_to_string(array, row).map_err(import_error)?;
+                    *problem = Some(format!(
+                        "invalid {} value '{}' in column '{}'",
+                        field.data_type(),
+                        value,
+                        field.name()
+                    ));
+                } else if !field.is_nullable() {
+                    *problem = Some(format!("NOT NULL column '{}' is NULL", field.name()));
+                }
+            }
+            arrays.push(converted);
+        }
+
+        let first_record = self.next_record;
+        self.next_record += num_rows;
+        let bad = problems.iter().filter(|p| p.is_some()).count();
+        if bad > 0 {
+            if self.policy == BadRecordPolicy::Fail {
+                let (row, problem) = problems
+                    .iter()
+                    .enumerate()
+                    .find_map(|(row, p)| p.as_ref().map(|p| (row, p)))
+                    .expect("a bad record");
+                return Err(bad_record(first_record + row, problem));
+            }
+            let keep: BooleanArray = problems.iter().map(|p| Some(p.is_none())).collect();
+            arrays = arrays
+                .iter()
+                .map(|array| filter(array, &keep))
+                .collect::<std::result::Result<_, _>>()
+                .map_err(import_error)?;
+        }
+
+        let schema = Arc::new(Schema::new(self.fields.clone()));
+        let batch = RecordBatch::try_new(schema, arrays).map_err(import_error)?;
+        Ok((batch, bad))
+    }
+
+    /// Convert a column to `to`, leaving NULL the values that do not convert
+    fn convert_column(
+        &self,
+        array: &ArrayRef,
+        to: &DataType,
+    ) -> std::result::Result<ArrayRef, ArrowError> {
+        match (to, &self.date_format, &self.timestamp_format) {
+            (DataType::Date32, Some(format), _) => {
+                let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid date");
+                let days: Int32Array = array
+                    .as_string::<i32>()
+                    .iter()
+                    .map(|value| {
+                        let date = NaiveDate::parse_from_str(value?, format).ok()?;
+                        i32::try_from(date.signed_duration_since(epoch).num_days()).ok()
+                    })
+                    .collect();
+                cast(&days, to)
+            }
+            (DataType::Timestamp(unit, _), _, Some(format)) => {
+                let values: Int64Array = array
+                    .as_string::<i32>()
+                    .iter()
+                    .map(|value| {
+                        let timestamp = parse_timestamp(value?, format)?;
+                        match unit {
+                            TimeUnit::Second => Some(timestamp.timestamp()),
+                            TimeUnit::Millisecond => Some(timestamp.timestamp_millis()),
+                            TimeUnit::Microsecond => Some(timestamp.timestamp_micros()),
+                            TimeUnit::Nanosecond => timestamp.timestamp_nanos_opt(),
+                        }
+                    })
+                    .collect();
+                cast(&values, to)
+            }
+            _ => cast_with_options(
+                array,
+                to,
+                &CastOptions {
+                    safe: true,
+                    ..Default::default()
+                },
+            ),
+        }
+    }
+}
+
+fn parse_timestamp(value: &str, format: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_str(value, format)
+        .map(|timestamp| timestamp.to_utc())
+        .or_else(|_| NaiveDateTime::parse_from_str(value, format).map(|t| t.and_utc()))
+        .ok()
+}
+
+/// Whether values of a top-level column of the type are parsed with a
+/// custom format
+fn formatted(data_type: &DataType, options: &ImportOptions) -> bool {
+    match data_type {
+        DataType::Date32 => options.date_format.is_some(),
+        DataType::Timestamp(_, _) => options.timestamp_format.is_some(),
+        _ => false,
+    }
+}
+
+fn csv_format(options: &ImportOptions) -> Result<Format> {
+    let mut format = Format::default()
+        .with_header(options.has_header)
+        .with_delimiter(options.delimiter);
+    if let Some(null_string) = &options.null_string {
+        let null_regex = Regex::new(&format!("^{}$", regex::escape(null_string)))
+            .map_err(|e| import_error(e.to_string()))?;
+        format = format.with_null_regex(null_regex);
+    }
+    Ok(format)
+}
+
+/// Replace the `Null` type inferred for columns without values by `Utf8`
+fn without_null_types(schema: &Schema) -> Schema {
+    Schema::new(
+        schema
+            .fields()
+            .iter()
+            .map(|f| match f.data_type() {
+                DataType::Null => f.as_ref().clone().with_data_type(DataType::Utf8),
+                _ => f.as_ref().clone(),
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn bad_record(record: usize, message: &str) -> DuckPondError {
+    import_error(format!("record {}: {}", record + 1, message))
+}
+
+fn import_error(message: impl ToString) -> DuckPondError {
+    DuckPondError::InvalidImport {
+        message: message.to_string(),
+    }
+}
+
+fn mismatch(message: String) -> DuckPondError {
+    DuckPondError::SchemaMismatch { message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Date32Array, StringArray};
+
+    fn table_schema() -> Schema {
+        Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("day", DataType::Date32, true),
+        ])
+    }
+
+    fn read_csv(data: &str, options: &ImportOptions) -> Result<Vec<ImportBatch>> {
+        csv_batches(data.as_bytes(), &table_schema(), options)?.collect()
+    }
+
+    #[test]
+    fn test_csv_batches() {
+        let options = ImportOptions {
+            delimiter: b';',
+            null_string: Some("NA".to_string()),
+            date_format: Some("%d/%m/%Y".to_string()),
+            ..Default::default()
+        };
+        // Header columns are matched by name
+        let batches = read_csv("day;id;name\n18/10/2026;1;NA\nNA;2;\n", &options).unwrap();
+        let (batch, skipped) = &batches[0];
+        assert_eq!(*skipped, 0);
+        assert_eq!(batch.schema().field(0).name(), "day");
+        let days: &Date32Array = batch.column(0).as_primitive();
+        assert_eq!(days.value_as_date(0), NaiveDate::from_ymd_opt(2026, 10, 18));
+        assert!(days.is_null(1));
+        let names: &StringArray = batch.column(2).as_string();
+        assert!(names.is_null(0));
+        assert_eq!(names.value(1), "");
+
+        assert!(matches!(
+            read_csv("id;other\n1;x\n", &options),
+            Err(DuckPondError::SchemaMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_bad_records() {
+        let data = "id,name,day\n1,a,2026-10-18\nx,b,\n,c,\n4,d,2026-13-01\n5,e,\n";
+        let error = read_csv(data, &ImportOptions::default()).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("record 2: invalid Int64 value 'x'"));
+
+        let options = ImportOptions {
+            bad_records: BadRecordPolicy::Skip,
+            ..Default::default()
+        };
+        let (batch, skipped) = read_csv(data, &options).unwrap().remove(0);
+        assert_eq!(skipped, 3);
+        let ids: &Int64Array = batch.column(0).as_primitive();
+        assert_eq!(ids.values(), &[1, 5]);
+    }
+
+    #[test]
+    fn test_json_batches() {
+        let data = "{\"id\": 1, \"name\": \"a\", \"extra\": true}\n\n{\"id\": \"two\"}\n{\"id\": 3, \"day\": \"2026-10-18\"}\n";
+        let error = json_batches(data.as_bytes(), &table_schema(), &ImportOptions::default())
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap_err();
+        assert!(error.to_string().contains("record 2"));
+
+        let options = ImportOptions {
+            bad_records: BadRecordPolicy::Skip,
+            ..Default::default()
+        };
+        let (batch, skipped) = json_batches(data.as_bytes(), &table_schema(), &options)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(skipped, 1);
+        let ids: &Int64Array = batch.column(0).as_primitive();
+        assert_eq!(ids.values(), &[1, 3]);
+        assert!(batch.column(2).is_null(0));
+        assert!(batch.column(2).is_valid(1));
+    }
+
+    #[test]
+    fn test_infer_csv_schema() {
+        let schema = infer_csv_schema(
+            "id,price,note\n1,2.5,\n2,3,\n".as_bytes(),
+            &ImportOptions::default(),
+        )
+        .unwrap();
+        let types: Vec<_> = schema.fields().iter().map(|f| f.data_type()).collect();
+        assert_eq!(
+            types,
+            vec![&DataType::Int64, &DataType::Float64, &DataType::Utf8]
+        );
+    }
+}
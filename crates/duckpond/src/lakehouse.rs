@@ -1,20 +1,53 @@
 //! High-level lakehouse operations that combine core, storage, and parquet functionality
 
-use arrow::array::RecordBatch;
+use crate::change_feed::{self, ChangeType};
+use crate::export::{self, ExportOptions};
+use crate::filter::Filter;
+use crate::import::{self, ImportBatch, ImportOptions, ImportSummary};
+use crate::schema;
+use arrow::array::{
+    new_null_array, Array, ArrayRef, AsArray, BooleanArray, Float64Array, Int64Array, RecordBatch,
+    StringArray, UInt32Array,
+};
+use arrow::compute::{cast, concat_batches, filter_record_batch, take_record_batch};
+use arrow::datatypes::{DataType, Field, Float64Type, Int64Type, Schema, SchemaRef};
+use chrono::{Duration, Utc};
 use duckpond_core::config::DuckPondConfig;
-use duckpond_core::DuckPond;
-use duckpond_parquet::{ParquetManager, ParquetReadConfig, ParquetWriteConfig};
+use duckpond_core::error::DuckPondError;
+use duckpond_core::inlining::InlinedColumnKind;
+use duckpond_core::models::{ColumnInfo, DataFileInfo, ViewInfo};
+use duckpond_core::{DuckPond, FlushedDataFile, InlinedRow, InlinedValue, NewDataFile, SortOrder};
+use duckpond_parquet::{ParquetColumnStats, ParquetManager, ParquetReadConfig, ParquetWriteConfig};
 use duckpond_storage::local::LocalFileSystem;
-use duckpond_storage::FileSystem;
+use duckpond_storage::path::PathUtils;
+use duckpond_storage::{FileMetadata, FileSystem, StorageError};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use uuid::Uuid;
 
+/// Virtual columns to materialize when reading a table
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    /// Add a `rowid` column holding each row's stable row ID
+    pub rowid: bool,
+    /// Add a `snapshot_id` column holding the snapshot that added each row
+    pub snapshot_id: bool,
+}
+
 /// High-level lakehouse interface that orchestrates all components
+///
+/// Clones share the catalog connection pool and storage.
+#[derive(Clone)]
 pub struct Lakehouse {
     core: DuckPond,
-    #[allow(dead_code)] // TODO: May be used for advanced file operations
-    filesystem: Box<dyn FileSystem + Send + Sync>,
-    parquet_manager: ParquetManager,
+    filesystem: Arc<dyn FileSystem + Send + Sync>,
+    parquet_manager: Arc<ParquetManager>,
+    /// Absolute path of the data directory
+    data_path: PathBuf,
 }
 
 impl Lakehouse {
@@ -31,27 +64,35 @@ impl Lakehouse {
         };
 
         let database = duckpond_core::database::Database::new(&config).await?;
+        let data_path =
+            std::path::absolute(&storage_path).unwrap_or_else(|_| PathBuf::from(&storage_path));
 
-        let core = DuckPond::new(database.pool().clone());
-        let filesystem = Box::new(
+        let core = DuckPond::open(database.pool().clone(), database.backend().clone()).await?;
+        let filesystem = Arc::new(
             LocalFileSystem::new(storage_path)
                 .map_err(|e| duckpond_core::error::DuckPondError::ConfigError(e.to_string()))?,
         );
 
         // Create ParquetManager with a clone of the filesystem
-        let parquet_manager = ParquetManager::new(Box::new(
+        let parquet_manager = Arc::new(ParquetManager::new(Box::new(
             LocalFileSystem::new(config.data_path)
                 .map_err(|e| duckpond_core::error::DuckPondError::ConfigError(e.to_string()))?,
-        ));
+        )));
 
         Ok(Self {
             core,
             filesystem,
             parquet_manager,
+            data_path,
         })
     }
 
     /// Write data to a table using Parquet files
+    ///
+    /// Writes of at most the catalog's data inlining row limit are kept in
+    /// the catalog instead, as long as all of the table's column types can
+    /// be inlined; see [`Lakehouse::flush_inlined_data`]. Batches must match
+    /// the table's columns, see [`schema::conform_batches`].
     pub async fn write_to_table(
         &self,
         schema_name: &str,
@@ -64,41 +105,41 @@ impl Lakehouse {
             ));
         }
 
-        // 1. Get table ID from schema and table names
+        // 1. Get table ID from schema and table names, and conform the data
+        // to the table's columns
         let table_id = self.get_table_id(schema_name, table_name).await?;
+        let columns = self.core.table_structure(table_id).await?;
+        let data = schema::conform_batches(&data, &columns)?;
 
-        // 2. Generate a unique file path
-        let file_path = format!(
-            "{}/{}/{}/data_{}.parquet",
-            schema_name,
-            table_name,
-            Uuid::new_v4(),
-            table_id
-        );
+        // 2. Inline small writes into the catalog
+        let record_count: usize = data.iter().map(RecordBatch::num_rows).sum();
+        if record_count > 0 && record_count <= self.core.data_inlining_row_limit().await? {
+            if let Some(rows) = inlined_rows(&data, &columns) {
+                self.core.insert_inlined_rows(table_id, &rows).await?;
+                return Ok(());
+            }
+        }
 
-        // 3. Write data to Parquet file using duckpond-parquet
-        let write_config = ParquetWriteConfig::default();
+        // 3. Generate a unique file path under the table's path
+        let table_path = self.table_path(schema_name, table_name, table_id).await?;
+        let file_path = data_file_path(&table_path, table_id);
+
+        // 4. Write data to Parquet file using duckpond-parquet, clustered by
+        // the table's sort order so per-file statistics stay tight
+        let write_config = ParquetWriteConfig {
+            sort_order: self.core.sort_order(table_id).await?,
+            ..Default::default()
+        };
         let file_stats = self
             .parquet_manager
             .write_file(&file_path, data.clone(), write_config)
             .await
             .map_err(|e| duckpond_core::error::DuckPondError::ConfigError(e.to_string()))?;
 
-        // 4. Convert parquet column stats to core column stats
-        let column_statistics: Vec<duckpond_core::FileColumnStatistics> = file_stats
-            .column_stats
-            .into_iter()
-            .map(|col_stat| duckpond_core::FileColumnStatistics {
-                column_id: col_stat.column_id,
-                value_count: col_stat.value_count as i64,
-                null_count: col_stat.null_count as i64,
-                nan_count: col_stat.nan_count as i64,
-                min_value: col_stat.min_value,
-                max_value: col_stat.max_value,
-            })
-            .collect();
+        // 5. Convert parquet column stats to core column stats
+        let column_statistics = column_statistics(file_stats.column_stats);
 
-        // 5. Insert file record and statistics using core method
+        // 6. Insert file record and statistics using core method
         self.core
             .insert_data_file(
                 table_id,
@@ -112,6 +153,340 @@ impl Lakehouse {
         Ok(())
     }
 
+    /// Register Parquet files that already exist in storage with a table,
+    /// without copying them
+    ///
+    /// Paths are relative to the storage root unless absolute; files outside
+    /// the table's path are registered by their absolute path under the
+    /// catalog's data path. Each file must hold the table's columns, see
+    /// [`schema::validate_file_schema`], and its statistics are read from
+    /// the footer. All files are added in one snapshot. Once added, the
+    /// files belong to the table: like any other data file, they are deleted
+    /// by vacuum when no snapshot needs them. Returns the IDs of the new data
+    /// files.
+    pub async fn add_files(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        paths: &[&str],
+    ) -> duckpond_core::Result<Vec<i64>> {
+        let table_id = self.get_table_id(schema_name, table_name).await?;
+        let columns = self.core.table_structure(table_id).await?;
+
+        let mut files = Vec::new();
+        for path in paths {
+            let invalid = |message: String| DuckPondError::InvalidDataFile {
+                path: path.to_string(),
+                message,
+            };
+            let file_schema = self
+                .parquet_manager
+                .read_schema(path)
+                .await
+                .map_err(|e| invalid(e.to_string()))?;
+            schema::validate_file_schema(&file_schema, &columns)
+                .map_err(|e| invalid(e.to_string()))?;
+            let file_stats = self
+                .parquet_manager
+                .collect_statistics(path)
+                .await
+                .map_err(|e| invalid(e.to_string()))?;
+
+            // The file's columns are the table's, in order, but may lack
+            // field IDs
+            let mut statistics = Vec::new();
+            for (stat, column) in column_statistics(file_stats.column_stats)
+                .into_iter()
+                .zip(&columns)
+            {
+                if !column.nulls_allowed && stat.null_count > 0 {
+                    return Err(invalid(format!(
+                        "NOT NULL column '{}' holds {} nulls",
+                        column.column_name, stat.null_count
+                    )));
+                }
+                statistics.push(duckpond_core::FileColumnStatistics {
+                    column_id: column.column_id,
+                    ..stat
+                });
+            }
+
+            files.push(NewDataFile {
+                path: path.to_string(),
+                path_is_relative: !PathUtils::is_absolute(path),
+                record_count: file_stats.record_count as i64,
+                file_size_bytes: file_stats.file_size_bytes as i64,
+                footer_size: file_stats.footer_size.map(|size| size as i64),
+                column_statistics: statistics,
+            });
+        }
+
+        self.core.add_data_files(table_id, &files).await
+    }
+
+    /// Import a CSV file into a table
+    ///
+    /// A table that does not exist yet is created first, with columns
+    /// inferred from the file's first records. Records are written to
+    /// Parquet files of up to `options.records_per_file` records, all added
+    /// in one snapshot; see [`ImportOptions`] for the parsing options.
+    pub async fn import_csv<R: Read + Seek + Send>(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        mut reader: R,
+        options: &ImportOptions,
+    ) -> duckpond_core::Result<ImportSummary> {
+        if !self.table_exists(schema_name, table_name).await? {
+            let inferred = import::infer_csv_schema(&mut reader, options)?;
+            reader.rewind()?;
+            self.create_table_from_arrow_schema(schema_name, table_name, &inferred)
+                .await?;
+        }
+        let table_id = self.get_table_id(schema_name, table_name).await?;
+        let columns = self.core.table_structure(table_id).await?;
+        let batches = import::csv_batches(reader, &schema::arrow_schema(&columns)?, options)?;
+        self.import_batches(
+            schema_name,
+            table_name,
+            table_id,
+            &columns,
+            batches,
+            options,
+        )
+        .await
+    }
+
+    /// Import a newline-delimited JSON file into a table, like
+    /// [`Lakehouse::import_csv`]
+    pub async fn import_json<R: Read + Seek + Send>(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        mut reader: R,
+        options: &ImportOptions,
+    ) -> duckpond_core::Result<ImportSummary> {
+        if !self.table_exists(schema_name, table_name).await? {
+            let inferred = import::infer_json_schema(&mut reader, options)?;
+            reader.rewind()?;
+            self.create_table_from_arrow_schema(schema_name, table_name, &inferred)
+                .await?;
+        }
+        let table_id = self.get_table_id(schema_name, table_name).await?;
+        let columns = self.core.table_structure(table_id).await?;
+        let batches = import::json_batches(reader, &schema::arrow_schema(&columns)?, options)?;
+        self.import_batches(
+            schema_name,
+            table_name,
+            table_id,
+            &columns,
+            batches,
+            options,
+        )
+        .await
+    }
+
+    /// The Arrow schema of a table, see [`schema::arrow_schema`]
+    pub async fn table_schema(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+    ) -> duckpond_core::Result<SchemaRef> {
+        let table_id = self.get_table_id(schema_name, table_name).await?;
+        let columns = self.core.table_structure(table_id).await?;
+        Ok(Arc::new(schema::arrow_schema(&columns)?))
+    }
+
+    /// Read the rows of a table, less those in delete files, that match an
+    /// optional filter
+    ///
+    /// Unlike [`Lakehouse::read_from_table`], batches are conformed to the
+    /// table's Arrow schema (see [`schema::conform_batches`]), which is
+    /// returned along with them so that tables without rows have one too.
+    /// Only the matching rows are held; see [`Lakehouse::scan_files`].
+    pub async fn scan_table(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        filter: Option<&Filter>,
+    ) -> duckpond_core::Result<(SchemaRef, Vec<RecordBatch>)> {
+        let mut batches = Vec::new();
+        let schema = self
+            .scan_files(schema_name, table_name, filter, |batch| {
+                batches.push(batch);
+                Ok(())
+            })
+            .await?;
+        Ok((schema, batches))
+    }
+
+    /// Pass the rows of a table, less those in delete files, that match an
+    /// optional filter to `each`, one data file at a time, and return the
+    /// table's Arrow schema
+    ///
+    /// Files whose column statistics rule out every row are not read; see
+    /// [`Filter::may_match`].
+    async fn scan_files(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        filter: Option<&Filter>,
+        mut each: impl FnMut(RecordBatch) -> duckpond_core::Result<()> + Send,
+    ) -> duckpond_core::Result<SchemaRef> {
+        // Pinned to one snapshot, like read_from_table
+        let table_id = self.get_table_id(schema_name, table_name).await?;
+        let snapshot = self.core.current_snapshot().await?.unwrap_or(0);
+        let time_travel = self.core.at_snapshot(snapshot);
+        let columns = time_travel.table_structure(table_id).await?;
+        let table_schema = Arc::new(schema::arrow_schema(&columns)?);
+        // Check the filter even if there are no rows to apply it to
+        if let Some(filter) = filter {
+            filter.evaluate(&RecordBatch::new_empty(table_schema.clone()))?;
+        }
+        let mut emit = |batches: Vec<RecordBatch>| -> duckpond_core::Result<()> {
+            for batch in schema::conform_batches(&batches, &columns)? {
+                let batch = match filter {
+                    Some(filter) => filter.apply(&batch)?,
+                    None => batch,
+                };
+                if batch.num_rows() > 0 {
+                    each(batch)?;
+                }
+            }
+            Ok(())
+        };
+
+        let mut file_stats: HashMap<i64, HashMap<&str, _>> = HashMap::new();
+        if filter.is_some() {
+            let names: HashMap<i64, &str> = columns
+                .iter()
+                .map(|c| (c.column_id, c.column_name.as_str()))
+                .collect();
+            for stats in self.core.file_column_stats(table_id).await? {
+                if let Some(&name) = names.get(&stats.column_id) {
+                    file_stats
+                        .entry(stats.data_file_id)
+                        .or_default()
+                        .insert(name, stats);
+                }
+            }
+        }
+        for file in time_travel.list_data_files(table_id).await? {
+            if let (Some(filter), Some(stats)) = (filter, file_stats.get(&file.data_file_id)) {
+                if !filter.may_match(&table_schema, stats) {
+                    continue;
+                }
+            }
+            emit(self.read_live_rows(&file, &ReadOptions::default()).await?)?;
+        }
+
+        let inlined = time_travel.list_inlined_rows(table_id).await?;
+        if !inlined.is_empty() {
+            let batch = inlined_batch(&inlined, &columns)
+                .map_err(|e| duckpond_core::error::DuckPondError::ConfigError(e.to_string()))?;
+            emit(vec![batch])?;
+        }
+        Ok(table_schema)
+    }
+
+    /// Export the rows of a table, less those in delete files, to `writer`
+    ///
+    /// Columns are written in table order, with their column IDs as Parquet
+    /// field IDs. To export the table as of an earlier snapshot, export from
+    /// [`Lakehouse::as_of`]. Rows are written one data file at a time, so
+    /// that only one file's rows are held at once. Returns the number of
+    /// rows written.
+    pub async fn export<W: Write + Send>(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        writer: W,
+        options: &ExportOptions,
+    ) -> duckpond_core::Result<usize> {
+        let table_schema = self.table_schema(schema_name, table_name).await?;
+        let mut writer = export::BatchWriter::try_new(writer, options.format, table_schema)?;
+        let mut rows = 0;
+        self.scan_files(schema_name, table_name, options.filter.as_ref(), |batch| {
+            rows += batch.num_rows();
+            writer.write(&batch)
+        })
+        .await?;
+        writer.finish()?;
+        Ok(rows)
+    }
+
+    /// Write parsed batches to Parquet files, rolling over to a new file
+    /// every `options.records_per_file` records, and add the files to the
+    /// table in one snapshot
+    async fn import_batches(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        table_id: i64,
+        columns: &[ColumnInfo],
+        batches: impl Iterator<Item = duckpond_core::Result<ImportBatch>>,
+        options: &ImportOptions,
+    ) -> duckpond_core::Result<ImportSummary> {
+        let write_config = ParquetWriteConfig {
+            sort_order: self.core.sort_order(table_id).await?,
+            ..Default::default()
+        };
+
+        let table_path = self.table_path(schema_name, table_name, table_id).await?;
+        let records_per_file = options.records_per_file.max(1);
+        let mut summary = ImportSummary::default();
+        let mut files = Vec::new();
+        let mut pending = Vec::new();
+        let mut pending_records = 0;
+        for batch in batches {
+            let (mut batch, skipped) = batch?;
+            summary.records_skipped += skipped;
+            while batch.num_rows() > 0 {
+                let take = batch.num_rows().min(records_per_file - pending_records);
+                pending.push(batch.slice(0, take));
+                pending_records += take;
+                batch = batch.slice(take, batch.num_rows() - take);
+
+                if pending_records == records_per_file {
+                    let path = data_file_path(&table_path, table_id);
+                    let data = schema::conform_batches(&std::mem::take(&mut pending), columns)?;
+                    files.push(self.write_data_file(path, data, &write_config).await?);
+                    pending_records = 0;
+                }
+            }
+        }
+        if pending_records > 0 {
+            let path = data_file_path(&table_path, table_id);
+            let data = schema::conform_batches(&pending, columns)?;
+            files.push(self.write_data_file(path, data, &write_config).await?);
+        }
+
+        summary.records_imported = files.iter().map(|f| f.record_count as usize).sum();
+        summary.data_file_ids = self.core.add_data_files(table_id, &files).await?;
+        Ok(summary)
+    }
+
+    async fn write_data_file(
+        &self,
+        path: String,
+        data: Vec<RecordBatch>,
+        write_config: &ParquetWriteConfig,
+    ) -> duckpond_core::Result<NewDataFile> {
+        let file_stats = self
+            .parquet_manager
+            .write_file(&path, data, write_config.clone())
+            .await
+            .map_err(|e| DuckPondError::ConfigError(e.to_string()))?;
+        Ok(NewDataFile {
+            path,
+            path_is_relative: true,
+            record_count: file_stats.record_count as i64,
+            file_size_bytes: file_stats.file_size_bytes as i64,
+            footer_size: file_stats.footer_size.map(|size| size as i64),
+            column_statistics: column_statistics(file_stats.column_stats),
+        })
+    }
+
     /// Read data from a table with optional time travel
     pub async fn read_from_table(
         &self,
@@ -119,41 +494,307 @@ impl Lakehouse {
         table_name: &str,
         snapshot_id: Option<Uuid>,
     ) -> duckpond_core::Result<Vec<RecordBatch>> {
-        // 1. Get table metadata and file list from duckpond-core
-        let table_id = self.get_table_id(schema_name, table_name).await?;
-        let files = self.core.list_data_files(table_id).await?;
+        self.read_from_table_with_options(
+            schema_name,
+            table_name,
+            snapshot_id,
+            &ReadOptions::default(),
+        )
+        .await
+    }
 
-        if files.is_empty() {
-            return Ok(Vec::new());
-        }
+    /// Read data from a table, appending the virtual columns selected in
+    /// `options` after the table's columns. Rows listed in delete files are
+    /// left out.
+    pub async fn read_from_table_with_options(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        snapshot_id: Option<Uuid>,
+        options: &ReadOptions,
+    ) -> duckpond_core::Result<Vec<RecordBatch>> {
+        // 1. Get table metadata and file list from duckpond-core, pinned to
+        // one snapshot so that a concurrent flush cannot show rows twice
+        let table_id = self.get_table_id(schema_name, table_name).await?;
+        let snapshot = self.core.current_snapshot().await?.unwrap_or(0);
+        let time_travel = self.core.at_snapshot(snapshot);
+        let files = time_travel.list_data_files(table_id).await?;
 
         // 2. Read all Parquet files and combine the results
+        let _ = snapshot_id; // TODO: Apply snapshot filtering if snapshot_id is provided
         let mut all_batches = Vec::new();
-        let read_config = ParquetReadConfig::default();
+        for file in &files {
+            all_batches.extend(self.read_live_rows(file, options).await?);
+        }
+
+        // 3. Add the rows inlined into the catalog
+        let inlined = time_travel.list_inlined_rows(table_id).await?;
+        if !inlined.is_empty() {
+            let columns = time_travel.table_structure(table_id).await?;
+            let batch = inlined_batch(&inlined, &columns)
+                .and_then(|batch| {
+                    append_virtual_columns(
+                        batch,
+                        options,
+                        inlined.iter().map(|r| r.row_id).collect(),
+                        inlined.iter().map(|r| r.begin_snapshot).collect(),
+                    )
+                })
+                .map_err(|e| duckpond_core::error::DuckPondError::ConfigError(e.to_string()))?;
+            all_batches.push(batch);
+        }
+
+        // 4. TODO: Apply any necessary schema evolution
+        // 5. TODO: Apply filters or projections if specified
+
+        Ok(all_batches)
+    }
+
+    /// Get the largest write, in rows, kept in the catalog instead of a
+    /// Parquet file; 0 disables inlining
+    pub async fn data_inlining_row_limit(&self) -> duckpond_core::Result<usize> {
+        self.core.data_inlining_row_limit().await
+    }
 
-        for file in files {
-            // TODO: Apply snapshot filtering if snapshot_id is provided
-            let _ = snapshot_id; // Silence unused warning for now
+    /// Set the largest write, in rows, kept in the catalog instead of a
+    /// Parquet file
+    pub async fn set_data_inlining_row_limit(&self, limit: usize) -> duckpond_core::Result<()> {
+        self.core.set_data_inlining_row_limit(limit).await
+    }
 
-            match self
+    /// Move a table's inlined rows into Parquet files, one per run of
+    /// consecutive row IDs
+    ///
+    /// Rows are written in row ID order, ignoring the table's sort order, so
+    /// that they keep their row IDs. Returns the number of rows moved.
+    pub async fn flush_inlined_data(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+    ) -> duckpond_core::Result<usize> {
+        let table_id = self.get_table_id(schema_name, table_name).await?;
+        let rows = self.core.list_inlined_rows(table_id).await?;
+        let columns = self.core.table_structure(table_id).await?;
+        let table_path = self.table_path(schema_name, table_name, table_id).await?;
+
+        let mut files = Vec::new();
+        for run in rows.chunk_by(|a, b| a.row_id + 1 == b.row_id) {
+            let batch = inlined_batch(run, &columns)
+                .map_err(|e| duckpond_core::error::DuckPondError::ConfigError(e.to_string()))?;
+            let batches = schema::conform_batches(&[batch], &columns)?;
+            let file_path = data_file_path(&table_path, table_id);
+            let file_stats = self
                 .parquet_manager
-                .read_file(&file.data_file_path, read_config.clone())
+                .write_file(&file_path, batches, ParquetWriteConfig::default())
                 .await
-            {
-                Ok(mut batches) => {
-                    all_batches.append(&mut batches);
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to read file {}: {}", file.data_file_path, e);
-                    // Continue reading other files rather than failing completely
-                }
+                .map_err(|e| duckpond_core::error::DuckPondError::ConfigError(e.to_string()))?;
+            files.push(FlushedDataFile {
+                path: file_path,
+                row_id_start: run[0].row_id,
+                record_count: file_stats.record_count as i64,
+                file_size_bytes: file_stats.file_size_bytes as i64,
+                column_statistics: column_statistics(file_stats.column_stats),
+            });
+        }
+
+        if !files.is_empty() {
+            self.core.flush_inlined_rows(table_id, &files).await?;
+        }
+        Ok(rows.len())
+    }
+
+    /// Read the rows inserted and deleted after snapshot `from_snapshot` up
+    /// to and including `to_snapshot`
+    ///
+    /// Each batch holds the changes of one data file in one snapshot, with
+    /// `snapshot_id`, `rowid` and `change_type` (`insert` or `delete`)
+    /// columns ahead of the table's columns. Deleted rows carry the values
+    /// they had before the delete. Batches are ordered by snapshot.
+    pub async fn table_changes(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        from_snapshot: i64,
+        to_snapshot: i64,
+    ) -> duckpond_core::Result<Vec<RecordBatch>> {
+        let table_id = self.get_table_id(schema_name, table_name).await?;
+        let history = self
+            .core
+            .table_file_history(table_id, from_snapshot, to_snapshot)
+            .await?;
+
+        let mut deleted_positions = HashMap::new();
+        for delete_file in &history.delete_files {
+            deleted_positions.insert(
+                delete_file.delete_file_id,
+                self.read_deleted_positions(&delete_file.path).await?,
+            );
+        }
+        let changes =
+            change_feed::row_changes(&history, &deleted_positions, from_snapshot, to_snapshot);
+
+        let mut files: HashMap<i64, RecordBatch> = HashMap::new();
+        let mut batches = Vec::new();
+        for group in changes.chunk_by(|a, b| {
+            (a.snapshot_id, a.data_file_id, a.change_type)
+                == (b.snapshot_id, b.data_file_id, b.change_type)
+        }) {
+            let (snapshot_id, data_file_id, change_type) = (
+                group[0].snapshot_id,
+                group[0].data_file_id,
+                group[0].change_type,
+            );
+            if let Entry::Vacant(entry) = files.entry(data_file_id) {
+                let path = history
+                    .data_files
+                    .iter()
+                    .find(|f| f.data_file_id == data_file_id)
+                    .map(|f| f.path.clone())
+                    .unwrap_or_default();
+                entry.insert(self.read_data_file(&path).await?);
             }
+
+            let positions: UInt32Array = group.iter().map(|c| c.position as u32).collect();
+            let rows = take_record_batch(&files[&data_file_id], &positions)
+                .map_err(|e| duckpond_core::error::DuckPondError::ConfigError(e.to_string()))?;
+            let rowids: Int64Array = group.iter().map(|c| c.rowid).collect();
+            batches.push(
+                with_change_columns(rows, snapshot_id, rowids, change_type)
+                    .map_err(|e| duckpond_core::error::DuckPondError::ConfigError(e.to_string()))?,
+            );
         }
 
-        // 3. TODO: Apply any necessary schema evolution
-        // 4. TODO: Apply filters or projections if specified
+        Ok(batches)
+    }
 
-        Ok(all_batches)
+    /// Read a data file into a single batch so rows can be taken by position
+    async fn read_data_file(&self, path: &str) -> duckpond_core::Result<RecordBatch> {
+        let batches = self
+            .parquet_manager
+            .read_file(path, ParquetReadConfig::default())
+            .await
+            .map_err(|e| duckpond_core::error::DuckPondError::ConfigError(e.to_string()))?;
+        let schema = match batches.first() {
+            Some(batch) => batch.schema(),
+            None => Arc::new(Schema::empty()),
+        };
+        concat_batches(&schema, &batches)
+            .map_err(|e| duckpond_core::error::DuckPondError::ConfigError(e.to_string()))
+    }
+
+    /// Read the live rows of a data file, leaving out the positions its
+    /// delete file lists; a file that cannot be read fails the read rather
+    /// than leaving its rows out
+    async fn read_live_rows(
+        &self,
+        file: &DataFileInfo,
+        options: &ReadOptions,
+    ) -> duckpond_core::Result<Vec<RecordBatch>> {
+        let deleted: HashSet<i64> = match &file.delete_file_path {
+            Some(path) => self
+                .read_deleted_positions(path)
+                .await?
+                .into_iter()
+                .collect(),
+            None => HashSet::new(),
+        };
+
+        let batches = self
+            .parquet_manager
+            .read_file(&file.data_file_path, ParquetReadConfig::default())
+            .await
+            .map_err(|e| {
+                DuckPondError::StorageError(format!(
+                    "Failed to read data file {}: {}",
+                    file.data_file_path, e
+                ))
+            })?;
+
+        // Row IDs follow the position of each row within its file
+        let mut live_batches = Vec::new();
+        let mut position = 0;
+        for batch in batches {
+            let num_rows = batch.num_rows() as i64;
+            let batch = with_virtual_columns(
+                batch,
+                options,
+                file.row_id_start + position,
+                file.begin_snapshot,
+            )
+            .and_then(|batch| {
+                if deleted.is_empty() {
+                    return Ok(batch);
+                }
+                let live: BooleanArray = (position..position + num_rows)
+                    .map(|p| Some(!deleted.contains(&p)))
+                    .collect();
+                filter_record_batch(&batch, &live)
+            })
+            .map_err(|e| DuckPondError::ConfigError(e.to_string()))?;
+            live_batches.push(batch);
+            position += num_rows;
+        }
+        Ok(live_batches)
+    }
+
+    /// Read the row positions listed in a delete file's `pos` column
+    async fn read_deleted_positions(&self, path: &str) -> duckpond_core::Result<Vec<i64>> {
+        let read_config = ParquetReadConfig {
+            column_selection: Some(vec!["pos".to_string()]),
+            ..Default::default()
+        };
+        let batches = self
+            .parquet_manager
+            .read_file(path, read_config)
+            .await
+            .map_err(|e| duckpond_core::error::DuckPondError::ConfigError(e.to_string()))?;
+
+        let mut positions = Vec::new();
+        for batch in batches {
+            let column = batch
+                .column_by_name("pos")
+                .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                .ok_or_else(|| {
+                    duckpond_core::error::DuckPondError::ConfigError(format!(
+                        "Delete file {} has no BIGINT pos column",
+                        path
+                    ))
+                })?;
+            positions.extend(column.iter().flatten());
+        }
+        Ok(positions)
+    }
+
+    /// Switch this lakehouse to read and write a branch
+    pub async fn on_branch(self, branch: &str) -> duckpond_core::Result<Self> {
+        Ok(Self {
+            core: self.core.on_branch(branch).await?,
+            ..self
+        })
+    }
+
+    /// Switch this lakehouse to read the catalog as of a snapshot
+    pub async fn as_of(self, snapshot_id: i64) -> duckpond_core::Result<Self> {
+        Ok(Self {
+            core: self.core.as_of(snapshot_id).await?,
+            ..self
+        })
+    }
+
+    /// The directory new data files of a table are written to, relative to
+    /// the data path; tables of catalogs still on spec 0.1 have no path of
+    /// their own and use `<schema>/<table>/`
+    async fn table_path(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        table_id: i64,
+    ) -> duckpond_core::Result<String> {
+        Ok(self
+            .core
+            .table_path(table_id)
+            .await?
+            .unwrap_or_else(|| format!("{}/{}/", schema_name, table_name)))
     }
 
     /// Get the underlying DuckPond core for advanced operations
@@ -161,8 +802,8 @@ impl Lakehouse {
         &self.core
     }
 
-    /// Helper function to get table ID from schema and table names
-    async fn get_table_id(
+    /// Get the ID of a table from schema and table names
+    pub async fn get_table_id(
         &self,
         schema_name: &str,
         table_name: &str,
@@ -196,8 +837,18 @@ impl Lakehouse {
         Ok(table_id)
     }
 
-    /// Helper function to get schema ID from schema name
-    async fn get_schema_id(&self, schema_name: &str) -> duckpond_core::Result<i64> {
+    async fn table_exists(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+    ) -> duckpond_core::Result<bool> {
+        let schema_id = self.get_schema_id(schema_name).await?;
+        let tables = self.core.list_tables(schema_id).await?;
+        Ok(tables.iter().any(|t| t.table_name == table_name))
+    }
+
+    /// Get the ID of a schema from its name
+    pub async fn get_schema_id(&self, schema_name: &str) -> duckpond_core::Result<i64> {
         let schemas = self.core.list_schemas().await?;
         schemas
             .iter()
@@ -231,6 +882,60 @@ impl Lakehouse {
         Ok(())
     }
 
+    /// Create a table with columns inferred from an Arrow schema, including
+    /// nested fields; see [`schema::column_definitions`]
+    pub async fn create_table_from_arrow_schema(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        arrow_schema: &Schema,
+    ) -> duckpond_core::Result<()> {
+        let columns = schema::column_definitions(arrow_schema)?;
+        self.create_table(schema_name, table_name, columns).await
+    }
+
+    /// Drop a schema, which must hold no tables or views
+    pub async fn drop_schema(&self, schema_name: &str) -> duckpond_core::Result<()> {
+        let schema_id = self.get_schema_id(schema_name).await?;
+        self.core.drop_schema(schema_id).await
+    }
+
+    /// Drop a table; its files stay in storage until the snapshots that can
+    /// see it are expired
+    pub async fn drop_table(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+    ) -> duckpond_core::Result<()> {
+        let schema_id = self.get_schema_id(schema_name).await?;
+        self.core.drop_table(schema_id, table_name).await
+    }
+
+    /// Rename a table within its schema
+    pub async fn rename_table(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        new_name: &str,
+    ) -> duckpond_core::Result<()> {
+        let schema_id = self.get_schema_id(schema_name).await?;
+        self.core
+            .rename_table(schema_id, table_name, new_name)
+            .await?;
+        Ok(())
+    }
+
+    /// Add a nullable column to a table
+    pub async fn add_column(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        column: duckpond_core::ColumnDefinition,
+    ) -> duckpond_core::Result<()> {
+        let table_id = self.get_table_id(schema_name, table_name).await?;
+        self.core.add_column(table_id, column).await
+    }
+
     /// List all schemas
     pub async fn list_schemas(&self) -> duckpond_core::Result<Vec<String>> {
         let schemas = self.core.list_schemas().await?;
@@ -244,6 +949,24 @@ impl Lakehouse {
         Ok(tables.into_iter().map(|t| t.table_name).collect())
     }
 
+    /// List views in a schema
+    pub async fn list_views(&self, schema_name: &str) -> duckpond_core::Result<Vec<String>> {
+        let schema_id = self.get_schema_id(schema_name).await?;
+        let views = self.core.list_views(schema_id).await?;
+        Ok(views.into_iter().map(|v| v.view_name).collect())
+    }
+
+    /// Resolve a view to its definition, for a query engine to expand in
+    /// place of a relation
+    pub async fn get_view(
+        &self,
+        schema_name: &str,
+        view_name: &str,
+    ) -> duckpond_core::Result<ViewInfo> {
+        let schema_id = self.get_schema_id(schema_name).await?;
+        self.core.get_view(schema_id, view_name).await
+    }
+
     /// Get table structure
     pub async fn show_table_structure(
         &self,
@@ -253,21 +976,128 @@ impl Lakehouse {
         let table_id = self.get_table_id(schema_name, table_name).await?;
         let columns = self.core.table_structure(table_id).await?;
 
-        // Convert ColumnInfo to ColumnDefinition
-        let column_defs = columns
-            .into_iter()
-            .map(|col| duckpond_core::ColumnDefinition {
-                column_id: Some(col.column_id),
-                name: col.column_name,
-                data_type: col.column_type,
-                nullable: col.nulls_allowed,
-            })
+        Ok(columns.into_iter().map(Into::into).collect())
+    }
+
+    /// Get the sort order applied when writing a table
+    pub async fn sort_order(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+    ) -> duckpond_core::Result<Option<SortOrder>> {
+        let table_id = self.get_table_id(schema_name, table_name).await?;
+        self.core.sort_order(table_id).await
+    }
+
+    /// Set (or clear, with `None`) the sort order applied when writing a table
+    pub async fn set_sort_order(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        sort_order: Option<SortOrder>,
+    ) -> duckpond_core::Result<()> {
+        let table_id = self.get_table_id(schema_name, table_name).await?;
+        self.core
+            .set_sort_order(table_id, sort_order.as_ref())
+            .await
+    }
+
+    /// Remove files scheduled for deletion from storage
+    ///
+    /// Only files scheduled at least `grace_period` ago are removed, so that
+    /// readers which planned a scan before the snapshots were expired can
+    /// still finish. Returns the paths that were deleted.
+    pub async fn vacuum(&self, grace_period: Duration) -> duckpond_core::Result<Vec<String>> {
+        let cutoff = Utc::now() - grace_period;
+        let mut deleted = Vec::new();
+
+        for file in self.core.scheduled_deletions().await? {
+            if file.schedule_start > cutoff {
+                continue;
+            }
+
+            match self.filesystem.delete_file(&file.path).await {
+                // A file that is already gone only needs its schedule entry removed
+                Ok(()) | Err(StorageError::FileNotFound { .. }) => {}
+                Err(e) => {
+                    tracing::warn!("Failed to delete {}: {}", file.path, e);
+                    continue;
+                }
+            }
+            self.core
+                .remove_scheduled_deletion(file.data_file_id, &file.path)
+                .await?;
+            deleted.push(file.path);
+        }
+
+        Ok(deleted)
+    }
+
+    /// Find Parquet files in storage that no catalog row references
+    ///
+    /// Such files are left behind by writes that failed before their metadata
+    /// was committed. Files modified within `safety_window` are skipped, since
+    /// they may belong to a write that is still in progress.
+    pub async fn find_orphans(
+        &self,
+        safety_window: Duration,
+    ) -> duckpond_core::Result<Vec<FileMetadata>> {
+        let referenced: HashSet<String> = self
+            .core
+            .referenced_file_paths()
+            .await?
+            .iter()
+            .map(|path| self.relative_to_data_path(path))
             .collect();
+        let cutoff = Utc::now() - safety_window;
+
+        let files = self
+            .filesystem
+            .list_files("")
+            .await
+            .map_err(|e| duckpond_core::error::DuckPondError::StorageError(e.to_string()))?;
+
+        Ok(files
+            .into_iter()
+            .filter(|file| file.path.ends_with(".parquet"))
+            .filter(|file| !referenced.contains(&self.relative_to_data_path(&file.path)))
+            .filter(|file| file.modified.is_some_and(|modified| modified <= cutoff))
+            .collect())
+    }
+
+    /// A file path relative to the data path, as storage lists it; absolute
+    /// paths under the data path are made relative, others kept as they are
+    fn relative_to_data_path(&self, path: &str) -> String {
+        if PathUtils::is_absolute(path) {
+            if let Ok(relative) = Path::new(path).strip_prefix(&self.data_path) {
+                return PathUtils::normalize_path(relative);
+            }
+        }
+        path.to_string()
+    }
 
-        Ok(column_defs)
+    /// Delete orphaned files found by [`Lakehouse::find_orphans`]
+    ///
+    /// Returns the paths that were deleted.
+    pub async fn remove_orphans(
+        &self,
+        safety_window: Duration,
+    ) -> duckpond_core::Result<Vec<String>> {
+        let mut deleted = Vec::new();
+        for file in self.find_orphans(safety_window).await? {
+            match self.filesystem.delete_file(&file.path).await {
+                Ok(()) | Err(StorageError::FileNotFound { .. }) => deleted.push(file.path),
+                Err(e) => tracing::warn!("Failed to delete orphan {}: {}", file.path, e),
+            }
+        }
+        Ok(deleted)
     }
 
-    /// Compact files for a table (merge small files into larger ones)
+    /// Compact the data files of a table into one file, in one snapshot
+    ///
+    /// Rows removed by delete files are left out and the rest are written
+    /// in the table's sort order, if it has one, under new row IDs. The
+    /// replaced files stay readable at earlier snapshots until those expire.
     pub async fn compact_table(
         &self,
         schema_name: &str,
@@ -277,32 +1107,654 @@ impl Lakehouse {
         let table_id = self.get_table_id(schema_name, table_name).await?;
         let files = self.core.list_data_files(table_id).await?;
 
-        if files.len() <= 1 {
+        if files.len() <= 1 && files.iter().all(|f| f.delete_file_path.is_none()) {
             return Ok(()); // Nothing to compact
         }
 
-        // 2. Read all files and merge them
-        let file_paths: Vec<String> = files.iter().map(|f| f.data_file_path.clone()).collect();
-        let output_path = format!(
-            "{}/{}/compacted_{}.parquet",
-            schema_name,
-            table_name,
-            Uuid::new_v4()
-        );
+        // 2. Read the live rows of every file, conformed to the current
+        // columns since older files may lack columns added later
+        let columns = self.core.table_structure(table_id).await?;
+        let mut batches = Vec::new();
+        for file in &files {
+            batches.extend(self.read_live_rows(file, &ReadOptions::default()).await?);
+        }
+        let batches = schema::conform_batches(&batches, &columns)?;
 
-        let write_config = ParquetWriteConfig::default();
-        let _compacted_stats = self
-            .parquet_manager
-            .merge_files(&file_paths, &output_path, write_config)
-            .await
-            .map_err(|e| duckpond_core::error::DuckPondError::ConfigError(e.to_string()))?;
+        // 3. Write them out clustered by the table's sort order
+        let write_config = ParquetWriteConfig {
+            sort_order: self.core.sort_order(table_id).await?,
+            ..Default::default()
+        };
+        let table_path = self.table_path(schema_name, table_name, table_id).await?;
+        let path = data_file_path(&table_path, table_id);
+        let file = self.write_data_file(path, batches, &write_config).await?;
 
-        // 3. TODO: Update metadata to reflect the compaction
-        // - Create new file record for compacted file
-        // - Mark old files as deleted or remove them
-        // - Create new snapshot
+        // 4. Swap the new file in for the old ones
+        let replaced: Vec<i64> = files.iter().map(|f| f.data_file_id).collect();
+        self.core
+            .replace_data_files(table_id, &replaced, &file)
+            .await?;
 
-        tracing::info!("Compacted {} files into {}", files.len(), output_path);
+        tracing::info!("Compacted {} files into {}", files.len(), file.path);
         Ok(())
     }
 }
+
+/// Unique path of a new data file of a table, under the table's path
+fn data_file_path(table_path: &str, table_id: i64) -> String {
+    format!("{}{}/data_{}.parquet", table_path, Uuid::new_v4(), table_id)
+}
+
+/// Prepend the `snapshot_id`, `rowid` and `change_type` columns of a change
+/// feed batch
+fn with_change_columns(
+    rows: RecordBatch,
+    snapshot_id: i64,
+    rowids: Int64Array,
+    change_type: ChangeType,
+) -> arrow::error::Result<RecordBatch> {
+    let num_rows = rows.num_rows();
+    let mut fields = vec![
+        Field::new("snapshot_id", DataType::Int64, false),
+        Field::new("rowid", DataType::Int64, false),
+        Field::new("change_type", DataType::Utf8, false),
+    ];
+    fields.extend(rows.schema().fields().iter().map(|f| f.as_ref().clone()));
+
+    let mut columns: Vec<ArrayRef> = vec![
+        Arc::new(Int64Array::from(vec![snapshot_id; num_rows])),
+        Arc::new(rowids),
+        Arc::new(StringArray::from(vec![change_type.as_str(); num_rows])),
+    ];
+    columns.extend(rows.columns().iter().cloned());
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+}
+
+/// Append the virtual columns selected in `options` to a batch read from a
+/// data file, given the row ID of the batch's first row
+fn with_virtual_columns(
+    batch: RecordBatch,
+    options: &ReadOptions,
+    first_rowid: i64,
+    snapshot_id: i64,
+) -> arrow::error::Result<RecordBatch> {
+    if !options.rowid && !options.snapshot_id {
+        return Ok(batch);
+    }
+
+    let num_rows = batch.num_rows();
+    append_virtual_columns(
+        batch,
+        options,
+        Int64Array::from_iter_values(first_rowid..first_rowid + num_rows as i64),
+        Int64Array::from(vec![snapshot_id; num_rows]),
+    )
+}
+
+/// Append the virtual columns selected in `options` to a batch, given each
+/// row's row ID and snapshot
+fn append_virtual_columns(
+    batch: RecordBatch,
+    options: &ReadOptions,
+    rowids: Int64Array,
+    snapshot_ids: Int64Array,
+) -> arrow::error::Result<RecordBatch> {
+    if !options.rowid && !options.snapshot_id {
+        return Ok(batch);
+    }
+
+    let mut fields: Vec<Field> = batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| f.as_ref().clone())
+        .collect();
+    let mut columns = batch.columns().to_vec();
+    if options.rowid {
+        fields.push(Field::new("rowid", DataType::Int64, false));
+        columns.push(Arc::new(rowids));
+    }
+    if options.snapshot_id {
+        fields.push(Field::new("snapshot_id", DataType::Int64, false));
+        columns.push(Arc::new(snapshot_ids));
+    }
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+}
+
+/// Convert parquet column stats to core column stats
+fn column_statistics(
+    column_stats: Vec<ParquetColumnStats>,
+) -> Vec<duckpond_core::FileColumnStatistics> {
+    column_stats
+        .into_iter()
+        .map(|col_stat| duckpond_core::FileColumnStatistics {
+            column_id: col_stat.column_id,
+            value_count: col_stat.value_count as i64,
+            null_count: col_stat.null_count as i64,
+            nan_count: col_stat.nan_count as i64,
+            min_value: col_stat.min_value,
+            max_value: col_stat.max_value,
+        })
+        .collect()
+}
+
+/// Convert batches to inlined rows with values in the order of the table's
+/// columns, or `None` if they hold data that cannot be inlined
+fn inlined_rows(batches: &[RecordBatch], columns: &[ColumnInfo]) -> Option<Vec<Vec<InlinedValue>>> {
+    let mut rows = Vec::new();
+    for batch in batches {
+        let schema = batch.schema();
+        if schema
+            .fields()
+            .iter()
+            .any(|f| !columns.iter().any(|c| &c.column_name == f.name()))
+        {
+            return None;
+        }
+
+        // Columns missing from the batch are inlined as NULL
+        let arrays = columns
+            .iter()
+            .map(|c| {
+                let kind = InlinedColumnKind::of(&c.column_type)?;
+                match batch.column_by_name(&c.column_name) {
+                    Some(array) => cast_for_inlining(array, kind).map(|a| Some((a, kind))),
+                    None => Some(None),
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+        for row in 0..batch.num_rows() {
+            rows.push(
+                arrays
+                    .iter()
+                    .map(|array| match array {
+                        Some((array, kind)) => inlined_value(array, row, *kind),
+                        None => InlinedValue::Null,
+                    })
+                    .collect(),
+            );
+        }
+    }
+    Some(rows)
+}
+
+/// Cast a column to the Arrow type inlined values of `kind` are taken from,
+/// or `None` if its type does not fit
+fn cast_for_inlining(array: &ArrayRef, kind: InlinedColumnKind) -> Option<ArrayRef> {
+    let target = match (kind, array.data_type()) {
+        (InlinedColumnKind::Boolean, DataType::Boolean) => DataType::Boolean,
+        (
+            InlinedColumnKind::Integer,
+            DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32,
+        ) => DataType::Int64,
+        (InlinedColumnKind::Float, DataType::Float16 | DataType::Float32 | DataType::Float64) => {
+            DataType::Float64
+        }
+        (InlinedColumnKind::Text, DataType::Utf8 | DataType::LargeUtf8) => DataType::Utf8,
+        _ => return None,
+    };
+    cast(array, &target).ok()
+}
+
+fn inlined_value(array: &ArrayRef, row: usize, kind: InlinedColumnKind) -> InlinedValue {
+    if array.is_null(row) {
+        return InlinedValue::Null;
+    }
+    match kind {
+        InlinedColumnKind::Boolean => InlinedValue::Boolean(array.as_boolean().value(row)),
+        InlinedColumnKind::Integer => {
+            InlinedValue::Integer(array.as_primitive::<Int64Type>().value(row))
+        }
+        InlinedColumnKind::Float => {
+            InlinedValue::Float(array.as_primitive::<Float64Type>().value(row))
+        }
+        InlinedColumnKind::Text => {
+            InlinedValue::Text(array.as_string::<i32>().value(row).to_string())
+        }
+    }
+}
+
+/// Build a batch of inlined rows with the table's columns
+fn inlined_batch(rows: &[InlinedRow], columns: &[ColumnInfo]) -> arrow::error::Result<RecordBatch> {
+    let mut fields = Vec::with_capacity(columns.len());
+    let mut arrays = Vec::with_capacity(columns.len());
+    for (i, column) in columns.iter().enumerate() {
+        let values = rows.iter().map(|r| &r.values[i]);
+        let array: ArrayRef = match InlinedColumnKind::of(&column.column_type) {
+            Some(InlinedColumnKind::Boolean) => Arc::new(
+                values
+                    .map(|v| match v {
+                        InlinedValue::Boolean(value) => Some(*value),
+                        _ => None,
+                    })
+                    .collect::<BooleanArray>(),
+            ),
+            Some(InlinedColumnKind::Integer) => Arc::new(
+                values
+                    .map(|v| match v {
+                        InlinedValue::Integer(value) => Some(*value),
+                        _ => None,
+                    })
+                    .collect::<Int64Array>(),
+            ),
+            Some(InlinedColumnKind::Float) => Arc::new(
+                values
+                    .map(|v| match v {
+                        InlinedValue::Float(value) => Some(*value),
+                        _ => None,
+                    })
+                    .collect::<Float64Array>(),
+            ),
+            Some(InlinedColumnKind::Text) => Arc::new(
+                values
+                    .map(|v| match v {
+                        InlinedValue::Text(value) => Some(value.as_str()),
+                        _ => None,
+                    })
+                    .collect::<StringArray>(),
+            ),
+            None => new_null_array(&DataType::Null, rows.len()),
+        };
+        let array = match column.column_type.parse().ok().and_then(schema::arrow_type) {
+            Some(data_type) if &data_type != array.data_type() => cast(&array, &data_type)?,
+            _ => array,
+        };
+        fields.push(Field::new(
+            &column.column_name,
+            array.data_type().clone(),
+            column.nulls_allowed || array.data_type() == &DataType::Null,
+        ));
+        arrays.push(array);
+    }
+
+    RecordBatch::try_new_with_options(
+        Arc::new(Schema::new(fields)),
+        arrays,
+        &arrow::array::RecordBatchOptions::new().with_row_count(Some(rows.len())),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use duckpond_core::ColumnDefinition;
+
+    /// A lakehouse over a fresh SQLite catalog and data directory
+    async fn test_lakehouse() -> (Lakehouse, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("duckpond-{}", Uuid::new_v4().simple()));
+        let data_path = dir.join("data").to_string_lossy().into_owned();
+        std::fs::create_dir_all(&data_path).unwrap();
+        let database_url = format!("sqlite://{}?mode=rwc", dir.join("catalog.db").display());
+
+        let database = duckpond_core::database::Database::new(&DuckPondConfig::new(
+            database_url.clone(),
+            data_path.clone(),
+        ))
+        .await
+        .unwrap();
+        database
+            .backend()
+            .dialect()
+            .migrator()
+            .run(database.pool())
+            .await
+            .unwrap();
+        DuckPond::new(database.pool().clone(), database.backend().clone())
+            .initialize(&data_path, false)
+            .await
+            .unwrap();
+        let lakehouse = Lakehouse::new_with_local_storage(database_url, data_path)
+            .await
+            .unwrap();
+        (lakehouse, dir)
+    }
+
+    async fn create_events(lakehouse: &Lakehouse) {
+        let column = |name: &str, data_type: &str| ColumnDefinition {
+            column_id: None,
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable: true,
+            children: Vec::new(),
+        };
+        lakehouse
+            .create_table(
+                "main",
+                "events",
+                vec![column("id", "int64"), column("name", "varchar")],
+            )
+            .await
+            .unwrap();
+    }
+
+    fn events_batch(ids: Vec<i64>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, true),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        let names: Vec<String> = ids.iter().map(|id| format!("event {}", id)).collect();
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(ids)),
+                Arc::new(StringArray::from(names)),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn ids(batches: &[RecordBatch]) -> Vec<i64> {
+        batches
+            .iter()
+            .flat_map(|batch| {
+                batch
+                    .column_by_name("id")
+                    .unwrap()
+                    .as_primitive::<Int64Type>()
+                    .values()
+                    .to_vec()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_vacuum_keeps_restored_files() {
+        let (lakehouse, dir) = test_lakehouse().await;
+        create_events(&lakehouse).await;
+        let table_id = lakehouse.get_table_id("main", "events").await.unwrap();
+        let created = lakehouse.core().current_snapshot().await.unwrap().unwrap();
+        lakehouse
+            .write_to_table("main", "events", vec![events_batch(vec![1, 2, 3])])
+            .await
+            .unwrap();
+        let written = lakehouse.core().current_snapshot().await.unwrap().unwrap();
+        let path = lakehouse.core().list_data_files(table_id).await.unwrap()[0]
+            .data_file_path
+            .clone();
+
+        // Roll the write back, then restore it: the file comes back under a
+        // new ID while its original row stays ended
+        lakehouse
+            .core()
+            .restore_table(table_id, created)
+            .await
+            .unwrap();
+        lakehouse
+            .core()
+            .restore_table(table_id, written)
+            .await
+            .unwrap();
+
+        let expiration = lakehouse
+            .core()
+            .expire_snapshots(Utc::now() + Duration::hours(1), 1)
+            .await
+            .unwrap();
+        assert!(!expiration.expired_snapshots.is_empty());
+        assert!(expiration.scheduled_files.is_empty());
+        lakehouse.vacuum(Duration::zero()).await.unwrap();
+
+        assert!(lakehouse.filesystem.file_exists(&path).await.unwrap());
+        let batches = lakehouse
+            .read_from_table("main", "events", None)
+            .await
+            .unwrap();
+        assert_eq!(ids(&batches), vec![1, 2, 3]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_orphans_match_absolute_paths() {
+        let (lakehouse, dir) = test_lakehouse().await;
+        create_events(&lakehouse).await;
+        lakehouse
+            .write_to_table("main", "events", vec![events_batch(vec![1, 2])])
+            .await
+            .unwrap();
+        let table_id = lakehouse.get_table_id("main", "events").await.unwrap();
+        let written = lakehouse.core().list_data_files(table_id).await.unwrap()[0]
+            .data_file_path
+            .clone();
+
+        // A file inside the data directory registered by its absolute path
+        lakehouse
+            .filesystem
+            .copy_file(&written, "external/events.parquet")
+            .await
+            .unwrap();
+        let absolute = dir.join("data/external/events.parquet");
+        lakehouse
+            .add_files("main", "events", &[absolute.to_str().unwrap()])
+            .await
+            .unwrap();
+
+        lakehouse
+            .filesystem
+            .copy_file(&written, "external/orphan.parquet")
+            .await
+            .unwrap();
+        let orphans: Vec<String> = lakehouse
+            .find_orphans(Duration::zero())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|file| file.path)
+            .collect();
+        assert_eq!(orphans, vec!["external/orphan.parquet".to_string()]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_fails_on_missing_file() {
+        let (lakehouse, dir) = test_lakehouse().await;
+        create_events(&lakehouse).await;
+        lakehouse
+            .write_to_table("main", "events", vec![events_batch(vec![1])])
+            .await
+            .unwrap();
+        lakehouse
+            .write_to_table("main", "events", vec![events_batch(vec![2])])
+            .await
+            .unwrap();
+        let table_id = lakehouse.get_table_id("main", "events").await.unwrap();
+        let missing = lakehouse.core().list_data_files(table_id).await.unwrap()[0]
+            .data_file_path
+            .clone();
+        lakehouse.filesystem.delete_file(&missing).await.unwrap();
+
+        let error = lakehouse
+            .read_from_table("main", "events", None)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains(&missing), "{}", error);
+        assert!(lakehouse.scan_table("main", "events", None).await.is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_export_skips_files_ruled_out_by_stats() {
+        let (lakehouse, dir) = test_lakehouse().await;
+        create_events(&lakehouse).await;
+        for ids in [vec![1, 2], vec![5, 6]] {
+            lakehouse
+                .write_to_table("main", "events", vec![events_batch(ids)])
+                .await
+                .unwrap();
+        }
+        // Only a file that is read can fail the export
+        let table_id = lakehouse.get_table_id("main", "events").await.unwrap();
+        let low = lakehouse.core().list_data_files(table_id).await.unwrap()[0]
+            .data_file_path
+            .clone();
+        lakehouse.filesystem.delete_file(&low).await.unwrap();
+
+        let options = |filter: Option<&str>| ExportOptions {
+            format: export::ExportFormat::Csv,
+            filter: filter.map(|f| f.parse().unwrap()),
+        };
+        let mut csv = Vec::new();
+        let rows = lakehouse
+            .export("main", "events", &mut csv, &options(Some("id > 5")))
+            .await
+            .unwrap();
+        assert_eq!(rows, 1);
+        assert_eq!(String::from_utf8(csv).unwrap(), "id,name\n6,event 6\n");
+        assert!(lakehouse
+            .export("main", "events", Vec::new(), &options(None))
+            .await
+            .is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_compact_table() {
+        let (lakehouse, dir) = test_lakehouse().await;
+        create_events(&lakehouse).await;
+        for ids in [vec![3, 1], vec![5, 2, 4]] {
+            lakehouse
+                .write_to_table("main", "events", vec![events_batch(ids)])
+                .await
+                .unwrap();
+        }
+        lakehouse
+            .set_sort_order("main", "events", Some("id DESC".parse().unwrap()))
+            .await
+            .unwrap();
+        let before = lakehouse.core().current_snapshot().await.unwrap().unwrap();
+
+        lakehouse.compact_table("main", "events").await.unwrap();
+
+        let table_id = lakehouse.get_table_id("main", "events").await.unwrap();
+        let files = lakehouse.core().list_data_files(table_id).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].record_count, 5);
+        let batches = lakehouse
+            .read_from_table("main", "events", None)
+            .await
+            .unwrap();
+        assert_eq!(ids(&batches), vec![5, 4, 3, 2, 1]);
+        let stats = lakehouse
+            .core()
+            .table_stats(table_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stats.record_count, 5);
+
+        // The replaced files are still there for earlier snapshots
+        let earlier = lakehouse
+            .core()
+            .list_data_files_at_snapshot(table_id, before)
+            .await
+            .unwrap();
+        assert_eq!(earlier.len(), 2);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_virtual_columns() {
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+        let batch =
+            RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(vec![7, 8, 9]))]).unwrap();
+
+        let unchanged = with_virtual_columns(batch.clone(), &ReadOptions::default(), 0, 0).unwrap();
+        assert_eq!(unchanged.num_columns(), 1);
+
+        let options = ReadOptions {
+            rowid: true,
+            snapshot_id: true,
+        };
+        let batch = with_virtual_columns(batch, &options, 100, 4).unwrap();
+        let names: Vec<&str> = batch
+            .schema_ref()
+            .fields()
+            .iter()
+            .map(|f| f.name().as_str())
+            .collect();
+        assert_eq!(names, vec!["id", "rowid", "snapshot_id"]);
+
+        let column = |name: &str| -> Vec<i64> {
+            batch
+                .column_by_name(name)
+                .unwrap()
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .unwrap()
+                .values()
+                .to_vec()
+        };
+        assert_eq!(column("rowid"), vec![100, 101, 102]);
+        assert_eq!(column("snapshot_id"), vec![4, 4, 4]);
+    }
+
+    #[test]
+    fn test_inlined_rows_round_trip() {
+        let columns = vec![
+            ColumnInfo {
+                column_id: 1,
+                column_name: "id".to_string(),
+                column_type: "int32".to_string(),
+                nulls_allowed: false,
+                children: Vec::new(),
+            },
+            ColumnInfo {
+                column_id: 2,
+                column_name: "name".to_string(),
+                column_type: "varchar".to_string(),
+                nulls_allowed: true,
+                children: Vec::new(),
+            },
+        ];
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(arrow::array::Int32Array::from(vec![1, 2]))],
+        )
+        .unwrap();
+
+        let rows = inlined_rows(&[batch], &columns).unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                vec![InlinedValue::Integer(1), InlinedValue::Null],
+                vec![InlinedValue::Integer(2), InlinedValue::Null],
+            ]
+        );
+
+        let rows: Vec<InlinedRow> = rows
+            .into_iter()
+            .zip(10..)
+            .map(|(values, row_id)| InlinedRow {
+                row_id,
+                begin_snapshot: 1,
+                values,
+            })
+            .collect();
+        let batch = inlined_batch(&rows, &columns).unwrap();
+        assert_eq!(batch.column(0).data_type(), &DataType::Int32);
+        assert_eq!(batch.column(1).null_count(), 2);
+
+        // Columns the table does not have are written to Parquet instead
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "other",
+            DataType::Int64,
+            false,
+        )]));
+        let batch =
+            RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(vec![1]))]).unwrap();
+        assert!(inlined_rows(&[batch], &columns).is_none());
+    }
+}
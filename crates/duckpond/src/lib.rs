@@ -15,8 +15,17 @@ pub use duckpond_storage::FileSystem;
 pub use duckpond_parquet as parquet;
 
 // High-level lakehouse operations
+pub mod change_feed;
+pub mod export;
+pub mod filter;
+pub mod import;
 pub mod lakehouse;
-pub use lakehouse::Lakehouse;
+pub mod schema;
+pub use change_feed::ChangeType;
+pub use export::{ExportFormat, ExportOptions};
+pub use filter::Filter;
+pub use import::{BadRecordPolicy, ImportOptions, ImportSummary};
+pub use lakehouse::{Lakehouse, ReadOptions};
 
 // Re-export commonly used types
 pub use duckpond_core::config::DuckPondConfig;
@@ -0,0 +1,689 @@
+//! Mapping between Arrow fields and DuckLake columns
+//!
+//! Nested Arrow types become child columns linked through `parent_column`:
+//! a `Struct` becomes a `struct` column with one child per field, a `List`
+//! a `list` column with a single `element` child, and a `Map` a `map` column
+//! with `key` and `value` children.
+//!
+//! Arrow fields built for a table's columns carry the DuckLake column ID as
+//! their Parquet field ID, so written files can be matched to `column_id`.
+
+use arrow::array::{
+    new_null_array, Array, ArrayRef, AsArray, ListArray, MapArray, RecordBatch, RecordBatchOptions,
+    StructArray,
+};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Fields, IntervalUnit, Schema, TimeUnit};
+use arrow::error::ArrowError;
+use duckpond_core::error::DuckPondError;
+use duckpond_core::models::ColumnInfo;
+use duckpond_core::{ColumnDefinition, DuckLakeType, Result};
+use duckpond_parquet::PARQUET_FIELD_ID_META_KEY;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Column definitions for the fields of an Arrow schema
+pub fn column_definitions(schema: &Schema) -> Result<Vec<ColumnDefinition>> {
+    schema
+        .fields()
+        .iter()
+        .map(|field| column_definition(field))
+        .collect()
+}
+
+/// Column definition for an Arrow field, with nested types as child columns
+pub fn column_definition(field: &Field) -> Result<ColumnDefinition> {
+    let column_type = ducklake_type(field.data_type()).ok_or_else(|| {
+        invalid_column(format!(
+            "field '{}' has unsupported type {}",
+            field.name(),
+            field.data_type()
+        ))
+    })?;
+    let children = match field.data_type() {
+        DataType::Struct(fields) => fields
+            .iter()
+            .map(|field| column_definition(field))
+            .collect::<Result<_>>()?,
+        DataType::List(element) | DataType::LargeList(element) => {
+            vec![column_definition(
+                &element.as_ref().clone().with_name("element"),
+            )?]
+        }
+        DataType::Map(entries, _) => {
+            let [key, value] = map_entries(entries).ok_or_else(|| {
+                invalid_column(format!(
+                    "map field '{}' does not hold key/value entries",
+                    field.name()
+                ))
+            })?;
+            vec![
+                column_definition(&key.as_ref().clone().with_name("key"))?,
+                column_definition(&value.as_ref().clone().with_name("value"))?,
+            ]
+        }
+        _ => Vec::new(),
+    };
+
+    Ok(ColumnDefinition {
+        column_id: None,
+        name: field.name().clone(),
+        data_type: column_type.to_string(),
+        nullable: field.is_nullable(),
+        children,
+    })
+}
+
+/// Arrow schema for the columns of a table
+pub fn arrow_schema(columns: &[ColumnInfo]) -> Result<Schema> {
+    Ok(Schema::new(
+        columns
+            .iter()
+            .map(arrow_field)
+            .collect::<Result<Vec<_>>>()?,
+    ))
+}
+
+/// Arrow field for a column, with child columns as nested fields
+pub fn arrow_field(column: &ColumnInfo) -> Result<Field> {
+    let column_type: DuckLakeType = column.column_type.parse()?;
+    if !column_type.accepts_children(column.children.len()) {
+        return Err(invalid_column(format!(
+            "column '{}' of type {} has {} child columns",
+            column.column_name,
+            column.column_type,
+            column.children.len()
+        )));
+    }
+
+    let mut children = column
+        .children
+        .iter()
+        .map(arrow_field)
+        .collect::<Result<Vec<_>>>()?;
+    let data_type = match column_type {
+        DuckLakeType::Struct => DataType::Struct(Fields::from(children)),
+        DuckLakeType::List => DataType::List(Arc::new(children.remove(0))),
+        DuckLakeType::Map => {
+            let value = children.remove(1);
+            let key = children.remove(0).with_nullable(false);
+            DataType::Map(
+                Arc::new(Field::new(
+                    "entries",
+                    DataType::Struct(Fields::from(vec![key, value])),
+                    false,
+                )),
+                false,
+            )
+        }
+        primitive => arrow_type(primitive).expect("primitive types map to Arrow"),
+    };
+
+    Ok(
+        Field::new(&column.column_name, data_type, column.nulls_allowed).with_metadata(
+            HashMap::from([(
+                PARQUET_FIELD_ID_META_KEY.to_string(),
+                column.column_id.to_string(),
+            )]),
+        ),
+    )
+}
+
+/// Arrow type of a primitive column type; nested types are built from their
+/// child columns by [`arrow_field`]
+pub fn arrow_type(column_type: DuckLakeType) -> Option<DataType> {
+    Some(match column_type {
+        DuckLakeType::Boolean => DataType::Boolean,
+        DuckLakeType::Int8 => DataType::Int8,
+        DuckLakeType::Int16 => DataType::Int16,
+        DuckLakeType::Int32 => DataType::Int32,
+        DuckLakeType::Int64 => DataType::Int64,
+        DuckLakeType::UInt8 => DataType::UInt8,
+        DuckLakeType::UInt16 => DataType::UInt16,
+        DuckLakeType::UInt32 => DataType::UInt32,
+        DuckLakeType::UInt64 => DataType::UInt64,
+        DuckLakeType::Float32 => DataType::Float32,
+        DuckLakeType::Float64 => DataType::Float64,
+        DuckLakeType::Decimal { precision, scale } => DataType::Decimal128(precision, scale as i8),
+        DuckLakeType::Varchar | DuckLakeType::Json => DataType::Utf8,
+        DuckLakeType::Blob => DataType::Binary,
+        DuckLakeType::Date => DataType::Date32,
+        // Arrow has no time with time zone; the offset is dropped
+        DuckLakeType::Time | DuckLakeType::TimeTz => DataType::Time64(TimeUnit::Microsecond),
+        DuckLakeType::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, None),
+        DuckLakeType::TimestampS => DataType::Timestamp(TimeUnit::Second, None),
+        DuckLakeType::TimestampMs => DataType::Timestamp(TimeUnit::Millisecond, None),
+        DuckLakeType::TimestampNs => DataType::Timestamp(TimeUnit::Nanosecond, None),
+        DuckLakeType::TimestampTz => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+        DuckLakeType::Interval => DataType::Interval(IntervalUnit::MonthDayNano),
+        DuckLakeType::Uuid => DataType::FixedSizeBinary(16),
+        DuckLakeType::Struct | DuckLakeType::List | DuckLakeType::Map => return None,
+    })
+}
+
+/// Column type of an Arrow type, or `None` if DuckLake has no matching type
+pub fn ducklake_type(data_type: &DataType) -> Option<DuckLakeType> {
+    Some(match data_type {
+        DataType::Boolean => DuckLakeType::Boolean,
+        DataType::Int8 => DuckLakeType::Int8,
+        DataType::Int16 => DuckLakeType::Int16,
+        DataType::Int32 => DuckLakeType::Int32,
+        DataType::Int64 => DuckLakeType::Int64,
+        DataType::UInt8 => DuckLakeType::UInt8,
+        DataType::UInt16 => DuckLakeType::UInt16,
+        DataType::UInt32 => DuckLakeType::UInt32,
+        DataType::UInt64 => DuckLakeType::UInt64,
+        DataType::Float32 => DuckLakeType::Float32,
+        DataType::Float64 => DuckLakeType::Float64,
+        DataType::Decimal128(precision, scale) => {
+            let column_type = format!("decimal({},{})", precision, scale);
+            return column_type.parse().ok();
+        }
+        DataType::Utf8 | DataType::LargeUtf8 => DuckLakeType::Varchar,
+        DataType::Binary | DataType::LargeBinary => DuckLakeType::Blob,
+        DataType::FixedSizeBinary(16) => DuckLakeType::Uuid,
+        DataType::Date32 => DuckLakeType::Date,
+        DataType::Time64(TimeUnit::Microsecond) => DuckLakeType::Time,
+        DataType::Timestamp(_, Some(_)) => DuckLakeType::TimestampTz,
+        DataType::Timestamp(TimeUnit::Second, None) => DuckLakeType::TimestampS,
+        DataType::Timestamp(TimeUnit::Millisecond, None) => DuckLakeType::TimestampMs,
+        DataType::Timestamp(TimeUnit::Microsecond, None) => DuckLakeType::Timestamp,
+        DataType::Timestamp(TimeUnit::Nanosecond, None) => DuckLakeType::TimestampNs,
+        DataType::Interval(IntervalUnit::MonthDayNano) => DuckLakeType::Interval,
+        DataType::Struct(_) => DuckLakeType::Struct,
+        DataType::List(_) | DataType::LargeList(_) => DuckLakeType::List,
+        DataType::Map(_, _) => DuckLakeType::Map,
+        _ => return None,
+    })
+}
+
+/// Validate batches against a table's columns and conform them to the
+/// table's Arrow schema: columns in table order, missing nullable columns
+/// filled with nulls, safe coercions (such as int32 to int64) applied, and
+/// column IDs attached as field IDs
+pub fn conform_batches(
+    batches: &[RecordBatch],
+    columns: &[ColumnInfo],
+) -> Result<Vec<RecordBatch>> {
+    batches
+        .iter()
+        .map(|batch| {
+            validate_batch(batch, columns)?;
+
+            let mut fields = Vec::new();
+            let mut arrays = Vec::new();
+            for column in columns {
+                let source = batch
+                    .schema()
+                    .field_with_name(&column.column_name)
+                    .ok()
+                    .cloned();
+                // Columns of a type that is not a DuckLake type keep their data
+                let field = match (arrow_field(column), source) {
+                    (Ok(field), _) => field,
+                    (Err(_), Some(source)) => source.with_metadata(HashMap::from([(
+                        PARQUET_FIELD_ID_META_KEY.to_string(),
+                        column.column_id.to_string(),
+                    )])),
+                    (Err(_), None) => continue,
+                };
+                let array = match batch.column_by_name(&column.column_name) {
+                    Some(array) => conform_array(array, &field)
+                        .map_err(|e| mismatch(format!("column '{}': {}", column.column_name, e)))?,
+                    None => new_null_array(field.data_type(), batch.num_rows()),
+                };
+                fields.push(field);
+                arrays.push(array);
+            }
+
+            RecordBatch::try_new_with_options(
+                Arc::new(Schema::new(fields)),
+                arrays,
+                &RecordBatchOptions::new().with_row_count(Some(batch.num_rows())),
+            )
+            .map_err(|e| mismatch(e.to_string()))
+        })
+        .collect()
+}
+
+/// Check that a data file written elsewhere can be read as part of a table
+/// as is: it must hold the table's columns, in order, with their types
+pub fn validate_file_schema(file_schema: &Schema, columns: &[ColumnInfo]) -> Result<()> {
+    let table_schema = arrow_schema(columns)?;
+    let names = |schema: &Schema| -> Vec<String> {
+        schema.fields().iter().map(|f| f.name().clone()).collect()
+    };
+    if names(file_schema) != names(&table_schema) {
+        return Err(mismatch(format!(
+            "the file has columns {:?} but the table has {:?}",
+            names(file_schema),
+            names(&table_schema)
+        )));
+    }
+    for (file_field, field) in file_schema.fields().iter().zip(table_schema.fields()) {
+        if !file_field.data_type().equals_datatype(field.data_type()) {
+            return Err(mismatch(format!(
+                "column '{}' is {} in the table but {} in the file",
+                field.name(),
+                field.data_type(),
+                file_field.data_type()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Convert an array to the type of a field, rebuilding nested arrays so
+/// that their child fields (matched by name) are those of the field
+fn conform_array(array: &ArrayRef, field: &Field) -> std::result::Result<ArrayRef, ArrowError> {
+    Ok(match (array.data_type(), field.data_type()) {
+        (DataType::Struct(_), DataType::Struct(fields)) => {
+            let source = array.as_struct();
+            let children = fields
+                .iter()
+                .map(|field| match source.column_by_name(field.name()) {
+                    Some(child) => conform_array(child, field),
+                    None => Ok(new_null_array(field.data_type(), source.len())),
+                })
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            Arc::new(StructArray::try_new(
+                fields.clone(),
+                children,
+                source.nulls().cloned(),
+            )?)
+        }
+        (DataType::LargeList(element), DataType::List(_)) => {
+            let list = cast(array, &DataType::List(element.clone()))?;
+            conform_array(&list, field)?
+        }
+        (DataType::List(_), DataType::List(element)) => {
+            let source = array.as_list::<i32>();
+            Arc::new(ListArray::try_new(
+                element.clone(),
+                source.offsets().clone(),
+                conform_array(source.values(), element)?,
+                source.nulls().cloned(),
+            )?)
+        }
+        (DataType::Map(_, _), DataType::Map(entries, sorted)) => {
+            let source = array.as_map();
+            let source_entries: ArrayRef = Arc::new(source.entries().clone());
+            Arc::new(MapArray::try_new(
+                entries.clone(),
+                source.offsets().clone(),
+                conform_array(&source_entries, entries)?.as_struct().clone(),
+                source.nulls().cloned(),
+                *sorted,
+            )?)
+        }
+        (from, to) if from == to => array.clone(),
+        (_, to) => cast(array, to)?,
+    })
+}
+
+/// Whether values of one Arrow type convert to another without loss
+fn can_coerce(from: &DataType, to: &DataType) -> bool {
+    use DataType::*;
+    match (from, to) {
+        _ if from == to => true,
+        (Null, _) => true,
+        (Int8, Int16 | Int32 | Int64 | Float32 | Float64)
+        | (Int16, Int32 | Int64 | Float32 | Float64)
+        | (Int32, Int64 | Float64)
+        | (UInt8, UInt16 | UInt32 | UInt64 | Int16 | Int32 | Int64 | Float32 | Float64)
+        | (UInt16, UInt32 | UInt64 | Int32 | Int64 | Float32 | Float64)
+        | (UInt32, UInt64 | Int64 | Float64)
+        | (Float32, Float64)
+        | (LargeUtf8 | Utf8View, Utf8)
+        | (LargeBinary | BinaryView, Binary) => true,
+        (Decimal128(from_precision, from_scale), Decimal128(to_precision, to_scale)) => {
+            to_scale >= from_scale
+                && (*to_precision as i16 - *to_scale as i16)
+                    >= (*from_precision as i16 - *from_scale as i16)
+        }
+        // Time zones only change how instants are displayed
+        (Timestamp(from_unit, from_tz), Timestamp(to_unit, to_tz)) => {
+            from_unit <= to_unit && from_tz.is_some() == to_tz.is_some()
+        }
+        _ => false,
+    }
+}
+
+/// Check that a batch can be written to a table: every field names a column
+/// and has the column's type, or one it safely coerces to, no required
+/// column is missing, and required columns hold no nulls. Columns with a
+/// type that is not a DuckLake type (created before types were checked)
+/// accept any data.
+pub fn validate_batch(batch: &RecordBatch, columns: &[ColumnInfo]) -> Result<()> {
+    check_fields("", batch.schema().fields(), columns)?;
+    for (field, array) in batch.schema().fields().iter().zip(batch.columns()) {
+        let column = columns.iter().find(|c| &c.column_name == field.name());
+        if column.is_some_and(|c| !c.nulls_allowed) && array.logical_null_count() > 0 {
+            return Err(mismatch(format!(
+                "column '{}' is NOT NULL but the data holds {} nulls",
+                field.name(),
+                array.logical_null_count()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Check fields against the columns they are written to
+fn check_fields(path: &str, fields: &Fields, columns: &[ColumnInfo]) -> Result<()> {
+    for field in fields {
+        let name = format!("{}{}", path, field.name());
+        let column = columns
+            .iter()
+            .find(|c| &c.column_name == field.name())
+            .ok_or_else(|| mismatch(format!("column '{}' does not exist", name)))?;
+        check_field(&name, field.data_type(), column)?;
+    }
+    if let Some(column) = columns
+        .iter()
+        .find(|c| !c.nulls_allowed && fields.find(&c.column_name).is_none())
+    {
+        return Err(mismatch(format!(
+            "NOT NULL column '{}{}' is missing",
+            path, column.column_name
+        )));
+    }
+    Ok(())
+}
+
+/// Check the type of a field, and of its nested fields, against a column
+fn check_field(name: &str, data_type: &DataType, column: &ColumnInfo) -> Result<()> {
+    let Ok(column_type) = column.column_type.parse::<DuckLakeType>() else {
+        return Ok(());
+    };
+    let matches = match arrow_type(column_type) {
+        Some(target) => can_coerce(data_type, &target),
+        None => ducklake_type(data_type) == Some(column_type),
+    };
+    if !matches {
+        return Err(mismatch(format!(
+            "column '{}' is {} but the data is {}",
+            name, column.column_type, data_type
+        )));
+    }
+
+    let path = format!("{}.", name);
+    match (data_type, &column.children[..]) {
+        (DataType::Struct(fields), children) => check_fields(&path, fields, children),
+        (DataType::List(element) | DataType::LargeList(element), [child]) => check_field(
+            &format!("{}{}", path, child.column_name),
+            element.data_type(),
+            child,
+        ),
+        (DataType::Map(entries, _), [key, value]) => match map_entries(entries) {
+            Some([key_field, value_field]) => {
+                check_field(&format!("{}key", path), key_field.data_type(), key)?;
+                check_field(&format!("{}value", path), value_field.data_type(), value)
+            }
+            None => Err(mismatch(format!(
+                "column '{}' holds malformed map data",
+                name
+            ))),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// The key and value fields of an Arrow map
+fn map_entries(entries: &Field) -> Option<[&Arc<Field>; 2]> {
+    match entries.data_type() {
+        DataType::Struct(fields) if fields.len() == 2 => Some([&fields[0], &fields[1]]),
+        _ => None,
+    }
+}
+
+fn mismatch(message: String) -> DuckPondError {
+    DuckPondError::SchemaMismatch { message }
+}
+
+fn invalid_column(message: String) -> DuckPondError {
+    DuckPondError::InvalidColumnDefinition { message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column_info(definition: &ColumnDefinition, next_id: &mut i64) -> ColumnInfo {
+        *next_id += 1;
+        ColumnInfo {
+            column_id: *next_id,
+            column_name: definition.name.clone(),
+            column_type: definition.data_type.clone(),
+            nulls_allowed: definition.nullable,
+            children: definition
+                .children
+                .iter()
+                .map(|child| column_info(child, next_id))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_nested_round_trip() {
+        let payload = Fields::from(vec![
+            Field::new("user_id", DataType::Int64, false),
+            Field::new(
+                "tags",
+                DataType::List(Arc::new(Field::new("element", DataType::Utf8, true))),
+                true,
+            ),
+            Field::new(
+                "attributes",
+                DataType::Map(
+                    Arc::new(Field::new(
+                        "entries",
+                        DataType::Struct(Fields::from(vec![
+                            Field::new("key", DataType::Utf8, false),
+                            Field::new("value", DataType::Float64, true),
+                        ])),
+                        false,
+                    )),
+                    false,
+                ),
+                true,
+            ),
+        ]);
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("payload", DataType::Struct(payload), true),
+        ]);
+
+        let definitions = column_definitions(&schema).unwrap();
+        let payload = &definitions[1];
+        assert_eq!(payload.data_type, "struct");
+        assert_eq!(payload.children.len(), 3);
+        assert_eq!(payload.children[1].data_type, "list");
+        assert_eq!(payload.children[1].children[0].name, "element");
+        assert_eq!(payload.children[2].data_type, "map");
+        let map_children: Vec<_> = payload.children[2]
+            .children
+            .iter()
+            .map(|c| (c.name.as_str(), c.data_type.as_str()))
+            .collect();
+        assert_eq!(map_children, vec![("key", "varchar"), ("value", "float64")]);
+
+        let mut next_id = 0;
+        let columns: Vec<_> = definitions
+            .iter()
+            .map(|definition| column_info(definition, &mut next_id))
+            .collect();
+        // Fields carry column IDs, and otherwise round trip
+        let converted = arrow_schema(&columns).unwrap();
+        let DataType::Struct(payload) = converted.fields()[1].data_type() else {
+            panic!("payload is not a struct");
+        };
+        assert_eq!(payload[0].metadata()[PARQUET_FIELD_ID_META_KEY], "3");
+        for (converted, field) in converted.fields().iter().zip(schema.fields()) {
+            assert_eq!(converted.name(), field.name());
+            assert_eq!(converted.is_nullable(), field.is_nullable());
+            assert!(converted.data_type().equals_datatype(field.data_type()));
+        }
+    }
+
+    #[test]
+    fn test_unsupported_type() {
+        let field = Field::new("d", DataType::Duration(TimeUnit::Second), true);
+        assert!(matches!(
+            column_definition(&field),
+            Err(DuckPondError::InvalidColumnDefinition { .. })
+        ));
+    }
+
+    #[test]
+    fn test_primitive_types() {
+        for column_type in [
+            "boolean",
+            "int8",
+            "uint64",
+            "float32",
+            "decimal(18,3)",
+            "varchar",
+            "blob",
+            "date",
+            "time",
+            "timestamp",
+            "timestamp_ms",
+            "timestamptz",
+            "interval",
+            "uuid",
+        ] {
+            let parsed: DuckLakeType = column_type.parse().unwrap();
+            let data_type = arrow_type(parsed).unwrap();
+            assert_eq!(ducklake_type(&data_type), Some(parsed), "{}", column_type);
+        }
+        assert_eq!(arrow_type(DuckLakeType::Json), Some(DataType::Utf8));
+        assert_eq!(arrow_type(DuckLakeType::Struct), None);
+    }
+
+    #[test]
+    fn test_validate_batch() {
+        let column = |name: &str, column_type: &str, nulls_allowed: bool| ColumnInfo {
+            column_id: 0,
+            column_name: name.to_string(),
+            column_type: column_type.to_string(),
+            nulls_allowed,
+            children: Vec::new(),
+        };
+        let columns = vec![column("id", "int64", false), column("doc", "json", true)];
+        let batch = |fields: Vec<Field>, arrays: Vec<arrow::array::ArrayRef>| {
+            RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays).unwrap()
+        };
+        let ids = |values: Vec<Option<i64>>| {
+            Arc::new(arrow::array::Int64Array::from(values)) as arrow::array::ArrayRef
+        };
+
+        let valid = batch(
+            vec![
+                Field::new("id", DataType::Int64, true),
+                Field::new("doc", DataType::Utf8, true),
+            ],
+            vec![
+                ids(vec![Some(1)]),
+                Arc::new(arrow::array::StringArray::from(vec!["{}"])),
+            ],
+        );
+        validate_batch(&valid, &columns).unwrap();
+
+        for invalid in [
+            batch(
+                vec![Field::new("id", DataType::Utf8, false)],
+                vec![Arc::new(arrow::array::StringArray::from(vec!["1"]))],
+            ),
+            batch(
+                vec![Field::new("id", DataType::Int64, true)],
+                vec![ids(vec![None])],
+            ),
+            batch(
+                vec![Field::new("doc", DataType::Utf8, true)],
+                vec![Arc::new(arrow::array::StringArray::from(vec!["{}"]))],
+            ),
+            batch(
+                vec![
+                    Field::new("id", DataType::Int64, false),
+                    Field::new("extra", DataType::Int64, false),
+                ],
+                vec![ids(vec![Some(1)]), ids(vec![Some(2)])],
+            ),
+        ] {
+            assert!(matches!(
+                validate_batch(&invalid, &columns),
+                Err(DuckPondError::SchemaMismatch { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_conform_batches() {
+        let column =
+            |column_id: i64, name: &str, column_type: &str, nulls_allowed: bool| ColumnInfo {
+                column_id,
+                column_name: name.to_string(),
+                column_type: column_type.to_string(),
+                nulls_allowed,
+                children: Vec::new(),
+            };
+        let columns = vec![
+            column(1, "id", "int64", false),
+            column(2, "score", "float64", true),
+            column(3, "name", "varchar", true),
+        ];
+
+        // Reordered, narrower columns, with a nullable column missing
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("score", DataType::Float32, true),
+                Field::new("id", DataType::Int32, false),
+            ])),
+            vec![
+                Arc::new(arrow::array::Float32Array::from(vec![0.5, 1.5])),
+                Arc::new(arrow::array::Int32Array::from(vec![7, 8])),
+            ],
+        )
+        .unwrap();
+
+        let conformed = conform_batches(&[batch], &columns).unwrap();
+        let conformed = &conformed[0];
+        let shape: Vec<_> = conformed
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| {
+                (
+                    f.name().clone(),
+                    f.data_type().clone(),
+                    f.metadata()[PARQUET_FIELD_ID_META_KEY].clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            shape,
+            vec![
+                ("id".to_string(), DataType::Int64, "1".to_string()),
+                ("score".to_string(), DataType::Float64, "2".to_string()),
+                ("name".to_string(), DataType::Utf8, "3".to_string()),
+            ]
+        );
+        assert_eq!(conformed.column(2).null_count(), 2);
+
+        // Narrowing is not a safe coercion
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new(
+                "id",
+                DataType::Float64,
+                false,
+            )])),
+            vec![Arc::new(arrow::array::Float64Array::from(vec![1.0]))],
+        )
+        .unwrap();
+        assert!(matches!(
+            conform_batches(&[batch], &columns),
+            Err(DuckPondError::SchemaMismatch { .. })
+        ));
+    }
+}
@@ -43,18 +43,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             name: "user_id".to_string(),
             data_type: "INT64".to_string(),
             nullable: false,
+            children: Vec::new(),
         },
         ColumnDefinition {
             column_id: Some(2),
             name: "event_type".to_string(),
             data_type: "STRING".to_string(),
             nullable: false,
+            children: Vec::new(),
         },
         ColumnDefinition {
             column_id: Some(3),
             name: "timestamp".to_string(),
             data_type: "INT64".to_string(),
             nullable: false,
+            children: Vec::new(),
         },
     ];
 